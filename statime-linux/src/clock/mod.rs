@@ -33,21 +33,29 @@ impl LinuxClock {
     /// Return three timestamps t1 t2 and t3 minted in that order.
     /// T1 and T3 are minted using the system TAI clock and T2 by the hardware
     /// clock
-    pub fn system_offset(&self) -> Result<(Time, Time, Time), clock_steering::unix::Error> {
+    ///
+    /// Returns `None` when the offset of the system clock to TAI changed during
+    /// the measurement because of a leap second, as the timestamps can then be
+    /// off by a second.
+    pub fn system_offset(&self) -> Result<Option<(Time, Time, Time)>, clock_steering::unix::Error> {
+        use clock_steering::Clock;
+
         // The clock crate's system offset gives the T1 and T3 timestamps on the
         // CLOCK_REALTIME timescale which is UTC, not TAI, so we need to correct
         // here.
-        self.clock.system_offset().map(|(mut t1, t2, mut t3)| {
-            use clock_steering::Clock;
-            let tai_offset = UnixClock::CLOCK_REALTIME.get_tai().unwrap();
-            t1.seconds += tai_offset as libc::time_t;
-            t3.seconds += tai_offset as libc::time_t;
-            (
-                clock_timestamp_to_time(t1),
-                clock_timestamp_to_time(t2),
-                clock_timestamp_to_time(t3),
-            )
-        })
+        let tai_offset = UnixClock::CLOCK_REALTIME.get_tai()?;
+        let (mut t1, t2, mut t3) = self.clock.system_offset()?;
+        if UnixClock::CLOCK_REALTIME.get_tai()? != tai_offset {
+            return Ok(None);
+        }
+
+        t1.seconds += tai_offset as libc::time_t;
+        t3.seconds += tai_offset as libc::time_t;
+        Ok(Some((
+            clock_timestamp_to_time(t1),
+            clock_timestamp_to_time(t2),
+            clock_timestamp_to_time(t3),
+        )))
     }
 
     pub fn get_tai_offset(&self) -> Result<i32, clock_steering::unix::Error> {
@@ -60,6 +68,21 @@ impl LinuxClock {
     }
 }
 
+/// Read the state of the kernel clock discipline, returning the clock state
+/// (`TIME_OK`, `TIME_INS`, ...) and the timex structure
+pub fn kernel_timex() -> std::io::Result<(libc::c_int, libc::timex)> {
+    // SAFETY: an all zero timex is valid, and with modes 0 it is only read
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+    // SAFETY: the reference points to a valid libc::timex
+    let state = unsafe { libc::ntp_adjtime(&mut timex) };
+
+    if state == -1 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok((state, timex))
+    }
+}
+
 fn clock_timestamp_to_time(t: clock_steering::Timestamp) -> Time {
     Time::from_nanos((t.seconds as u64) * 1_000_000_000 + (t.nanos as u64))
 }
@@ -134,6 +157,18 @@ impl Clock for LinuxClock {
 
         // These properties should always be communicated to the system clock.
 
+        // Right after the kernel applied a leap second, announce messages can
+        // still carry the leap second and the old UTC offset for a moment.
+        // Passing those on would schedule the leap second again at the end of
+        // the next day, and undo the new offset. The kernel leaves this state
+        // once the leap second is no longer announced.
+        if time_properties.leap_indicator() != LeapIndicator::NoLeap
+            && matches!(kernel_timex(), Ok((libc::TIME_OOP | libc::TIME_WAIT, _)))
+        {
+            log::debug!("Ignoring leap second that was already applied");
+            return Ok(());
+        }
+
         if let Some(offset) = time_properties.utc_offset() {
             UnixClock::CLOCK_REALTIME.set_tai(offset as _)?;
         }
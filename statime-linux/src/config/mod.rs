@@ -1,16 +1,22 @@
 use std::{
     fs::read_to_string,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
+use arrayvec::ArrayString;
 use log::warn;
 use serde::{Deserialize, Deserializer};
 use statime::{
-    config::{ClockIdentity, DelayMechanism},
-    time::{Duration, Interval},
+    config::{
+        ClockClassDegradation, ClockIdentity, DelayMechanism, IntegrityAlgorithm, PortAddress,
+        ProfileError, SecurityKey, SlaveEventMonitoringConfig, UnicastNegotiationConfig,
+        MAX_DISPLAY_NAME_LENGTH, MAX_KEY_LENGTH, MAX_SECURITY_KEYS, MAX_UNICAST_MASTER_TABLE_SIZE,
+    },
+    observability::slave_event_monitoring::MAX_SLAVE_EVENT_RECORDS,
+    time::{Duration, Interval, Time},
 };
 use timestamped_socket::interface::InterfaceName;
 
@@ -24,18 +30,38 @@ pub struct Config {
     pub loglevel: log::LevelFilter,
     #[serde(default = "default_sdo_id")]
     pub sdo_id: u16,
-    #[serde(default = "default_domain")]
-    pub domain: u8,
+    #[serde(default)]
+    pub domain: Option<u8>,
     #[serde(default, deserialize_with = "deserialize_clock_identity")]
     pub identity: Option<ClockIdentity>,
     #[serde(default = "default_priority1")]
     pub priority1: u8,
     #[serde(default = "default_priority2")]
     pub priority2: u8,
+    #[serde(default = "default_local_priority")]
+    pub local_priority: u8,
+    #[serde(default)]
+    pub clock_type: ClockType,
+    #[serde(default)]
+    pub profile: Profile,
     #[serde(rename = "port")]
     pub ports: Vec<PortConfig>,
     #[serde(default)]
     pub observability: ObservabilityConfig,
+    #[serde(default)]
+    pub synchronization_metadata: Option<SynchronizationMetadataConfig>,
+    #[serde(default)]
+    pub power_profile: Option<PowerProfileConfig>,
+    #[serde(default)]
+    pub alternate_time_offset: Option<AlternateTimeOffsetConfig>,
+    #[serde(default)]
+    pub time_source: Option<TimeSourceConfig>,
+    #[serde(default)]
+    pub holdover: HoldoverConfig,
+    #[serde(default)]
+    pub security: Option<SecurityConfig>,
+    #[serde(default)]
+    pub statistics: Option<StatisticsConfig>,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -48,20 +74,44 @@ pub struct PortConfig {
     pub hardware_clock: Option<PathBuf>,
     #[serde(default)]
     pub network_mode: NetworkMode,
-    #[serde(default = "default_announce_interval")]
-    pub announce_interval: i8,
-    #[serde(default = "default_sync_interval")]
-    pub sync_interval: i8,
+    #[serde(default)]
+    pub ethernet_address: EthernetAddressType,
+    #[serde(default)]
+    pub announce_interval: Option<i8>,
+    #[serde(default)]
+    pub sync_interval: Option<i8>,
     #[serde(default = "default_announce_receipt_timeout")]
     pub announce_receipt_timeout: u8,
-    #[serde(default)]
+    #[serde(default, alias = "not-slave")]
     pub master_only: bool,
+    #[serde(default = "default_local_priority")]
+    pub local_priority: u8,
     #[serde(default = "default_delay_asymmetry")]
     pub delay_asymmetry: i64,
     #[serde(default)]
     pub delay_mechanism: DelayType,
-    #[serde(default = "default_delay_interval")]
-    pub delay_interval: i8,
+    #[serde(default)]
+    pub delay_interval: Option<i8>,
+    #[serde(default)]
+    pub unicast_negotiation: bool,
+    #[serde(default = "default_unicast_grant_duration")]
+    pub unicast_grant_duration: u32,
+    #[serde(default)]
+    pub unicast_max_grant_duration: Option<u32>,
+    #[serde(default = "default_unicast_max_clients")]
+    pub unicast_max_clients: usize,
+    #[serde(default, deserialize_with = "deserialize_unicast_master_table")]
+    pub unicast_master_table: Vec<PortAddress>,
+    #[serde(default)]
+    pub hybrid_mode: bool,
+    #[serde(default)]
+    pub one_step: bool,
+    #[serde(default)]
+    pub slave_event_monitoring: bool,
+    #[serde(default = "default_slave_event_records")]
+    pub slave_event_records: u8,
+    #[serde(default, deserialize_with = "deserialize_slave_event_destination")]
+    pub slave_event_destination: Option<PortAddress>,
 }
 
 fn deserialize_loglevel<'de, D>(deserializer: D) -> Result<log::LevelFilter, D::Error>
@@ -107,23 +157,118 @@ where
     )?)))
 }
 
-impl From<PortConfig> for statime::config::PortConfig<Option<Vec<ClockIdentity>>> {
-    fn from(pc: PortConfig) -> Self {
-        Self {
+fn deserialize_unicast_master_table<'de, D>(deserializer: D) -> Result<Vec<PortAddress>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let raw: Vec<String> = Deserialize::deserialize(deserializer)?;
+    if raw.len() > MAX_UNICAST_MASTER_TABLE_SIZE {
+        return Err(D::Error::custom(format!(
+            "Too many unicast masters, at most {} are supported",
+            MAX_UNICAST_MASTER_TABLE_SIZE
+        )));
+    }
+
+    raw.into_iter()
+        .map(|address| {
+            parse_port_address(&address).ok_or_else(|| {
+                D::Error::custom(format!("Invalid unicast master address: {}", address))
+            })
+        })
+        .collect()
+}
+
+fn deserialize_slave_event_destination<'de, D>(
+    deserializer: D,
+) -> Result<Option<PortAddress>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let raw: String = Deserialize::deserialize(deserializer)?;
+    let address = parse_port_address(&raw).ok_or_else(|| {
+        D::Error::custom(format!("Invalid slave event destination address: {}", raw))
+    })?;
+    Ok(Some(address))
+}
+
+/// Parse an IPv4 or IPv6 address, or a MAC address like 00:1b:19:00:00:00
+fn parse_port_address(address: &str) -> Option<PortAddress> {
+    use hex::FromHex;
+
+    if let Ok(ip) = IpAddr::from_str(address) {
+        return Some(ip.into());
+    }
+
+    let mac = <[u8; 6]>::from_hex(address.replace([':', '-'], "")).ok()?;
+    Some(PortAddress::Ethernet(mac))
+}
+
+impl PortConfig {
+    /// The time between two (peer) delay requests, the default of `profile`
+    /// when not configured
+    pub fn delay_interval(&self, profile: Profile) -> Interval {
+        self.delay_interval
+            .map(Interval::from_log_2)
+            .unwrap_or_else(|| statime::config::Profile::from(profile).default_delay_interval())
+    }
+
+    /// Convert into the configuration of a statime port, using the defaults of
+    /// `profile` for message intervals that are not configured
+    pub fn into_port_config(
+        self,
+        profile: Profile,
+    ) -> statime::config::PortConfig<Option<Vec<ClockIdentity>>> {
+        let defaults = statime::config::Profile::from(profile);
+        let delay_interval = self.delay_interval(profile);
+        let pc = self;
+
+        statime::config::PortConfig {
             acceptable_master_list: pc.acceptable_master_list,
-            announce_interval: Interval::from_log_2(pc.announce_interval),
-            sync_interval: Interval::from_log_2(pc.sync_interval),
+            announce_interval: pc
+                .announce_interval
+                .map(Interval::from_log_2)
+                .unwrap_or_else(|| defaults.default_announce_interval()),
+            sync_interval: pc
+                .sync_interval
+                .map(Interval::from_log_2)
+                .unwrap_or_else(|| defaults.default_sync_interval()),
             announce_receipt_timeout: pc.announce_receipt_timeout,
             master_only: pc.master_only,
+            local_priority: pc.local_priority,
             delay_asymmetry: Duration::from_nanos(pc.delay_asymmetry),
             delay_mechanism: match pc.delay_mechanism {
                 DelayType::E2E => DelayMechanism::E2E {
-                    interval: Interval::from_log_2(pc.delay_interval),
+                    interval: delay_interval,
                 },
                 DelayType::P2P => DelayMechanism::P2P {
-                    interval: Interval::from_log_2(pc.delay_interval),
+                    interval: delay_interval,
                 },
             },
+            // The unicast master table relies on unicast negotiation
+            unicast_negotiation: (pc.unicast_negotiation || !pc.unicast_master_table.is_empty())
+                .then_some(UnicastNegotiationConfig {
+                    grant_duration: pc.unicast_grant_duration,
+                    max_grant_duration: pc
+                        .unicast_max_grant_duration
+                        .or_else(|| defaults.max_grant_duration())
+                        .unwrap_or(pc.unicast_grant_duration),
+                    max_clients: pc.unicast_max_clients,
+                }),
+            unicast_master_table: pc.unicast_master_table.into_iter().collect(),
+            hybrid_mode: pc.hybrid_mode,
+            // One-step messages need hardware timestamping
+            one_step: pc.one_step && pc.hardware_clock.is_some(),
+            slave_event_monitoring: pc.slave_event_monitoring.then_some(
+                SlaveEventMonitoringConfig {
+                    records_per_message: pc.slave_event_records,
+                    destination: pc.slave_event_destination,
+                    ..Default::default()
+                },
+            ),
         }
     }
 }
@@ -137,6 +282,432 @@ pub enum NetworkMode {
     Ethernet,
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EthernetAddressType {
+    /// Multicast to 01:1B:19:00:00:00, which is forwarded by bridges
+    #[default]
+    Forwardable,
+    /// Multicast every message to 01:80:C2:00:00:0E, which is not forwarded
+    NonForwardable,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClockType {
+    #[default]
+    Ordinary,
+    E2eTransparent,
+    P2pTransparent,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Profile {
+    #[default]
+    Default,
+    Gptp,
+    #[serde(rename = "g8275.1")]
+    G8275_1,
+    #[serde(rename = "g8275.2")]
+    G8275_2,
+    #[serde(rename = "st2059-2")]
+    St2059_2,
+    #[serde(rename = "c37.238")]
+    C37_238,
+    Aes67,
+}
+
+impl From<Profile> for statime::config::Profile {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Default => statime::config::Profile::Default,
+            Profile::Gptp => statime::config::Profile::Gptp,
+            Profile::G8275_1 => statime::config::Profile::G8275_1,
+            Profile::G8275_2 => statime::config::Profile::G8275_2,
+            Profile::St2059_2 => statime::config::Profile::St2059_2,
+            Profile::C37_238 => statime::config::Profile::C37_238,
+            Profile::Aes67 => statime::config::Profile::Aes67,
+        }
+    }
+}
+
+/// The synchronization metadata sent while we are the grandmaster, see
+/// [`statime::config::SynchronizationMetadata`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SynchronizationMetadataConfig {
+    pub default_system_frame_rate_numerator: u32,
+    #[serde(default = "default_frame_rate_denominator")]
+    pub default_system_frame_rate_denominator: u32,
+    #[serde(default)]
+    pub master_locking_status: MasterLockingStatus,
+    #[serde(default)]
+    pub drop_frame: bool,
+    #[serde(default)]
+    pub color_frame_identification: bool,
+    #[serde(default)]
+    pub current_local_offset: i32,
+    #[serde(default)]
+    pub jump_seconds: i32,
+    #[serde(default)]
+    pub time_of_next_jump: u64,
+    #[serde(default)]
+    pub time_of_next_jam: u64,
+    #[serde(default)]
+    pub time_of_previous_jam: u64,
+    #[serde(default)]
+    pub previous_jam_local_offset: i32,
+    #[serde(default)]
+    pub daylight_saving: bool,
+    #[serde(default)]
+    pub daylight_saving_at_next_jump: bool,
+    #[serde(default)]
+    pub daylight_saving_at_previous_jam: bool,
+    #[serde(default)]
+    pub leap_second_jump: bool,
+}
+
+impl From<SynchronizationMetadataConfig> for statime::config::SynchronizationMetadata {
+    fn from(config: SynchronizationMetadataConfig) -> Self {
+        statime::config::SynchronizationMetadata {
+            default_system_frame_rate_numerator: config.default_system_frame_rate_numerator,
+            default_system_frame_rate_denominator: config.default_system_frame_rate_denominator,
+            master_locking_status: config.master_locking_status.into(),
+            drop_frame: config.drop_frame,
+            color_frame_identification: config.color_frame_identification,
+            current_local_offset: config.current_local_offset,
+            jump_seconds: config.jump_seconds,
+            time_of_next_jump: config.time_of_next_jump,
+            time_of_next_jam: config.time_of_next_jam,
+            time_of_previous_jam: config.time_of_previous_jam,
+            previous_jam_local_offset: config.previous_jam_local_offset,
+            daylight_saving: config.daylight_saving,
+            daylight_saving_at_next_jump: config.daylight_saving_at_next_jump,
+            daylight_saving_at_previous_jam: config.daylight_saving_at_previous_jam,
+            leap_second_jump: config.leap_second_jump,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MasterLockingStatus {
+    #[default]
+    NotInUse,
+    FreeRun,
+    ColdLocking,
+    WarmLocking,
+    Locked,
+}
+
+impl From<MasterLockingStatus> for statime::config::MasterLockingStatus {
+    fn from(status: MasterLockingStatus) -> Self {
+        match status {
+            MasterLockingStatus::NotInUse => statime::config::MasterLockingStatus::NotInUse,
+            MasterLockingStatus::FreeRun => statime::config::MasterLockingStatus::FreeRun,
+            MasterLockingStatus::ColdLocking => statime::config::MasterLockingStatus::ColdLocking,
+            MasterLockingStatus::WarmLocking => statime::config::MasterLockingStatus::WarmLocking,
+            MasterLockingStatus::Locked => statime::config::MasterLockingStatus::Locked,
+        }
+    }
+}
+
+/// The settings of the power profile, see
+/// [`statime::config::PowerProfileConfig`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PowerProfileConfig {
+    pub grandmaster_id: u16,
+    #[serde(default)]
+    pub time_inaccuracy: u32,
+}
+
+impl From<PowerProfileConfig> for statime::config::PowerProfileConfig {
+    fn from(config: PowerProfileConfig) -> Self {
+        statime::config::PowerProfileConfig {
+            grandmaster_id: config.grandmaster_id,
+            time_inaccuracy: config.time_inaccuracy,
+        }
+    }
+}
+
+/// The alternate timescale announced while we are the grandmaster, see
+/// [`statime::config::AlternateTimeOffset`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AlternateTimeOffsetConfig {
+    #[serde(default)]
+    pub key_field: u8,
+    pub current_offset: i32,
+    #[serde(default)]
+    pub jump_seconds: i32,
+    #[serde(default)]
+    pub time_of_next_jump: u64,
+    #[serde(default, deserialize_with = "deserialize_display_name")]
+    pub display_name: ArrayString<MAX_DISPLAY_NAME_LENGTH>,
+}
+
+impl From<AlternateTimeOffsetConfig> for statime::config::AlternateTimeOffset {
+    fn from(config: AlternateTimeOffsetConfig) -> Self {
+        statime::config::AlternateTimeOffset {
+            key_field: config.key_field,
+            current_offset: config.current_offset,
+            jump_seconds: config.jump_seconds,
+            time_of_next_jump: config.time_of_next_jump,
+            display_name: config.display_name,
+        }
+    }
+}
+
+fn deserialize_display_name<'de, D>(
+    deserializer: D,
+) -> Result<ArrayString<MAX_DISPLAY_NAME_LENGTH>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let raw: String = Deserialize::deserialize(deserializer)?;
+    ArrayString::from(&raw).map_err(|_| {
+        D::Error::custom(format!(
+            "Display name {raw:?} is longer than {MAX_DISPLAY_NAME_LENGTH} bytes"
+        ))
+    })
+}
+
+/// The external time source of the instance while it is the grandmaster, see
+/// [`crate::time_source`]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimeSourceConfig {
+    #[serde(rename = "type")]
+    pub source_type: TimeSourceType,
+    #[serde(default)]
+    pub nmea_device: Option<PathBuf>,
+    #[serde(default)]
+    pub pps_device: Option<PathBuf>,
+    #[serde(default)]
+    pub phc_device: Option<PathBuf>,
+    #[serde(default = "default_holdover")]
+    pub holdover: u64,
+    #[serde(default)]
+    pub degradation: ClockClassDegradation,
+    #[serde(default)]
+    pub accuracy: Option<u64>,
+    #[serde(default)]
+    pub leap_seconds_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeSourceType {
+    /// A GNSS receiver sending NMEA sentences, optionally with a PPS signal
+    Gnss,
+    /// The system clock, synchronized by NTP
+    Ntp,
+    /// A PHC disciplined by another program
+    Phc,
+}
+
+fn default_holdover() -> u64 {
+    300
+}
+
+/// The holdover specification of the clock after losing the grandmaster, see
+/// [`statime::config::HoldoverConfig`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HoldoverConfig {
+    #[serde(default = "default_holdover_enabled")]
+    pub enabled: bool,
+    /// Seconds
+    #[serde(default = "default_holdover")]
+    pub duration: u64,
+    /// Nanoseconds
+    #[serde(default = "default_max_uncertainty")]
+    pub max_uncertainty: u64,
+    /// Parts per trillion per second
+    #[serde(default = "default_frequency_drift")]
+    pub frequency_drift: u64,
+    #[serde(default)]
+    pub degradation: ClockClassDegradation,
+}
+
+impl Default for HoldoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_holdover_enabled(),
+            duration: default_holdover(),
+            max_uncertainty: default_max_uncertainty(),
+            frequency_drift: default_frequency_drift(),
+            degradation: Default::default(),
+        }
+    }
+}
+
+impl From<HoldoverConfig> for Option<statime::config::HoldoverConfig> {
+    fn from(config: HoldoverConfig) -> Self {
+        config.enabled.then(|| statime::config::HoldoverConfig {
+            duration: Duration::from_secs(config.duration as i64),
+            max_uncertainty: Duration::from_nanos(config.max_uncertainty as i64),
+            frequency_drift: config.frequency_drift as f64 * 1e-12,
+            degradation: config.degradation,
+        })
+    }
+}
+
+fn default_holdover_enabled() -> bool {
+    true
+}
+
+fn default_max_uncertainty() -> u64 {
+    1_000
+}
+
+fn default_frequency_drift() -> u64 {
+    10
+}
+
+/// Authentication of all messages with the AUTHENTICATION TLV, see
+/// [`statime::config::SecurityConfig`]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SecurityConfig {
+    #[serde(default)]
+    pub spp: u8,
+    /// File with the key-id and keys, reloaded when it changes
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+    /// The key used for sent messages
+    #[serde(default)]
+    pub key_id: Option<u32>,
+    #[serde(default, rename = "key")]
+    pub keys: Vec<SecurityKeyConfig>,
+}
+
+/// The contents of a [`SecurityConfig::key_file`]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct KeyFileConfig {
+    pub key_id: u32,
+    #[serde(rename = "key")]
+    pub keys: Vec<SecurityKeyConfig>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SecurityKeyConfig {
+    pub id: u32,
+    #[serde(default = "default_integrity_algorithm")]
+    pub algorithm: IntegrityAlgorithm,
+    #[serde(deserialize_with = "deserialize_secret")]
+    pub secret: Vec<u8>,
+    /// Seconds since the unix epoch
+    #[serde(default)]
+    pub valid_from: Option<u64>,
+    /// Seconds since the unix epoch
+    #[serde(default)]
+    pub valid_until: Option<u64>,
+}
+
+impl std::fmt::Debug for SecurityKeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecurityKeyConfig")
+            .field("id", &self.id)
+            .field("algorithm", &self.algorithm)
+            .field("valid_from", &self.valid_from)
+            .field("valid_until", &self.valid_until)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<&SecurityKeyConfig> for SecurityKey {
+    fn from(key: &SecurityKeyConfig) -> Self {
+        SecurityKey {
+            key_id: key.id,
+            algorithm: key.algorithm,
+            secret: key.secret.iter().copied().collect(),
+            valid_from: key.valid_from.map(Time::from_secs),
+            valid_until: key.valid_until.map(Time::from_secs),
+        }
+    }
+}
+
+/// Checks that `keys` can be used for authentication with `key_id`
+pub fn check_keys(key_id: u32, keys: &[SecurityKeyConfig]) -> Result<(), &'static str> {
+    if keys.len() > MAX_SECURITY_KEYS {
+        return Err("too many keys");
+    }
+    if !keys.iter().any(|key| key.id == key_id) {
+        return Err("the key-id is not one of the keys");
+    }
+    for (index, key) in keys.iter().enumerate() {
+        if keys[..index].iter().any(|other| other.id == key.id) {
+            return Err("duplicate key id");
+        }
+        if key.secret.len() > MAX_KEY_LENGTH {
+            return Err("secret too long");
+        }
+        if !SecurityKey::from(key).is_valid() {
+            return Err("secret length does not match the algorithm");
+        }
+        if let (Some(from), Some(until)) = (key.valid_from, key.valid_until) {
+            if from >= until {
+                return Err("valid-from must be before valid-until");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn default_integrity_algorithm() -> IntegrityAlgorithm {
+    IntegrityAlgorithm::HmacSha256_128
+}
+
+fn deserialize_secret<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use hex::FromHex;
+    use serde::de::Error;
+    let raw: String = Deserialize::deserialize(deserializer)?;
+    Vec::from_hex(raw).map_err(|e| D::Error::custom(format!("Invalid secret: {}", e)))
+}
+
+/// The file the measurements, clock adjustments and port state transitions
+/// are written to, see [`crate::statistics`]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StatisticsConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub format: StatisticsFormat,
+    /// Bytes
+    #[serde(default = "default_statistics_max_size")]
+    pub max_size: u64,
+    /// Number of rotated files kept next to the current one
+    #[serde(default = "default_statistics_max_files")]
+    pub max_files: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatisticsFormat {
+    #[default]
+    Csv,
+    JsonLines,
+}
+
+fn default_statistics_max_size() -> u64 {
+    10_000_000
+}
+
+fn default_statistics_max_files() -> u32 {
+    5
+}
+
 #[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DelayType {
@@ -157,10 +728,96 @@ impl Config {
 
         let contents = read_to_string(file).map_err(ConfigError::Io)?;
         let config: Config = toml::de::from_str(&contents).map_err(ConfigError::Toml)?;
+        config.check_profile()?;
+        config.check_time_source()?;
+        config.check_security()?;
+
+        if config.security.is_some() && perm.mode() as libc::mode_t & libc::S_IROTH != 0 {
+            warn!("Unrestricted config file permissions: Others can read the security keys.");
+        }
+
         config.warn_when_unreasonable();
         Ok(config)
     }
 
+    /// Checks that the settings of every port are allowed by the profile
+    pub fn check_profile(&self) -> Result<(), ConfigError> {
+        let profile = statime::config::Profile::from(self.profile);
+        for port in &self.ports {
+            let ethernet = port.network_mode == NetworkMode::Ethernet;
+            let network_mode_error = match self.profile {
+                Profile::Gptp if !ethernet => Some("gPTP needs network-mode ethernet"),
+                Profile::G8275_1 if !ethernet => Some("G.8275.1 needs network-mode ethernet"),
+                Profile::C37_238 if !ethernet => Some("C37.238 needs network-mode ethernet"),
+                Profile::Aes67 if port.network_mode != NetworkMode::Ipv4 => {
+                    Some("AES67 needs network-mode ipv4")
+                }
+                Profile::G8275_2 if ethernet => Some("G.8275.2 needs network-mode ipv4 or ipv6"),
+                _ => None,
+            };
+            if let Some(error) = network_mode_error {
+                return Err(ConfigError::NetworkMode(port.interface, error));
+            }
+
+            profile
+                .check_port_config(&port.clone().into_port_config(self.profile))
+                .map_err(|error| ConfigError::Profile(port.interface, error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the time source has the devices it needs
+    pub fn check_time_source(&self) -> Result<(), ConfigError> {
+        let Some(source) = &self.time_source else {
+            return Ok(());
+        };
+
+        match source.source_type {
+            TimeSourceType::Gnss if source.nmea_device.is_none() => Err(ConfigError::TimeSource(
+                "a gnss time source needs an nmea-device",
+            )),
+            TimeSourceType::Phc if source.phc_device.is_none() => Err(ConfigError::TimeSource(
+                "a phc time source needs a phc-device",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the keys can be used for authentication
+    pub fn check_security(&self) -> Result<(), ConfigError> {
+        let Some(security) = &self.security else {
+            return Ok(());
+        };
+
+        match (&security.key_file, security.key_id) {
+            (Some(_), None) if security.keys.is_empty() => {}
+            (Some(_), _) => {
+                return Err(ConfigError::Security(
+                    "a key-file replaces the key-id and keys",
+                ))
+            }
+            (None, Some(key_id)) => {
+                check_keys(key_id, &security.keys).map_err(ConfigError::Security)?
+            }
+            (None, None) => return Err(ConfigError::Security("a key-id or key-file is needed")),
+        }
+        if self.ports.iter().any(|port| port.one_step) {
+            return Err(ConfigError::Security(
+                "one-step ports cannot authenticate their messages",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The domain of the instance, the default of the profile when not
+    /// configured
+    pub fn domain(&self) -> u8 {
+        self.domain
+            .unwrap_or_else(|| statime::config::Profile::from(self.profile).default_domain())
+    }
+
     /// Warns about unreasonable config values
     pub fn warn_when_unreasonable(&self) {
         if self.ports.is_empty() {
@@ -170,6 +827,125 @@ impl Config {
         if self.ports.len() > 16 {
             warn!("Too many ports are configured.");
         }
+
+        if self.profile == Profile::Gptp && self.sdo_id != 0x100 {
+            warn!("The gPTP profile uses sdo-id 0x100.");
+        }
+
+        if self.profile == Profile::G8275_1 && !(24..=43).contains(&self.domain()) {
+            warn!("The G.8275.1 profile uses a domain between 24 and 43.");
+        }
+
+        if self.profile == Profile::G8275_2 && !(44..=63).contains(&self.domain()) {
+            warn!("The G.8275.2 profile uses a domain between 44 and 63.");
+        }
+
+        if self.profile == Profile::St2059_2 && self.domain() > 127 {
+            warn!("The ST 2059-2 profile uses a domain between 0 and 127.");
+        }
+
+        if self.synchronization_metadata.is_some() && self.profile != Profile::St2059_2 {
+            warn!("Synchronization metadata is only used by the ST 2059-2 profile.");
+        }
+
+        if let Some(metadata) = &self.synchronization_metadata {
+            if metadata.default_system_frame_rate_denominator == 0 {
+                warn!("The default system frame rate has a denominator of 0.");
+            }
+        }
+
+        if (self.power_profile.is_some() || self.alternate_time_offset.is_some())
+            && self.profile != Profile::C37_238
+        {
+            warn!("Power-profile and alternate-time-offset are only used by the C37.238 profile.");
+        }
+
+        if let Some(power) = &self.power_profile {
+            if !(3..=254).contains(&power.grandmaster_id) {
+                warn!("Grandmaster capable clocks use a grandmaster-id between 3 and 254.");
+            }
+        }
+
+        if let Some(source) = &self.time_source {
+            if source.pps_device.is_some() && source.source_type != TimeSourceType::Gnss {
+                warn!("The pps-device is only used by a gnss time source.");
+            }
+
+            if source.phc_device.is_some() && source.source_type != TimeSourceType::Phc {
+                warn!("The phc-device is only used by a phc time source.");
+            }
+
+            if source.source_type == TimeSourceType::Phc {
+                for port in &self.ports {
+                    if port.hardware_clock == source.phc_device && !port.master_only {
+                        warn!(
+                            "Port {} shares the time source PHC, and should be master-only.",
+                            port.interface
+                        );
+                    }
+                }
+            }
+        }
+
+        if matches!(self.profile, Profile::G8275_1 | Profile::G8275_2) && self.priority1 != 128 {
+            warn!("The telecom profiles ignore priority1, which should be 128.");
+        }
+
+        for port in &self.ports {
+            if port.ethernet_address != EthernetAddressType::Forwardable
+                && port.network_mode != NetworkMode::Ethernet
+            {
+                warn!(
+                    "Ethernet-address of port {} is only used with network mode ethernet.",
+                    port.interface
+                );
+            }
+
+            if port.one_step && port.hardware_clock.is_none() {
+                warn!(
+                    "One-step operation of port {} needs a hardware clock, using two-step.",
+                    port.interface
+                );
+            }
+
+            let reachable = |address: &PortAddress| {
+                matches!(
+                    (port.network_mode, address),
+                    (NetworkMode::Ipv4, PortAddress::Ipv4(_))
+                        | (NetworkMode::Ipv6, PortAddress::Ipv6(_))
+                        | (NetworkMode::Ethernet, PortAddress::Ethernet(_))
+                )
+            };
+
+            for address in &port.unicast_master_table {
+                if !reachable(address) {
+                    warn!(
+                        "Unicast master {:?} can't be reached with network mode {:?} of port {}.",
+                        address, port.network_mode, port.interface
+                    );
+                }
+            }
+
+            if !(1..=MAX_SLAVE_EVENT_RECORDS).contains(&(port.slave_event_records as usize)) {
+                warn!(
+                    "Slave-event-records of port {} should be between 1 and {}.",
+                    port.interface, MAX_SLAVE_EVENT_RECORDS
+                );
+            }
+
+            if let Some(address) = &port.slave_event_destination {
+                if !reachable(address) {
+                    warn!(
+                        "Slave event destination {:?} can't be reached with network mode {:?} of port {}.",
+                        address, port.network_mode, port.interface
+                    );
+                }
+            }
+        }
+
+        if self.statistics.is_some() && self.clock_type != ClockType::Ordinary {
+            warn!("Transparent clocks only write statistics of their hardware clocks.");
+        }
     }
 }
 
@@ -177,6 +953,10 @@ impl Config {
 pub enum ConfigError {
     Io(std::io::Error),
     Toml(toml::de::Error),
+    Profile(InterfaceName, ProfileError),
+    NetworkMode(InterfaceName, &'static str),
+    TimeSource(&'static str),
+    Security(&'static str),
 }
 
 impl std::fmt::Display for ConfigError {
@@ -184,6 +964,14 @@ impl std::fmt::Display for ConfigError {
         match self {
             ConfigError::Io(e) => writeln!(f, "io error while reading config: {e}"),
             ConfigError::Toml(e) => writeln!(f, "config toml parsing error: {e}"),
+            ConfigError::Profile(interface, e) => {
+                writeln!(f, "port {interface} conflicts with the profile: {e}")
+            }
+            ConfigError::NetworkMode(interface, e) => {
+                writeln!(f, "port {interface} conflicts with the profile: {e}")
+            }
+            ConfigError::TimeSource(e) => writeln!(f, "invalid time source: {e}"),
+            ConfigError::Security(e) => writeln!(f, "invalid security: {e}"),
         }
     }
 }
@@ -194,22 +982,10 @@ fn default_loglevel() -> log::LevelFilter {
     log::LevelFilter::Info
 }
 
-fn default_domain() -> u8 {
-    0
-}
-
 fn default_sdo_id() -> u16 {
     0x000
 }
 
-fn default_announce_interval() -> i8 {
-    1
-}
-
-fn default_sync_interval() -> i8 {
-    0
-}
-
 fn default_announce_receipt_timeout() -> u8 {
     3
 }
@@ -222,12 +998,28 @@ fn default_priority2() -> u8 {
     128
 }
 
+fn default_local_priority() -> u8 {
+    128
+}
+
 fn default_delay_asymmetry() -> i64 {
     0
 }
 
-fn default_delay_interval() -> i8 {
-    0
+fn default_unicast_grant_duration() -> u32 {
+    300
+}
+
+fn default_slave_event_records() -> u8 {
+    8
+}
+
+fn default_unicast_max_clients() -> usize {
+    32
+}
+
+fn default_frame_rate_denominator() -> u32 {
+    1
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -275,7 +1067,7 @@ mod tests {
 
     use timestamped_socket::interface::InterfaceName;
 
-    use crate::config::ObservabilityConfig;
+    use crate::config::{HoldoverConfig, ObservabilityConfig};
 
     // Minimal amount of config results in default values
     #[test]
@@ -290,28 +1082,821 @@ interface = "enp0s31f6"
             acceptable_master_list: None,
             hardware_clock: None,
             network_mode: crate::config::NetworkMode::Ipv4,
-            announce_interval: 1,
-            sync_interval: 0,
+            ethernet_address: crate::config::EthernetAddressType::Forwardable,
+            announce_interval: None,
+            sync_interval: None,
             announce_receipt_timeout: 3,
             master_only: false,
+            local_priority: 128,
             delay_asymmetry: 0,
             delay_mechanism: crate::config::DelayType::E2E,
-            delay_interval: 0,
+            delay_interval: None,
+            unicast_negotiation: false,
+            unicast_grant_duration: 300,
+            unicast_max_grant_duration: None,
+            unicast_max_clients: 32,
+            unicast_master_table: vec![],
+            hybrid_mode: false,
+            one_step: false,
+            slave_event_monitoring: false,
+            slave_event_records: 8,
+            slave_event_destination: None,
         };
 
         let expected = crate::config::Config {
             loglevel: log::LevelFilter::Info,
             sdo_id: 0x000,
-            domain: 0,
+            domain: None,
             identity: None,
             priority1: 128,
             priority2: 128,
+            local_priority: 128,
+            clock_type: crate::config::ClockType::Ordinary,
+            profile: crate::config::Profile::Default,
             ports: vec![expected_port],
             observability: ObservabilityConfig::default(),
+            synchronization_metadata: None,
+            power_profile: None,
+            alternate_time_offset: None,
+            time_source: None,
+            holdover: HoldoverConfig::default(),
+            security: None,
+            statistics: None,
         };
 
         let actual = toml::from_str(MINIMAL_CONFIG).unwrap();
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn clock_type() {
+        const TRANSPARENT_CONFIG: &str = r#"
+clock-type = "e2e-transparent"
+
+[[port]]
+interface = "enp0s31f6"
+
+[[port]]
+interface = "enp0s31f7"
+"#;
+
+        let actual: crate::config::Config = toml::from_str(TRANSPARENT_CONFIG).unwrap();
+        assert_eq!(actual.clock_type, crate::config::ClockType::E2eTransparent);
+        assert_eq!(actual.ports.len(), 2);
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+clock-type = "p2p-transparent"
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.clock_type, crate::config::ClockType::P2pTransparent);
+
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+clock-type = "transparent"
+
+[[port]]
+interface = "enp0s31f6"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unicast_master_table() {
+        use statime::config::PortAddress;
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+unicast-master-table = ["192.168.1.1", "fe80::1", "00:1b:19:00:00:01"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            actual.ports[0].unicast_master_table,
+            [
+                PortAddress::Ipv4([192, 168, 1, 1]),
+                PortAddress::Ipv6([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+                PortAddress::Ethernet([0x00, 0x1b, 0x19, 0x00, 0x00, 0x01]),
+            ]
+        );
+
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+unicast-master-table = ["192.168.1.300"]
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn slave_event_monitoring() {
+        use statime::config::{PortAddress, SlaveEventMonitoringConfig};
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+slave-event-monitoring = true
+slave-event-records = 4
+slave-event-destination = "192.168.1.10"
+"#,
+        )
+        .unwrap();
+        let port = actual.ports[0]
+            .clone()
+            .into_port_config(crate::config::Profile::Default);
+        assert_eq!(
+            port.slave_event_monitoring,
+            Some(SlaveEventMonitoringConfig {
+                records_per_message: 4,
+                destination: Some(PortAddress::Ipv4([192, 168, 1, 10])),
+                ..Default::default()
+            })
+        );
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+slave-event-destination = "192.168.1.10"
+"#,
+        )
+        .unwrap();
+        let port = actual.ports[0]
+            .clone()
+            .into_port_config(crate::config::Profile::Default);
+        assert_eq!(port.slave_event_monitoring, None);
+
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+slave-event-destination = "monitor"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn profile() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "gptp"
+sdo-id = 0x100
+
+[[port]]
+interface = "enp0s31f6"
+network-mode = "ethernet"
+delay-mechanism = "P2P"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::Gptp);
+
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+profile = "802.1as"
+
+[[port]]
+interface = "enp0s31f6"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn profile_conflicts() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+unicast-master-table = ["192.168.1.1"]
+sync-interval = -7
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::G8275_2);
+        assert_eq!(actual.domain(), 44);
+        assert!(actual.check_profile().is_ok());
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+unicast-negotiation = true
+delay-mechanism = "P2P"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::Profile(
+                _,
+                statime::config::ProfileError::DelayMechanism
+            ))
+        ));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+unicast-negotiation = true
+sync-interval = -8
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::Profile(
+                _,
+                statime::config::ProfileError::SyncInterval
+            ))
+        ));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::Profile(
+                _,
+                statime::config::ProfileError::UnicastNegotiation
+            ))
+        ));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+network-mode = "ethernet"
+unicast-negotiation = true
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::NetworkMode(_, _))
+        ));
+
+        for profile in ["gptp", "g8275.1", "c37.238", "aes67"] {
+            let actual: crate::config::Config = toml::from_str(&format!(
+                r#"
+profile = "{profile}"
+
+[[port]]
+interface = "enp0s31f6"
+network-mode = "ipv6"
+"#
+            ))
+            .unwrap();
+            assert!(matches!(
+                actual.check_profile(),
+                Err(crate::config::ConfigError::NetworkMode(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn unicast_max_grant_duration() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+unicast-negotiation = true
+unicast-grant-duration = 120
+
+[[port]]
+interface = "enp0s31f7"
+unicast-negotiation = true
+unicast-max-grant-duration = 600
+"#,
+        )
+        .unwrap();
+        let negotiation = |index: usize| {
+            actual.ports[index]
+                .clone()
+                .into_port_config(actual.profile)
+                .unicast_negotiation
+                .unwrap()
+        };
+        assert_eq!(negotiation(0).grant_duration, 120);
+        assert_eq!(negotiation(0).max_grant_duration, 120);
+        assert_eq!(negotiation(1).grant_duration, 300);
+        assert_eq!(negotiation(1).max_grant_duration, 600);
+
+        // G.8275.2 grants up to its maximum by default
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+unicast-negotiation = true
+"#,
+        )
+        .unwrap();
+        let port = actual.ports[0].clone().into_port_config(actual.profile);
+        assert_eq!(port.unicast_negotiation.unwrap().max_grant_duration, 1000);
+        assert!(actual.check_profile().is_ok());
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.2"
+
+[[port]]
+interface = "enp0s31f6"
+unicast-negotiation = true
+unicast-max-grant-duration = 2000
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::Profile(
+                _,
+                statime::config::ProfileError::GrantDuration
+            ))
+        ));
+    }
+
+    #[test]
+    fn profile_defaults() {
+        use statime::time::Interval;
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "g8275.1"
+
+[[port]]
+interface = "enp0s31f6"
+network-mode = "ethernet"
+ethernet-address = "non-forwardable"
+not-slave = true
+local-priority = 10
+
+[[port]]
+interface = "enp0s31f7"
+network-mode = "ethernet"
+sync-interval = -3
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::G8275_1);
+        assert_eq!(actual.domain(), 24);
+        assert_eq!(
+            actual.ports[0].ethernet_address,
+            crate::config::EthernetAddressType::NonForwardable
+        );
+
+        let port = actual.ports[0].clone().into_port_config(actual.profile);
+        assert!(port.master_only);
+        assert_eq!(port.local_priority, 10);
+        assert_eq!(port.announce_interval, Interval::from_log_2(-3));
+        assert_eq!(port.sync_interval, Interval::from_log_2(-4));
+        assert_eq!(port.min_delay_req_interval(), Interval::from_log_2(-4));
+
+        let port = actual.ports[1].clone().into_port_config(actual.profile);
+        assert!(!port.master_only);
+        assert_eq!(port.local_priority, 128);
+        assert_eq!(port.sync_interval, Interval::from_log_2(-3));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+domain = 3
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.domain(), 3);
+
+        let port = actual.ports[0].clone().into_port_config(actual.profile);
+        assert_eq!(port.announce_interval, Interval::TWO_SECONDS);
+        assert_eq!(port.sync_interval, Interval::ONE_SECOND);
+        assert_eq!(port.min_delay_req_interval(), Interval::ONE_SECOND);
+    }
+
+    #[test]
+    fn synchronization_metadata() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "st2059-2"
+
+[synchronization-metadata]
+default-system-frame-rate-numerator = 30000
+default-system-frame-rate-denominator = 1001
+master-locking-status = "locked"
+drop-frame = true
+current-local-offset = 37
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::St2059_2);
+        assert_eq!(actual.domain(), 127);
+
+        let metadata = statime::config::SynchronizationMetadata::from(
+            actual.synchronization_metadata.unwrap(),
+        );
+        assert_eq!(
+            metadata,
+            statime::config::SynchronizationMetadata {
+                default_system_frame_rate_numerator: 30000,
+                default_system_frame_rate_denominator: 1001,
+                master_locking_status: statime::config::MasterLockingStatus::Locked,
+                drop_frame: true,
+                current_local_offset: 37,
+                ..Default::default()
+            }
+        );
+
+        // The frame rate is required
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+profile = "st2059-2"
+
+[synchronization-metadata]
+master-locking-status = "locked"
+
+[[port]]
+interface = "enp0s31f6"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn power_profile() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "c37.238"
+
+[power-profile]
+grandmaster-id = 5
+time-inaccuracy = 50
+
+[alternate-time-offset]
+current-offset = 3600
+display-name = "CET"
+
+[[port]]
+interface = "enp0s31f6"
+network-mode = "ethernet"
+delay-mechanism = "P2P"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::C37_238);
+        assert!(actual.check_profile().is_ok());
+        assert_eq!(
+            statime::config::PowerProfileConfig::from(actual.power_profile.unwrap()),
+            statime::config::PowerProfileConfig {
+                grandmaster_id: 5,
+                time_inaccuracy: 50,
+            }
+        );
+        assert_eq!(
+            statime::config::AlternateTimeOffset::from(actual.alternate_time_offset.unwrap()),
+            statime::config::AlternateTimeOffset {
+                current_offset: 3600,
+                display_name: arrayvec::ArrayString::from("CET").unwrap(),
+                ..Default::default()
+            }
+        );
+
+        // The display name must fit in the TLV
+        assert!(toml::from_str::<crate::config::Config>(
+            r#"
+profile = "c37.238"
+
+[alternate-time-offset]
+current-offset = 3600
+display-name = "Central European Time"
+
+[[port]]
+interface = "enp0s31f6"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn holdover() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[holdover]
+duration = 600
+max-uncertainty = 1500
+frequency-drift = 5
+degradation = "alternative-b"
+"#,
+        )
+        .unwrap();
+
+        let config: Option<statime::config::HoldoverConfig> = actual.holdover.into();
+        let config = config.unwrap();
+        assert_eq!(config.duration, statime::time::Duration::from_secs(600));
+        assert_eq!(
+            config.max_uncertainty,
+            statime::time::Duration::from_nanos(1500)
+        );
+        assert!((config.frequency_drift - 5e-12).abs() < 1e-18);
+        assert_eq!(
+            config.degradation,
+            statime::config::ClockClassDegradation::AlternativeB
+        );
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[holdover]
+enabled = false
+"#,
+        )
+        .unwrap();
+        let config: Option<statime::config::HoldoverConfig> = actual.holdover.into();
+        assert!(config.is_none());
+    }
+
+    #[test]
+    fn statistics() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[statistics]
+path = "/var/log/statime/statistics.jsonl"
+format = "json-lines"
+max-size = 1000000
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            actual.statistics,
+            Some(crate::config::StatisticsConfig {
+                path: "/var/log/statime/statistics.jsonl".into(),
+                format: crate::config::StatisticsFormat::JsonLines,
+                max_size: 1_000_000,
+                max_files: 5,
+            })
+        );
+
+        let actual: Result<crate::config::Config, _> = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[statistics]
+format = "csv"
+"#,
+        );
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn aes67() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "aes67"
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual.profile, crate::config::Profile::Aes67);
+        assert_eq!(actual.domain(), 0);
+        assert!(actual.check_profile().is_ok());
+
+        let port = actual.ports[0].clone().into_port_config(actual.profile);
+        assert_eq!(
+            port.announce_interval,
+            statime::time::Interval::from_log_2(-1)
+        );
+        assert_eq!(port.sync_interval, statime::time::Interval::from_log_2(-3));
+        assert_eq!(port.announce_receipt_timeout, 3);
+        assert_eq!(
+            port,
+            statime::config::Profile::Aes67.default_port_config(None)
+        );
+
+        // Out of range values are rejected
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+profile = "aes67"
+
+[[port]]
+interface = "enp0s31f6"
+sync-interval = -6
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_profile(),
+            Err(crate::config::ConfigError::Profile(
+                _,
+                statime::config::ProfileError::SyncInterval
+            ))
+        ));
+    }
+
+    #[test]
+    fn time_source() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[time-source]
+type = "gnss"
+nmea-device = "/dev/ttyACM0"
+pps-device = "/dev/pps0"
+degradation = "alternative-b"
+leap-seconds-file = "/usr/share/zoneinfo/leap-seconds.list"
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert!(actual.check_time_source().is_ok());
+        let source = actual.time_source.unwrap();
+        assert_eq!(source.source_type, crate::config::TimeSourceType::Gnss);
+        assert_eq!(
+            source.pps_device,
+            Some(std::path::PathBuf::from("/dev/pps0"))
+        );
+        assert_eq!(source.holdover, 300);
+        assert_eq!(
+            source.leap_seconds_file,
+            Some(std::path::PathBuf::from(
+                "/usr/share/zoneinfo/leap-seconds.list"
+            ))
+        );
+        assert_eq!(
+            source.degradation,
+            statime::config::ClockClassDegradation::AlternativeB
+        );
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[time-source]
+type = "phc"
+
+[[port]]
+interface = "enp0s31f6"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_time_source(),
+            Err(crate::config::ConfigError::TimeSource(_))
+        ));
+    }
+
+    #[test]
+    fn security() {
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[security]
+spp = 2
+key-id = 5
+
+[[security.key]]
+id = 5
+secret = "000102030405060708090a0b0c0d0e0f"
+
+[[security.key]]
+id = 6
+algorithm = "aes256-cmac"
+secret = "000102030405060708090a0b0c0d0e0f000102030405060708090a0b0c0d0e0f"
+valid-from = 1700000000
+"#,
+        )
+        .unwrap();
+        assert!(actual.check_security().is_ok());
+
+        let mut provider =
+            crate::security::key_provider(actual.security.as_ref().unwrap()).unwrap();
+        let config = statime::config::KeyProvider::security_config(provider.as_mut());
+        assert_eq!(config.spp, 2);
+        assert_eq!(config.key_id, 5);
+        assert_eq!(config.keys.len(), 2);
+        assert_eq!(
+            config.keys[0].algorithm,
+            statime::config::IntegrityAlgorithm::HmacSha256_128
+        );
+        assert_eq!(config.keys[0].secret[..], (0..16).collect::<Vec<u8>>());
+        assert_eq!(
+            config.keys[1].algorithm,
+            statime::config::IntegrityAlgorithm::Aes256Cmac
+        );
+        assert_eq!(
+            config.keys[1].valid_from,
+            Some(statime::time::Time::from_secs(1700000000))
+        );
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[security]
+key-file = "/etc/statime/keys.toml"
+"#,
+        )
+        .unwrap();
+        assert!(actual.check_security().is_ok());
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[security]
+key-file = "/etc/statime/keys.toml"
+key-id = 1
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_security(),
+            Err(crate::config::ConfigError::Security(_))
+        ));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+
+[security]
+key-id = 1
+
+[[security.key]]
+id = 1
+algorithm = "aes128-cmac"
+secret = "0001"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_security(),
+            Err(crate::config::ConfigError::Security(_))
+        ));
+
+        let actual: crate::config::Config = toml::from_str(
+            r#"
+[[port]]
+interface = "enp0s31f6"
+hardware-clock = "/dev/ptp0"
+one-step = true
+
+[security]
+key-id = 1
+
+[[security.key]]
+id = 1
+secret = "0001"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            actual.check_security(),
+            Err(crate::config::ConfigError::Security(_))
+        ));
+    }
 }
@@ -0,0 +1,189 @@
+//! The leap second table of a grandmaster
+//!
+//! The table is read from a file in the format of the `leap-seconds.list`
+//! published by the IERS, as shipped by most distributions (for instance in
+//! `/usr/share/zoneinfo/leap-seconds.list`). It tells the grandmaster the
+//! current offset of UTC to TAI, and when the next leap second is to be
+//! announced.
+
+use std::path::Path;
+
+use statime::config::LeapIndicator;
+
+/// Seconds from the NTP epoch (1900) to the unix epoch (1970)
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug)]
+pub enum LeapSecondTableError {
+    Io(std::io::Error),
+    /// A line of the table could not be parsed
+    Parse(usize),
+    Empty,
+}
+
+impl std::fmt::Display for LeapSecondTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeapSecondTableError::Io(e) => write!(f, "io error while reading leap seconds: {e}"),
+            LeapSecondTableError::Parse(line) => {
+                write!(f, "invalid leap second entry on line {line}")
+            }
+            LeapSecondTableError::Empty => write!(f, "no leap second entries"),
+        }
+    }
+}
+
+impl std::error::Error for LeapSecondTableError {}
+
+impl From<std::io::Error> for LeapSecondTableError {
+    fn from(value: std::io::Error) -> Self {
+        LeapSecondTableError::Io(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeapSecondTable {
+    /// Unix time from which each offset of TAI to UTC applies, in order
+    entries: Vec<(u64, i16)>,
+    /// Unix time after which the table can no longer be trusted
+    expires: Option<u64>,
+}
+
+impl LeapSecondTable {
+    pub fn from_file(path: &Path) -> Result<Self, LeapSecondTableError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    fn parse(contents: &str) -> Result<Self, LeapSecondTableError> {
+        let mut entries = Vec::new();
+        let mut expires = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            let error = || LeapSecondTableError::Parse(index + 1);
+
+            if let Some(expiry) = line.strip_prefix("#@") {
+                let expiry: u64 = expiry.trim().parse().map_err(|_| error())?;
+                expires = Some(expiry.checked_sub(NTP_UNIX_OFFSET).ok_or_else(error)?);
+                continue;
+            }
+
+            let data = line.split('#').next().unwrap_or_default();
+            let mut fields = data.split_whitespace();
+            let (Some(start), Some(offset)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+
+            let start: u64 = start.parse().map_err(|_| error())?;
+            let offset = offset.parse().map_err(|_| error())?;
+            entries.push((
+                start.checked_sub(NTP_UNIX_OFFSET).ok_or_else(error)?,
+                offset,
+            ));
+        }
+
+        if entries.is_empty() {
+            return Err(LeapSecondTableError::Empty);
+        }
+        entries.sort_unstable();
+
+        Ok(Self { entries, expires })
+    }
+
+    /// Whether the table expired at unix time `now`
+    pub fn expired(&self, now: u64) -> bool {
+        matches!(self.expires, Some(expires) if now >= expires)
+    }
+
+    /// The offset of TAI to UTC at unix time `now`, and the leap second to
+    /// announce when one occurs at the end of the current UTC day
+    pub fn lookup(&self, now: u64) -> Option<(i16, LeapIndicator)> {
+        let next = self.entries.partition_point(|&(start, _)| start <= now);
+        let (_, offset) = *self.entries.get(next.checked_sub(1)?)?;
+
+        let end_of_day = (now / SECONDS_PER_DAY + 1) * SECONDS_PER_DAY;
+        let leap_indicator = match self.entries.get(next) {
+            Some(&(start, next_offset)) if start == end_of_day => match next_offset.cmp(&offset) {
+                std::cmp::Ordering::Greater => LeapIndicator::Leap61,
+                std::cmp::Ordering::Less => LeapIndicator::Leap59,
+                std::cmp::Ordering::Equal => LeapIndicator::NoLeap,
+            },
+            _ => LeapIndicator::NoLeap,
+        };
+
+        Some((offset, leap_indicator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "\
+#	Updated through IERS Bulletin C
+#$	 3913697179
+#@	3960057600
+#
+2272060800	10	# 1 Jan 1972
+3644697600	36	# 1 Jul 2015
+3692217600	37	# 1 Jan 2017
+#h	16edd0f0 3666784f 37db6bdd e74ced87 59af48f1
+";
+
+    // 1 Jan 2017 in unix time
+    const LEAP_2017: u64 = 3692217600 - NTP_UNIX_OFFSET;
+
+    #[test]
+    fn parse() {
+        let table = LeapSecondTable::parse(TABLE).unwrap();
+        assert_eq!(table.entries.len(), 3);
+        assert_eq!(table.entries[0], (63072000, 10));
+        assert_eq!(table.expires, Some(3960057600 - NTP_UNIX_OFFSET));
+
+        assert!(!table.expired(LEAP_2017));
+        assert!(table.expired(3960057600 - NTP_UNIX_OFFSET));
+
+        assert!(matches!(
+            LeapSecondTable::parse("2272060800\tten\n"),
+            Err(LeapSecondTableError::Parse(1))
+        ));
+        assert!(matches!(
+            LeapSecondTable::parse("# nothing\n"),
+            Err(LeapSecondTableError::Empty)
+        ));
+    }
+
+    #[test]
+    fn lookup() {
+        let table = LeapSecondTable::parse(TABLE).unwrap();
+
+        assert_eq!(table.lookup(0), None);
+        assert_eq!(
+            table.lookup(LEAP_2017 - 2 * SECONDS_PER_DAY),
+            Some((36, LeapIndicator::NoLeap))
+        );
+        // During the last day before the leap second it is announced
+        assert_eq!(
+            table.lookup(LEAP_2017 - SECONDS_PER_DAY),
+            Some((36, LeapIndicator::Leap61))
+        );
+        assert_eq!(
+            table.lookup(LEAP_2017 - 1),
+            Some((36, LeapIndicator::Leap61))
+        );
+        assert_eq!(table.lookup(LEAP_2017), Some((37, LeapIndicator::NoLeap)));
+        assert_eq!(
+            table.lookup(LEAP_2017 + 1000 * SECONDS_PER_DAY),
+            Some((37, LeapIndicator::NoLeap))
+        );
+    }
+
+    #[test]
+    fn negative_leap_second() {
+        let table = LeapSecondTable::parse("2272060800 10\n2272147200 9\n").unwrap();
+        assert_eq!(
+            table.lookup(2272060800 - NTP_UNIX_OFFSET),
+            Some((10, LeapIndicator::Leap59))
+        );
+    }
+}
@@ -2,13 +2,20 @@ extern crate core;
 
 pub mod clock;
 pub mod config;
+pub mod leap_seconds;
 pub mod metrics;
 pub mod observer;
+pub mod one_step;
+pub mod pmc;
+pub mod security;
 pub mod socket;
+pub mod statistics;
+pub mod time_source;
 pub mod tlvforwarder;
 
 use fern::colors::Color;
 pub use metrics::exporter::main as metrics_exporter_main;
+pub use pmc::main as pmc_main;
 
 pub fn setup_logger(level: log::LevelFilter) -> Result<(), fern::InitError> {
     let colors = fern::colors::ColoredLevelConfig::new()
@@ -8,22 +8,37 @@ use std::{
 use clap::Parser;
 use rand::{rngs::StdRng, SeedableRng};
 use statime::{
-    config::{ClockIdentity, InstanceConfig, SdoId, TimePropertiesDS, TimeSource},
+    config::{
+        ClockIdentity, DelayMechanism, InstanceConfig, KeyProvider, LeapIndicator, SdoId,
+        TimePropertiesDS, TimeSource, TransparentClockConfig,
+    },
     filters::{Filter, KalmanConfiguration, KalmanFilter},
+    observability::{
+        performance::{FIFTEEN_MINUTE_RECORDS, PERFORMANCE_RECORDS},
+        port::PortState,
+        security::AuthenticationCounters,
+    },
     port::{
-        InBmca, Measurement, Port, PortAction, PortActionIterator, TimestampContext, MAX_DATA_LEN,
+        ForwardedMessage, InBmca, Measurement, Port, PortAction, PortActionIterator,
+        TimestampContext, TransparentPort, TransparentPortAction, TransparentPortActionIterator,
+        MAX_DATA_LEN,
     },
-    time::Time,
-    PtpInstance,
+    time::{Interval, Time},
+    PtpInstance, TransparentClock,
 };
 use statime_linux::{
     clock::LinuxClock,
-    config::Config,
-    observer::ObservableInstanceState,
+    config::{ClockType, Config, DelayType, EthernetAddressType},
+    observer::{ObservableInstanceState, ObservablePortState},
+    one_step::{enable_one_step, OneStepAddress, OneStepSocket},
     socket::{
         open_ethernet_socket, open_ipv4_event_socket, open_ipv4_general_socket,
         open_ipv6_event_socket, open_ipv6_general_socket, timestamp_to_time, PtpTargetAddress,
     },
+    statistics::{
+        StatisticsEvent, StatisticsFilter, StatisticsFilterConfig, StatisticsSink, StatisticsSource,
+    },
+    time_source::TimeSourceMonitor,
     tlvforwarder::TlvForwarder,
 };
 use timestamped_socket::{
@@ -32,7 +47,10 @@ use timestamped_socket::{
     socket::{InterfaceTimestampMode, Open, Socket},
 };
 use tokio::{
-    sync::mpsc::{Receiver, Sender},
+    sync::{
+        broadcast,
+        mpsc::{Receiver, Sender},
+    },
     time::Sleep,
 };
 
@@ -135,16 +153,26 @@ enum ClockSyncMode {
     ToSystem,
 }
 
-fn start_clock_task(clock: LinuxClock) -> tokio::sync::watch::Sender<ClockSyncMode> {
+fn start_clock_task(
+    clock: LinuxClock,
+    statistics: StatisticsSink,
+    id: usize,
+) -> tokio::sync::watch::Sender<ClockSyncMode> {
     let (mode_sender, mode_receiver) = tokio::sync::watch::channel(ClockSyncMode::FromSystem);
 
-    tokio::spawn(clock_task(clock, mode_receiver));
+    let filter_config = StatisticsFilterConfig {
+        inner: KalmanConfiguration::default(),
+        sink: statistics,
+        source: StatisticsSource::Clock(id),
+    };
+    tokio::spawn(clock_task(clock, filter_config, mode_receiver));
 
     mode_sender
 }
 
 async fn clock_task(
     clock: LinuxClock,
+    filter_config: StatisticsFilterConfig<KalmanConfiguration>,
     mut mode_receiver: tokio::sync::watch::Receiver<ClockSyncMode>,
 ) {
     let mut measurement_timer = pin!(Timer::new());
@@ -152,7 +180,7 @@ async fn clock_task(
 
     measurement_timer.as_mut().reset(std::time::Duration::ZERO);
 
-    let mut filter = KalmanFilter::new(KalmanConfiguration::default());
+    let mut filter = StatisticsFilter::<KalmanFilter>::new(filter_config.clone());
 
     let mut current_mode = *mode_receiver.borrow_and_update();
     let mut filter_clock = match current_mode {
@@ -162,36 +190,39 @@ async fn clock_task(
     loop {
         tokio::select! {
             () = &mut measurement_timer => {
-                let (t1, t2, t3) = clock.system_offset().expect("Unable to determine offset from system clock");
-
-                log::debug!("Interclock measurement: {} {} {}", t1, t2, t3);
-
-                let delay = (t3-t1)/2;
-                let offset_a = t2 - t1;
-                let offset_b = t3 - t2;
-
-                let m = match current_mode {
-                    ClockSyncMode::FromSystem => Measurement {
-                        event_time: t2,
-                        offset: Some(offset_a - delay),
-                        delay: Some(delay),
-                        peer_delay: None,
-                        raw_sync_offset: Some(offset_a),
-                        raw_delay_offset: Some(-offset_b),
-                    },
-                    ClockSyncMode::ToSystem => Measurement {
-                        event_time: t1+delay,
-                        offset: Some(offset_b - delay),
-                        delay: Some(delay),
-                        peer_delay: None,
-                        raw_sync_offset: Some(offset_b),
-                        raw_delay_offset: Some(-offset_a),
-                    },
-                };
+                let offset = clock.system_offset().expect("Unable to determine offset from system clock");
+                if let Some((t1, t2, t3)) = offset {
+                    log::debug!("Interclock measurement: {} {} {}", t1, t2, t3);
+
+                    let delay = (t3-t1)/2;
+                    let offset_a = t2 - t1;
+                    let offset_b = t3 - t2;
+
+                    let m = match current_mode {
+                        ClockSyncMode::FromSystem => Measurement {
+                            event_time: t2,
+                            offset: Some(offset_a - delay),
+                            delay: Some(delay),
+                            peer_delay: None,
+                            raw_sync_offset: Some(offset_a),
+                            raw_delay_offset: Some(-offset_b),
+                        },
+                        ClockSyncMode::ToSystem => Measurement {
+                            event_time: t1+delay,
+                            offset: Some(offset_b - delay),
+                            delay: Some(delay),
+                            peer_delay: None,
+                            raw_sync_offset: Some(offset_b),
+                            raw_delay_offset: Some(-offset_a),
+                        },
+                    };
 
-                let update = filter.measurement(m, &mut filter_clock);
-                if let Some(timeout) = update.next_update {
-                    update_timer.as_mut().reset(timeout);
+                    let update = filter.measurement(m, &mut filter_clock);
+                    if let Some(timeout) = update.next_update {
+                        update_timer.as_mut().reset(timeout);
+                    }
+                } else {
+                    log::debug!("Skipping interclock measurement during leap second");
                 }
 
                 measurement_timer.as_mut().reset(std::time::Duration::from_millis(250));
@@ -205,7 +236,7 @@ async fn clock_task(
             _ = mode_receiver.changed() => {
                 let new_mode = *mode_receiver.borrow_and_update();
                 if new_mode != current_mode {
-                    let mut new_filter = KalmanFilter::new(KalmanConfiguration::default());
+                    let mut new_filter = StatisticsFilter::new(filter_config.clone());
                     std::mem::swap(&mut filter, &mut new_filter);
                     new_filter.demobilize(&mut filter_clock);
                     match new_mode {
@@ -242,17 +273,40 @@ async fn actual_main() {
 
     log::info!("Clock identity: {}", hex::encode(clock_identity.0));
 
+    let statistics = match &config.statistics {
+        Some(statistics) => {
+            StatisticsSink::spawn(statistics).expect("Could not open the statistics file")
+        }
+        None => StatisticsSink::default(),
+    };
+
+    if matches!(
+        config.clock_type,
+        ClockType::E2eTransparent | ClockType::P2pTransparent
+    ) {
+        run_transparent_clock(config, clock_identity, statistics).await
+    }
+
     let instance_config = InstanceConfig {
         clock_identity,
         priority_1: config.priority1,
         priority_2: config.priority2,
-        domain_number: config.domain,
+        domain_number: config.domain(),
         slave_only: false,
         sdo_id: SdoId::try_from(config.sdo_id).expect("sdo-id should be between 0 and 4095"),
+        profile: config.profile.into(),
+        local_priority: config.local_priority,
     };
 
-    let time_properties_ds =
-        TimePropertiesDS::new_arbitrary_time(false, false, TimeSource::InternalOscillator);
+    // Without a time source we announce the PTP timescale of the system clock,
+    // without claiming it is traceable
+    let time_properties_ds = TimePropertiesDS::new_ptp_time(
+        None,
+        LeapIndicator::NoLeap,
+        false,
+        false,
+        TimeSource::InternalOscillator,
+    );
 
     // Leak to get a static reference, the ptp instance will be around for the rest
     // of the program anyway
@@ -260,6 +314,27 @@ async fn actual_main() {
         instance_config,
         time_properties_ds,
     )));
+    instance.set_synchronization_metadata(config.synchronization_metadata.map(Into::into));
+    instance.set_power_profile(config.power_profile.map(Into::into));
+    instance.set_alternate_time_offset(config.alternate_time_offset.map(Into::into));
+    instance.set_holdover_config(config.holdover.into());
+
+    let mut key_provider = config.security.as_ref().map(|security| {
+        statime_linux::security::key_provider(security).expect("Could not read the keys")
+    });
+    if let Some(key_provider) = &mut key_provider {
+        instance.update_keys(key_provider.as_mut(), statime_linux::security::now());
+    }
+
+    let mut time_source = config
+        .time_source
+        .as_ref()
+        .map(|source| TimeSourceMonitor::new(source).expect("Could not open the time source"));
+    if let Some(time_source) = &mut time_source {
+        let (clock_quality, time_properties_ds) = time_source.update();
+        instance.set_clock_quality(clock_quality);
+        instance.set_time_properties_ds(time_properties_ds);
+    }
 
     // The observer for the metrics exporter
     let (instance_state_sender, instance_state_receiver) =
@@ -268,6 +343,12 @@ async fn actual_main() {
             current_ds: instance.current_ds(),
             parent_ds: instance.parent_ds(),
             time_properties_ds: instance.time_properties_ds(),
+            synchronization_metadata: instance.synchronization_metadata(),
+            power_profile: instance.power_profile(),
+            alternate_time_offset: instance.alternate_time_offset(),
+            holdover: instance.holdover(),
+            authentication: Default::default(),
+            ports: vec![],
         });
     statime_linux::observer::spawn(&config, instance_state_receiver).await;
 
@@ -288,6 +369,7 @@ async fn actual_main() {
     for port_config in config.ports {
         let interface = port_config.interface;
         let network_mode = port_config.network_mode;
+        let non_forwardable = port_config.ethernet_address == EthernetAddressType::NonForwardable;
         let (port_clock, timestamping) = match &port_config.hardware_clock {
             Some(path) => {
                 let clock = LinuxClock::open(path).expect("Unable to open clock");
@@ -297,7 +379,11 @@ async fn actual_main() {
                     let id = internal_sync_senders.len();
                     clock_port_map.push(Some(id));
                     clock_name_map.insert(path.clone(), id);
-                    internal_sync_senders.push(start_clock_task(clock.clone()));
+                    internal_sync_senders.push(start_clock_task(
+                        clock.clone(),
+                        statistics.clone(),
+                        id,
+                    ));
                 }
                 (clock, InterfaceTimestampMode::HardwarePTPAll)
             }
@@ -307,10 +393,20 @@ async fn actual_main() {
             }
         };
 
+        let one_step = port_config.one_step && port_config.hardware_clock.is_some();
+        let one_step_p2p = port_config.delay_mechanism == DelayType::P2P;
+
+        // Ports are numbered in the order they are added
+        let port_number = instance.default_ds().number_ports;
+
         let rng = StdRng::from_entropy();
         let port = instance.add_port(
-            port_config.into(),
-            KalmanConfiguration::default(),
+            port_config.into_port_config(config.profile),
+            StatisticsFilterConfig {
+                inner: KalmanConfiguration::default(),
+                sink: statistics.clone(),
+                source: StatisticsSource::Port(port_number),
+            },
             port_clock.clone(),
             rng,
         );
@@ -331,12 +427,19 @@ async fn actual_main() {
                     .expect("Could not open event socket");
                 let general_socket =
                     open_ipv4_general_socket(interface).expect("Could not open general socket");
+                let one_step_socket = one_step.then(|| {
+                    enable_one_step(interface, one_step_p2p).expect("Could not enable one-step");
+                    OneStepSocket::open_ipv4(interface).expect("Could not open one-step socket")
+                });
 
                 tokio::spawn(port_task(
                     port_task_receiver,
                     port_task_sender,
-                    event_socket,
-                    general_socket,
+                    IpSockets {
+                        event: event_socket,
+                        general: general_socket,
+                        one_step: one_step_socket,
+                    },
                     bmca_notify_receiver.clone(),
                     tlv_forwarder.duplicate(),
                     port_clock,
@@ -347,12 +450,19 @@ async fn actual_main() {
                     .expect("Could not open event socket");
                 let general_socket =
                     open_ipv6_general_socket(interface).expect("Could not open general socket");
+                let one_step_socket = one_step.then(|| {
+                    enable_one_step(interface, one_step_p2p).expect("Could not enable one-step");
+                    OneStepSocket::open_ipv6(interface).expect("Could not open one-step socket")
+                });
 
                 tokio::spawn(port_task(
                     port_task_receiver,
                     port_task_sender,
-                    event_socket,
-                    general_socket,
+                    IpSockets {
+                        event: event_socket,
+                        general: general_socket,
+                        one_step: one_step_socket,
+                    },
                     bmca_notify_receiver.clone(),
                     tlv_forwarder.duplicate(),
                     port_clock,
@@ -361,14 +471,25 @@ async fn actual_main() {
             statime_linux::config::NetworkMode::Ethernet => {
                 let socket =
                     open_ethernet_socket(interface, timestamping).expect("Could not open socket");
+                let one_step_socket = one_step.then(|| {
+                    enable_one_step(interface, one_step_p2p).expect("Could not enable one-step");
+                    OneStepSocket::open_ethernet().expect("Could not open one-step socket")
+                });
 
                 tokio::spawn(ethernet_port_task(
                     port_task_receiver,
                     port_task_sender,
-                    interface
-                        .get_index()
-                        .expect("Unable to get network interface index") as _,
-                    socket,
+                    EthernetInterface {
+                        index: interface
+                            .get_index()
+                            .expect("Unable to get network interface index")
+                            as _,
+                        non_forwardable,
+                    },
+                    EthernetSockets {
+                        socket,
+                        one_step: one_step_socket,
+                    },
                     bmca_notify_receiver.clone(),
                     tlv_forwarder.duplicate(),
                     port_clock,
@@ -380,6 +501,22 @@ async fn actual_main() {
     // Drop the forwarder so we don't keep an unneeded subscriber.
     drop(tlv_forwarder);
 
+    // A PHC time source is never steered, the system clock and through it the
+    // other clocks follow it instead
+    let source_clock = config
+        .time_source
+        .as_ref()
+        .and_then(|source| source.phc_device.as_ref())
+        .map(|path| match clock_name_map.get(path) {
+            Some(id) => *id,
+            None => {
+                let clock = LinuxClock::open(path).expect("Unable to open clock");
+                let id = internal_sync_senders.len();
+                internal_sync_senders.push(start_clock_task(clock, statistics.clone(), id));
+                id
+            }
+        });
+
     // All ports created, so we can start running them.
     for (i, port) in ports.into_iter().enumerate() {
         main_task_senders[i]
@@ -394,25 +531,55 @@ async fn actual_main() {
         instance_state_sender,
         main_task_receivers,
         main_task_senders,
-        internal_sync_senders,
-        clock_port_map,
+        InstanceInputs {
+            internal_sync_senders,
+            clock_port_map,
+            time_source,
+            key_provider,
+            source_clock,
+            statistics,
+        },
     )
     .await
 }
 
+// Everything besides the ports that the main task uses at every BMCA
+struct InstanceInputs {
+    internal_sync_senders: Vec<tokio::sync::watch::Sender<ClockSyncMode>>,
+    // The clock of each port, as index into internal_sync_senders
+    clock_port_map: Vec<Option<usize>>,
+    time_source: Option<TimeSourceMonitor>,
+    key_provider: Option<Box<dyn KeyProvider + Send>>,
+    // Clock that is never steered, as it is the source of our time
+    source_clock: Option<usize>,
+    statistics: StatisticsSink,
+}
+
 async fn run(
-    instance: &'static PtpInstance<KalmanFilter>,
+    instance: &'static PtpInstance<StatisticsFilter<KalmanFilter>>,
     bmca_notify_sender: tokio::sync::watch::Sender<bool>,
     instance_state_sender: tokio::sync::watch::Sender<ObservableInstanceState>,
     mut main_task_receivers: Vec<Receiver<BmcaPort>>,
     main_task_senders: Vec<Sender<BmcaPort>>,
-    internal_sync_senders: Vec<tokio::sync::watch::Sender<ClockSyncMode>>,
-    clock_port_map: Vec<Option<usize>>,
+    inputs: InstanceInputs,
 ) -> ! {
+    let InstanceInputs {
+        internal_sync_senders,
+        clock_port_map,
+        mut time_source,
+        mut key_provider,
+        source_clock,
+        statistics,
+    } = inputs;
+
     // run bmca over all of the ports at the same time. The ports don't perform
     // their normal actions at this time: bmca is stop-the-world!
     let mut bmca_timer = pin!(Timer::new());
 
+    // Ports start out listening. Transitions are recorded around the BMCA, so
+    // those made by the port tasks are recorded at the next BMCA.
+    let mut port_states = vec![PortState::Listening; main_task_receivers.len()];
+
     loop {
         // reset bmca timer
         bmca_timer.as_mut().reset(instance.bmca_interval());
@@ -441,7 +608,26 @@ async fn run(
             mut_bmca_ports.push(mut_bmca_port);
         }
 
+        // Announce the state of our time source, for when we are the grandmaster
+        if let Some(time_source) = &mut time_source {
+            let (clock_quality, time_properties_ds) = time_source.update();
+            instance.set_clock_quality(clock_quality);
+            instance.set_time_properties_ds(time_properties_ds);
+        }
+
+        // Pick up rotated keys
+        if let Some(key_provider) = &mut key_provider {
+            instance.update_keys(key_provider.as_mut(), statime_linux::security::now());
+        }
+
+        record_port_states(&statistics, &mut_bmca_ports, &mut port_states);
         instance.bmca(&mut mut_bmca_ports);
+        record_port_states(&statistics, &mut_bmca_ports, &mut port_states);
+
+        let mut authentication = AuthenticationCounters::default();
+        for port in mut_bmca_ports.iter() {
+            authentication += port.authentication_counters();
+        }
 
         // Update instance state for observability
         // We don't care if isn't anybody on the other side
@@ -450,6 +636,25 @@ async fn run(
             current_ds: instance.current_ds(),
             parent_ds: instance.parent_ds(),
             time_properties_ds: instance.time_properties_ds(),
+            synchronization_metadata: instance.synchronization_metadata(),
+            power_profile: instance.power_profile(),
+            alternate_time_offset: instance.alternate_time_offset(),
+            holdover: instance.holdover(),
+            authentication,
+            ports: mut_bmca_ports
+                .iter()
+                .map(|port| ObservablePortState {
+                    port_ds: port.port_ds(),
+                    counters: port.port_counters(),
+                    fifteen_minute_records: (0..FIFTEEN_MINUTE_RECORDS)
+                        .map_while(|index| port.performance_record(index))
+                        .collect(),
+                    twenty_four_hour_records: (FIFTEEN_MINUTE_RECORDS..PERFORMANCE_RECORDS)
+                        .map_while(|index| port.performance_record(index))
+                        .collect(),
+                    slave_event_reports: port.slave_event_reports().to_vec(),
+                })
+                .collect(),
         });
 
         let mut clock_states = vec![ClockSyncMode::FromSystem; internal_sync_senders.len()];
@@ -460,6 +665,9 @@ async fn run(
                 }
             }
         }
+        if let Some(id) = source_clock {
+            clock_states[id] = ClockSyncMode::ToSystem;
+        }
         for (mode, sender) in clock_states.into_iter().zip(internal_sync_senders.iter()) {
             sender.send(mode).expect("Clock mode change failed");
         }
@@ -472,7 +680,41 @@ async fn run(
     }
 }
 
-type BmcaPort = Port<InBmca<'static>, Option<Vec<ClockIdentity>>, StdRng, LinuxClock, KalmanFilter>;
+fn record_port_states(
+    statistics: &StatisticsSink,
+    ports: &[&mut BmcaPort],
+    port_states: &mut [PortState],
+) {
+    for (port, last_state) in ports.iter().zip(port_states.iter_mut()) {
+        let port_ds = port.port_ds();
+        if port_ds.port_state != *last_state {
+            statistics.record(
+                StatisticsSource::Port(port_ds.port_identity.port_number),
+                StatisticsEvent::PortState {
+                    from: *last_state,
+                    to: port_ds.port_state,
+                },
+            );
+            *last_state = port_ds.port_state;
+        }
+    }
+}
+
+type BmcaPort = Port<
+    InBmca<'static>,
+    Option<Vec<ClockIdentity>>,
+    StdRng,
+    LinuxClock,
+    StatisticsFilter<KalmanFilter>,
+>;
+
+// The sockets of a port using UDP over IPv4 or IPv6
+struct IpSockets<A> {
+    event: Socket<A, Open>,
+    general: Socket<A, Open>,
+    // Only for ports sending one-step messages
+    one_step: Option<OneStepSocket>,
+}
 
 // the Port task
 //
@@ -480,21 +722,27 @@ type BmcaPort = Port<InBmca<'static>, Option<Vec<ClockIdentity>>, StdRng, LinuxC
 // It will then move the port into the running state, and process actions. When
 // the task is notified of a BMCA, it will stop running, move the port into the
 // bmca state, and send it on its Sender
-async fn port_task<A: NetworkAddress + PtpTargetAddress>(
+async fn port_task<A: NetworkAddress + PtpTargetAddress + OneStepAddress>(
     mut port_task_receiver: Receiver<BmcaPort>,
     port_task_sender: Sender<BmcaPort>,
-    mut event_socket: Socket<A, Open>,
-    mut general_socket: Socket<A, Open>,
+    sockets: IpSockets<A>,
     mut bmca_notify: tokio::sync::watch::Receiver<bool>,
     mut tlv_forwarder: TlvForwarder,
     clock: LinuxClock,
 ) {
+    let IpSockets {
+        event: mut event_socket,
+        general: mut general_socket,
+        one_step: one_step_socket,
+    } = sockets;
+
     let mut timers = Timers {
         port_sync_timer: pin!(Timer::new()),
         port_announce_timer: pin!(Timer::new()),
         port_announce_timeout_timer: pin!(Timer::new()),
         delay_request_timer: pin!(Timer::new()),
         filter_update_timer: pin!(Timer::new()),
+        unicast_timer: pin!(Timer::new()),
     };
 
     loop {
@@ -507,6 +755,7 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
             actions,
             &mut event_socket,
             &mut general_socket,
+            one_step_socket.as_ref(),
             &mut timers,
             &tlv_forwarder,
             &clock,
@@ -518,6 +767,7 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
                 port.handle_send_timestamp(context, timestamp),
                 &mut event_socket,
                 &mut general_socket,
+                one_step_socket.as_ref(),
                 &mut timers,
                 &tlv_forwarder,
                 &clock,
@@ -537,7 +787,7 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
                             // correction when this port uses software timestamping
                             timestamp.seconds += clock.get_tai_offset().expect("Unable to get tai offset") as i64;
                             log::trace!("Recv timestamp: {:?}", packet.timestamp);
-                            port.handle_event_receive(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp))
+                            port.handle_event_receive_from(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp), packet.remote_addr.port_address())
                         } else {
                             log::error!("Missing recv timestamp");
                             PortActionIterator::empty()
@@ -546,7 +796,7 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
                     Err(error) => panic!("Error receiving: {error:?}"),
                 },
                 result = general_socket.recv(&mut general_buffer) => match result {
-                    Ok(packet) => port.handle_general_receive(&general_buffer[..packet.bytes_read]),
+                    Ok(packet) => port.handle_general_receive_from(&general_buffer[..packet.bytes_read], packet.remote_addr.port_address()),
                     Err(error) => panic!("Error receiving: {error:?}"),
                 },
                 () = &mut timers.port_announce_timer => {
@@ -564,6 +814,9 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
                 () = &mut timers.filter_update_timer => {
                     port.handle_filter_update_timer()
                 },
+                () = &mut timers.unicast_timer => {
+                    port.handle_unicast_timer()
+                },
                 result = bmca_notify.wait_for(|v| *v) => match result {
                     Ok(_) => break,
                     Err(error) => panic!("Error on bmca notify: {error:?}"),
@@ -575,6 +828,7 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
                     actions,
                     &mut event_socket,
                     &mut general_socket,
+                    one_step_socket.as_ref(),
                     &mut timers,
                     &tlv_forwarder,
                     &clock,
@@ -594,6 +848,13 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
     }
 }
 
+// The sockets of a port using ethernet
+struct EthernetSockets {
+    socket: Socket<EthernetAddress, Open>,
+    // Only for ports sending one-step messages
+    one_step: Option<OneStepSocket>,
+}
+
 // the Port task for ethernet transport
 //
 // This task waits for a new port (in the bmca state) to arrive on its Receiver.
@@ -603,18 +864,24 @@ async fn port_task<A: NetworkAddress + PtpTargetAddress>(
 async fn ethernet_port_task(
     mut port_task_receiver: Receiver<BmcaPort>,
     port_task_sender: Sender<BmcaPort>,
-    interface: libc::c_int,
-    mut socket: Socket<EthernetAddress, Open>,
+    interface: EthernetInterface,
+    sockets: EthernetSockets,
     mut bmca_notify: tokio::sync::watch::Receiver<bool>,
     mut tlv_forwarder: TlvForwarder,
     clock: LinuxClock,
 ) {
+    let EthernetSockets {
+        mut socket,
+        one_step: one_step_socket,
+    } = sockets;
+
     let mut timers = Timers {
         port_sync_timer: pin!(Timer::new()),
         port_announce_timer: pin!(Timer::new()),
         port_announce_timeout_timer: pin!(Timer::new()),
         delay_request_timer: pin!(Timer::new()),
         filter_update_timer: pin!(Timer::new()),
+        unicast_timer: pin!(Timer::new()),
     };
 
     loop {
@@ -633,6 +900,7 @@ async fn ethernet_port_task(
             actions,
             interface,
             &mut socket,
+            one_step_socket.as_ref(),
             &mut timers,
             &tlv_forwarder,
             &clock,
@@ -644,6 +912,7 @@ async fn ethernet_port_task(
                 port.handle_send_timestamp(context, timestamp),
                 interface,
                 &mut socket,
+                one_step_socket.as_ref(),
                 &mut timers,
                 &tlv_forwarder,
                 &clock,
@@ -662,9 +931,9 @@ async fn ethernet_port_task(
                             // correction when this port uses software timestamping
                             timestamp.seconds += clock.get_tai_offset().expect("Unable to get tai offset") as i64;
                             log::trace!("Recv timestamp: {:?}", packet.timestamp);
-                            port.handle_event_receive(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp))
+                            port.handle_event_receive_from(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp), packet.remote_addr.port_address())
                         } else {
-                            port.handle_general_receive(&event_buffer[..packet.bytes_read])
+                            port.handle_general_receive_from(&event_buffer[..packet.bytes_read], packet.remote_addr.port_address())
                         }
                     }
                     Err(error) => panic!("Error receiving: {error:?}"),
@@ -684,6 +953,9 @@ async fn ethernet_port_task(
                 () = &mut timers.filter_update_timer => {
                     port.handle_filter_update_timer()
                 },
+                () = &mut timers.unicast_timer => {
+                    port.handle_unicast_timer()
+                },
                 result = bmca_notify.wait_for(|v| *v) => match result {
                     Ok(_) => break,
                     Err(error) => panic!("Error on bmca notify: {error:?}"),
@@ -695,6 +967,7 @@ async fn ethernet_port_task(
                     actions,
                     interface,
                     &mut socket,
+                    one_step_socket.as_ref(),
                     &mut timers,
                     &tlv_forwarder,
                     &clock,
@@ -720,12 +993,14 @@ struct Timers<'a> {
     port_announce_timeout_timer: Pin<&'a mut Timer>,
     delay_request_timer: Pin<&'a mut Timer>,
     filter_update_timer: Pin<&'a mut Timer>,
+    unicast_timer: Pin<&'a mut Timer>,
 }
 
-async fn handle_actions<A: NetworkAddress + PtpTargetAddress>(
+async fn handle_actions<A: NetworkAddress + PtpTargetAddress + OneStepAddress>(
     actions: PortActionIterator<'_>,
     event_socket: &mut Socket<A, Open>,
     general_socket: &mut Socket<A, Open>,
+    one_step_socket: Option<&OneStepSocket>,
     timers: &mut Timers<'_>,
     tlv_forwarder: &TlvForwarder,
     clock: &LinuxClock,
@@ -738,17 +1013,29 @@ async fn handle_actions<A: NetworkAddress + PtpTargetAddress>(
                 context,
                 data,
                 link_local,
+                destination,
+                one_step,
             } => {
+                let target = match destination {
+                    Some(destination) => match A::unicast_event(destination) {
+                        Some(target) => target,
+                        None => {
+                            log::error!("Cannot send to {:?} from this port", destination);
+                            continue;
+                        }
+                    },
+                    None if link_local => A::PDELAY_EVENT,
+                    None => A::PRIMARY_EVENT,
+                };
+
+                if one_step.is_some() {
+                    send_one_step(one_step_socket, data, target).await;
+                    continue;
+                }
+
                 // send timestamp of the send
                 let time = event_socket
-                    .send_to(
-                        data,
-                        if link_local {
-                            A::PDELAY_EVENT
-                        } else {
-                            A::PRIMARY_EVENT
-                        },
-                    )
+                    .send_to(data, target)
                     .await
                     .expect("Failed to send event message");
 
@@ -764,16 +1051,25 @@ async fn handle_actions<A: NetworkAddress + PtpTargetAddress>(
                     log::error!("Missing send timestamp");
                 }
             }
-            PortAction::SendGeneral { data, link_local } => {
+            PortAction::SendGeneral {
+                data,
+                link_local,
+                destination,
+            } => {
+                let target = match destination {
+                    Some(destination) => match A::unicast_general(destination) {
+                        Some(target) => target,
+                        None => {
+                            log::error!("Cannot send to {:?} from this port", destination);
+                            continue;
+                        }
+                    },
+                    None if link_local => A::PDELAY_GENERAL,
+                    None => A::PRIMARY_GENERAL,
+                };
+
                 general_socket
-                    .send_to(
-                        data,
-                        if link_local {
-                            A::PDELAY_GENERAL
-                        } else {
-                            A::PRIMARY_GENERAL
-                        },
-                    )
+                    .send_to(data, target)
                     .await
                     .expect("Failed to send general message");
             }
@@ -792,6 +1088,9 @@ async fn handle_actions<A: NetworkAddress + PtpTargetAddress>(
             PortAction::ResetFilterUpdateTimer { duration } => {
                 timers.filter_update_timer.as_mut().reset(duration);
             }
+            PortAction::ResetUnicastTimer { duration } => {
+                timers.unicast_timer.as_mut().reset(duration);
+            }
             PortAction::ForwardTLV { tlv } => {
                 tlv_forwarder.forward(tlv.into_owned());
             }
@@ -801,10 +1100,29 @@ async fn handle_actions<A: NetworkAddress + PtpTargetAddress>(
     pending_timestamp
 }
 
+// The hardware completes one-step messages while sending them, so there is no
+// send timestamp to wait for
+async fn send_one_step(socket: Option<&OneStepSocket>, data: &[u8], target: impl OneStepAddress) {
+    socket
+        .expect("Statime bug: one-step message on a port without one-step socket")
+        .send_to(data, target)
+        .await
+        .expect("Failed to send one-step event message");
+}
+
+// The network interface of an ethernet port
+#[derive(Clone, Copy)]
+struct EthernetInterface {
+    index: libc::c_int,
+    // Multicast all messages to the address that is not forwarded by bridges
+    non_forwardable: bool,
+}
+
 async fn handle_actions_ethernet(
     actions: PortActionIterator<'_>,
-    interface: libc::c_int,
+    interface: EthernetInterface,
     socket: &mut Socket<EthernetAddress, Open>,
+    one_step_socket: Option<&OneStepSocket>,
     timers: &mut Timers<'_>,
     tlv_forwarder: &TlvForwarder,
     clock: &LinuxClock,
@@ -817,6 +1135,433 @@ async fn handle_actions_ethernet(
                 context,
                 data,
                 link_local,
+                destination,
+                one_step,
+            } => {
+                let target = match destination {
+                    Some(destination) => match EthernetAddress::unicast_event(destination) {
+                        Some(target) => target,
+                        None => {
+                            log::error!("Cannot send to {:?} from this port", destination);
+                            continue;
+                        }
+                    },
+                    None if link_local || interface.non_forwardable => {
+                        EthernetAddress::PDELAY_EVENT
+                    }
+                    None => EthernetAddress::PRIMARY_EVENT,
+                };
+
+                let target = EthernetAddress::new(target.protocol(), target.mac(), interface.index);
+                if one_step.is_some() {
+                    send_one_step(one_step_socket, data, target).await;
+                    continue;
+                }
+
+                // send timestamp of the send
+                let time = socket
+                    .send_to(data, target)
+                    .await
+                    .expect("Failed to send event message");
+
+                // anything we send later will have a later pending (send) timestamp
+                if let Some(mut time) = time {
+                    // get_tai gives zero if this is a hardware clock, and the needed
+                    // correction when this port uses software timestamping
+                    time.seconds +=
+                        clock.get_tai_offset().expect("Unable to get tai offset") as libc::time_t;
+                    log::trace!("Send timestamp {:?}", time);
+                    pending_timestamp = Some((context, timestamp_to_time(time)));
+                } else {
+                    log::error!("Missing send timestamp");
+                }
+            }
+            PortAction::SendGeneral {
+                data,
+                link_local,
+                destination,
+            } => {
+                let target = match destination {
+                    Some(destination) => match EthernetAddress::unicast_general(destination) {
+                        Some(target) => target,
+                        None => {
+                            log::error!("Cannot send to {:?} from this port", destination);
+                            continue;
+                        }
+                    },
+                    None if link_local || interface.non_forwardable => {
+                        EthernetAddress::PDELAY_GENERAL
+                    }
+                    None => EthernetAddress::PRIMARY_GENERAL,
+                };
+
+                socket
+                    .send_to(
+                        data,
+                        EthernetAddress::new(target.protocol(), target.mac(), interface.index),
+                    )
+                    .await
+                    .expect("Failed to send general message");
+            }
+            PortAction::ResetAnnounceTimer { duration } => {
+                timers.port_announce_timer.as_mut().reset(duration);
+            }
+            PortAction::ResetSyncTimer { duration } => {
+                timers.port_sync_timer.as_mut().reset(duration);
+            }
+            PortAction::ResetDelayRequestTimer { duration } => {
+                timers.delay_request_timer.as_mut().reset(duration);
+            }
+            PortAction::ResetAnnounceReceiptTimer { duration } => {
+                timers.port_announce_timeout_timer.as_mut().reset(duration);
+            }
+            PortAction::ResetFilterUpdateTimer { duration } => {
+                timers.filter_update_timer.as_mut().reset(duration);
+            }
+            PortAction::ResetUnicastTimer { duration } => {
+                timers.unicast_timer.as_mut().reset(duration);
+            }
+            PortAction::ForwardTLV { tlv } => tlv_forwarder.forward(tlv.into_owned()),
+        }
+    }
+
+    pending_timestamp
+}
+
+async fn run_transparent_clock(
+    config: Config,
+    clock_identity: ClockIdentity,
+    statistics: StatisticsSink,
+) -> ! {
+    // The peer delay is measured on all ports at the same rate, use the most
+    // frequent one requested
+    let delay_interval = config
+        .ports
+        .iter()
+        .map(|port_config| port_config.delay_interval(config.profile))
+        .min()
+        .unwrap_or(Interval::ONE_SECOND);
+    let delay_mechanism = match config.clock_type {
+        ClockType::P2pTransparent => DelayMechanism::P2P {
+            interval: delay_interval,
+        },
+        _ => DelayMechanism::E2E {
+            interval: delay_interval,
+        },
+    };
+
+    let transparent_clock = TransparentClock::new(TransparentClockConfig {
+        clock_identity,
+        primary_domain: config.domain(),
+        sdo_id: SdoId::try_from(config.sdo_id).expect("sdo-id should be between 0 and 4095"),
+        delay_mechanism,
+        time_inaccuracy: config
+            .power_profile
+            .map_or(0, |power| power.time_inaccuracy),
+    });
+
+    // Every port gets all forwarded messages, including its own, which it ignores
+    let (forward_sender, _) = broadcast::channel(128);
+
+    // The residence time is measured between timestamps of different ports, so
+    // all hardware clocks are kept in sync with the system clock.
+    let mut clock_name_map = HashMap::new();
+    let mut internal_sync_senders = vec![];
+
+    for port_config in config.ports {
+        let interface = port_config.interface;
+        let network_mode = port_config.network_mode;
+        let (port_clock, timestamping) = match &port_config.hardware_clock {
+            Some(path) => {
+                let clock = LinuxClock::open(path).expect("Unable to open clock");
+                if !clock_name_map.contains_key(path) {
+                    let id = internal_sync_senders.len();
+                    clock_name_map.insert(path.clone(), id);
+                    internal_sync_senders.push(start_clock_task(
+                        clock.clone(),
+                        statistics.clone(),
+                        id,
+                    ));
+                }
+                (clock, InterfaceTimestampMode::HardwarePTPAll)
+            }
+            None => (LinuxClock::CLOCK_TAI, InterfaceTimestampMode::SoftwareAll),
+        };
+
+        let port = transparent_clock.add_port();
+
+        match network_mode {
+            statime_linux::config::NetworkMode::Ipv4 => {
+                let event_socket = open_ipv4_event_socket(interface, timestamping)
+                    .expect("Could not open event socket");
+                let general_socket =
+                    open_ipv4_general_socket(interface).expect("Could not open general socket");
+
+                tokio::spawn(transparent_port_task(
+                    port,
+                    event_socket,
+                    general_socket,
+                    forward_sender.clone(),
+                    port_clock,
+                ));
+            }
+            statime_linux::config::NetworkMode::Ipv6 => {
+                let event_socket = open_ipv6_event_socket(interface, timestamping)
+                    .expect("Could not open event socket");
+                let general_socket =
+                    open_ipv6_general_socket(interface).expect("Could not open general socket");
+
+                tokio::spawn(transparent_port_task(
+                    port,
+                    event_socket,
+                    general_socket,
+                    forward_sender.clone(),
+                    port_clock,
+                ));
+            }
+            statime_linux::config::NetworkMode::Ethernet => {
+                let socket =
+                    open_ethernet_socket(interface, timestamping).expect("Could not open socket");
+
+                tokio::spawn(transparent_ethernet_port_task(
+                    port,
+                    interface
+                        .get_index()
+                        .expect("Unable to get network interface index") as _,
+                    socket,
+                    forward_sender.clone(),
+                    port_clock,
+                ));
+            }
+        }
+    }
+
+    // The ports do all the work, keep the clock tasks running in the meantime
+    let _internal_sync_senders = internal_sync_senders;
+    loop {
+        std::future::pending::<()>().await;
+    }
+}
+
+// the Port task of a transparent clock
+//
+// This task handles the messages received on its sockets, and the messages
+// forwarded to it by the other ports of the transparent clock.
+async fn transparent_port_task<A: NetworkAddress + PtpTargetAddress>(
+    mut port: TransparentPort,
+    mut event_socket: Socket<A, Open>,
+    mut general_socket: Socket<A, Open>,
+    forward_sender: broadcast::Sender<ForwardedMessage>,
+    clock: LinuxClock,
+) {
+    let mut forward_receiver = forward_sender.subscribe();
+    let mut delay_request_timer = pin!(Timer::new());
+
+    let mut event_buffer = [0; MAX_DATA_LEN];
+    let mut general_buffer = [0; 2048];
+
+    let mut actions = port.start();
+
+    loop {
+        loop {
+            let pending_timestamp = handle_transparent_actions(
+                actions,
+                &mut event_socket,
+                &mut general_socket,
+                &mut delay_request_timer,
+                &forward_sender,
+                &clock,
+            )
+            .await;
+
+            // there might be more actions to handle based on the current action
+            actions = match pending_timestamp {
+                Some((context, timestamp)) => port.handle_send_timestamp(context, timestamp),
+                None => break,
+            };
+        }
+
+        actions = tokio::select! {
+            result = event_socket.recv(&mut event_buffer) => match result {
+                Ok(packet) => {
+                    if let Some(mut timestamp) = packet.timestamp {
+                        // get_tai gives zero if this is a hardware clock, and the needed
+                        // correction when this port uses software timestamping
+                        timestamp.seconds += clock.get_tai_offset().expect("Unable to get tai offset") as i64;
+                        log::trace!("Recv timestamp: {:?}", packet.timestamp);
+                        port.handle_event_receive(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp))
+                    } else {
+                        log::error!("Missing recv timestamp");
+                        TransparentPortActionIterator::empty()
+                    }
+                }
+                Err(error) => panic!("Error receiving: {error:?}"),
+            },
+            result = general_socket.recv(&mut general_buffer) => match result {
+                Ok(packet) => port.handle_general_receive(&general_buffer[..packet.bytes_read]),
+                Err(error) => panic!("Error receiving: {error:?}"),
+            },
+            result = forward_receiver.recv() => match result {
+                Ok(message) => port.handle_forwarded_message(&message),
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    log::warn!("Port {} missed {count} forwarded messages", port.number());
+                    TransparentPortActionIterator::empty()
+                }
+                Err(error) => panic!("Error receiving forwarded message: {error:?}"),
+            },
+            () = &mut delay_request_timer => port.handle_delay_request_timer(),
+        };
+    }
+}
+
+// the Port task of a transparent clock for ethernet transport
+//
+// This task handles the messages received on its socket, and the messages
+// forwarded to it by the other ports of the transparent clock.
+async fn transparent_ethernet_port_task(
+    mut port: TransparentPort,
+    interface: libc::c_int,
+    mut socket: Socket<EthernetAddress, Open>,
+    forward_sender: broadcast::Sender<ForwardedMessage>,
+    clock: LinuxClock,
+) {
+    let mut forward_receiver = forward_sender.subscribe();
+    let mut delay_request_timer = pin!(Timer::new());
+
+    let mut event_buffer = [0; MAX_DATA_LEN];
+
+    let mut actions = port.start();
+
+    loop {
+        loop {
+            let pending_timestamp = handle_transparent_actions_ethernet(
+                actions,
+                interface,
+                &mut socket,
+                &mut delay_request_timer,
+                &forward_sender,
+                &clock,
+            )
+            .await;
+
+            // there might be more actions to handle based on the current action
+            actions = match pending_timestamp {
+                Some((context, timestamp)) => port.handle_send_timestamp(context, timestamp),
+                None => break,
+            };
+        }
+
+        actions = tokio::select! {
+            result = socket.recv(&mut event_buffer) => match result {
+                Ok(packet) => {
+                    if let Some(mut timestamp) = packet.timestamp {
+                        // get_tai gives zero if this is a hardware clock, and the needed
+                        // correction when this port uses software timestamping
+                        timestamp.seconds += clock.get_tai_offset().expect("Unable to get tai offset") as i64;
+                        log::trace!("Recv timestamp: {:?}", packet.timestamp);
+                        port.handle_event_receive(&event_buffer[..packet.bytes_read], timestamp_to_time(timestamp))
+                    } else {
+                        port.handle_general_receive(&event_buffer[..packet.bytes_read])
+                    }
+                }
+                Err(error) => panic!("Error receiving: {error:?}"),
+            },
+            result = forward_receiver.recv() => match result {
+                Ok(message) => port.handle_forwarded_message(&message),
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    log::warn!("Port {} missed {count} forwarded messages", port.number());
+                    TransparentPortActionIterator::empty()
+                }
+                Err(error) => panic!("Error receiving forwarded message: {error:?}"),
+            },
+            () = &mut delay_request_timer => port.handle_delay_request_timer(),
+        };
+    }
+}
+
+async fn handle_transparent_actions<A: NetworkAddress + PtpTargetAddress>(
+    actions: TransparentPortActionIterator<'_>,
+    event_socket: &mut Socket<A, Open>,
+    general_socket: &mut Socket<A, Open>,
+    delay_request_timer: &mut Pin<&mut Timer>,
+    forward_sender: &broadcast::Sender<ForwardedMessage>,
+    clock: &LinuxClock,
+) -> Option<(TimestampContext, Time)> {
+    let mut pending_timestamp = None;
+
+    for action in actions {
+        match action {
+            TransparentPortAction::SendEvent {
+                context,
+                data,
+                link_local,
+            } => {
+                // send timestamp of the send
+                let time = event_socket
+                    .send_to(
+                        data,
+                        if link_local {
+                            A::PDELAY_EVENT
+                        } else {
+                            A::PRIMARY_EVENT
+                        },
+                    )
+                    .await
+                    .expect("Failed to send event message");
+
+                if let Some(mut time) = time {
+                    // get_tai gives zero if this is a hardware clock, and the needed
+                    // correction when this port uses software timestamping
+                    time.seconds +=
+                        clock.get_tai_offset().expect("Unable to get tai offset") as i64;
+                    log::trace!("Send timestamp {:?}", time);
+                    pending_timestamp = Some((context, timestamp_to_time(time)));
+                } else {
+                    log::error!("Missing send timestamp");
+                }
+            }
+            TransparentPortAction::SendGeneral { data, link_local } => {
+                general_socket
+                    .send_to(
+                        data,
+                        if link_local {
+                            A::PDELAY_GENERAL
+                        } else {
+                            A::PRIMARY_GENERAL
+                        },
+                    )
+                    .await
+                    .expect("Failed to send general message");
+            }
+            TransparentPortAction::ResetDelayRequestTimer { duration } => {
+                delay_request_timer.as_mut().reset(duration);
+            }
+            TransparentPortAction::Forward { message } => {
+                // Can't fail, we hold a receiver ourselves
+                let _ = forward_sender.send(message);
+            }
+        }
+    }
+
+    pending_timestamp
+}
+
+async fn handle_transparent_actions_ethernet(
+    actions: TransparentPortActionIterator<'_>,
+    interface: libc::c_int,
+    socket: &mut Socket<EthernetAddress, Open>,
+    delay_request_timer: &mut Pin<&mut Timer>,
+    forward_sender: &broadcast::Sender<ForwardedMessage>,
+    clock: &LinuxClock,
+) -> Option<(TimestampContext, Time)> {
+    let mut pending_timestamp = None;
+
+    for action in actions {
+        match action {
+            TransparentPortAction::SendEvent {
+                context,
+                data,
+                link_local,
             } => {
                 // send timestamp of the send
                 let time = socket
@@ -839,7 +1584,6 @@ async fn handle_actions_ethernet(
                     .await
                     .expect("Failed to send event message");
 
-                // anything we send later will have a later pending (send) timestamp
                 if let Some(mut time) = time {
                     // get_tai gives zero if this is a hardware clock, and the needed
                     // correction when this port uses software timestamping
@@ -851,7 +1595,7 @@ async fn handle_actions_ethernet(
                     log::error!("Missing send timestamp");
                 }
             }
-            PortAction::SendGeneral { data, link_local } => {
+            TransparentPortAction::SendGeneral { data, link_local } => {
                 socket
                     .send_to(
                         data,
@@ -872,22 +1616,13 @@ async fn handle_actions_ethernet(
                     .await
                     .expect("Failed to send general message");
             }
-            PortAction::ResetAnnounceTimer { duration } => {
-                timers.port_announce_timer.as_mut().reset(duration);
+            TransparentPortAction::ResetDelayRequestTimer { duration } => {
+                delay_request_timer.as_mut().reset(duration);
             }
-            PortAction::ResetSyncTimer { duration } => {
-                timers.port_sync_timer.as_mut().reset(duration);
-            }
-            PortAction::ResetDelayRequestTimer { duration } => {
-                timers.delay_request_timer.as_mut().reset(duration);
+            TransparentPortAction::Forward { message } => {
+                // Can't fail, we hold a receiver ourselves
+                let _ = forward_sender.send(message);
             }
-            PortAction::ResetAnnounceReceiptTimer { duration } => {
-                timers.port_announce_timeout_timer.as_mut().reset(duration);
-            }
-            PortAction::ResetFilterUpdateTimer { duration } => {
-                timers.filter_update_timer.as_mut().reset(duration);
-            }
-            PortAction::ForwardTLV { tlv } => tlv_forwarder.forward(tlv.into_owned()),
         }
     }
 
@@ -2,10 +2,20 @@ use std::fmt::Write;
 
 use statime::{
     config::TimePropertiesDS,
-    observability::{current::CurrentDS, default::DefaultDS, parent::ParentDS},
+    observability::{
+        current::CurrentDS,
+        default::DefaultDS,
+        holdover::HoldoverState,
+        parent::ParentDS,
+        port::{DelayMechanism, MessageCounters},
+        security::AuthenticationCounters,
+        slave_event_monitoring::SyncComputedRecord,
+        AlternateTimeOffset, PowerProfileInformation, SynchronizationMetadata,
+    },
 };
 
 use super::exporter::ObservableState;
+use crate::observer::ObservablePortState;
 
 macro_rules! format_bool {
     ($value:expr) => {
@@ -302,6 +312,500 @@ pub fn format_time_properties_ds(
     Ok(())
 }
 
+pub fn format_synchronization_metadata(
+    w: &mut impl std::fmt::Write,
+    metadata: &SynchronizationMetadata,
+    labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    format_metric(
+        w,
+        "smpte_default_system_frame_rate",
+        "The default video frame rate of the system",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.default_system_frame_rate_numerator as f64
+                / metadata.default_system_frame_rate_denominator as f64,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_master_locking_status",
+        "How the grandmaster is locked to its reference (0 not in use, 1 free run, 2 cold locking, 3 warm locking, 4 locked)",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.master_locking_status.to_primitive(),
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_current_local_offset",
+        "Offset of the local time from the PTP time",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.current_local_offset,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_jump_seconds",
+        "The change of the local offset at the next jump",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.jump_seconds,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_time_of_next_jump",
+        "PTP time of the next jump of the local offset",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.time_of_next_jump,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_time_of_next_jam",
+        "PTP time of the next resynchronization of time addresses",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: metadata.time_of_next_jam,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_daylight_saving",
+        "Whether daylight saving time is in effect",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: format_bool!(metadata.daylight_saving),
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "smpte_leap_second_jump",
+        "Whether the next jump is caused by a leap second",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: format_bool!(metadata.leap_second_jump),
+        }],
+    )?;
+
+    Ok(())
+}
+
+pub fn format_power_profile(
+    w: &mut impl std::fmt::Write,
+    information: &PowerProfileInformation,
+    labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    format_metric(
+        w,
+        "power_grandmaster_id",
+        "The power profile identifier of the grandmaster",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: information.grandmaster_id,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "power_grandmaster_time_inaccuracy",
+        "The time inaccuracy of the grandmaster",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: information.grandmaster_time_inaccuracy,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "power_network_time_inaccuracy",
+        "The time inaccuracy added by the network between the grandmaster and the master",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: information.network_time_inaccuracy,
+        }],
+    )?;
+
+    Ok(())
+}
+
+pub fn format_alternate_time_offset(
+    w: &mut impl std::fmt::Write,
+    offset: &AlternateTimeOffset,
+    mut labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    labels.push(("display_name", offset.display_name.to_string()));
+
+    format_metric(
+        w,
+        "alternate_time_current_offset",
+        "Offset of the alternate timescale from the PTP time",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: offset.current_offset,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "alternate_time_jump_seconds",
+        "The change of the alternate time offset at the next jump",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: offset.jump_seconds,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "alternate_time_of_next_jump",
+        "PTP time of the next jump of the alternate time offset",
+        MetricType::Gauge,
+        Some(Unit::Seconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: offset.time_of_next_jump,
+        }],
+    )?;
+
+    Ok(())
+}
+
+pub fn format_holdover(
+    w: &mut impl std::fmt::Write,
+    holdover: &HoldoverState,
+    labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    format_metric(
+        w,
+        "holdover_duration",
+        "Time since the grandmaster was lost",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        vec![Measurement {
+            labels: labels.clone(),
+            value: holdover.duration,
+        }],
+    )?;
+
+    if let Some(uncertainty) = holdover.uncertainty {
+        format_metric(
+            w,
+            "holdover_uncertainty",
+            "Bound on the uncertainty of the clock in holdover",
+            MetricType::Gauge,
+            Some(Unit::Nanoseconds),
+            vec![Measurement {
+                labels: labels.clone(),
+                value: uncertainty,
+            }],
+        )?;
+    }
+
+    format_metric(
+        w,
+        "holdover_in_specification",
+        "Whether the clock is still within its holdover specification",
+        MetricType::Gauge,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: format_bool!(holdover.in_specification),
+        }],
+    )?;
+
+    Ok(())
+}
+
+pub fn format_ports(
+    w: &mut impl std::fmt::Write,
+    ports: &[ObservablePortState],
+    labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    let port_labels = |port: &ObservablePortState| {
+        let mut labels = labels.clone();
+        labels.push(("port", port.port_ds.port_identity.port_number.to_string()));
+        labels
+    };
+    let measurements = |value: &dyn Fn(&ObservablePortState) -> i128| {
+        ports
+            .iter()
+            .map(|port| Measurement {
+                labels: port_labels(port),
+                value: value(port),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    format_metric(
+        w,
+        "port_state",
+        "The state of the port as encoded in IEEE1588-2019 table 20 (2 is faulty, 4 is \
+         listening, 6 is master, 7 is passive, 9 is slave)",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.port_state.to_primitive().into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_delay_mechanism",
+        "The delay mechanism of the port as encoded in IEEE1588-2019 table 21 (1 is E2E, 2 is \
+         P2P)",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.delay_mechanism.to_primitive().into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_peer_mean_path_delay",
+        "The mean link delay measured with the peer delay mechanism",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        measurements(&|port| port.port_ds.peer_mean_path_delay),
+    )?;
+
+    format_metric(
+        w,
+        "port_log_announce_interval",
+        "2-log of the interval between announce messages (in seconds)",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.log_announce_interval.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_announce_receipt_timeout",
+        "Number of announce intervals without announce messages before the port times out",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.announce_receipt_timeout.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_log_sync_interval",
+        "2-log of the interval between sync messages (in seconds)",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.log_sync_interval.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_log_min_delay_req_interval",
+        "2-log of the interval between (peer) delay requests (in seconds)",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| match port.port_ds.delay_mechanism {
+            DelayMechanism::E2E => port.port_ds.log_min_delay_req_interval.into(),
+            DelayMechanism::P2P => port.port_ds.log_min_pdelay_req_interval.into(),
+        }),
+    )?;
+
+    format_metric(
+        w,
+        "port_version_number",
+        "The PTP version of the port",
+        MetricType::Gauge,
+        None,
+        measurements(&|port| port.port_ds.version_number.into()),
+    )?;
+
+    let mut messages = vec![];
+    for port in ports {
+        for (direction, counters) in [("rx", &port.counters.rx), ("tx", &port.counters.tx)] {
+            for (message_type, value) in message_counts(counters) {
+                let mut labels = port_labels(port);
+                labels.push(("direction", direction.to_string()));
+                labels.push(("message_type", message_type.to_string()));
+                messages.push(Measurement { labels, value });
+            }
+        }
+    }
+    format_metric(
+        w,
+        "port_messages",
+        "Number of messages received (rx) and sent (tx) by the port",
+        MetricType::Counter,
+        None,
+        messages,
+    )?;
+
+    format_metric(
+        w,
+        "port_parse_errors",
+        "Number of received packets that could not be parsed",
+        MetricType::Counter,
+        None,
+        measurements(&|port| port.counters.parse_errors.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_domain_mismatches",
+        "Number of received messages for another sdoId or domain",
+        MetricType::Counter,
+        None,
+        measurements(&|port| port.counters.domain_mismatches.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_sequence_id_mismatches",
+        "Number of responses that did not match the sequence id of the message they respond to",
+        MetricType::Counter,
+        None,
+        measurements(&|port| port.counters.sequence_id_mismatches.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_missing_follow_ups",
+        "Number of two-step sync messages whose follow up never arrived",
+        MetricType::Counter,
+        None,
+        measurements(&|port| port.counters.missing_follow_ups.into()),
+    )?;
+
+    format_metric(
+        w,
+        "port_timestamp_timeouts",
+        "Number of (peer) delay requests whose send timestamp did not arrive in time",
+        MetricType::Counter,
+        None,
+        measurements(&|port| port.counters.timestamp_timeouts.into()),
+    )?;
+
+    // The last values reported by the slaves through slave event monitoring
+    let slave_measurements = |value: &dyn Fn(&SyncComputedRecord) -> Option<i128>| {
+        let mut result = vec![];
+        for port in ports {
+            for report in &port.slave_event_reports {
+                let Some(value) = report.sync_computed.iter().rev().find_map(value) else {
+                    continue;
+                };
+                let mut labels = port_labels(port);
+                labels.push((
+                    "slave_clock_identity",
+                    format!("{}", report.source_port_identity.clock_identity),
+                ));
+                labels.push((
+                    "slave_port",
+                    report.source_port_identity.port_number.to_string(),
+                ));
+                result.push(Measurement { labels, value });
+            }
+        }
+        result
+    };
+
+    format_metric(
+        w,
+        "port_slave_offset_from_master",
+        "The offset from its master last reported by a slave with slave event monitoring",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        slave_measurements(&|record| record.offset_from_master),
+    )?;
+
+    format_metric(
+        w,
+        "port_slave_mean_path_delay",
+        "The mean path delay last reported by a slave with slave event monitoring",
+        MetricType::Gauge,
+        Some(Unit::Nanoseconds),
+        slave_measurements(&|record| record.mean_path_delay),
+    )?;
+
+    Ok(())
+}
+
+fn message_counts(counters: &MessageCounters) -> [(&'static str, u64); 10] {
+    [
+        ("sync", counters.sync),
+        ("delay_req", counters.delay_req),
+        ("pdelay_req", counters.pdelay_req),
+        ("pdelay_resp", counters.pdelay_resp),
+        ("follow_up", counters.follow_up),
+        ("delay_resp", counters.delay_resp),
+        ("pdelay_resp_follow_up", counters.pdelay_resp_follow_up),
+        ("announce", counters.announce),
+        ("signaling", counters.signaling),
+        ("management", counters.management),
+    ]
+}
+
+pub fn format_authentication(
+    w: &mut impl std::fmt::Write,
+    authentication: &AuthenticationCounters,
+    labels: Vec<(&'static str, String)>,
+) -> std::fmt::Result {
+    format_metric(
+        w,
+        "authentication_unauthenticated",
+        "Number of messages dropped because they were not authenticated",
+        MetricType::Counter,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: authentication.unauthenticated,
+        }],
+    )?;
+
+    format_metric(
+        w,
+        "authentication_failed",
+        "Number of messages dropped because their authentication failed",
+        MetricType::Counter,
+        None,
+        vec![Measurement {
+            labels: labels.clone(),
+            value: authentication.authentication_failed,
+        }],
+    )?;
+
+    Ok(())
+}
+
 pub fn format_state(w: &mut impl std::fmt::Write, state: &ObservableState) -> std::fmt::Result {
     format_metric(
         w,
@@ -328,6 +832,20 @@ pub fn format_state(w: &mut impl std::fmt::Write, state: &ObservableState) -> st
     format_current_ds(w, &state.instance.current_ds, labels.clone())?;
     format_parent_ds(w, &state.instance.parent_ds, labels.clone())?;
     format_time_properties_ds(w, &state.instance.time_properties_ds, labels.clone())?;
+    if let Some(metadata) = &state.instance.synchronization_metadata {
+        format_synchronization_metadata(w, metadata, labels.clone())?;
+    }
+    if let Some(information) = &state.instance.power_profile {
+        format_power_profile(w, information, labels.clone())?;
+    }
+    if let Some(offset) = &state.instance.alternate_time_offset {
+        format_alternate_time_offset(w, offset, labels.clone())?;
+    }
+    if let Some(holdover) = &state.instance.holdover {
+        format_holdover(w, holdover, labels.clone())?;
+    }
+    format_authentication(w, &state.instance.authentication, labels.clone())?;
+    format_ports(w, &state.instance.ports, labels.clone())?;
 
     w.write_str("# EOF\n")?;
     Ok(())
@@ -358,9 +876,15 @@ fn format_metric<T: std::fmt::Display>(
         writeln!(w, "# UNIT {name} {}", unit.as_str())?;
     }
 
+    // counter samples carry the _total suffix
+    let sample_name = match metric_type {
+        MetricType::Counter => format!("{name}_total"),
+        MetricType::Gauge => name.clone(),
+    };
+
     // write all the measurements
     for measurement in measurements {
-        w.write_str(&name)?;
+        w.write_str(&sample_name)?;
         if !measurement.labels.is_empty() {
             w.write_str("{")?;
 
@@ -404,7 +928,6 @@ impl Unit {
     }
 }
 
-#[allow(dead_code)]
 enum MetricType {
     Gauge,
     Counter,
@@ -1,6 +1,16 @@
 use statime::{
     config::TimePropertiesDS,
-    observability::{current::CurrentDS, default::DefaultDS, parent::ParentDS},
+    observability::{
+        current::CurrentDS,
+        default::DefaultDS,
+        holdover::HoldoverState,
+        parent::ParentDS,
+        performance::PerformanceRecord,
+        port::{PortCounters, PortDS},
+        security::AuthenticationCounters,
+        slave_event_monitoring::SlaveEventReport,
+        AlternateTimeOffset, PowerProfileInformation, SynchronizationMetadata,
+    },
 };
 use std::{fs::Permissions, os::unix::prelude::PermissionsExt, path::Path, time::Instant};
 use tokio::{io::AsyncWriteExt, net::UnixStream, task::JoinHandle};
@@ -11,7 +21,7 @@ use crate::{
 };
 
 /// Observable version of the InstanceState struct
-#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ObservableInstanceState {
     /// A concrete implementation of the PTP Default dataset (IEEE1588-2019 section 8.2.1)
     pub default_ds: DefaultDS,
@@ -21,6 +31,36 @@ pub struct ObservableInstanceState {
     pub parent_ds: ParentDS,
     /// A concrete implementation of the PTP Time Properties dataset (IEEE1588-2019 section 8.2.4)
     pub time_properties_ds: TimePropertiesDS,
+    /// The SMPTE ST 2059-2 synchronization metadata of the grandmaster
+    pub synchronization_metadata: Option<SynchronizationMetadata>,
+    /// The IEEE C37.238 power profile information of the grandmaster
+    pub power_profile: Option<PowerProfileInformation>,
+    /// The alternate timescale announced by the grandmaster
+    pub alternate_time_offset: Option<AlternateTimeOffset>,
+    /// The state of the clock while it is in holdover
+    pub holdover: Option<HoldoverState>,
+    /// The messages dropped by all ports because of their authentication
+    pub authentication: AuthenticationCounters,
+    /// The state of every port
+    pub ports: Vec<ObservablePortState>,
+}
+
+/// Observable version of the state of a port
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ObservablePortState {
+    /// A concrete implementation of the PTP Port dataset (IEEE1588-2019 section 8.2.15)
+    pub port_ds: PortDS,
+    /// The messages handled by the port, and the errors it encountered
+    pub counters: PortCounters,
+    /// The 15-minute performance monitoring records of the port, the current
+    /// one first (IEEE1588-2019 annex J)
+    pub fifteen_minute_records: Vec<PerformanceRecord>,
+    /// The current and previous 24-hour performance monitoring record of the
+    /// port
+    pub twenty_four_hour_records: Vec<PerformanceRecord>,
+    /// The last slave event monitoring records received from each slave
+    /// (IEEE1588-2019 section 16.11)
+    pub slave_event_reports: Vec<SlaveEventReport>,
 }
 
 pub async fn spawn(
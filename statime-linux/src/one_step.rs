@@ -0,0 +1,318 @@
+//! Hardware insertion of transmit times into one-step messages
+
+use std::{
+    net::{SocketAddrV4, SocketAddrV6, UdpSocket},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+use timestamped_socket::{interface::InterfaceName, networkaddress::EthernetAddress};
+use tokio::io::{unix::AsyncFd, Interest};
+
+/// Let the hardware of `interface` insert the transmit time into outgoing
+/// one-step Sync messages, and with `p2p` also into Pdelay_Resp messages.
+///
+/// This changes the timestamping configuration of the whole interface, so it
+/// should be called after opening the sockets, which enable normal hardware
+/// timestamping.
+pub fn enable_one_step(interface: InterfaceName, p2p: bool) -> std::io::Result<()> {
+    // Any socket will do to configure the interface
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+
+    let mut tstamp_config = libc::hwtstamp_config {
+        flags: 0,
+        tx_type: if p2p {
+            libc::HWTSTAMP_TX_ONESTEP_P2P
+        } else {
+            libc::HWTSTAMP_TX_ONESTEP_SYNC
+        } as _,
+        rx_filter: libc::HWTSTAMP_FILTER_PTP_V2_EVENT as _,
+    };
+
+    let mut ifreq = libc::ifreq {
+        ifr_name: interface.to_ifr_name(),
+        ifr_ifru: libc::__c_anonymous_ifr_ifru {
+            ifru_data: (&mut tstamp_config as *mut libc::hwtstamp_config).cast(),
+        },
+    };
+
+    // Safety:
+    // ifreq and the config it points to live for the duration of the call
+    let result = unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSHWTSTAMP as _, &mut ifreq) };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Socket for sending one-step event messages
+///
+/// The event sockets wait for a transmit timestamp after every message they
+/// send, but the kernel reports none for messages the hardware completed
+/// itself. This socket requests hardware transmit timestamping, so the driver
+/// applies the one-step mode set by [`enable_one_step`] to its messages, but
+/// only waits until a message is written.
+///
+/// It never receives anything. UDP messages are sent from an ephemeral port
+/// instead of the event port, which PTP does not require.
+#[derive(Debug)]
+pub struct OneStepSocket {
+    socket: AsyncFd<OwnedFd>,
+}
+
+impl OneStepSocket {
+    pub fn open_ipv4(interface: InterfaceName) -> std::io::Result<Self> {
+        let socket = open_socket(libc::AF_INET, libc::SOCK_DGRAM)?;
+        bind_to_device(&socket, interface)?;
+        let request = libc::ip_mreqn {
+            imr_multiaddr: libc::in_addr { s_addr: 0 },
+            imr_address: libc::in_addr { s_addr: 0 },
+            imr_ifindex: interface_index(interface)?,
+        };
+        set_option(&socket, libc::IPPROTO_IP, libc::IP_MULTICAST_IF, request)?;
+        set_option(&socket, libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP, 0)?;
+
+        Self::new(socket)
+    }
+
+    pub fn open_ipv6(interface: InterfaceName) -> std::io::Result<Self> {
+        let socket = open_socket(libc::AF_INET6, libc::SOCK_DGRAM)?;
+        bind_to_device(&socket, interface)?;
+        let index = interface_index(interface)?;
+        set_option(&socket, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_IF, index)?;
+        set_option(&socket, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP, 0)?;
+
+        Self::new(socket)
+    }
+
+    /// The messages are sent to the interface of their [`EthernetAddress`]
+    pub fn open_ethernet() -> std::io::Result<Self> {
+        // Without a protocol the socket does not receive any frames
+        Self::new(open_socket(libc::AF_PACKET, libc::SOCK_DGRAM)?)
+    }
+
+    fn new(socket: OwnedFd) -> std::io::Result<Self> {
+        let options = libc::SOF_TIMESTAMPING_TX_HARDWARE
+            | libc::SOF_TIMESTAMPING_RAW_HARDWARE
+            | libc::SOF_TIMESTAMPING_OPT_TSONLY;
+        set_option(&socket, libc::SOL_SOCKET, libc::SO_TIMESTAMPING, options)?;
+
+        Ok(Self {
+            socket: AsyncFd::new(socket)?,
+        })
+    }
+
+    /// Send a one-step message, waiting until it is written but not for a
+    /// transmit timestamp
+    pub async fn send_to(&self, data: &[u8], target: impl OneStepAddress) -> std::io::Result<()> {
+        self.check_timestamps();
+
+        self.socket
+            .async_io(Interest::WRITABLE, |socket| {
+                target.with_sockaddr(|address, length| {
+                    // Safety:
+                    // data and the address live for the duration of the call
+                    let result = unsafe {
+                        libc::sendto(
+                            socket.as_raw_fd(),
+                            data.as_ptr().cast(),
+                            data.len(),
+                            0,
+                            address,
+                            length,
+                        )
+                    };
+                    if result < 0 {
+                        Err(std::io::Error::last_os_error())
+                    } else {
+                        Ok(())
+                    }
+                })
+            })
+            .await
+    }
+
+    // Hardware that completes a one-step message reports no transmit timestamp
+    // for it, so any timestamp in the error queue points at a message sent
+    // without the transmit time
+    fn check_timestamps(&self) {
+        let mut control = [0u8; 256];
+
+        loop {
+            // Safety:
+            // msghdr is a plain C struct, for which all zeroes is a valid value
+            let mut message: libc::msghdr = unsafe { std::mem::zeroed() };
+            message.msg_control = control.as_mut_ptr().cast();
+            message.msg_controllen = control.len() as _;
+
+            // Safety:
+            // the message and the control buffer it points to live for the
+            // duration of the call
+            let result = unsafe {
+                libc::recvmsg(
+                    self.socket.as_raw_fd(),
+                    &mut message,
+                    libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT,
+                )
+            };
+            if result < 0 {
+                break;
+            }
+
+            log::warn!(
+                "Got a send timestamp for a one-step message, the hardware might not have \
+                 inserted the transmit time"
+            );
+        }
+    }
+}
+
+/// Addresses a [`OneStepSocket`] can send to
+pub trait OneStepAddress {
+    /// Call `f` with the address as a C socket address and its length
+    fn with_sockaddr<T>(&self, f: impl FnOnce(*const libc::sockaddr, libc::socklen_t) -> T) -> T;
+}
+
+impl OneStepAddress for SocketAddrV4 {
+    fn with_sockaddr<T>(&self, f: impl FnOnce(*const libc::sockaddr, libc::socklen_t) -> T) -> T {
+        // Safety:
+        // sockaddr_in is a plain C struct, for which all zeroes is a valid value
+        let mut address: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+        address.sin_family = libc::AF_INET as _;
+        address.sin_port = self.port().to_be();
+        address.sin_addr.s_addr = u32::from_ne_bytes(self.ip().octets());
+
+        f(
+            (&address as *const libc::sockaddr_in).cast(),
+            std::mem::size_of_val(&address) as _,
+        )
+    }
+}
+
+impl OneStepAddress for SocketAddrV6 {
+    fn with_sockaddr<T>(&self, f: impl FnOnce(*const libc::sockaddr, libc::socklen_t) -> T) -> T {
+        // Safety:
+        // sockaddr_in6 is a plain C struct, for which all zeroes is a valid value
+        let mut address: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+        address.sin6_family = libc::AF_INET6 as _;
+        address.sin6_port = self.port().to_be();
+        address.sin6_addr.s6_addr = self.ip().octets();
+        address.sin6_scope_id = self.scope_id();
+
+        f(
+            (&address as *const libc::sockaddr_in6).cast(),
+            std::mem::size_of_val(&address) as _,
+        )
+    }
+}
+
+impl OneStepAddress for EthernetAddress {
+    fn with_sockaddr<T>(&self, f: impl FnOnce(*const libc::sockaddr, libc::socklen_t) -> T) -> T {
+        // Safety:
+        // sockaddr_ll is a plain C struct, for which all zeroes is a valid value
+        let mut address: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        address.sll_family = libc::AF_PACKET as _;
+        address.sll_protocol = self.protocol().to_be();
+        address.sll_ifindex = self.interface();
+        address.sll_halen = 6;
+        address.sll_addr[..6].copy_from_slice(self.mac().as_ref());
+
+        f(
+            (&address as *const libc::sockaddr_ll).cast(),
+            std::mem::size_of_val(&address) as _,
+        )
+    }
+}
+
+fn open_socket(domain: libc::c_int, ty: libc::c_int) -> std::io::Result<OwnedFd> {
+    // Safety:
+    // socket has no memory safety requirements
+    let fd = unsafe { libc::socket(domain, ty | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    // Safety:
+    // the file descriptor was just opened and nothing else owns it
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn set_option<T>(
+    socket: &OwnedFd,
+    level: libc::c_int,
+    name: libc::c_int,
+    value: T,
+) -> std::io::Result<()> {
+    // Safety:
+    // value lives for the duration of the call, and its size is passed along
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            (&value as *const T).cast(),
+            std::mem::size_of::<T>() as _,
+        )
+    };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+fn bind_to_device(socket: &OwnedFd, interface: InterfaceName) -> std::io::Result<()> {
+    let name = interface.as_cstr().to_bytes_with_nul();
+
+    // Safety:
+    // name lives for the duration of the call, and its length is passed along
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            name.as_ptr().cast(),
+            name.len() as _,
+        )
+    };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+fn interface_index(interface: InterfaceName) -> std::io::Result<libc::c_int> {
+    match interface.get_index() {
+        Some(index) => Ok(index as _),
+        None => Err(std::io::ErrorKind::InvalidInput.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use super::*;
+
+    #[test]
+    fn send_ipv4() {
+        let receiver = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let SocketAddr::V4(target) = receiver.local_addr().unwrap() else {
+            panic!("Expected an IPv4 address");
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let socket = OneStepSocket::open_ipv4("lo".parse().unwrap()).unwrap();
+            socket.send_to(&[1, 2, 3], target).await.unwrap();
+        });
+
+        let mut buffer = [0; 8];
+        let length = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..length], &[1, 2, 3]);
+    }
+}
@@ -0,0 +1,655 @@
+use std::fmt::Write;
+
+use serde_json::{json, Map, Value};
+use statime::{
+    config::ClockIdentity,
+    management::{
+        ManagementAction, ManagementData, ManagementErrorId, ManagementId, ManagementRequest,
+        ManagementResponse, PortIdentity,
+    },
+    observability::performance::PerformanceStatistics,
+    time::Duration,
+};
+
+use crate::metrics::exporter::ObservableState;
+
+// Names as used in IEEE1588-2019 table 59 and by linuxptp's pmc
+const MANAGEMENT_IDS: &[(&str, ManagementId)] = &[
+    ("NULL_PTP_MANAGEMENT", ManagementId::NullPtpManagement),
+    ("CLOCK_DESCRIPTION", ManagementId::ClockDescription),
+    ("USER_DESCRIPTION", ManagementId::UserDescription),
+    (
+        "SAVE_IN_NON_VOLATILE_STORAGE",
+        ManagementId::SaveInNonVolatileStorage,
+    ),
+    (
+        "RESET_NON_VOLATILE_STORAGE",
+        ManagementId::ResetNonVolatileStorage,
+    ),
+    ("INITIALIZE", ManagementId::Initialize),
+    ("FAULT_LOG", ManagementId::FaultLog),
+    ("FAULT_LOG_RESET", ManagementId::FaultLogReset),
+    ("DEFAULT_DATA_SET", ManagementId::DefaultDataSet),
+    ("CURRENT_DATA_SET", ManagementId::CurrentDataSet),
+    ("PARENT_DATA_SET", ManagementId::ParentDataSet),
+    (
+        "TIME_PROPERTIES_DATA_SET",
+        ManagementId::TimePropertiesDataSet,
+    ),
+    ("PORT_DATA_SET", ManagementId::PortDataSet),
+    ("PRIORITY1", ManagementId::Priority1),
+    ("PRIORITY2", ManagementId::Priority2),
+    ("DOMAIN", ManagementId::Domain),
+    ("SLAVE_ONLY", ManagementId::SlaveOnly),
+    ("LOG_ANNOUNCE_INTERVAL", ManagementId::LogAnnounceInterval),
+    (
+        "ANNOUNCE_RECEIPT_TIMEOUT",
+        ManagementId::AnnounceReceiptTimeout,
+    ),
+    ("LOG_SYNC_INTERVAL", ManagementId::LogSyncInterval),
+    ("VERSION_NUMBER", ManagementId::VersionNumber),
+    ("ENABLE_PORT", ManagementId::EnablePort),
+    ("DISABLE_PORT", ManagementId::DisablePort),
+    ("TIME", ManagementId::Time),
+    ("CLOCK_ACCURACY", ManagementId::ClockAccuracy),
+    ("UTC_PROPERTIES", ManagementId::UtcProperties),
+    (
+        "TRACEABILITY_PROPERTIES",
+        ManagementId::TraceabilityProperties,
+    ),
+    ("TIMESCALE_PROPERTIES", ManagementId::TimescaleProperties),
+    (
+        "UNICAST_NEGOTIATION_ENABLE",
+        ManagementId::UnicastNegotiationEnable,
+    ),
+    ("PATH_TRACE_LIST", ManagementId::PathTraceList),
+    ("PATH_TRACE_ENABLE", ManagementId::PathTraceEnable),
+    (
+        "GRANDMASTER_CLUSTER_TABLE",
+        ManagementId::GrandmasterClusterTable,
+    ),
+    ("UNICAST_MASTER_TABLE", ManagementId::UnicastMasterTable),
+    (
+        "UNICAST_MASTER_MAX_TABLE_SIZE",
+        ManagementId::UnicastMasterMaxTableSize,
+    ),
+    (
+        "ACCEPTABLE_MASTER_TABLE",
+        ManagementId::AcceptableMasterTable,
+    ),
+    (
+        "ACCEPTABLE_MASTER_TABLE_ENABLED",
+        ManagementId::AcceptableMasterTableEnabled,
+    ),
+    (
+        "ACCEPTABLE_MASTER_MAX_TABLE_SIZE",
+        ManagementId::AcceptableMasterMaxTableSize,
+    ),
+    ("ALTERNATE_MASTER", ManagementId::AlternateMaster),
+    (
+        "ALTERNATE_TIME_OFFSET_ENABLE",
+        ManagementId::AlternateTimeOffsetEnable,
+    ),
+    (
+        "ALTERNATE_TIME_OFFSET_NAME",
+        ManagementId::AlternateTimeOffsetName,
+    ),
+    (
+        "ALTERNATE_TIME_OFFSET_MAX_KEY",
+        ManagementId::AlternateTimeOffsetMaxKey,
+    ),
+    (
+        "ALTERNATE_TIME_OFFSET_PROPERTIES",
+        ManagementId::AlternateTimeOffsetProperties,
+    ),
+    (
+        "TRANSPARENT_CLOCK_DEFAULT_DATA_SET",
+        ManagementId::TransparentClockDefaultDataSet,
+    ),
+    (
+        "TRANSPARENT_CLOCK_PORT_DATA_SET",
+        ManagementId::TransparentClockPortDataSet,
+    ),
+    ("PRIMARY_DOMAIN", ManagementId::PrimaryDomain),
+    ("DELAY_MECHANISM", ManagementId::DelayMechanism),
+    (
+        "LOG_MIN_PDELAY_REQ_INTERVAL",
+        ManagementId::LogMinPdelayReqInterval,
+    ),
+    (
+        "PERFORMANCE_MONITORING_RECORD",
+        ManagementId::PerformanceMonitoringRecord,
+    ),
+];
+
+pub(super) fn management_id_from_name(name: &str) -> Option<ManagementId> {
+    MANAGEMENT_IDS
+        .iter()
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+        .map(|(_, management_id)| *management_id)
+}
+
+fn management_id_name(management_id: ManagementId) -> String {
+    MANAGEMENT_IDS
+        .iter()
+        .find(|(_, candidate)| *candidate == management_id)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| format!("0x{:04x}", management_id.to_primitive()))
+}
+
+fn management_error_name(management_error_id: ManagementErrorId) -> String {
+    match management_error_id {
+        ManagementErrorId::ResponseTooBig => "RESPONSE_TOO_BIG".to_string(),
+        ManagementErrorId::NoSuchId => "NO_SUCH_ID".to_string(),
+        ManagementErrorId::WrongLength => "WRONG_LENGTH".to_string(),
+        ManagementErrorId::WrongValue => "WRONG_VALUE".to_string(),
+        ManagementErrorId::NotSetable => "NOT_SETABLE".to_string(),
+        ManagementErrorId::NotSupported => "NOT_SUPPORTED".to_string(),
+        ManagementErrorId::Unpopulated => "UNPOPULATED".to_string(),
+        ManagementErrorId::GeneralError => "GENERAL_ERROR".to_string(),
+        ManagementErrorId::Reserved(value) => format!("0x{value:04x}"),
+    }
+}
+
+fn action_name(action: ManagementAction) -> &'static str {
+    match action {
+        ManagementAction::GET => "GET",
+        ManagementAction::SET => "SET",
+        ManagementAction::RESPONSE => "RESPONSE",
+        ManagementAction::COMMAND => "COMMAND",
+        ManagementAction::ACKNOWLEDGE => "ACKNOWLEDGE",
+        ManagementAction::Reserved => "RESERVED",
+    }
+}
+
+/// Parse the value of a SET request for the given management id
+pub(super) fn parse_set_value(
+    management_id: ManagementId,
+    value: &str,
+) -> Result<ManagementData, String> {
+    fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        value
+            .parse()
+            .map_err(|e| format!("invalid value {value}: {e}"))
+    }
+
+    let data = match management_id {
+        ManagementId::Priority1 => ManagementData::Priority1(parse(value)?),
+        ManagementId::Priority2 => ManagementData::Priority2(parse(value)?),
+        ManagementId::Domain => ManagementData::Domain(parse(value)?),
+        ManagementId::SlaveOnly => ManagementData::SlaveOnly(match value {
+            "1" => true,
+            "0" => false,
+            _ => parse(value)?,
+        }),
+        ManagementId::LogAnnounceInterval => ManagementData::LogAnnounceInterval(parse(value)?),
+        ManagementId::AnnounceReceiptTimeout => {
+            ManagementData::AnnounceReceiptTimeout(parse(value)?)
+        }
+        ManagementId::LogSyncInterval => ManagementData::LogSyncInterval(parse(value)?),
+        ManagementId::LogMinPdelayReqInterval => {
+            ManagementData::LogMinPdelayReqInterval(parse(value)?)
+        }
+        _ => {
+            return Err(format!(
+                "{} can not be set with statime-pmc",
+                management_id_name(management_id)
+            ))
+        }
+    };
+
+    Ok(data)
+}
+
+fn clock_identity(clock_identity: ClockIdentity) -> Value {
+    Value::String(hex::encode(clock_identity.0))
+}
+
+fn port_identity(port_identity: PortIdentity) -> Value {
+    Value::String(format!(
+        "{}-{}",
+        hex::encode(port_identity.clock_identity.0),
+        port_identity.port_number
+    ))
+}
+
+fn nanos(duration: Duration) -> Value {
+    json!(duration.nanos_lossy())
+}
+
+// Statistics in nanoseconds, or null without measurements
+fn statistics(statistics: Option<PerformanceStatistics>) -> Value {
+    serde_json::to_value(statistics).unwrap_or(Value::Null)
+}
+
+// See IEEE1588-2019 table 20
+fn port_state(port_state: u8) -> Value {
+    let name = match port_state {
+        1 => "INITIALIZING",
+        2 => "FAULTY",
+        3 => "DISABLED",
+        4 => "LISTENING",
+        5 => "PRE_MASTER",
+        6 => "MASTER",
+        7 => "PASSIVE",
+        8 => "UNCALIBRATED",
+        9 => "SLAVE",
+        _ => return json!(port_state),
+    };
+    Value::String(name.to_string())
+}
+
+// See IEEE1588-2019 table 21
+fn delay_mechanism(delay_mechanism: u8) -> Value {
+    let name = match delay_mechanism {
+        0x01 => "E2E",
+        0x02 => "P2P",
+        0x03 => "COMMON_P2P",
+        0x04 => "SPECIAL",
+        0xfe => "NO_MECHANISM",
+        _ => return json!(delay_mechanism),
+    };
+    Value::String(name.to_string())
+}
+
+/// The fields of a management TLV, named as in IEEE1588-2019
+fn data_fields(data: &ManagementData) -> Vec<(&'static str, Value)> {
+    match *data {
+        ManagementData::Empty(_) => vec![],
+        ManagementData::DefaultDataSet {
+            two_step,
+            slave_only,
+            number_ports,
+            priority_1,
+            clock_quality,
+            priority_2,
+            clock_identity: identity,
+            domain_number,
+        } => vec![
+            ("twoStepFlag", json!(two_step)),
+            ("slaveOnly", json!(slave_only)),
+            ("numberPorts", json!(number_ports)),
+            ("priority1", json!(priority_1)),
+            ("clockClass", json!(clock_quality.clock_class)),
+            (
+                "clockAccuracy",
+                json!(clock_quality.clock_accuracy.to_primitive()),
+            ),
+            (
+                "offsetScaledLogVariance",
+                json!(clock_quality.offset_scaled_log_variance),
+            ),
+            ("priority2", json!(priority_2)),
+            ("clockIdentity", clock_identity(identity)),
+            ("domainNumber", json!(domain_number)),
+        ],
+        ManagementData::CurrentDataSet {
+            steps_removed,
+            offset_from_master,
+            mean_path_delay,
+        } => vec![
+            ("stepsRemoved", json!(steps_removed)),
+            ("offsetFromMaster", nanos(offset_from_master)),
+            ("meanPathDelay", nanos(mean_path_delay)),
+        ],
+        ManagementData::ParentDataSet {
+            parent_port_identity,
+            parent_stats,
+            observed_parent_offset_scaled_log_variance,
+            observed_parent_clock_phase_change_rate,
+            grandmaster_priority_1,
+            grandmaster_clock_quality,
+            grandmaster_priority_2,
+            grandmaster_identity,
+        } => vec![
+            ("parentPortIdentity", port_identity(parent_port_identity)),
+            ("parentStats", json!(parent_stats)),
+            (
+                "observedParentOffsetScaledLogVariance",
+                json!(observed_parent_offset_scaled_log_variance),
+            ),
+            (
+                "observedParentClockPhaseChangeRate",
+                json!(observed_parent_clock_phase_change_rate),
+            ),
+            ("grandmasterPriority1", json!(grandmaster_priority_1)),
+            (
+                "gm.ClockClass",
+                json!(grandmaster_clock_quality.clock_class),
+            ),
+            (
+                "gm.ClockAccuracy",
+                json!(grandmaster_clock_quality.clock_accuracy.to_primitive()),
+            ),
+            (
+                "gm.OffsetScaledLogVariance",
+                json!(grandmaster_clock_quality.offset_scaled_log_variance),
+            ),
+            ("grandmasterPriority2", json!(grandmaster_priority_2)),
+            ("grandmasterIdentity", clock_identity(grandmaster_identity)),
+        ],
+        ManagementData::TimePropertiesDataSet {
+            current_utc_offset,
+            leap61,
+            leap59,
+            current_utc_offset_valid,
+            ptp_timescale,
+            time_traceable,
+            frequency_traceable,
+            time_source,
+        } => vec![
+            ("currentUtcOffset", json!(current_utc_offset)),
+            ("leap61", json!(leap61)),
+            ("leap59", json!(leap59)),
+            ("currentUtcOffsetValid", json!(current_utc_offset_valid)),
+            ("ptpTimescale", json!(ptp_timescale)),
+            ("timeTraceable", json!(time_traceable)),
+            ("frequencyTraceable", json!(frequency_traceable)),
+            ("timeSource", json!(time_source.to_primitive())),
+        ],
+        ManagementData::PortDataSet {
+            port_identity: identity,
+            port_state: state,
+            log_min_delay_req_interval,
+            peer_mean_path_delay,
+            log_announce_interval,
+            announce_receipt_timeout,
+            log_sync_interval,
+            delay_mechanism: mechanism,
+            log_min_pdelay_req_interval,
+            version_number,
+            minor_version_number,
+        } => vec![
+            ("portIdentity", port_identity(identity)),
+            ("portState", port_state(state)),
+            ("logMinDelayReqInterval", json!(log_min_delay_req_interval)),
+            ("peerMeanPathDelay", nanos(peer_mean_path_delay)),
+            ("logAnnounceInterval", json!(log_announce_interval)),
+            ("announceReceiptTimeout", json!(announce_receipt_timeout)),
+            ("logSyncInterval", json!(log_sync_interval)),
+            ("delayMechanism", delay_mechanism(mechanism)),
+            (
+                "logMinPdelayReqInterval",
+                json!(log_min_pdelay_req_interval),
+            ),
+            ("versionNumber", json!(version_number)),
+            ("minorVersionNumber", json!(minor_version_number)),
+        ],
+        ManagementData::Priority1(value) => vec![("priority1", json!(value))],
+        ManagementData::Priority2(value) => vec![("priority2", json!(value))],
+        ManagementData::Domain(value) => vec![("domainNumber", json!(value))],
+        ManagementData::SlaveOnly(value) => vec![("slaveOnly", json!(value))],
+        ManagementData::LogAnnounceInterval(value) => {
+            vec![("logAnnounceInterval", json!(value))]
+        }
+        ManagementData::AnnounceReceiptTimeout(value) => {
+            vec![("announceReceiptTimeout", json!(value))]
+        }
+        ManagementData::LogSyncInterval(value) => vec![("logSyncInterval", json!(value))],
+        ManagementData::VersionNumber {
+            version_number,
+            minor_version_number,
+        } => vec![
+            ("versionNumber", json!(version_number)),
+            ("minorVersionNumber", json!(minor_version_number)),
+        ],
+        ManagementData::ClockAccuracy(value) => {
+            vec![("clockAccuracy", json!(value.to_primitive()))]
+        }
+        ManagementData::UtcProperties {
+            current_utc_offset,
+            leap61,
+            leap59,
+            current_utc_offset_valid,
+        } => vec![
+            ("currentUtcOffset", json!(current_utc_offset)),
+            ("leap61", json!(leap61)),
+            ("leap59", json!(leap59)),
+            ("currentUtcOffsetValid", json!(current_utc_offset_valid)),
+        ],
+        ManagementData::TraceabilityProperties {
+            time_traceable,
+            frequency_traceable,
+        } => vec![
+            ("timeTraceable", json!(time_traceable)),
+            ("frequencyTraceable", json!(frequency_traceable)),
+        ],
+        ManagementData::TimescaleProperties {
+            ptp_timescale,
+            time_source,
+        } => vec![
+            ("ptpTimescale", json!(ptp_timescale)),
+            ("timeSource", json!(time_source.to_primitive())),
+        ],
+        ManagementData::DelayMechanism(value) => {
+            vec![("delayMechanism", delay_mechanism(value))]
+        }
+        ManagementData::LogMinPdelayReqInterval(value) => {
+            vec![("logMinPdelayReqInterval", json!(value))]
+        }
+        ManagementData::PerformanceMonitoringRecordIndex(index) => vec![("index", json!(index))],
+        ManagementData::PerformanceMonitoringRecord { index, record } => vec![
+            ("index", json!(index)),
+            ("start", json!(record.start)),
+            ("complete", json!(record.complete)),
+            ("offsetFromMaster", statistics(record.offset_from_master)),
+            ("meanPathDelay", statistics(record.mean_path_delay)),
+            ("masterSlaveDelay", statistics(record.master_slave_delay)),
+            ("slaveMasterDelay", statistics(record.slave_master_delay)),
+        ],
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => (*b as u8).to_string(),
+        other => other.to_string(),
+    }
+}
+
+pub(super) fn format_responses(
+    w: &mut impl Write,
+    request: &ManagementRequest,
+    responses: &[ManagementResponse],
+) -> std::fmt::Result {
+    writeln!(
+        w,
+        "sending: {} {}",
+        action_name(request.action),
+        management_id_name(request.data.management_id())
+    )?;
+
+    if responses.is_empty() {
+        writeln!(w, "\tno responses received")?;
+    }
+
+    for response in responses {
+        write!(
+            w,
+            "\t{} seq {} {} ",
+            format_value(&port_identity(response.source_port_identity)),
+            response.sequence_id,
+            action_name(response.action),
+        )?;
+
+        match &response.result {
+            Ok(data) => {
+                writeln!(w, "MANAGEMENT {}", management_id_name(data.management_id()))?;
+                for (name, value) in data_fields(data) {
+                    writeln!(w, "\t\t{name:<40}{}", format_value(&value))?;
+                }
+            }
+            Err(error) => writeln!(
+                w,
+                "MANAGEMENT_ERROR_STATUS {} {}",
+                management_id_name(error.management_id),
+                management_error_name(error.management_error_id),
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn responses_to_json(responses: &[ManagementResponse]) -> Value {
+    let responses = responses
+        .iter()
+        .map(|response| {
+            let mut object = Map::new();
+            object.insert(
+                "sourcePortIdentity".to_string(),
+                port_identity(response.source_port_identity),
+            );
+            object.insert("sequenceId".to_string(), json!(response.sequence_id));
+            object.insert("action".to_string(), json!(action_name(response.action)));
+
+            match &response.result {
+                Ok(data) => {
+                    object.insert(
+                        "managementId".to_string(),
+                        json!(management_id_name(data.management_id())),
+                    );
+                    let fields = data_fields(data)
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value))
+                        .collect();
+                    object.insert("data".to_string(), Value::Object(fields));
+                }
+                Err(error) => {
+                    object.insert(
+                        "managementId".to_string(),
+                        json!(management_id_name(error.management_id)),
+                    );
+                    object.insert(
+                        "error".to_string(),
+                        json!(management_error_name(error.management_error_id)),
+                    );
+                }
+            }
+
+            Value::Object(object)
+        })
+        .collect();
+
+    Value::Array(responses)
+}
+
+fn format_object(
+    w: &mut impl Write,
+    indent: usize,
+    object: &Map<String, Value>,
+) -> std::fmt::Result {
+    for (name, value) in object {
+        match value {
+            Value::Object(inner) => {
+                writeln!(w, "{:indent$}{name}", "")?;
+                format_object(w, indent + 4, inner)?;
+            }
+            Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+                for (index, item) in items.iter().enumerate() {
+                    writeln!(w, "{:indent$}{name}[{index}]", "")?;
+                    format_object(w, indent + 4, item.as_object().unwrap())?;
+                }
+            }
+            _ => writeln!(w, "{:indent$}{name:<40}{}", "", format_value(value))?,
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn format_local(w: &mut impl Write, state: &ObservableState) -> std::fmt::Result {
+    let Ok(Value::Object(state)) = serde_json::to_value(state) else {
+        return Err(std::fmt::Error);
+    };
+
+    format_object(w, 0, &state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn management_id_names() {
+        for (name, management_id) in MANAGEMENT_IDS {
+            assert_eq!(management_id_from_name(name), Some(*management_id));
+            assert_eq!(management_id_name(*management_id), *name);
+        }
+
+        assert_eq!(
+            management_id_from_name("priority1"),
+            Some(ManagementId::Priority1)
+        );
+        assert_eq!(management_id_from_name("PRIORITY3"), None);
+        assert_eq!(management_id_name(ManagementId::Reserved(0xc000)), "0xc000");
+    }
+
+    #[test]
+    fn set_values() {
+        assert_eq!(
+            parse_set_value(ManagementId::Priority2, "12"),
+            Ok(ManagementData::Priority2(12))
+        );
+        assert_eq!(
+            parse_set_value(ManagementId::SlaveOnly, "1"),
+            Ok(ManagementData::SlaveOnly(true))
+        );
+        assert_eq!(
+            parse_set_value(ManagementId::LogSyncInterval, "-3"),
+            Ok(ManagementData::LogSyncInterval(-3))
+        );
+        assert!(parse_set_value(ManagementId::Priority1, "256").is_err());
+        assert!(parse_set_value(ManagementId::DefaultDataSet, "1").is_err());
+    }
+
+    #[test]
+    fn format_response() {
+        let request = ManagementRequest {
+            sdo_id: Default::default(),
+            domain_number: 0,
+            source_port_identity: PortIdentity::default(),
+            sequence_id: 7,
+            target_port_identity: PortIdentity::default(),
+            boundary_hops: 1,
+            action: ManagementAction::GET,
+            data: ManagementData::Empty(ManagementId::Priority1),
+        };
+        let response = ManagementResponse {
+            sdo_id: Default::default(),
+            domain_number: 0,
+            source_port_identity: PortIdentity {
+                clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+                port_number: 1,
+            },
+            sequence_id: 7,
+            target_port_identity: PortIdentity::default(),
+            action: ManagementAction::RESPONSE,
+            result: Ok(ManagementData::Priority1(128)),
+        };
+
+        let mut output = String::new();
+        format_responses(&mut output, &request, &[response]).unwrap();
+        assert_eq!(
+            output,
+            "sending: GET PRIORITY1\n\
+             \t0102030405060708-1 seq 7 RESPONSE MANAGEMENT PRIORITY1\n\
+             \t\tpriority1                               128\n"
+        );
+
+        assert_eq!(
+            responses_to_json(&[response]),
+            json!([{
+                "sourcePortIdentity": "0102030405060708-1",
+                "sequenceId": 7,
+                "action": "RESPONSE",
+                "managementId": "PRIORITY1",
+                "data": { "priority1": 128 },
+            }])
+        );
+    }
+}
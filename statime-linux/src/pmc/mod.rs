@@ -0,0 +1,443 @@
+//! A PTP management client, comparable to linuxptp's `pmc`
+
+use std::{
+    net::{SocketAddrV4, SocketAddrV6},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use statime::{
+    config::{ClockIdentity, SdoId, SecurityConfig},
+    management::{
+        ManagementAction, ManagementData, ManagementId, ManagementRequest, ManagementResponse,
+        PortIdentity, ALL_PORTS,
+    },
+    port::MAX_DATA_LEN,
+};
+use timestamped_socket::{
+    interface::{interfaces, InterfaceName},
+    networkaddress::{EthernetAddress, NetworkAddress},
+    socket::{InterfaceTimestampMode, Open, Socket},
+};
+use tokio::net::UnixStream;
+
+use crate::{
+    config::Config,
+    metrics::exporter::{read_json, ObservableState},
+    security::{key_provider, now},
+    socket::{
+        open_ethernet_socket, open_ipv4_general_socket, open_ipv6_general_socket, PtpTargetAddress,
+    },
+};
+
+mod format;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub(crate) struct Args {
+    /// Print the results as JSON instead of human-readable text
+    #[clap(long = "json", global = true)]
+    json: bool,
+    #[clap(subcommand)]
+    action: Action,
+}
+
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Show the datasets of the statime daemon running on this machine
+    Local {
+        /// Configuration file of the daemon, used to find its observation
+        /// socket
+        #[clap(
+            long = "config",
+            short = 'c',
+            default_value = "/etc/statime/statime.toml"
+        )]
+        config: PathBuf,
+    },
+    /// Request the value of a management id, e.g. DEFAULT_DATA_SET
+    Get {
+        /// The management id to request
+        id: ManagementIdArg,
+        /// The record to request for PERFORMANCE_MONITORING_RECORD: 0 to 95
+        /// for the 15-minute records, most recent first, and 96 and 97 for
+        /// the current and previous 24-hour record
+        #[clap(long = "index")]
+        index: Option<u16>,
+        #[clap(flatten)]
+        network: NetworkArgs,
+    },
+    /// Change the value of a management id, e.g. PRIORITY1 128
+    Set {
+        /// The management id to change
+        id: ManagementIdArg,
+        /// The new value
+        value: String,
+        #[clap(flatten)]
+        network: NetworkArgs,
+    },
+    /// Send a command, e.g. DISABLE_PORT
+    Command {
+        /// The management id of the command
+        id: ManagementIdArg,
+        #[clap(flatten)]
+        network: NetworkArgs,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct NetworkArgs {
+    /// Network interface to send the management message on
+    #[clap(long = "interface", short = 'i', value_parser = parse_interface_name)]
+    interface: InterfaceName,
+    /// Network transport to use
+    #[clap(long = "transport", short = 't', value_enum, default_value = "ipv4")]
+    transport: Transport,
+    /// PTP domain of the targeted instances
+    #[clap(long = "domain", short = 'd', default_value = "0")]
+    domain: u8,
+    /// sdoId of the targeted instances
+    #[clap(long = "sdo-id", default_value = "0")]
+    sdo_id: u16,
+    /// Port identity to address, as <clock identity>-<port number>. Defaults
+    /// to all ports of all instances
+    #[clap(long = "target", short = 'p')]
+    target: Option<TargetPortIdentity>,
+    /// Number of boundary clocks the message may be forwarded through
+    #[clap(long = "boundary-hops", short = 'b', default_value = "1")]
+    boundary_hops: u8,
+    /// Time to wait for responses, in milliseconds
+    #[clap(long = "timeout", default_value = "1000")]
+    timeout: u64,
+    /// Configuration file of the targeted daemon, whose [security] section is
+    /// used to authenticate the request
+    #[clap(long = "config", short = 'c')]
+    config: Option<PathBuf>,
+}
+
+fn parse_interface_name(s: &str) -> Result<InterfaceName, String> {
+    InterfaceName::from_str(s).map_err(|_| format!("invalid interface name: {s}"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    Ipv4,
+    Ipv6,
+    Ethernet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ManagementIdArg(ManagementId);
+
+impl FromStr for ManagementIdArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        format::management_id_from_name(s)
+            .map(ManagementIdArg)
+            .ok_or_else(|| format!("unknown management id: {s}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TargetPortIdentity(PortIdentity);
+
+impl FromStr for TargetPortIdentity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use hex::FromHex;
+
+        let (clock_identity, port_number) = s
+            .rsplit_once('-')
+            .ok_or_else(|| format!("expected <clock identity>-<port number>, got {s}"))?;
+
+        let clock_identity: String = clock_identity
+            .chars()
+            .filter(|c| !matches!(c, ':' | '.'))
+            .collect();
+        let clock_identity = match clock_identity.as_str() {
+            "*" => ALL_PORTS.clock_identity,
+            _ => ClockIdentity(
+                <[u8; 8]>::from_hex(clock_identity)
+                    .map_err(|e| format!("invalid clock identity: {e}"))?,
+            ),
+        };
+        let port_number = match port_number {
+            "*" => ALL_PORTS.port_number,
+            _ => port_number
+                .parse()
+                .map_err(|e| format!("invalid port number: {e}"))?,
+        };
+
+        Ok(TargetPortIdentity(PortIdentity {
+            clock_identity,
+            port_number,
+        }))
+    }
+}
+
+pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Args::parse();
+
+    let (network, action, data) = match options.action {
+        Action::Local { config } => {
+            let config = match Config::from_file(config.as_path()) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+            return query_local(config, options.json).await;
+        }
+        Action::Get { id, index, network } => {
+            let data = match index {
+                None => ManagementData::Empty(id.0),
+                Some(index) if id.0 == ManagementId::PerformanceMonitoringRecord => {
+                    ManagementData::PerformanceMonitoringRecordIndex(index)
+                }
+                Some(_) => {
+                    eprintln!("--index is only supported for PERFORMANCE_MONITORING_RECORD");
+                    std::process::exit(1);
+                }
+            };
+            (network, ManagementAction::GET, data)
+        }
+        Action::Set { id, value, network } => {
+            let data = match format::parse_set_value(id.0, &value) {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+            (network, ManagementAction::SET, data)
+        }
+        Action::Command { id, network } => (
+            network,
+            ManagementAction::COMMAND,
+            ManagementData::Empty(id.0),
+        ),
+    };
+
+    let Ok(sdo_id) = SdoId::try_from(network.sdo_id) else {
+        eprintln!("sdo-id must be in the range 0..=0xfff");
+        std::process::exit(1);
+    };
+
+    let request = ManagementRequest {
+        sdo_id,
+        domain_number: network.domain,
+        source_port_identity: source_port_identity(network.interface),
+        sequence_id: rand::random(),
+        target_port_identity: network.target.map(|t| t.0).unwrap_or(ALL_PORTS),
+        boundary_hops: network.boundary_hops,
+        action,
+        data,
+    };
+
+    let security = match &network.config {
+        Some(path) => match Config::from_file(path) {
+            Ok(Config {
+                security: Some(security),
+                ..
+            }) => match key_provider(&security) {
+                Ok(mut provider) => Some(provider.security_config().active(now())),
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            },
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let timeout = Duration::from_millis(network.timeout);
+    let security = security.as_ref();
+    let responses = match network.transport {
+        Transport::Ipv4 => {
+            let socket = open_ipv4_general_socket(network.interface)?;
+            exchange::<SocketAddrV4>(socket, &request, security, timeout).await?
+        }
+        Transport::Ipv6 => {
+            let socket = open_ipv6_general_socket(network.interface)?;
+            exchange::<SocketAddrV6>(socket, &request, security, timeout).await?
+        }
+        Transport::Ethernet => {
+            let socket = open_ethernet_socket(network.interface, InterfaceTimestampMode::None)?;
+            exchange::<EthernetAddress>(socket, &request, security, timeout).await?
+        }
+    };
+
+    if options.json {
+        let json = format::responses_to_json(&responses);
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        let mut output = String::new();
+        format::format_responses(&mut output, &request, &responses)?;
+        print!("{output}");
+    }
+
+    if responses.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+// Like linuxptp's pmc, identify ourselves by the mac address of the interface
+// we use and our process id
+fn source_port_identity(interface: InterfaceName) -> PortIdentity {
+    let mac = interfaces()
+        .ok()
+        .and_then(|interfaces| interfaces.get(&interface).and_then(|data| data.mac()))
+        .unwrap_or_default();
+
+    PortIdentity {
+        clock_identity: ClockIdentity::from_mac_address(mac),
+        port_number: std::process::id() as u16,
+    }
+}
+
+/// Send `request`, authenticated when a security configuration is given, and
+/// collect all responses to it that arrive within `timeout`
+async fn exchange<A: NetworkAddress + PtpTargetAddress>(
+    mut socket: Socket<A, Open>,
+    request: &ManagementRequest,
+    security: Option<&SecurityConfig>,
+    timeout: Duration,
+) -> std::io::Result<Vec<ManagementResponse>> {
+    let mut buffer = [0; MAX_DATA_LEN];
+    let length = match security {
+        Some(security) => request.serialize_authenticated(&mut buffer, security),
+        None => request.serialize(&mut buffer),
+    }
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    socket
+        .send_to(&buffer[..length], A::PRIMARY_GENERAL)
+        .await?;
+
+    let mut responses = vec![];
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let packet = match tokio::time::timeout_at(deadline, socket.recv(&mut buffer)).await {
+            Ok(packet) => packet?,
+            Err(_elapsed) => break,
+        };
+
+        match ManagementResponse::deserialize(&buffer[..packet.bytes_read]) {
+            Ok(Some(response))
+                if response.sequence_id == request.sequence_id
+                    && response.target_port_identity == request.source_port_identity =>
+            {
+                responses.push(response)
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Ignoring malformed message: {e}"),
+        }
+    }
+
+    Ok(responses)
+}
+
+async fn query_local(config: Config, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(observation_socket_path) = config.observability.observation_path else {
+        eprintln!(
+            "An observation socket path must be configured using the observation-path option in \
+             the [observability] section of the configuration"
+        );
+        std::process::exit(1);
+    };
+
+    let mut stream = UnixStream::connect(observation_socket_path).await?;
+    let mut msg = Vec::with_capacity(16 * 1024);
+    let observable_state: ObservableState = read_json(&mut stream, &mut msg).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&observable_state)?);
+    } else {
+        let mut output = String::new();
+        format::format_local(&mut output, &observable_state)?;
+        print!("{output}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::Parser;
+
+    use super::*;
+
+    const BINARY: &str = "/usr/bin/statime-pmc";
+
+    #[test]
+    fn cli_local() {
+        let options = Args::try_parse_from([BINARY, "local", "-c", "/foo/statime.toml"]).unwrap();
+        assert!(!options.json);
+        assert!(
+            matches!(options.action, Action::Local { config } if config == Path::new("/foo/statime.toml"))
+        );
+    }
+
+    #[test]
+    fn cli_set() {
+        let options = Args::try_parse_from([
+            BINARY,
+            "set",
+            "priority1",
+            "12",
+            "-i",
+            "lo",
+            "-t",
+            "ethernet",
+            "-p",
+            "0011:2233:4455:6677-2",
+            "--json",
+        ])
+        .unwrap();
+        assert!(options.json);
+
+        let Action::Set { id, value, network } = options.action else {
+            panic!("Expected a set command");
+        };
+        assert_eq!(id.0, ManagementId::Priority1);
+        assert_eq!(value, "12");
+        assert_eq!(network.transport, Transport::Ethernet);
+        assert_eq!(network.boundary_hops, 1);
+        assert_eq!(
+            network.target.unwrap().0,
+            PortIdentity {
+                clock_identity: ClockIdentity([0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77]),
+                port_number: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn target_port_identity() {
+        assert_eq!(TargetPortIdentity::from_str("*-*").unwrap().0, ALL_PORTS);
+        assert_eq!(
+            TargetPortIdentity::from_str("001122.fffe.334455-1")
+                .unwrap()
+                .0,
+            PortIdentity {
+                clock_identity: ClockIdentity([0x00, 0x11, 0x22, 0xff, 0xfe, 0x33, 0x44, 0x55]),
+                port_number: 1,
+            }
+        );
+        assert!(TargetPortIdentity::from_str("0011223344556677").is_err());
+        assert!(TargetPortIdentity::from_str("00112233-1").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Keys for the authentication of PTP messages
+//!
+//! The keys come either from the `[security]` section of the configuration,
+//! or from a separate key file that is read again whenever it changes. Keys
+//! can then be rotated by replacing the key file, without restarting the
+//! daemon.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use statime::{
+    config::{KeyProvider, SecurityKey},
+    time::Time,
+};
+
+use crate::config::{check_keys, KeyFileConfig, SecurityConfig, SecurityKeyConfig};
+
+#[derive(Debug)]
+pub enum KeyFileError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Keys(&'static str),
+}
+
+impl std::fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyFileError::Io(e) => write!(f, "io error while reading the key file: {e}"),
+            KeyFileError::Toml(e) => write!(f, "key file toml parsing error: {e}"),
+            KeyFileError::Keys(e) => write!(f, "invalid keys: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyFileError {}
+
+impl From<std::io::Error> for KeyFileError {
+    fn from(value: std::io::Error) -> Self {
+        KeyFileError::Io(value)
+    }
+}
+
+/// The provider of the keys of `config`
+pub fn key_provider(config: &SecurityConfig) -> Result<Box<dyn KeyProvider + Send>, KeyFileError> {
+    match &config.key_file {
+        Some(path) => Ok(Box::new(KeyFile::open(path, config.spp)?)),
+        None => Ok(Box::new(security_config(
+            config.spp,
+            config.key_id.unwrap_or_default(),
+            &config.keys,
+        ))),
+    }
+}
+
+/// The current time, in the timescale of the validity windows of the keys
+pub fn now() -> Time {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Time::from_nanos(now.as_nanos() as u64)
+}
+
+fn security_config(
+    spp: u8,
+    key_id: u32,
+    keys: &[SecurityKeyConfig],
+) -> statime::config::SecurityConfig {
+    statime::config::SecurityConfig {
+        spp,
+        keys: keys.iter().map(SecurityKey::from).collect(),
+        key_id,
+    }
+}
+
+/// Keys read from a key file, which are replaced when the file changes
+pub struct KeyFile {
+    path: PathBuf,
+    spp: u8,
+    modified: SystemTime,
+    config: statime::config::SecurityConfig,
+}
+
+impl KeyFile {
+    pub fn open(path: &Path, spp: u8) -> Result<Self, KeyFileError> {
+        let (modified, config) = Self::read(path, spp)?;
+        Ok(KeyFile {
+            path: path.to_owned(),
+            spp,
+            modified,
+            config,
+        })
+    }
+
+    fn read(
+        path: &Path,
+        spp: u8,
+    ) -> Result<(SystemTime, statime::config::SecurityConfig), KeyFileError> {
+        let modified = std::fs::metadata(path)?.modified()?;
+        let contents = std::fs::read_to_string(path)?;
+        let keys: KeyFileConfig = toml::from_str(&contents).map_err(KeyFileError::Toml)?;
+        check_keys(keys.key_id, &keys.keys).map_err(KeyFileError::Keys)?;
+
+        Ok((modified, security_config(spp, keys.key_id, &keys.keys)))
+    }
+
+    /// Read the key file again when it was modified. On errors, the previous
+    /// keys are kept.
+    fn reload(&mut self) {
+        match std::fs::metadata(&self.path).and_then(|meta| meta.modified()) {
+            Ok(modified) if modified == self.modified => return,
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("Could not check key file {}: {e}", self.path.display());
+                return;
+            }
+        }
+
+        match Self::read(&self.path, self.spp) {
+            Ok((modified, config)) => {
+                log::info!("Reloaded keys from {}", self.path.display());
+                self.modified = modified;
+                self.config = config;
+            }
+            Err(e) => {
+                log::warn!("Keeping the previous keys: {e}");
+                // Don't retry until the file changes again
+                if let Ok(modified) = std::fs::metadata(&self.path).and_then(|m| m.modified()) {
+                    self.modified = modified;
+                }
+            }
+        }
+    }
+}
+
+impl KeyProvider for KeyFile {
+    fn security_config(&mut self) -> &statime::config::SecurityConfig {
+        self.reload();
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_file_reload() {
+        let path = std::env::temp_dir().join(format!("statime-keys-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+key-id = 1
+
+[[key]]
+id = 1
+secret = "00112233445566778899aabbccddeeff"
+valid-until = 2000
+"#,
+        )
+        .unwrap();
+
+        let mut provider = KeyFile::open(&path, 4).unwrap();
+        let config = provider.security_config();
+        assert_eq!(config.spp, 4);
+        assert_eq!(config.key_id, 1);
+        assert_eq!(config.keys.len(), 1);
+        assert_eq!(config.keys[0].valid_until, Some(Time::from_secs(2000)));
+
+        // Unchanged files are not read again
+        std::fs::write(&path, "key-id = 3\n").unwrap();
+        provider.modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(provider.security_config().key_id, 1);
+
+        // The modification time may not change on every write
+        std::fs::write(
+            &path,
+            r#"
+key-id = 2
+
+[[key]]
+id = 1
+secret = "00112233445566778899aabbccddeeff"
+valid-until = 2000
+
+[[key]]
+id = 2
+secret = "ffeeddccbbaa99887766554433221100"
+valid-from = 1900
+"#,
+        )
+        .unwrap();
+        provider.modified = UNIX_EPOCH;
+
+        let config = provider.security_config();
+        assert_eq!(config.key_id, 2);
+        assert_eq!(config.keys.len(), 2);
+
+        // Invalid keys don't replace the previous ones
+        std::fs::write(&path, "key-id = 3\n").unwrap();
+        provider.modified = UNIX_EPOCH;
+        assert_eq!(provider.security_config().key_id, 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
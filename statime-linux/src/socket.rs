@@ -4,7 +4,7 @@
 
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
 
-use statime::time::Time;
+use statime::{config::PortAddress, time::Time};
 use timestamped_socket::{
     interface::InterfaceName,
     networkaddress::{EthernetAddress, MacAddress},
@@ -25,11 +25,22 @@ const GENERAL_PORT: u16 = 320;
 
 const PTP_ETHERTYPE: u16 = 0x88f7;
 
-pub trait PtpTargetAddress {
+pub trait PtpTargetAddress: Sized {
     const PRIMARY_EVENT: Self;
     const PRIMARY_GENERAL: Self;
     const PDELAY_EVENT: Self;
     const PDELAY_GENERAL: Self;
+
+    /// The address statime knows the sender of a message by
+    fn port_address(&self) -> PortAddress;
+
+    /// Where to send unicast event messages for the port at `address`, or
+    /// `None` if it can't be reached with this kind of socket
+    fn unicast_event(address: PortAddress) -> Option<Self>;
+
+    /// Where to send unicast general messages for the port at `address`, or
+    /// `None` if it can't be reached with this kind of socket
+    fn unicast_general(address: PortAddress) -> Option<Self>;
 }
 
 impl PtpTargetAddress for SocketAddrV4 {
@@ -37,6 +48,24 @@ impl PtpTargetAddress for SocketAddrV4 {
     const PRIMARY_GENERAL: Self = SocketAddrV4::new(IPV4_PRIMARY_MULTICAST, GENERAL_PORT);
     const PDELAY_EVENT: Self = SocketAddrV4::new(IPV4_PDELAY_MULTICAST, EVENT_PORT);
     const PDELAY_GENERAL: Self = SocketAddrV4::new(IPV4_PDELAY_MULTICAST, GENERAL_PORT);
+
+    fn port_address(&self) -> PortAddress {
+        (*self.ip()).into()
+    }
+
+    fn unicast_event(address: PortAddress) -> Option<Self> {
+        match address {
+            PortAddress::Ipv4(ip) => Some(SocketAddrV4::new(ip.into(), EVENT_PORT)),
+            _ => None,
+        }
+    }
+
+    fn unicast_general(address: PortAddress) -> Option<Self> {
+        match address {
+            PortAddress::Ipv4(ip) => Some(SocketAddrV4::new(ip.into(), GENERAL_PORT)),
+            _ => None,
+        }
+    }
 }
 
 impl PtpTargetAddress for SocketAddrV6 {
@@ -44,6 +73,24 @@ impl PtpTargetAddress for SocketAddrV6 {
     const PRIMARY_GENERAL: Self = SocketAddrV6::new(IPV6_PRIMARY_MULTICAST, GENERAL_PORT, 0, 0);
     const PDELAY_EVENT: Self = SocketAddrV6::new(IPV6_PDELAY_MULTICAST, EVENT_PORT, 0, 0);
     const PDELAY_GENERAL: Self = SocketAddrV6::new(IPV6_PDELAY_MULTICAST, GENERAL_PORT, 0, 0);
+
+    fn port_address(&self) -> PortAddress {
+        (*self.ip()).into()
+    }
+
+    fn unicast_event(address: PortAddress) -> Option<Self> {
+        match address {
+            PortAddress::Ipv6(ip) => Some(SocketAddrV6::new(ip.into(), EVENT_PORT, 0, 0)),
+            _ => None,
+        }
+    }
+
+    fn unicast_general(address: PortAddress) -> Option<Self> {
+        match address {
+            PortAddress::Ipv6(ip) => Some(SocketAddrV6::new(ip.into(), GENERAL_PORT, 0, 0)),
+            _ => None,
+        }
+    }
 }
 
 impl PtpTargetAddress for EthernetAddress {
@@ -59,6 +106,25 @@ impl PtpTargetAddress for EthernetAddress {
         0,
     );
     const PDELAY_GENERAL: Self = Self::PDELAY_EVENT;
+
+    fn port_address(&self) -> PortAddress {
+        let mut mac = [0; 6];
+        mac.copy_from_slice(self.mac().as_ref());
+        PortAddress::Ethernet(mac)
+    }
+
+    fn unicast_event(address: PortAddress) -> Option<Self> {
+        match address {
+            PortAddress::Ethernet(mac) => {
+                Some(EthernetAddress::new(PTP_ETHERTYPE, MacAddress::new(mac), 0))
+            }
+            _ => None,
+        }
+    }
+
+    fn unicast_general(address: PortAddress) -> Option<Self> {
+        Self::unicast_event(address)
+    }
 }
 
 pub fn open_ipv4_event_socket(
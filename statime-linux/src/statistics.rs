@@ -0,0 +1,623 @@
+//! Time-series of the synchronization, written to a file for later analysis
+//!
+//! Every measurement passed to a filter, every frequency adjustment and step
+//! of a clock by a filter, and every port state transition is written as a
+//! line of CSV or JSON. The file is rotated once it grows beyond its maximum
+//! size, keeping a configured number of older files next to it with the
+//! suffixes `.1`, `.2` and so on, `.1` being the most recent one.
+
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use statime::{
+    config::TimePropertiesDS,
+    filters::{ClockUncertainty, Filter, FilterUpdate},
+    observability::port::PortState,
+    port::Measurement,
+    time::{Duration, Time},
+    Clock,
+};
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
+
+use crate::config::{StatisticsConfig, StatisticsFormat};
+
+/// Number of records that can wait for the writer before new records are
+/// dropped
+const CHANNEL_SIZE: usize = 1024;
+
+const CSV_HEADER: &str = "time,source,event,event_time,offset,delay,peer_delay,raw_sync_offset,\
+                          raw_delay_offset,frequency,step,from_state,to_state\n";
+
+/// What produced a [`StatisticsRecord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsSource {
+    /// A port, identified by its port number
+    Port(u16),
+    /// The task keeping a hardware clock synchronized with the system clock
+    Clock(usize),
+}
+
+impl std::fmt::Display for StatisticsSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatisticsSource::Port(number) => write!(f, "port {number}"),
+            StatisticsSource::Clock(id) => write!(f, "clock {id}"),
+        }
+    }
+}
+
+impl Serialize for StatisticsSource {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A single line of the statistics file
+///
+/// Times are in nanoseconds since the unix epoch, offsets and delays in
+/// nanoseconds and frequencies in ppm.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatisticsRecord {
+    pub time: u128,
+    pub source: StatisticsSource,
+    #[serde(flatten)]
+    pub event: StatisticsEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum StatisticsEvent {
+    /// A measurement was passed to a filter
+    Measurement {
+        /// Nanoseconds since the start of the timescale of the measuring clock
+        event_time: u128,
+        offset: Option<i128>,
+        delay: Option<i128>,
+        peer_delay: Option<i128>,
+        raw_sync_offset: Option<i128>,
+        raw_delay_offset: Option<i128>,
+    },
+    /// A filter changed the frequency of its clock
+    FrequencyAdjustment { frequency: f64 },
+    /// A filter stepped its clock
+    Step { offset: i128 },
+    /// A port changed its state
+    PortState { from: PortState, to: PortState },
+}
+
+impl StatisticsRecord {
+    fn to_csv(&self) -> String {
+        fn opt<T: ToString>(value: Option<T>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+
+        let mut columns: [String; 11] = Default::default();
+        match &self.event {
+            StatisticsEvent::Measurement {
+                event_time,
+                offset,
+                delay,
+                peer_delay,
+                raw_sync_offset,
+                raw_delay_offset,
+            } => {
+                columns[0] = "measurement".into();
+                columns[1] = event_time.to_string();
+                columns[2] = opt(*offset);
+                columns[3] = opt(*delay);
+                columns[4] = opt(*peer_delay);
+                columns[5] = opt(*raw_sync_offset);
+                columns[6] = opt(*raw_delay_offset);
+            }
+            StatisticsEvent::FrequencyAdjustment { frequency } => {
+                columns[0] = "frequency-adjustment".into();
+                columns[7] = frequency.to_string();
+            }
+            StatisticsEvent::Step { offset } => {
+                columns[0] = "step".into();
+                columns[8] = offset.to_string();
+            }
+            StatisticsEvent::PortState { from, to } => {
+                columns[0] = "port-state".into();
+                columns[9] = format!("{from:?}");
+                columns[10] = format!("{to:?}");
+            }
+        }
+
+        format!("{},{},{}\n", self.time, self.source, columns.join(","))
+    }
+
+    fn to_json_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("statistics records serialize");
+        line.push('\n');
+        line
+    }
+}
+
+/// Handle through which the daemon tasks pass [`StatisticsRecord`]s to the
+/// writer of the statistics file
+///
+/// A disabled sink drops all records. Recording never blocks the daemon:
+/// records that arrive while the writer is too far behind are dropped and
+/// counted, and the writer logs a warning about them.
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsSink {
+    sender: Option<Sender<StatisticsRecord>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl StatisticsSink {
+    /// Opens the statistics file and spawns the task writing to it
+    pub fn spawn(config: &StatisticsConfig) -> std::io::Result<Self> {
+        let mut writer = RotatingWriter::open(config)?;
+        let (sink, mut receiver) = Self::channel(CHANNEL_SIZE);
+        let dropped = sink.dropped.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut reported = 0;
+            while let Some(record) = receiver.blocking_recv() {
+                if let Err(error) = writer.write(&record) {
+                    log::error!("Could not write statistics: {}", error);
+                }
+
+                let dropped = dropped.load(Ordering::Relaxed);
+                if dropped > reported {
+                    log::warn!(
+                        "Dropped {} statistics records, the writer could not keep up",
+                        dropped - reported
+                    );
+                    reported = dropped;
+                }
+            }
+        });
+
+        Ok(sink)
+    }
+
+    fn channel(size: usize) -> (Self, Receiver<StatisticsRecord>) {
+        let (sender, receiver) = channel(size);
+        let sink = Self {
+            sender: Some(sender),
+            dropped: Arc::default(),
+        };
+        (sink, receiver)
+    }
+
+    /// The number of records dropped because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn record(&self, source: StatisticsSource, event: StatisticsEvent) {
+        if let Some(sender) = &self.sender {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+
+            let record = StatisticsRecord {
+                time,
+                source,
+                event,
+            };
+
+            // The writer only stops when the sink is dropped
+            if let Err(TrySendError::Full(_)) = sender.try_send(record) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Writes lines to a file, rotating it when it grows beyond the configured
+/// size
+struct RotatingWriter {
+    path: PathBuf,
+    format: StatisticsFormat,
+    max_size: u64,
+    max_files: u32,
+    file: File,
+    size: u64,
+}
+
+impl RotatingWriter {
+    fn open(config: &StatisticsConfig) -> std::io::Result<Self> {
+        let (file, size) = Self::open_file(&config.path, config.format)?;
+
+        Ok(Self {
+            path: config.path.clone(),
+            format: config.format,
+            max_size: config.max_size,
+            max_files: config.max_files,
+            file,
+            size,
+        })
+    }
+
+    fn open_file(path: &Path, format: StatisticsFormat) -> std::io::Result<(File, u64)> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let mut size = file.metadata()?.len();
+
+        if size == 0 && format == StatisticsFormat::Csv {
+            file.write_all(CSV_HEADER.as_bytes())?;
+            size = CSV_HEADER.len() as u64;
+        }
+
+        Ok((file, size))
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        (self.file, self.size) = Self::open_file(&self.path, self.format)?;
+        Ok(())
+    }
+
+    fn write(&mut self, record: &StatisticsRecord) -> std::io::Result<()> {
+        let line = match self.format {
+            StatisticsFormat::Csv => record.to_csv(),
+            StatisticsFormat::JsonLines => record.to_json_line(),
+        };
+
+        let header_size = match self.format {
+            StatisticsFormat::Csv => CSV_HEADER.len() as u64,
+            StatisticsFormat::JsonLines => 0,
+        };
+        if self.size > header_size && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn time_nanos(time: Time) -> u128 {
+    time.secs() as u128 * 1_000_000_000 + time.subsec_nanos() as u128
+}
+
+impl From<Measurement> for StatisticsEvent {
+    fn from(measurement: Measurement) -> Self {
+        StatisticsEvent::Measurement {
+            event_time: time_nanos(measurement.event_time),
+            offset: measurement.offset.map(|offset| offset.nanos_rounded()),
+            delay: measurement.delay.map(|delay| delay.nanos_rounded()),
+            peer_delay: measurement.peer_delay.map(|delay| delay.nanos_rounded()),
+            raw_sync_offset: measurement
+                .raw_sync_offset
+                .map(|offset| offset.nanos_rounded()),
+            raw_delay_offset: measurement
+                .raw_delay_offset
+                .map(|offset| offset.nanos_rounded()),
+        }
+    }
+}
+
+/// Configuration of a [`StatisticsFilter`]
+#[derive(Debug, Clone)]
+pub struct StatisticsFilterConfig<C> {
+    pub inner: C,
+    pub sink: StatisticsSink,
+    pub source: StatisticsSource,
+}
+
+/// A [`Filter`] that records its measurements and the clock adjustments of
+/// the filter it wraps in a [`StatisticsSink`]
+pub struct StatisticsFilter<F> {
+    inner: F,
+    sink: StatisticsSink,
+    source: StatisticsSource,
+}
+
+impl<F: Filter> Filter for StatisticsFilter<F> {
+    type Config = StatisticsFilterConfig<F::Config>;
+
+    fn new(config: Self::Config) -> Self {
+        Self {
+            inner: F::new(config.inner),
+            sink: config.sink,
+            source: config.source,
+        }
+    }
+
+    fn measurement<C: Clock>(&mut self, m: Measurement, clock: &mut C) -> FilterUpdate {
+        self.sink.record(self.source, m.into());
+
+        let mut clock = RecordingClock {
+            inner: clock,
+            sink: &self.sink,
+            source: self.source,
+        };
+        self.inner.measurement(m, &mut clock)
+    }
+
+    fn update<C: Clock>(&mut self, clock: &mut C) -> FilterUpdate {
+        let mut clock = RecordingClock {
+            inner: clock,
+            sink: &self.sink,
+            source: self.source,
+        };
+        self.inner.update(&mut clock)
+    }
+
+    fn demobilize<C: Clock>(self, clock: &mut C) {
+        let mut clock = RecordingClock {
+            inner: clock,
+            sink: &self.sink,
+            source: self.source,
+        };
+        self.inner.demobilize(&mut clock)
+    }
+
+    fn uncertainty(&self) -> Option<ClockUncertainty> {
+        self.inner.uncertainty()
+    }
+}
+
+/// A [`Clock`] that records the adjustments made through it
+struct RecordingClock<'a, C> {
+    inner: &'a mut C,
+    sink: &'a StatisticsSink,
+    source: StatisticsSource,
+}
+
+impl<C: Clock> Clock for RecordingClock<'_, C> {
+    type Error = C::Error;
+
+    fn now(&self) -> Time {
+        self.inner.now()
+    }
+
+    fn step_clock(&mut self, offset: Duration) -> Result<Time, Self::Error> {
+        let time = self.inner.step_clock(offset)?;
+        self.sink.record(
+            self.source,
+            StatisticsEvent::Step {
+                offset: offset.nanos_rounded(),
+            },
+        );
+        Ok(time)
+    }
+
+    fn set_frequency(&mut self, ppm: f64) -> Result<Time, Self::Error> {
+        let time = self.inner.set_frequency(ppm)?;
+        self.sink.record(
+            self.source,
+            StatisticsEvent::FrequencyAdjustment { frequency: ppm },
+        );
+        Ok(time)
+    }
+
+    fn set_properties(&mut self, time_properties_ds: &TimePropertiesDS) -> Result<(), Self::Error> {
+        self.inner.set_properties(time_properties_ds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use statime::filters::BasicFilter;
+
+    use super::*;
+
+    struct TestClock;
+
+    impl Clock for TestClock {
+        type Error = ();
+
+        fn now(&self) -> Time {
+            Time::from_secs(1000)
+        }
+
+        fn step_clock(&mut self, _offset: Duration) -> Result<Time, Self::Error> {
+            Ok(self.now())
+        }
+
+        fn set_frequency(&mut self, _ppm: f64) -> Result<Time, Self::Error> {
+            Ok(self.now())
+        }
+
+        fn set_properties(&mut self, _: &TimePropertiesDS) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn measurement_record() -> StatisticsRecord {
+        StatisticsRecord {
+            time: 1_700_000_000_000_000_000,
+            source: StatisticsSource::Port(1),
+            event: Measurement {
+                event_time: Time::from_fixed_nanos(5_000_000_250u64),
+                offset: Some(Duration::from_nanos(-20)),
+                delay: Some(Duration::from_nanos(100)),
+                peer_delay: None,
+                raw_sync_offset: Some(Duration::from_nanos(80)),
+                raw_delay_offset: None,
+            }
+            .into(),
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let record = measurement_record();
+        assert_eq!(
+            record.to_csv(),
+            "1700000000000000000,port 1,measurement,5000000250,-20,100,,80,,,,,\n"
+        );
+        assert_eq!(
+            record.to_json_line(),
+            "{\"time\":1700000000000000000,\"source\":\"port 1\",\"event\":\"measurement\",\
+             \"event_time\":5000000250,\"offset\":-20,\"delay\":100,\"peer_delay\":null,\
+             \"raw_sync_offset\":80,\"raw_delay_offset\":null}\n"
+        );
+
+        let record = StatisticsRecord {
+            time: 1,
+            source: StatisticsSource::Clock(0),
+            event: StatisticsEvent::FrequencyAdjustment { frequency: 1.5 },
+        };
+        assert_eq!(
+            record.to_csv(),
+            "1,clock 0,frequency-adjustment,,,,,,,1.5,,,\n"
+        );
+        assert_eq!(
+            record.to_json_line(),
+            "{\"time\":1,\"source\":\"clock 0\",\"event\":\"frequency-adjustment\",\
+             \"frequency\":1.5}\n"
+        );
+
+        let record = StatisticsRecord {
+            time: 1,
+            source: StatisticsSource::Port(2),
+            event: StatisticsEvent::PortState {
+                from: PortState::Listening,
+                to: PortState::Slave,
+            },
+        };
+        assert_eq!(
+            record.to_csv(),
+            "1,port 2,port-state,,,,,,,,,Listening,Slave\n"
+        );
+        assert_eq!(
+            record.to_json_line(),
+            "{\"time\":1,\"source\":\"port 2\",\"event\":\"port-state\",\"from\":\"Listening\",\
+             \"to\":\"Slave\"}\n"
+        );
+    }
+
+    #[test]
+    fn rotation() {
+        let path = std::env::temp_dir().join(format!("statime-stats-{}.csv", std::process::id()));
+        let record = measurement_record();
+        let line = record.to_csv();
+        let config = StatisticsConfig {
+            path: path.clone(),
+            format: StatisticsFormat::Csv,
+            max_size: (CSV_HEADER.len() + 2 * line.len()) as u64,
+            max_files: 2,
+        };
+        let mut writer = RotatingWriter::open(&config).unwrap();
+
+        // Two records fit in a file, and the rotated files are kept
+        for _ in 0..7 {
+            writer.write(&record).unwrap();
+        }
+        let rotated_1 = writer.rotated_path(1);
+        let rotated_2 = writer.rotated_path(2);
+        let rotated_3 = writer.rotated_path(3);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{CSV_HEADER}{line}")
+        );
+        assert_eq!(
+            std::fs::read_to_string(&rotated_1).unwrap(),
+            format!("{CSV_HEADER}{line}{line}")
+        );
+        assert!(rotated_2.exists());
+        assert!(!rotated_3.exists());
+
+        // A restart appends to the current file
+        drop(writer);
+        let mut writer = RotatingWriter::open(&config).unwrap();
+        writer.write(&record).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{CSV_HEADER}{line}{line}")
+        );
+
+        for path in [path, rotated_1, rotated_2] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn filter_records() {
+        let (sink, mut receiver) = StatisticsSink::channel(16);
+        let mut filter = StatisticsFilter::<BasicFilter>::new(StatisticsFilterConfig {
+            inner: 0.25,
+            sink,
+            source: StatisticsSource::Clock(3),
+        });
+
+        let measurement = Measurement {
+            event_time: Time::from_secs(1000),
+            offset: Some(Duration::from_secs(2)),
+            delay: Some(Duration::from_nanos(100)),
+            peer_delay: None,
+            raw_sync_offset: None,
+            raw_delay_offset: None,
+        };
+
+        // The large offset makes the filter step the clock
+        filter.measurement(measurement, &mut TestClock);
+        let record = receiver.try_recv().unwrap();
+        assert_eq!(record.source, StatisticsSource::Clock(3));
+        assert_eq!(record.event, measurement.into());
+        let record = receiver.try_recv().unwrap();
+        assert_eq!(
+            record.event,
+            StatisticsEvent::Step {
+                offset: -2_000_000_000
+            }
+        );
+        assert!(receiver.try_recv().is_err());
+
+        // The first small offset initializes the frequency
+        let measurement = Measurement {
+            offset: Some(Duration::from_nanos(10)),
+            ..measurement
+        };
+        filter.measurement(measurement, &mut TestClock);
+        assert_eq!(receiver.try_recv().unwrap().event, measurement.into());
+        assert_eq!(
+            receiver.try_recv().unwrap().event,
+            StatisticsEvent::FrequencyAdjustment { frequency: 0.0 }
+        );
+    }
+
+    #[test]
+    fn full_channel() {
+        let (sink, mut receiver) = StatisticsSink::channel(1);
+        let step = |offset| StatisticsEvent::Step { offset };
+
+        sink.record(StatisticsSource::Port(1), step(1));
+        sink.record(StatisticsSource::Port(1), step(2));
+        assert_eq!(sink.dropped(), 1);
+
+        assert_eq!(receiver.try_recv().unwrap().event, step(1));
+        assert!(receiver.try_recv().is_err());
+
+        sink.record(StatisticsSource::Port(1), step(3));
+        assert_eq!(receiver.try_recv().unwrap().event, step(3));
+        assert_eq!(sink.clone().dropped(), 1);
+    }
+}
@@ -0,0 +1,508 @@
+//! Monitoring of the external time source of a grandmaster
+//!
+//! statime does not steer the clock to the time source itself. The system clock
+//! is disciplined by a time daemon (for instance chrony using NTP, or gpsd with
+//! the PPS signal of a GNSS receiver), or a PHC is disciplined by a tool such
+//! as ts2phc. This module watches whether that source is locked, and derives
+//! the clock quality and time properties announced while we are the
+//! grandmaster.
+//!
+//! The offset of UTC to TAI and upcoming leap seconds are taken from the leap
+//! second table when one is configured, see [`crate::leap_seconds`], and from
+//! the kernel otherwise.
+
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use statime::{
+    config::{
+        ClockAccuracy, ClockClassDegradation, ClockQuality, LeapIndicator, TimePropertiesDS,
+        TimeSource,
+    },
+    Clock,
+};
+
+use crate::{
+    clock::{kernel_timex, LinuxClock},
+    config::{TimeSourceConfig, TimeSourceType},
+    leap_seconds::LeapSecondTable,
+};
+
+/// How long NMEA sentences stay valid, receivers send them every second
+const NMEA_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a PPS pulse stays valid
+const PPS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The state of the clock of a grandmaster relative to its time source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    /// Locked to the time source
+    Locked,
+    /// The time source was lost, but the clock is still within its holdover
+    /// specification
+    Holdover,
+    /// The time source was lost longer than the holdover specification allows
+    Degraded,
+    /// The clock has never been locked to the time source
+    FreeRunning,
+}
+
+impl LockState {
+    /// The state while the time source is not locked, given when it was
+    /// locked last
+    fn unlocked(last_locked: Option<Instant>, now: Instant, holdover: Duration) -> Self {
+        match last_locked {
+            Some(last_locked) if now - last_locked <= holdover => LockState::Holdover,
+            Some(_) => LockState::Degraded,
+            None => LockState::FreeRunning,
+        }
+    }
+
+    /// The clockClass of a grandmaster in this state, see *IEEE1588-2019 table
+    /// 4*
+    pub fn clock_class(self, degradation: ClockClassDegradation) -> u8 {
+        match (self, degradation) {
+            (LockState::Locked, _) => 6,
+            (LockState::Holdover, _) => 7,
+            (LockState::Degraded, degradation) => degradation.clock_class(),
+            (LockState::FreeRunning, _) => 248,
+        }
+    }
+}
+
+/// The lock state of the time source at one moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceStatus {
+    Locked { accuracy: ClockAccuracy },
+    Unlocked,
+}
+
+enum Source {
+    Ntp,
+    Gnss {
+        fix: Arc<Mutex<Option<Instant>>>,
+        pps: Option<PpsMonitor>,
+    },
+    Phc(LinuxClock),
+}
+
+/// Watches the time source and derives what we announce as grandmaster
+pub struct TimeSourceMonitor {
+    source: Source,
+    source_type: TimeSourceType,
+    holdover: Duration,
+    degradation: ClockClassDegradation,
+    accuracy: Option<ClockAccuracy>,
+    last_locked: Option<Instant>,
+    last_accuracy: ClockAccuracy,
+    state: LockState,
+    leap_seconds: Option<LeapSecondTable>,
+    leap_seconds_expired: bool,
+}
+
+impl TimeSourceMonitor {
+    /// Start monitoring the time source, spawning a thread to read the NMEA
+    /// sentences of a GNSS receiver
+    pub fn new(config: &TimeSourceConfig) -> std::io::Result<Self> {
+        let source = match config.source_type {
+            TimeSourceType::Ntp => Source::Ntp,
+            TimeSourceType::Gnss => {
+                let fix = Arc::new(Mutex::new(None));
+                let nmea_device = config.nmea_device.clone().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing nmea-device")
+                })?;
+                let nmea_fix = fix.clone();
+                std::thread::spawn(move || read_nmea(nmea_device, nmea_fix));
+                Source::Gnss {
+                    fix,
+                    pps: config.pps_device.as_deref().map(PpsMonitor::new),
+                }
+            }
+            TimeSourceType::Phc => {
+                let phc_device = config.phc_device.as_ref().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing phc-device")
+                })?;
+                Source::Phc(LinuxClock::open(phc_device)?)
+            }
+        };
+
+        let leap_seconds = config
+            .leap_seconds_file
+            .as_deref()
+            .map(LeapSecondTable::from_file)
+            .transpose()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+        Ok(Self {
+            source,
+            source_type: config.source_type,
+            holdover: Duration::from_secs(config.holdover),
+            degradation: config.degradation,
+            accuracy: config.accuracy.map(accuracy_from_nanos),
+            last_locked: None,
+            last_accuracy: ClockAccuracy::Unknown,
+            state: LockState::FreeRunning,
+            leap_seconds,
+            leap_seconds_expired: false,
+        })
+    }
+
+    /// Poll the time source, returns the clock quality and time properties to
+    /// announce as grandmaster
+    pub fn update(&mut self) -> (ClockQuality, TimePropertiesDS) {
+        let now = Instant::now();
+        let state = match self.poll(now) {
+            SourceStatus::Locked { accuracy } => {
+                self.last_locked = Some(now);
+                self.last_accuracy = self.accuracy.unwrap_or(accuracy);
+                LockState::Locked
+            }
+            SourceStatus::Unlocked => LockState::unlocked(self.last_locked, now, self.holdover),
+        };
+        if state != self.state {
+            log::info!("Time source {:?}: {:?}", self.source_type, state);
+            self.state = state;
+        }
+
+        let (utc_offset, leap_indicator) = self.leap_seconds();
+        let time_properties_ds =
+            time_properties(state, self.source_type, utc_offset, leap_indicator);
+
+        // Schedule the leap seconds of the table on the system clock as well, so
+        // it is applied at the same moment as announced
+        if self.leap_seconds.is_some() {
+            let mut system_clock = LinuxClock::CLOCK_TAI;
+            if let Err(error) = system_clock.set_properties(&time_properties_ds) {
+                log::warn!("Could not apply leap seconds to the system clock: {error:?}");
+            }
+        }
+
+        (self.clock_quality(), time_properties_ds)
+    }
+
+    /// The offset of UTC to TAI and the upcoming leap second
+    fn leap_seconds(&mut self) -> (Option<i16>, LeapIndicator) {
+        if let Some(table) = &self.leap_seconds {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_secs());
+
+            if table.expired(now) && !self.leap_seconds_expired {
+                log::warn!("The leap second table expired, upcoming leap seconds may be missed");
+            }
+            self.leap_seconds_expired = table.expired(now);
+
+            if let Some((utc_offset, leap_indicator)) = table.lookup(now) {
+                return (Some(utc_offset), leap_indicator);
+            }
+        }
+
+        let utc_offset = match LinuxClock::CLOCK_TAI.get_tai_offset() {
+            Ok(offset) if offset > 0 => i16::try_from(offset).ok(),
+            _ => None,
+        };
+
+        (utc_offset, kernel_leap_indicator())
+    }
+
+    fn clock_quality(&self) -> ClockQuality {
+        ClockQuality {
+            clock_class: self.state.clock_class(self.degradation),
+            clock_accuracy: match self.state {
+                LockState::Locked | LockState::Holdover => self.last_accuracy,
+                LockState::Degraded | LockState::FreeRunning => ClockAccuracy::Unknown,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn poll(&mut self, now: Instant) -> SourceStatus {
+        match &mut self.source {
+            Source::Ntp => ntp_status(),
+            Source::Gnss { fix, pps } => {
+                let fix_valid = fix.lock().unwrap().map_or(false, |fix| {
+                    now.saturating_duration_since(fix) <= NMEA_TIMEOUT
+                });
+                let pps_valid = pps.as_mut().map(|pps| pps.poll(now));
+
+                match (fix_valid, pps_valid) {
+                    (true, Some(true)) => SourceStatus::Locked {
+                        accuracy: ClockAccuracy::NS100,
+                    },
+                    // The NMEA sentences only mark the second roughly
+                    (true, None) => SourceStatus::Locked {
+                        accuracy: ClockAccuracy::MS10,
+                    },
+                    _ => SourceStatus::Unlocked,
+                }
+            }
+            // The lock state of the PHC is not visible to us, it is used as long
+            // as it can be read
+            Source::Phc(clock) => match clock.system_offset() {
+                Ok(Some(_)) => SourceStatus::Locked {
+                    accuracy: ClockAccuracy::US1,
+                },
+                // During a leap second, keep the previous state
+                Ok(None) => match self.state {
+                    LockState::Locked => SourceStatus::Locked {
+                        accuracy: self.last_accuracy,
+                    },
+                    _ => SourceStatus::Unlocked,
+                },
+                Err(error) => {
+                    log::warn!("Could not read time source PHC: {error:?}");
+                    SourceStatus::Unlocked
+                }
+            },
+        }
+    }
+}
+
+/// The time properties of a grandmaster in `state`
+fn time_properties(
+    state: LockState,
+    source_type: TimeSourceType,
+    utc_offset: Option<i16>,
+    leap_indicator: LeapIndicator,
+) -> TimePropertiesDS {
+    let traceable = matches!(state, LockState::Locked | LockState::Holdover);
+    let time_source = match (state, source_type) {
+        (LockState::FreeRunning, _) => TimeSource::InternalOscillator,
+        (_, TimeSourceType::Gnss) => TimeSource::Gnss,
+        (_, TimeSourceType::Ntp) => TimeSource::Ntp,
+        (_, TimeSourceType::Phc) => TimeSource::Other,
+    };
+
+    TimePropertiesDS::new_ptp_time(
+        utc_offset,
+        leap_indicator,
+        traceable,
+        traceable,
+        time_source,
+    )
+}
+
+/// Whether the kernel considers the system clock synchronized by NTP
+fn ntp_status() -> SourceStatus {
+    match kernel_timex() {
+        Ok((state, timex)) if state != libc::TIME_ERROR && timex.status & libc::STA_UNSYNC == 0 => {
+            SourceStatus::Locked {
+                accuracy: accuracy_from_nanos(timex.esterror.max(0) as u64 * 1000),
+            }
+        }
+        _ => SourceStatus::Unlocked,
+    }
+}
+
+/// The leap second scheduled in the kernel, for instance by an NTP daemon
+fn kernel_leap_indicator() -> LeapIndicator {
+    match kernel_timex() {
+        // The leap second was applied already
+        Ok((libc::TIME_OOP | libc::TIME_WAIT, _)) => LeapIndicator::NoLeap,
+        Ok((_, timex)) if timex.status & libc::STA_INS != 0 => LeapIndicator::Leap61,
+        Ok((_, timex)) if timex.status & libc::STA_DEL != 0 => LeapIndicator::Leap59,
+        _ => LeapIndicator::NoLeap,
+    }
+}
+
+/// The smallest clock accuracy that covers `nanos`
+fn accuracy_from_nanos(nanos: u64) -> ClockAccuracy {
+    ClockAccuracy::from_uncertainty(statime::time::Duration::from_nanos(
+        nanos.try_into().unwrap_or(i64::MAX),
+    ))
+}
+
+/// Read NMEA sentences from a GNSS receiver, recording when it last reported a
+/// valid fix
+///
+/// Reading a serial device blocks, so this runs on its own thread.
+fn read_nmea(device: PathBuf, fix: Arc<Mutex<Option<Instant>>>) {
+    loop {
+        if let Err(error) = read_nmea_sentences(&device, &fix) {
+            log::warn!("Could not read NMEA from {}: {error}", device.display());
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+fn read_nmea_sentences(device: &Path, fix: &Mutex<Option<Instant>>) -> std::io::Result<()> {
+    let file = std::fs::File::open(device)?;
+
+    for line in BufReader::new(file).lines() {
+        if parse_nmea_fix(&line?) == Some(true) {
+            *fix.lock().unwrap() = Some(Instant::now());
+        }
+    }
+
+    Err(std::io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Whether an RMC or GGA sentence reports a valid fix, `None` for other or
+/// corrupted sentences
+fn parse_nmea_fix(line: &str) -> Option<bool> {
+    let (data, checksum) = line.trim().strip_prefix('$')?.split_once('*')?;
+    let checksum = u8::from_str_radix(checksum, 16).ok()?;
+    if data.bytes().fold(0, |acc, byte| acc ^ byte) != checksum {
+        return None;
+    }
+
+    let mut fields = data.split(',');
+    let sentence = fields.next()?;
+    // Skip the talker id, such as GP for GPS or GN for multiple systems
+    match sentence.get(2..)? {
+        "RMC" => Some(fields.nth(1)? == "A"),
+        "GGA" => Some(
+            fields
+                .nth(5)?
+                .parse::<u8>()
+                .map_or(false, |quality| quality > 0),
+        ),
+        _ => None,
+    }
+}
+
+/// Watches the assert events of a PPS device through sysfs
+struct PpsMonitor {
+    assert_path: PathBuf,
+    last_assert: Option<String>,
+    last_change: Option<Instant>,
+}
+
+impl PpsMonitor {
+    fn new(device: &Path) -> Self {
+        let name = device.file_name().unwrap_or(device.as_os_str());
+        Self {
+            assert_path: Path::new("/sys/class/pps").join(name).join("assert"),
+            last_assert: None,
+            last_change: None,
+        }
+    }
+
+    /// Whether a pulse was seen recently
+    fn poll(&mut self, now: Instant) -> bool {
+        match std::fs::read_to_string(&self.assert_path) {
+            Ok(assert) => {
+                if self.last_assert.as_ref() != Some(&assert) {
+                    // The first read may show a pulse from long ago
+                    if self.last_assert.is_some() {
+                        self.last_change = Some(now);
+                    }
+                    self.last_assert = Some(assert);
+                }
+            }
+            Err(error) => log::warn!(
+                "Could not read PPS events from {}: {error}",
+                self.assert_path.display()
+            ),
+        }
+
+        self.last_change
+            .map_or(false, |last_change| now - last_change <= PPS_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_state() {
+        let now = Instant::now();
+        let holdover = Duration::from_secs(300);
+
+        assert_eq!(
+            LockState::unlocked(None, now, holdover),
+            LockState::FreeRunning
+        );
+        assert_eq!(
+            LockState::unlocked(Some(now - Duration::from_secs(10)), now, holdover),
+            LockState::Holdover
+        );
+        assert_eq!(
+            LockState::unlocked(Some(now - Duration::from_secs(301)), now, holdover),
+            LockState::Degraded
+        );
+
+        let a = ClockClassDegradation::AlternativeA;
+        let b = ClockClassDegradation::AlternativeB;
+        assert_eq!(LockState::Locked.clock_class(a), 6);
+        assert_eq!(LockState::Holdover.clock_class(a), 7);
+        assert_eq!(LockState::Degraded.clock_class(a), 52);
+        assert_eq!(LockState::Degraded.clock_class(b), 187);
+        assert_eq!(LockState::FreeRunning.clock_class(b), 248);
+    }
+
+    #[test]
+    fn time_properties_follow_lock_state() {
+        let locked = time_properties(
+            LockState::Locked,
+            TimeSourceType::Gnss,
+            Some(37),
+            LeapIndicator::Leap61,
+        );
+        assert!(locked.ptp_timescale);
+        assert!(locked.time_traceable && locked.frequency_traceable);
+        assert_eq!(locked.current_utc_offset, Some(37));
+        assert_eq!(locked.time_source, TimeSource::Gnss);
+        assert_eq!(locked.leap_indicator, LeapIndicator::Leap61);
+
+        let degraded = time_properties(
+            LockState::Degraded,
+            TimeSourceType::Ntp,
+            Some(37),
+            LeapIndicator::NoLeap,
+        );
+        assert!(!degraded.time_traceable && !degraded.frequency_traceable);
+        assert_eq!(degraded.time_source, TimeSource::Ntp);
+
+        let free_running = time_properties(
+            LockState::FreeRunning,
+            TimeSourceType::Ntp,
+            None,
+            LeapIndicator::NoLeap,
+        );
+        assert_eq!(free_running.time_source, TimeSource::InternalOscillator);
+        assert_eq!(free_running.current_utc_offset, None);
+    }
+
+    #[test]
+    fn nmea() {
+        assert_eq!(
+            parse_nmea_fix(
+                "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n"
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            parse_nmea_fix("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47"),
+            Some(true)
+        );
+        // No fix
+        assert_eq!(
+            parse_nmea_fix("$GPGGA,123519,4807.038,N,01131.000,E,0,08,0.9,545.4,M,46.9,M,,*46"),
+            Some(false)
+        );
+        // Corrupted checksum
+        assert_eq!(
+            parse_nmea_fix("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B"),
+            None
+        );
+        // Other sentences
+        assert_eq!(
+            parse_nmea_fix("$GPGSA,A,3,04,05,,09,12,,,24,,,,,2.5,1.3,2.1*39"),
+            None
+        );
+    }
+
+    #[test]
+    fn accuracy() {
+        assert_eq!(accuracy_from_nanos(80), ClockAccuracy::NS100);
+        assert_eq!(accuracy_from_nanos(1_000), ClockAccuracy::US1);
+        assert_eq!(accuracy_from_nanos(3_000_000), ClockAccuracy::MS10);
+        assert_eq!(accuracy_from_nanos(60_000_000_000), ClockAccuracy::SGT10);
+    }
+}
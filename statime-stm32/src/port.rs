@@ -9,8 +9,8 @@ use smoltcp::{
 use static_cell::StaticCell;
 use statime::{
     config::{
-        AcceptAnyMaster, ClockIdentity, DelayMechanism, InstanceConfig, PortConfig, SdoId,
-        TimePropertiesDS, TimeSource,
+        AcceptAnyMaster, ClockIdentity, DelayMechanism, InstanceConfig, PortConfig, Profile,
+        SdoId, TimePropertiesDS, TimeSource,
     },
     filters::BasicFilter,
     port::{InBmca, NoForwardedTLVs, PortAction, PortActionIterator, Running, TimestampContext},
@@ -166,6 +166,7 @@ impl Port {
                 }
                 // Single port implementation, so no need to forward TLVs
                 PortAction::ForwardTLV { .. } => {}
+                PortAction::ResetUnicastTimer { .. } => {}
             }
         }
     }
@@ -277,6 +278,8 @@ pub fn setup_statime(
         domain_number: 0,
         slave_only: false,
         sdo_id: SdoId::default(),
+        profile: Profile::Default,
+        local_priority: 128,
     };
     let time_properties_ds =
         TimePropertiesDS::new_arbitrary_time(false, false, TimeSource::InternalOscillator);
@@ -292,7 +295,13 @@ pub fn setup_statime(
         announce_receipt_timeout: 3,
         sync_interval: Interval::from_log_2(-6),
         master_only: false,
+        local_priority: 128,
         delay_asymmetry: Duration::ZERO,
+        unicast_negotiation: None,
+        unicast_master_table: Default::default(),
+        hybrid_mode: false,
+        one_step: false,
+        slave_event_monitoring: None,
     };
     let filter_config = 0.1;
 
@@ -8,6 +8,7 @@ use super::{
     foreign_master::ForeignMasterList,
 };
 use crate::{
+    config::Profile,
     datastructures::{
         common::{PortIdentity, TimeInterval},
         datasets::InternalDefaultDS,
@@ -35,6 +36,8 @@ pub(crate) struct Bmca<A> {
     foreign_master_list: ForeignMasterList,
     acceptable_master_list: A,
     own_port_identity: PortIdentity,
+    own_port_local_priority: u8,
+    profile: Profile,
 }
 
 impl<A> Bmca<A> {
@@ -42,6 +45,8 @@ impl<A> Bmca<A> {
         acceptable_master_list: A,
         own_port_announce_interval: TimeInterval,
         own_port_identity: PortIdentity,
+        own_port_local_priority: u8,
+        profile: Profile,
     ) -> Self {
         Self {
             foreign_master_list: ForeignMasterList::new(
@@ -50,6 +55,8 @@ impl<A> Bmca<A> {
             ),
             acceptable_master_list,
             own_port_identity,
+            own_port_local_priority,
+            profile,
         }
     }
 
@@ -62,23 +69,24 @@ impl<A> Bmca<A> {
     /// the announce message.
     pub(crate) fn find_best_announce_message(
         announce_messages: impl IntoIterator<Item = BestAnnounceMessage>,
+        profile: Profile,
     ) -> Option<BestAnnounceMessage> {
         announce_messages
             .into_iter()
-            .max_by(BestAnnounceMessage::compare)
+            .max_by(|a, b| a.compare(b, profile))
     }
 
     fn compare_d0_best(
         d0: &ComparisonDataset,
         opt_best: Option<BestAnnounceMessage>,
+        profile: Profile,
     ) -> MessageComparison {
         match opt_best {
             None => MessageComparison::Better,
             Some(best) => {
-                let dataset =
-                    ComparisonDataset::from_announce_message(&best.message, &best.identity);
+                let dataset = best.comparison_dataset();
 
-                match d0.compare(&dataset).as_ordering() {
+                match d0.compare_for_profile(&dataset, profile).as_ordering() {
                     Ordering::Less => MessageComparison::Worse(best),
                     Ordering::Equal => MessageComparison::Same,
                     Ordering::Greater => MessageComparison::Better,
@@ -135,7 +143,7 @@ impl<A> Bmca<A> {
     ) -> RecommendedState {
         let d0 = ComparisonDataset::from_own_data(own_data);
 
-        match Self::compare_d0_best(&d0, best_port_announce_message) {
+        match Self::compare_d0_best(&d0, best_port_announce_message, own_data.profile) {
             MessageComparison::Better => RecommendedState::M1(*own_data),
             MessageComparison::Same => RecommendedState::M1(*own_data),
             MessageComparison::Worse(port) => RecommendedState::P1(port.message),
@@ -149,12 +157,14 @@ impl<A> Bmca<A> {
     ) -> RecommendedState {
         let d0 = ComparisonDataset::from_own_data(own_data);
 
-        match Self::compare_d0_best(&d0, best_global_announce_message) {
+        match Self::compare_d0_best(&d0, best_global_announce_message, own_data.profile) {
             MessageComparison::Better => RecommendedState::M2(*own_data),
             MessageComparison::Same => RecommendedState::M2(*own_data),
             MessageComparison::Worse(global_message) => match best_port_announce_message {
                 None => RecommendedState::M3(global_message.message),
-                Some(port_message) => Self::compare_global_and_port(global_message, port_message),
+                Some(port_message) => {
+                    Self::compare_global_and_port(global_message, port_message, own_data.profile)
+                }
             },
         }
     }
@@ -162,23 +172,20 @@ impl<A> Bmca<A> {
     fn compare_global_and_port(
         global_message: BestAnnounceMessage,
         port_message: BestAnnounceMessage,
+        profile: Profile,
     ) -> RecommendedState {
         if global_message == port_message {
             // effectively, E_best == E_rbest
             RecommendedState::S1(global_message.message)
         } else {
-            let ebest = ComparisonDataset::from_announce_message(
-                &global_message.message,
-                &global_message.identity,
-            );
-
-            let erbest = ComparisonDataset::from_announce_message(
-                &port_message.message,
-                &port_message.identity,
-            );
+            let ebest = global_message.comparison_dataset();
+            let erbest = port_message.comparison_dataset();
 
             // E_best better by topology than E_rbest
-            if matches!(ebest.compare(&erbest), DatasetOrdering::BetterByTopology) {
+            if matches!(
+                ebest.compare_for_profile(&erbest, profile),
+                DatasetOrdering::BetterByTopology
+            ) {
                 RecommendedState::P2(port_message.message)
             } else {
                 RecommendedState::M3(global_message.message)
@@ -235,14 +242,16 @@ impl<A: AcceptableMasterList> Bmca<A> {
         let announce_messages = self.foreign_master_list.take_qualified_announce_messages();
 
         // The best of the foreign master messages is our erbest
-        let erbest = Self::find_best_announce_message(announce_messages.map(|message| {
-            BestAnnounceMessage {
+        let erbest = Self::find_best_announce_message(
+            announce_messages.map(|message| BestAnnounceMessage {
                 header: message.header,
                 message: message.message,
                 age: message.age,
                 identity: self.own_port_identity,
-            }
-        }));
+                local_priority: self.own_port_local_priority,
+            }),
+            self.profile,
+        );
 
         if let Some(best) = &erbest {
             // All messages that were considered have been removed from the
@@ -268,20 +277,25 @@ pub(crate) struct BestAnnounceMessage {
     message: AnnounceMessage,
     age: Duration,
     identity: PortIdentity,
+    local_priority: u8,
 }
 
 impl BestAnnounceMessage {
-    fn compare(&self, other: &Self) -> Ordering {
+    fn compare(&self, other: &Self, profile: Profile) -> Ordering {
         // use the age as a tie-break if needed (prefer newer messages)
         let tie_break = other.age.cmp(&self.age);
-        self.compare_dataset(other).as_ordering().then(tie_break)
+        self.compare_dataset(other, profile)
+            .as_ordering()
+            .then(tie_break)
     }
 
-    fn compare_dataset(&self, other: &Self) -> DatasetOrdering {
-        let data1 = ComparisonDataset::from_announce_message(&self.message, &self.identity);
-        let data2 = ComparisonDataset::from_announce_message(&other.message, &other.identity);
+    fn compare_dataset(&self, other: &Self, profile: Profile) -> DatasetOrdering {
+        self.comparison_dataset()
+            .compare_for_profile(&other.comparison_dataset(), profile)
+    }
 
-        data1.compare(&data2)
+    fn comparison_dataset(&self) -> ComparisonDataset {
+        ComparisonDataset::from_announce_message(&self.message, &self.identity, self.local_priority)
     }
 }
 
@@ -357,6 +371,7 @@ mod tests {
             message,
             age: Duration::ZERO,
             identity,
+            local_priority: 128,
         }
     }
 
@@ -366,6 +381,8 @@ mod tests {
             AcceptAnyMaster,
             TimeInterval(100.into()),
             PortIdentity::default(),
+            128,
+            Profile::Default,
         );
         let mut announce = default_announce_message();
         announce.header.source_port_identity.clock_identity.0 = [1, 2, 3, 4, 5, 6, 7, 8];
@@ -384,6 +401,8 @@ mod tests {
             std::vec![],
             TimeInterval(100.into()),
             PortIdentity::default(),
+            128,
+            Profile::Default,
         );
         let mut announce = default_announce_message();
         announce.header.source_port_identity.clock_identity.0 = [1, 2, 3, 4, 5, 6, 7, 8];
@@ -400,7 +419,9 @@ mod tests {
         let message1 = default_best_announce_message();
         let message2 = default_best_announce_message();
 
-        let ordering = message1.compare_dataset(&message2).as_ordering();
+        let ordering = message1
+            .compare_dataset(&message2, Profile::Default)
+            .as_ordering();
         assert_eq!(ordering, Ordering::Equal);
     }
 
@@ -418,11 +439,23 @@ mod tests {
         message2.message.grandmaster_priority_1 = 1;
 
         // hence we expect message1 to be better than message2
-        assert_eq!(message1.compare_dataset(&message2), DatasetOrdering::Better);
-        assert_eq!(message2.compare_dataset(&message1), DatasetOrdering::Worse);
+        assert_eq!(
+            message1.compare_dataset(&message2, Profile::Default),
+            DatasetOrdering::Better
+        );
+        assert_eq!(
+            message2.compare_dataset(&message1, Profile::Default),
+            DatasetOrdering::Worse
+        );
 
-        assert_eq!(message1.compare(&message2), Ordering::Greater);
-        assert_eq!(message2.compare(&message1), Ordering::Less);
+        assert_eq!(
+            message1.compare(&message2, Profile::Default),
+            Ordering::Greater
+        );
+        assert_eq!(
+            message2.compare(&message1, Profile::Default),
+            Ordering::Less
+        );
     }
 
     #[test]
@@ -436,11 +469,41 @@ mod tests {
         // the newest message should be preferred
         assert!(message2.age < message1.age);
 
-        let ordering = message1.compare_dataset(&message2).as_ordering();
+        let ordering = message1
+            .compare_dataset(&message2, Profile::Default)
+            .as_ordering();
         assert_eq!(ordering, Ordering::Equal);
 
         // so message1 is lower in the ordering than message2
-        assert_eq!(message1.compare(&message2), Ordering::Less)
+        assert_eq!(
+            message1.compare(&message2, Profile::Default),
+            Ordering::Less
+        )
+    }
+
+    #[test]
+    fn best_announce_message_local_priority() {
+        let mut message1 = default_best_announce_message();
+        let mut message2 = default_best_announce_message();
+
+        message1.message.grandmaster_identity = ClockIdentity([0; 8]);
+        message2.message.grandmaster_identity = ClockIdentity([1; 8]);
+        message1.message.grandmaster_clock_quality.clock_class = 165;
+        message2.message.grandmaster_clock_quality.clock_class = 165;
+
+        // message2 was received on a port with a higher local priority
+        message1.local_priority = 128;
+        message2.local_priority = 64;
+
+        let messages = [message1, message2];
+        assert_eq!(
+            Bmca::<()>::find_best_announce_message(messages, Profile::Default),
+            Some(message1)
+        );
+        assert_eq!(
+            Bmca::<()>::find_best_announce_message(messages, Profile::G8275_1),
+            Some(message2)
+        );
     }
 
     fn default_own_data() -> InternalDefaultDS {
@@ -458,6 +521,8 @@ mod tests {
             domain_number,
             slave_only,
             sdo_id,
+            profile: Default::default(),
+            local_priority: 128,
         })
     }
 
@@ -499,6 +564,8 @@ mod tests {
             domain_number,
             slave_only,
             sdo_id,
+            profile: Default::default(),
+            local_priority: 128,
         });
 
         own_data.clock_quality.clock_class = 1;
@@ -510,7 +577,7 @@ mod tests {
         let port_message = default_best_announce_message();
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(port_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(port_message), Profile::Default),
             MessageComparison::Same
         ));
 
@@ -531,7 +598,7 @@ mod tests {
         port_message.identity.port_number = 1;
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(port_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(port_message), Profile::Default),
             MessageComparison::Better
         ));
 
@@ -559,7 +626,7 @@ mod tests {
         let d0 = ComparisonDataset::from_own_data(&own_data);
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(port_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(port_message), Profile::Default),
             MessageComparison::Worse(_)
         ));
 
@@ -587,7 +654,7 @@ mod tests {
         let global_message = default_best_announce_message();
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(global_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(global_message), Profile::Default),
             MessageComparison::Same
         ));
 
@@ -608,7 +675,7 @@ mod tests {
         global_message.identity.port_number = 1;
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(global_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(global_message), Profile::Default),
             MessageComparison::Better
         ));
 
@@ -636,7 +703,7 @@ mod tests {
         let d0 = ComparisonDataset::from_own_data(&own_data);
 
         assert!(matches!(
-            Bmca::<()>::compare_d0_best(&d0, Some(global_message)),
+            Bmca::<()>::compare_d0_best(&d0, Some(global_message), Profile::Default),
             MessageComparison::Worse(_)
         ));
 
@@ -670,13 +737,8 @@ mod tests {
         global_message.age = Duration::from_micros(4);
         port_message.age = Duration::from_micros(2);
 
-        let ebest = ComparisonDataset::from_announce_message(
-            &global_message.message,
-            &global_message.identity,
-        );
-
-        let erbest =
-            ComparisonDataset::from_announce_message(&port_message.message, &port_message.identity);
+        let ebest = global_message.comparison_dataset();
+        let erbest = port_message.comparison_dataset();
 
         assert!(!matches!(
             ebest.compare(&erbest),
@@ -713,13 +775,8 @@ mod tests {
         global_message.age = Duration::from_micros(4);
         port_message.age = Duration::from_micros(2);
 
-        let ebest = ComparisonDataset::from_announce_message(
-            &global_message.message,
-            &global_message.identity,
-        );
-
-        let erbest =
-            ComparisonDataset::from_announce_message(&port_message.message, &port_message.identity);
+        let ebest = global_message.comparison_dataset();
+        let erbest = port_message.comparison_dataset();
 
         assert!(!matches!(
             ebest.compare(&erbest),
@@ -1,11 +1,15 @@
-//! Implementation of chapter 9.3.4 Data set comparison algorithm
+//! Implementation of chapter 9.3.4 Data set comparison algorithm, and of the
+//! alternate data set comparison of *ITU-T G.8275.1 section 6.3.7*
 
 use core::cmp::Ordering;
 
-use crate::datastructures::{
-    common::{ClockIdentity, ClockQuality, PortIdentity},
-    datasets::InternalDefaultDS,
-    messages::AnnounceMessage,
+use crate::{
+    config::Profile,
+    datastructures::{
+        common::{ClockIdentity, ClockQuality, PortIdentity},
+        datasets::InternalDefaultDS,
+        messages::AnnounceMessage,
+    },
 };
 
 /// A collection of data that is gathered from other sources (mainly announce
@@ -19,6 +23,7 @@ pub(crate) struct ComparisonDataset {
     gm_identity: ClockIdentity,
     gm_clock_quality: ClockQuality,
     gm_priority_2: u8,
+    local_priority: u8,
     steps_removed: u16,
     identity_of_senders: ClockIdentity,
     identity_of_receiver: PortIdentity,
@@ -26,16 +31,19 @@ pub(crate) struct ComparisonDataset {
 
 impl ComparisonDataset {
     /// Create a ComparisonDataset from the data in an announce message and the
-    /// port identity of the port that received the announce message
+    /// port identity and local priority of the port that received the announce
+    /// message
     pub(crate) fn from_announce_message(
         message: &AnnounceMessage,
         port_receiver_identity: &PortIdentity,
+        local_priority: u8,
     ) -> Self {
         Self {
             gm_priority_1: message.grandmaster_priority_1,
             gm_identity: message.grandmaster_identity,
             gm_clock_quality: message.grandmaster_clock_quality,
             gm_priority_2: message.grandmaster_priority_2,
+            local_priority,
             steps_removed: message.steps_removed,
             identity_of_senders: message.header.source_port_identity.clock_identity,
             identity_of_receiver: *port_receiver_identity,
@@ -48,6 +56,7 @@ impl ComparisonDataset {
             gm_identity: data.clock_identity,
             gm_clock_quality: data.clock_quality,
             gm_priority_2: data.priority_2,
+            local_priority: data.local_priority,
             steps_removed: 0,
             identity_of_senders: data.clock_identity,
             identity_of_receiver: PortIdentity {
@@ -57,6 +66,16 @@ impl ComparisonDataset {
         }
     }
 
+    /// Returns the ordering of `self` in comparison to other, using the
+    /// comparison of the given profile.
+    pub(crate) fn compare_for_profile(&self, other: &Self, profile: Profile) -> DatasetOrdering {
+        if profile.uses_alternate_bmca() {
+            self.compare_alternate(other)
+        } else {
+            self.compare(other)
+        }
+    }
+
     /// Returns the ordering of `self` in comparison to other.
     pub(crate) fn compare(&self, other: &Self) -> DatasetOrdering {
         if self.gm_identity == other.gm_identity {
@@ -66,6 +85,45 @@ impl ComparisonDataset {
         }
     }
 
+    /// Returns the ordering of `self` in comparison to other according to the
+    /// alternate BMCA of *ITU-T G.8275.1* and *G.8275.2*, which ignores
+    /// priority 1 but uses the local priority.
+    pub(crate) fn compare_alternate(&self, other: &Self) -> DatasetOrdering {
+        if self.gm_identity == other.gm_identity {
+            return Self::compare_same_identity(self, other);
+        }
+
+        let self_quality = self.gm_clock_quality;
+        let other_quality = other.gm_clock_quality;
+
+        // Figure 4 of G.8275.1
+        let ordering = (self_quality.clock_class.cmp(&other_quality.clock_class))
+            .then_with(|| {
+                self_quality
+                    .clock_accuracy
+                    .cmp_numeric(&other_quality.clock_accuracy)
+            })
+            .then_with(|| {
+                self_quality
+                    .offset_scaled_log_variance
+                    .cmp(&other_quality.offset_scaled_log_variance)
+            })
+            .then_with(|| self.gm_priority_2.cmp(&other.gm_priority_2))
+            .then_with(|| self.local_priority.cmp(&other.local_priority));
+
+        // Equally good grandmasters that can't be a slave are chosen by their
+        // distance
+        if ordering == Ordering::Equal && self_quality.clock_class <= 127 {
+            return Self::compare_same_identity(self, other);
+        }
+
+        match ordering.then_with(|| self.gm_identity.cmp(&other.gm_identity)) {
+            Ordering::Equal => unreachable!("gm_identity is guaranteed to be different"),
+            Ordering::Greater => DatasetOrdering::Worse,
+            Ordering::Less => DatasetOrdering::Better,
+        }
+    }
+
     /// PTP grandmaster instances are different
     fn compare_different_identity(&self, other: &Self) -> DatasetOrdering {
         let self_quality = self.gm_clock_quality;
@@ -218,6 +276,68 @@ mod tests {
         assert_eq!(b.compare(&a), DatasetOrdering::Better);
     }
 
+    #[test]
+    fn alternate_comparison() {
+        let (mut a, mut b) = get_default_test_pair();
+
+        a.gm_identity = IDENTITY_A;
+        b.gm_identity = IDENTITY_B;
+        a.gm_clock_quality.clock_class = 165;
+        b.gm_clock_quality.clock_class = 165;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Better);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Worse);
+
+        a.local_priority = 2;
+        b.local_priority = 1;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Worse);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Better);
+
+        a.gm_priority_2 = 0;
+        b.gm_priority_2 = 1;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Better);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Worse);
+
+        a.gm_clock_quality.clock_class = 7;
+        b.gm_clock_quality.clock_class = 6;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Worse);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Better);
+
+        // priority 1 is ignored
+        a.gm_priority_1 = 0;
+        b.gm_priority_1 = 255;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Worse);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Better);
+        assert_eq!(a.compare(&b), DatasetOrdering::Better);
+    }
+
+    #[test]
+    fn alternate_comparison_low_class() {
+        let (mut a, mut b) = get_default_test_pair();
+
+        // Equal grandmasters of a low class are compared by topology
+        a.gm_identity = IDENTITY_B;
+        b.gm_identity = IDENTITY_A;
+        a.gm_clock_quality.clock_class = 6;
+        b.gm_clock_quality.clock_class = 6;
+        a.steps_removed = 0;
+        b.steps_removed = 2;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Better);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Worse);
+
+        // but by identity for higher classes
+        a.gm_clock_quality.clock_class = 248;
+        b.gm_clock_quality.clock_class = 248;
+
+        assert_eq!(a.compare_alternate(&b), DatasetOrdering::Worse);
+        assert_eq!(b.compare_alternate(&a), DatasetOrdering::Better);
+    }
+
     #[test]
     fn figure_35() {
         let (mut a, mut b) = get_default_test_pair();
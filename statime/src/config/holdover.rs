@@ -0,0 +1,67 @@
+use crate::time::Duration;
+#[cfg(doc)]
+use crate::{filters::Filter, PtpInstance};
+
+/// The specification of the clock of a [`PtpInstance`] while it is in
+/// holdover, see [`PtpInstance::set_holdover_config`]
+///
+/// After losing a grandmaster with a traceable time, the instance keeps
+/// running on the last frequency estimate of its [`Filter`]. While the
+/// uncertainty of the clock stays within this specification the instance
+/// announces clockClass 7. After that it announces the clockClass of the
+/// [`degradation`](`Self::degradation`).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HoldoverConfig {
+    /// How long the clock stays within its holdover specification at most
+    pub duration: Duration,
+
+    /// The largest uncertainty of the clock within its holdover specification
+    pub max_uncertainty: Duration,
+
+    /// How fast the frequency of the local oscillator may wander, as a
+    /// fractional frequency change per second
+    ///
+    /// This bounds the growth of the frequency error of the clock after the
+    /// last estimate of the filter.
+    pub frequency_drift: f64,
+
+    /// The clockClass used after the holdover specification is exceeded
+    pub degradation: ClockClassDegradation,
+}
+
+impl Default for HoldoverConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(300),
+            max_uncertainty: Duration::from_micros(1),
+            frequency_drift: 1e-11,
+            degradation: ClockClassDegradation::default(),
+        }
+    }
+}
+
+/// The clockClass announced after the holdover specification of a clock is
+/// exceeded, see *IEEE1588-2019 table 4*
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum ClockClassDegradation {
+    /// clockClass 52
+    #[default]
+    AlternativeA,
+    /// clockClass 187
+    AlternativeB,
+}
+
+impl ClockClassDegradation {
+    /// The clockClass of this degradation alternative
+    pub fn clock_class(self) -> u8 {
+        match self {
+            ClockClassDegradation::AlternativeA => 52,
+            ClockClassDegradation::AlternativeB => 187,
+        }
+    }
+}
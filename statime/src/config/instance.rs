@@ -1,4 +1,4 @@
-use crate::config::{ClockIdentity, SdoId};
+use crate::config::{ClockIdentity, Profile, SdoId};
 #[cfg(doc)]
 use crate::PtpInstance;
 
@@ -7,7 +7,7 @@ use crate::PtpInstance;
 /// # Example
 /// A configuration with common default values:
 /// ```
-/// # use statime::config::{ClockIdentity, InstanceConfig, SdoId};
+/// # use statime::config::{ClockIdentity, InstanceConfig, Profile, SdoId};
 /// let config = InstanceConfig {
 ///     clock_identity: ClockIdentity::from_mac_address([1,2,3,4,5,6]),
 ///     priority_1: 128,
@@ -15,6 +15,8 @@ use crate::PtpInstance;
 ///     domain_number: 0,
 ///     sdo_id: SdoId::default(),
 ///     slave_only: false,
+///     profile: Profile::Default,
+///     local_priority: 128,
 /// };
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -45,4 +47,14 @@ pub struct InstanceConfig {
 
     /// Whether this node may never become a master in the network
     pub slave_only: bool,
+
+    /// The PTP profile this instance follows, see [`Profile`]
+    pub profile: Profile,
+
+    /// Priority of this clock in the alternate BMCA of the
+    /// [`G8275_1`](`Profile::G8275_1`) profile, used after its clock quality
+    /// and [`InstanceConfig::priority_2`].
+    ///
+    /// Lower values assign a higher priority. Ignored by the other profiles.
+    pub local_priority: u8,
 }
@@ -3,22 +3,45 @@
 //! Configurations for a [`PtpInstance`](`crate::PtpInstance`):
 //! * [`InstanceConfig`]
 //! * [`TimePropertiesDS`]
+//! * [`HoldoverConfig`]
+//! * [`SecurityConfig`]
 //!
 //! Configurations for a [`Port`](`crate::port::Port`):
 //! * [`PortConfig`]
 //!
+//! Configurations for a [`TransparentClock`](`crate::TransparentClock`):
+//! * [`TransparentClockConfig`]
+//!
 //! And types used within those configurations.
 
+mod holdover;
 mod instance;
 mod port;
+mod profile;
+mod security;
+mod transparent_clock;
 
+pub use holdover::{ClockClassDegradation, HoldoverConfig};
 pub use instance::InstanceConfig;
-pub use port::{DelayMechanism, PortConfig};
+pub use port::{
+    DelayMechanism, PortConfig, SlaveEventMonitoringConfig, UnicastNegotiationConfig,
+    MAX_UNICAST_MASTER_TABLE_SIZE,
+};
+pub use profile::{Profile, ProfileError};
+pub(crate) use security::MAX_ICV_LENGTH;
+pub use security::{
+    IntegrityAlgorithm, KeyProvider, SecurityConfig, SecurityKey, MAX_KEY_LENGTH, MAX_SECURITY_KEYS,
+};
+pub use transparent_clock::TransparentClockConfig;
 
 pub use crate::{
     bmc::acceptable_master::{AcceptAnyMaster, AcceptableMasterList},
     datastructures::{
-        common::{ClockAccuracy, ClockIdentity, ClockQuality, LeapIndicator, TimeSource},
+        common::{
+            AlternateTimeOffset, ClockAccuracy, ClockIdentity, ClockQuality, LeapIndicator,
+            MasterLockingStatus, PortAddress, PowerProfileConfig, SynchronizationMetadata,
+            TimeSource, MAX_DISPLAY_NAME_LENGTH,
+        },
         datasets::TimePropertiesDS,
         messages::SdoId,
     },
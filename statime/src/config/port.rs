@@ -1,8 +1,12 @@
+use arrayvec::ArrayVec;
 use rand::Rng;
 
-use crate::time::{Duration, Interval};
 #[cfg(doc)]
 use crate::{config::AcceptableMasterList, port::Port};
+use crate::{
+    datastructures::common::PortAddress,
+    time::{Duration, Interval},
+};
 
 /// Which delay mechanism a port is using.
 ///
@@ -24,9 +28,13 @@ pub enum DelayMechanism {
     // No support for other delay mechanisms
 }
 
+/// Maximum number of entries in the
+/// [`unicast_master_table`](`PortConfig::unicast_master_table`)
+pub const MAX_UNICAST_MASTER_TABLE_SIZE: usize = 8;
+
 /// Configuration items of the PTP PortDS dataset. Dynamical fields are kept
 /// as part of [crate::port::Port].
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct PortConfig<A> {
     /// A list that contains all nodes that this [`Port`] will accept as a
     /// master.
@@ -48,15 +56,127 @@ pub struct PortConfig<A> {
     pub sync_interval: Interval,
 
     /// Never let this [`Port`] become a slave.
+    ///
+    /// Announce messages received on this [`Port`] are not used to select a
+    /// master. This is called notSlave in *ITU-T G.8275.1*.
     pub master_only: bool,
 
+    /// Priority of the masters found on this [`Port`] in the alternate BMCA of
+    /// the [`G8275_1`](`crate::config::Profile::G8275_1`) profile.
+    ///
+    /// Lower values assign a higher priority. Ignored by the other profiles.
+    pub local_priority: u8,
+
     /// The estimated asymmetry in the link connected to this [`Port`]
     pub delay_asymmetry: Duration,
+
+    /// Negotiate unicast transmission of messages with other ports, see
+    /// [`UnicastNegotiationConfig`]. Disabled when `None`.
+    pub unicast_negotiation: Option<UnicastNegotiationConfig>,
+
+    /// The unicast master table, see *IEEE1588-2019 section 17.5*
+    ///
+    /// The [`Port`] requests unicast Announce messages from every address in
+    /// this table, so masters can be found on networks without multicast.
+    /// Their announce messages are handled like any other by the BMCA. This
+    /// uses unicast negotiation, which is enabled with default settings when
+    /// [`unicast_negotiation`](`Self::unicast_negotiation`) is `None`.
+    pub unicast_master_table: ArrayVec<PortAddress, MAX_UNICAST_MASTER_TABLE_SIZE>,
+
+    /// Use hybrid mode for the E2E delay mechanism: Sync and Announce messages
+    /// stay multicast, but Delay_Req messages are sent unicast to the address
+    /// the master sends its Sync messages from. In the master state Delay_Resp
+    /// messages are sent unicast to the sender of the Delay_Req.
+    pub hybrid_mode: bool,
+
+    /// Send one-step Sync and Pdelay_Resp messages, without a Follow_Up or
+    /// Pdelay_Resp_Follow_Up. This needs hardware that inserts the transmit
+    /// time into the messages while sending them, see
+    /// [`OneStepInsertion`](`crate::port::OneStepInsertion`).
+    pub one_step: bool,
+
+    /// Send slave event monitoring TLVs while this [`Port`] is a slave, see
+    /// [`SlaveEventMonitoringConfig`]. Disabled when `None`.
+    pub slave_event_monitoring: Option<SlaveEventMonitoringConfig>,
     // Notes:
     // Fields specific for delay mechanism are kept as part of [DelayMechanism].
     // Version is always 2.1, so not stored (versionNumber, minorVersionNumber)
 }
 
+/// Configuration of unicast message negotiation, see *IEEE1588-2019 section
+/// 16.1*
+///
+/// With unicast negotiation enabled a [`Port`] in the slave state requests
+/// unicast Sync and (for the E2E delay mechanism) Delay_Resp messages from its
+/// master. In the master state it grants such requests, and sends Sync
+/// messages only to the ports that were granted them. Announce messages are
+/// still multicast, and are additionally sent unicast to every port that
+/// requested them.
+///
+/// Requests for messages at a higher rate than configured for this [`Port`] are
+/// denied, as are requests from new clients once `max_clients` ports have been
+/// granted messages.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UnicastNegotiationConfig {
+    /// The duration in seconds of the grants this [`Port`] requests
+    pub grant_duration: u32,
+    /// The longest duration in seconds this [`Port`] grants to others
+    pub max_grant_duration: u32,
+    /// The maximum number of other ports this [`Port`] sends unicast messages
+    /// to at the same time. Statime supports at most 64.
+    pub max_clients: usize,
+}
+
+impl Default for UnicastNegotiationConfig {
+    fn default() -> Self {
+        Self {
+            grant_duration: 300,
+            max_grant_duration: 300,
+            max_clients: 32,
+        }
+    }
+}
+
+/// Configuration of slave event monitoring, see *IEEE1588-2019 section 16.11*
+///
+/// A [`Port`] in the slave state collects records about the Sync messages it
+/// receives and the Delay_Req or Pdelay_Req messages it sends. Once
+/// `records_per_message` records of one kind are collected, it sends them all
+/// in a Signaling message with the SLAVE_RX_SYNC_TIMING_DATA,
+/// SLAVE_RX_SYNC_COMPUTED_DATA and SLAVE_TX_EVENT_TIMESTAMPS TLVs that are
+/// enabled. The rate of these messages is thus set by `records_per_message`
+/// and the sync and delay request intervals.
+///
+/// Received slave event monitoring TLVs are available through
+/// [`Port::slave_event_reports`], regardless of this configuration.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SlaveEventMonitoringConfig {
+    /// Send the SLAVE_RX_SYNC_TIMING_DATA TLV
+    pub rx_sync_timing_data: bool,
+    /// Send the SLAVE_RX_SYNC_COMPUTED_DATA TLV
+    pub rx_sync_computed_data: bool,
+    /// Send the SLAVE_TX_EVENT_TIMESTAMPS TLV
+    pub tx_event_timestamps: bool,
+    /// The number of records to collect before sending them, at most
+    /// [`MAX_SLAVE_EVENT_RECORDS`](`crate::observability::slave_event_monitoring::MAX_SLAVE_EVENT_RECORDS`)
+    pub records_per_message: u8,
+    /// Send the Signaling messages unicast to this address instead of
+    /// multicast
+    pub destination: Option<PortAddress>,
+}
+
+impl Default for SlaveEventMonitoringConfig {
+    fn default() -> Self {
+        Self {
+            rx_sync_timing_data: true,
+            rx_sync_computed_data: true,
+            tx_event_timestamps: true,
+            records_per_message: 8,
+            destination: None,
+        }
+    }
+}
+
 impl<A> PortConfig<A> {
     /// Minimum time between two delay request messages
     pub fn min_delay_req_interval(&self) -> Interval {
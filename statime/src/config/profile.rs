@@ -0,0 +1,560 @@
+use core::ops::RangeInclusive;
+
+#[cfg(doc)]
+use crate::config::InstanceConfig;
+use crate::{
+    config::{DelayMechanism, PortConfig},
+    time::{Duration, Interval},
+};
+
+/// The PTP profile followed by a [`PtpInstance`](`crate::PtpInstance`)
+///
+/// A profile selects which variant of the protocol is used on the network.
+/// The [`InstanceConfig`] and the [`PortConfig`]s of the ports still need to
+/// match the requirements of the profile.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Profile {
+    /// The default profiles of *IEEE1588-2019 annex I*
+    #[default]
+    Default,
+    /// Generalized PTP (gPTP) as specified by *IEEE802.1AS-2020*
+    ///
+    /// gPTP runs over IEEE 802.3 only, with every port using the
+    /// [`DelayMechanism::P2P`] and an [`sdo_id`](`InstanceConfig::sdo_id`) of
+    /// `0x100`. In this profile:
+    /// * All messages are sent to the link-local address
+    ///   `01:80:C2:00:00:0E`, so they are never forwarded by bridges.
+    /// * A port only sends and accepts Sync, Follow_Up and Announce messages
+    ///   while it is asCapable: its peer delay measurements succeed and the
+    ///   measured link delay stays below a threshold of 800ns.
+    /// * The link delay accounts for the rate of the clock of the peer
+    ///   (neighborRateRatio).
+    /// * Follow_Up messages carry the Follow_Up information TLV.
+    /// * Announce messages carry a path trace TLV, and announce messages that
+    ///   already passed this instance are discarded.
+    ///
+    /// An instance with multiple ports acts as a time-aware relay, but in the
+    /// manner of a boundary clock: Sync and Follow_Up messages are not
+    /// forwarded, instead the master ports pass on time from the local clock,
+    /// which is synchronized to the grandmaster. The cumulativeScaledRateOffset
+    /// these ports send is therefore always zero, and the rate ratio received
+    /// from the master is only logged.
+    Gptp,
+    /// The telecom profile for phase/time synchronization with full timing
+    /// support from the network, *ITU-T G.8275.1*
+    ///
+    /// G.8275.1 runs over IEEE 802.3 with the E2E delay mechanism, in the
+    /// domains 24 to 43. In this profile the alternate BMCA is used:
+    /// * The priority_1 of the grandmasters is ignored, and should be left at
+    ///   128.
+    /// * After the clock quality and priority_2, masters are compared by the
+    ///   [`local_priority`](`PortConfig::local_priority`) of the port they
+    ///   were received on. The instance itself is compared using its
+    ///   [`local_priority`](`InstanceConfig::local_priority`).
+    /// * Grandmasters with a clockClass of 127 or lower and an otherwise equal
+    ///   quality are compared by their distance, instead of by their identity.
+    ///
+    /// Ports that should never become a slave (notSlave in G.8275.1) are
+    /// configured through [`master_only`](`PortConfig::master_only`).
+    G8275_1,
+    /// The telecom profile for phase/time synchronization with partial timing
+    /// support from the network, *ITU-T G.8275.2*
+    ///
+    /// G.8275.2 runs over unicast IPv4 or IPv6 with the E2E delay mechanism, in
+    /// the domains 44 to 63. Every port uses
+    /// [`unicast_negotiation`](`PortConfig::unicast_negotiation`), and finds
+    /// its masters through the
+    /// [`unicast_master_table`](`PortConfig::unicast_master_table`). Announce
+    /// messages are only sent to the ports that requested them.
+    ///
+    /// The ports request sync and delay response messages at up to 128 per
+    /// second from their master. The masters are selected by the same
+    /// alternate BMCA as in [`Profile::G8275_1`].
+    G8275_2,
+    /// The broadcast profile for synchronization of audio and video equipment,
+    /// *SMPTE ST 2059-2*
+    ///
+    /// ST 2059-2 uses domain 127 by default, with 4 announce and 8 sync and
+    /// delay request messages per second. Grandmasters send their
+    /// synchronization metadata, such as the video frame rate and the local
+    /// time offset, in announce messages. It is configured with
+    /// [`PtpInstance::set_synchronization_metadata`](`crate::PtpInstance::set_synchronization_metadata`),
+    /// and the metadata of the current grandmaster is available through
+    /// [`PtpInstance::synchronization_metadata`](`crate::PtpInstance::synchronization_metadata`).
+    St2059_2,
+    /// The power profile for substations, *IEEE C37.238-2011*
+    ///
+    /// The power profile runs over IEEE 802.3 with every port using the
+    /// [`DelayMechanism::P2P`], and one message of each type per second.
+    /// Announce messages carry the IEEE_C37_238 TLV with the identifier and
+    /// the time inaccuracy of the grandmaster, and the time inaccuracy added by
+    /// the network. Boundary and transparent clocks add their own time
+    /// inaccuracy to the latter. The grandmaster can announce the offset of
+    /// local time in the alternate time offset indicator TLV.
+    ///
+    /// These are configured with
+    /// [`PtpInstance::set_power_profile`](`crate::PtpInstance::set_power_profile`),
+    /// [`PtpInstance::set_alternate_time_offset`](`crate::PtpInstance::set_alternate_time_offset`)
+    /// and [`TransparentClockConfig::time_inaccuracy`](`crate::config::TransparentClockConfig::time_inaccuracy`).
+    C37_238,
+    /// The media profile for audio over IP, *AES67-2018 annex A*, with the
+    /// parameters recommended by *AES-R16-2016* for interoperability
+    ///
+    /// AES67 runs over multicast UDP on IPv4 with the E2E delay mechanism, in
+    /// domain 0 by default, with 2 announce and 8 sync messages per second.
+    /// The intervals and the announce receipt timeout are limited to the
+    /// ranges of AES67, where the announce interval may also go down to the
+    /// range of [`Profile::St2059_2`].
+    Aes67,
+}
+
+/// A [`PortConfig`] setting that conflicts with the [`Profile`], see
+/// [`Profile::check_port_config`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProfileError {
+    /// The profile needs another delay mechanism
+    DelayMechanism,
+    /// The profile needs unicast negotiation enabled, or doesn't allow it
+    UnicastNegotiation,
+    /// The profile doesn't allow hybrid mode
+    HybridMode,
+    /// The announce interval is outside the range of the profile
+    AnnounceInterval,
+    /// The sync interval is outside the range of the profile
+    SyncInterval,
+    /// The delay request interval is outside the range of the profile
+    DelayInterval,
+    /// The duration of unicast grants is outside the range of the profile
+    GrantDuration,
+    /// The announce receipt timeout is outside the range of the profile
+    AnnounceReceiptTimeout,
+}
+
+impl core::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProfileError::DelayMechanism => f.write_str("the delay mechanism is not allowed"),
+            ProfileError::UnicastNegotiation => {
+                f.write_str("unicast negotiation is required or not allowed")
+            }
+            ProfileError::HybridMode => f.write_str("hybrid mode is not allowed"),
+            ProfileError::AnnounceInterval => f.write_str("the announce interval is out of range"),
+            ProfileError::SyncInterval => f.write_str("the sync interval is out of range"),
+            ProfileError::DelayInterval => f.write_str("the delay interval is out of range"),
+            ProfileError::GrantDuration => f.write_str("the grant duration is out of range"),
+            ProfileError::AnnounceReceiptTimeout => {
+                f.write_str("the announce receipt timeout is out of range")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProfileError {}
+
+impl Profile {
+    /// The domain number used when no other domain is configured
+    pub fn default_domain(self) -> u8 {
+        match self {
+            Profile::Default | Profile::Gptp | Profile::C37_238 | Profile::Aes67 => 0,
+            Profile::G8275_1 => 24,
+            Profile::G8275_2 => 44,
+            Profile::St2059_2 => 127,
+        }
+    }
+
+    /// The default time between two announce messages
+    pub fn default_announce_interval(self) -> Interval {
+        match self {
+            Profile::Default => Interval::TWO_SECONDS,
+            Profile::Gptp | Profile::G8275_2 | Profile::C37_238 => Interval::ONE_SECOND,
+            Profile::G8275_1 => Interval::from_log_2(-3),
+            Profile::St2059_2 => Interval::from_log_2(-2),
+            Profile::Aes67 => Interval::from_log_2(-1),
+        }
+    }
+
+    /// The default time between two sync messages
+    pub fn default_sync_interval(self) -> Interval {
+        match self {
+            Profile::Default | Profile::C37_238 => Interval::ONE_SECOND,
+            Profile::Gptp => Interval::from_log_2(-3),
+            Profile::G8275_1 => Interval::from_log_2(-4),
+            Profile::G8275_2 => Interval::from_log_2(-6),
+            Profile::St2059_2 | Profile::Aes67 => Interval::from_log_2(-3),
+        }
+    }
+
+    /// The default minimum time between two delay request or peer delay
+    /// request messages
+    pub fn default_delay_interval(self) -> Interval {
+        match self {
+            Profile::Default | Profile::Gptp | Profile::C37_238 | Profile::Aes67 => {
+                Interval::ONE_SECOND
+            }
+            Profile::G8275_1 => Interval::from_log_2(-4),
+            Profile::G8275_2 => Interval::from_log_2(-6),
+            Profile::St2059_2 => Interval::from_log_2(-3),
+        }
+    }
+
+    /// A [`PortConfig`] with the defaults of this profile, which is allowed by
+    /// [`Profile::check_port_config`]
+    ///
+    /// A [`Profile::G8275_2`] port still needs masters in its
+    /// [`unicast_master_table`](`PortConfig::unicast_master_table`).
+    pub fn default_port_config<A>(self, acceptable_master_list: A) -> PortConfig<A> {
+        let interval = self.default_delay_interval();
+        let delay_mechanism = match self {
+            Profile::Gptp | Profile::C37_238 => DelayMechanism::P2P { interval },
+            _ => DelayMechanism::E2E { interval },
+        };
+
+        PortConfig {
+            acceptable_master_list,
+            delay_mechanism,
+            announce_interval: self.default_announce_interval(),
+            announce_receipt_timeout: 3,
+            sync_interval: self.default_sync_interval(),
+            master_only: false,
+            local_priority: 128,
+            delay_asymmetry: Duration::ZERO,
+            unicast_negotiation: (self == Profile::G8275_2).then(Default::default),
+            unicast_master_table: Default::default(),
+            hybrid_mode: false,
+            one_step: false,
+            slave_event_monitoring: None,
+        }
+    }
+
+    /// The longest duration in seconds of the unicast grants this profile
+    /// allows, if it limits them
+    pub fn max_grant_duration(self) -> Option<u32> {
+        match self {
+            Profile::G8275_2 => Some(*G8275_2_GRANT_DURATIONS.end()),
+            _ => None,
+        }
+    }
+
+    /// Whether masters are selected with the alternate BMCA of the telecom
+    /// profiles
+    pub(crate) fn uses_alternate_bmca(self) -> bool {
+        matches!(self, Profile::G8275_1 | Profile::G8275_2)
+    }
+
+    /// Check that the settings of a port are allowed by this profile
+    pub fn check_port_config<A>(self, config: &PortConfig<A>) -> Result<(), ProfileError> {
+        let p2p = matches!(config.delay_mechanism, DelayMechanism::P2P { .. });
+        let unicast =
+            config.unicast_negotiation.is_some() || !config.unicast_master_table.is_empty();
+
+        match self {
+            Profile::Default | Profile::St2059_2 => Ok(()),
+            Profile::Gptp | Profile::C37_238 => {
+                check(p2p, ProfileError::DelayMechanism)?;
+                check(!unicast, ProfileError::UnicastNegotiation)?;
+                check(!config.hybrid_mode, ProfileError::HybridMode)
+            }
+            Profile::G8275_1 => {
+                check(!p2p, ProfileError::DelayMechanism)?;
+                check(!unicast, ProfileError::UnicastNegotiation)?;
+                check(!config.hybrid_mode, ProfileError::HybridMode)
+            }
+            Profile::G8275_2 => {
+                // Between 1 and 8 announce messages, and between 1 and 128 sync
+                // and delay response messages per second
+                const ANNOUNCE_LOG_INTERVALS: RangeInclusive<i8> = -3..=0;
+                const MESSAGE_LOG_INTERVALS: RangeInclusive<i8> = -7..=0;
+
+                check(!p2p, ProfileError::DelayMechanism)?;
+                check(!config.hybrid_mode, ProfileError::HybridMode)?;
+                check(
+                    ANNOUNCE_LOG_INTERVALS.contains(&config.announce_interval.as_log_2()),
+                    ProfileError::AnnounceInterval,
+                )?;
+                check(
+                    MESSAGE_LOG_INTERVALS.contains(&config.sync_interval.as_log_2()),
+                    ProfileError::SyncInterval,
+                )?;
+                check(
+                    MESSAGE_LOG_INTERVALS.contains(&config.min_delay_req_interval().as_log_2()),
+                    ProfileError::DelayInterval,
+                )?;
+
+                // The unicast master table enables negotiation with its defaults
+                let negotiation = match config.unicast_negotiation {
+                    Some(negotiation) => negotiation,
+                    None if unicast => Default::default(),
+                    None => return Err(ProfileError::UnicastNegotiation),
+                };
+                check(
+                    G8275_2_GRANT_DURATIONS.contains(&negotiation.grant_duration)
+                        && G8275_2_GRANT_DURATIONS.contains(&negotiation.max_grant_duration),
+                    ProfileError::GrantDuration,
+                )
+            }
+            Profile::Aes67 => {
+                // The ranges of AES67 annex A, with the announce interval
+                // extended to that of ST 2059-2 as in AES-R16
+                const ANNOUNCE_LOG_INTERVALS: RangeInclusive<i8> = -3..=4;
+                const SYNC_LOG_INTERVALS: RangeInclusive<i8> = -4..=1;
+                const DELAY_LOG_INTERVALS: RangeInclusive<i8> = -3..=5;
+                const ANNOUNCE_RECEIPT_TIMEOUTS: RangeInclusive<u8> = 2..=10;
+
+                check(!p2p, ProfileError::DelayMechanism)?;
+                check(!unicast, ProfileError::UnicastNegotiation)?;
+                check(!config.hybrid_mode, ProfileError::HybridMode)?;
+                check(
+                    ANNOUNCE_LOG_INTERVALS.contains(&config.announce_interval.as_log_2()),
+                    ProfileError::AnnounceInterval,
+                )?;
+                check(
+                    SYNC_LOG_INTERVALS.contains(&config.sync_interval.as_log_2()),
+                    ProfileError::SyncInterval,
+                )?;
+                check(
+                    DELAY_LOG_INTERVALS.contains(&config.min_delay_req_interval().as_log_2()),
+                    ProfileError::DelayInterval,
+                )?;
+                check(
+                    ANNOUNCE_RECEIPT_TIMEOUTS.contains(&config.announce_receipt_timeout),
+                    ProfileError::AnnounceReceiptTimeout,
+                )
+            }
+        }
+    }
+}
+
+/// The durations in seconds of the unicast grants G.8275.2 allows
+const G8275_2_GRANT_DURATIONS: RangeInclusive<u32> = 60..=1000;
+
+fn check(condition: bool, error: ProfileError) -> Result<(), ProfileError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AcceptAnyMaster, PortAddress, UnicastNegotiationConfig};
+
+    fn port_config(delay_mechanism: DelayMechanism) -> PortConfig<AcceptAnyMaster> {
+        PortConfig {
+            acceptable_master_list: AcceptAnyMaster,
+            delay_mechanism,
+            announce_interval: Interval::ONE_SECOND,
+            announce_receipt_timeout: 3,
+            sync_interval: Interval::from_log_2(-7),
+            master_only: false,
+            local_priority: 128,
+            delay_asymmetry: Default::default(),
+            unicast_negotiation: None,
+            unicast_master_table: Default::default(),
+            hybrid_mode: false,
+            one_step: false,
+            slave_event_monitoring: None,
+        }
+    }
+
+    #[test]
+    fn check_g8275_2_port_config() {
+        let e2e = DelayMechanism::E2E {
+            interval: Interval::from_log_2(-7),
+        };
+
+        let mut config = port_config(e2e);
+        assert_eq!(
+            Profile::G8275_2.check_port_config(&config),
+            Err(ProfileError::UnicastNegotiation)
+        );
+        assert_eq!(Profile::Default.check_port_config(&config), Ok(()));
+
+        config
+            .unicast_master_table
+            .push(PortAddress::Ipv4([10, 0, 0, 1]));
+        assert_eq!(Profile::G8275_2.check_port_config(&config), Ok(()));
+
+        config.sync_interval = Interval::from_log_2(-8);
+        assert_eq!(
+            Profile::G8275_2.check_port_config(&config),
+            Err(ProfileError::SyncInterval)
+        );
+        config.sync_interval = Interval::ONE_SECOND;
+
+        config.announce_interval = Interval::TWO_SECONDS;
+        assert_eq!(
+            Profile::G8275_2.check_port_config(&config),
+            Err(ProfileError::AnnounceInterval)
+        );
+        config.announce_interval = Interval::ONE_SECOND;
+
+        config.unicast_negotiation = Some(UnicastNegotiationConfig {
+            grant_duration: 30,
+            ..Default::default()
+        });
+        assert_eq!(
+            Profile::G8275_2.check_port_config(&config),
+            Err(ProfileError::GrantDuration)
+        );
+
+        let mut config = port_config(DelayMechanism::P2P {
+            interval: Interval::ONE_SECOND,
+        });
+        config.unicast_negotiation = Some(Default::default());
+        assert_eq!(
+            Profile::G8275_2.check_port_config(&config),
+            Err(ProfileError::DelayMechanism)
+        );
+        assert_eq!(
+            Profile::Gptp.check_port_config(&config),
+            Err(ProfileError::UnicastNegotiation)
+        );
+    }
+
+    #[test]
+    fn default_port_configs_are_allowed() {
+        for profile in [
+            Profile::Default,
+            Profile::Gptp,
+            Profile::G8275_1,
+            Profile::G8275_2,
+            Profile::St2059_2,
+            Profile::C37_238,
+            Profile::Aes67,
+        ] {
+            let config = profile.default_port_config(AcceptAnyMaster);
+            assert_eq!(profile.check_port_config(&config), Ok(()), "{profile:?}");
+        }
+    }
+
+    #[test]
+    fn check_aes67_port_config() {
+        let mut config = Profile::Aes67.default_port_config(AcceptAnyMaster);
+        assert_eq!(config.announce_interval, Interval::from_log_2(-1));
+        assert_eq!(config.sync_interval, Interval::from_log_2(-3));
+        assert_eq!(config.announce_receipt_timeout, 3);
+
+        config.sync_interval = Interval::from_log_2(-5);
+        assert_eq!(
+            Profile::Aes67.check_port_config(&config),
+            Err(ProfileError::SyncInterval)
+        );
+        config.sync_interval = Interval::from_log_2(-3);
+
+        config.announce_interval = Interval::from_log_2(5);
+        assert_eq!(
+            Profile::Aes67.check_port_config(&config),
+            Err(ProfileError::AnnounceInterval)
+        );
+        config.announce_interval = Interval::from_log_2(-1);
+
+        config.announce_receipt_timeout = 1;
+        assert_eq!(
+            Profile::Aes67.check_port_config(&config),
+            Err(ProfileError::AnnounceReceiptTimeout)
+        );
+        config.announce_receipt_timeout = 3;
+
+        config.delay_mechanism = DelayMechanism::E2E {
+            interval: Interval::from_log_2(-4),
+        };
+        assert_eq!(
+            Profile::Aes67.check_port_config(&config),
+            Err(ProfileError::DelayInterval)
+        );
+
+        config.delay_mechanism = DelayMechanism::P2P {
+            interval: Interval::ONE_SECOND,
+        };
+        assert_eq!(
+            Profile::Aes67.check_port_config(&config),
+            Err(ProfileError::DelayMechanism)
+        );
+    }
+
+    /// The messages in a hex dump as in `testdata/aes67.txt`
+    fn parse_hex_dump(dump: &str) -> std::vec::Vec<std::vec::Vec<u8>> {
+        let mut messages = std::vec![];
+        let mut message = std::vec![];
+        for line in dump.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                if !message.is_empty() {
+                    messages.push(core::mem::take(&mut message));
+                }
+                continue;
+            }
+
+            // Skip the offset
+            for byte in line.split_whitespace().skip(1) {
+                message.push(u8::from_str_radix(byte, 16).unwrap());
+            }
+        }
+        if !message.is_empty() {
+            messages.push(message);
+        }
+
+        messages
+    }
+
+    #[test]
+    fn aes67_message_format() {
+        use crate::datastructures::messages::{Message, MessageBody};
+
+        let profile = Profile::Aes67;
+        let config = profile.default_port_config(AcceptAnyMaster);
+
+        let packets = parse_hex_dump(include_str!("../../testdata/aes67.txt"));
+        let messages: std::vec::Vec<_> = packets
+            .iter()
+            .map(|packet| Message::deserialize(packet).unwrap())
+            .collect();
+        assert!(!messages.is_empty());
+
+        for (packet, message) in packets.iter().zip(&messages) {
+            assert_eq!(message.header.domain_number, profile.default_domain());
+
+            // We send these messages in exactly the same format
+            let mut buffer = [0; 128];
+            let length = message.serialize(&mut buffer).unwrap();
+            assert_eq!(&buffer[..length], &packet[..]);
+
+            let expected_interval = match message.body {
+                MessageBody::Announce(_) => Some(config.announce_interval),
+                MessageBody::Sync(_) | MessageBody::FollowUp(_) => Some(config.sync_interval),
+                MessageBody::DelayResp(_) => Some(config.min_delay_req_interval()),
+                _ => None,
+            };
+            if let Some(interval) = expected_interval {
+                assert_eq!(message.header.log_message_interval, interval.as_log_2());
+            }
+        }
+
+        // Every two-step sync is followed up, and every delay response answers
+        // a delay request in the trace
+        for message in &messages {
+            match message.body {
+                MessageBody::Sync(_) if message.header.two_step_flag => {
+                    assert!(messages.iter().any(|follow_up| {
+                        matches!(follow_up.body, MessageBody::FollowUp(_))
+                            && follow_up.header.source_port_identity
+                                == message.header.source_port_identity
+                            && follow_up.header.sequence_id == message.header.sequence_id
+                    }));
+                }
+                MessageBody::DelayResp(response) => {
+                    assert!(messages.iter().any(|request| {
+                        matches!(request.body, MessageBody::DelayReq(_))
+                            && request.header.source_port_identity
+                                == response.requesting_port_identity
+                            && request.header.sequence_id == message.header.sequence_id
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use aes::{Aes128, Aes256};
+use arrayvec::ArrayVec;
+use cmac::Cmac;
+use hmac::{
+    digest::{KeyInit, Mac},
+    Hmac,
+};
+use sha2::Sha256;
+
+use crate::time::{Duration, Time};
+#[cfg(doc)]
+use crate::PtpInstance;
+
+/// Maximum number of keys in a [`SecurityConfig`]
+pub const MAX_SECURITY_KEYS: usize = 8;
+
+/// Maximum length in bytes of the secret of a [`SecurityKey`]
+pub const MAX_KEY_LENGTH: usize = 64;
+
+/// Maximum length in bytes of an integrity check value
+pub(crate) const MAX_ICV_LENGTH: usize = 32;
+
+/// Offset of the correctionField in the header of a message
+const CORRECTION_FIELD: core::ops::Range<usize> = 8..16;
+
+/// Seconds by which the validity window of a key is extended for received
+/// messages, to cover differences between the clocks of the instances
+const VALIDITY_MARGIN_SECS: i64 = 2;
+
+/// Integrity protection of PTP messages with the AUTHENTICATION TLV, see
+/// *IEEE1588-2019 annex P*
+///
+/// With a security configuration every message sent by the ports of a
+/// [`PtpInstance`] carries an AUTHENTICATION TLV with an integrity check value
+/// (ICV) computed with the key [`key_id`](`Self::key_id`), using immediate
+/// security processing. Received messages without such a TLV, or with an ICV
+/// that does not match any of the [`keys`](`Self::keys`), are dropped.
+///
+/// The ICV is computed over the entire message with its correctionField set to
+/// zero, so transparent clocks on the path can update the correctionField
+/// without invalidating it. One-step messages cannot be authenticated, as the
+/// hardware changes them after the ICV is computed.
+///
+/// Keys can be rotated by giving them a validity window and supplying them
+/// through a [`KeyProvider`], see [`PtpInstance::update_keys`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityConfig {
+    /// The security parameter pointer (SPP) of the security association
+    pub spp: u8,
+    /// The keys accepted for received messages
+    pub keys: ArrayVec<SecurityKey, MAX_SECURITY_KEYS>,
+    /// The id of the key used for sent messages, which must be one of the
+    /// [`keys`](`Self::keys`)
+    pub key_id: u32,
+}
+
+impl SecurityConfig {
+    pub(crate) fn key(&self, key_id: u32) -> Option<&SecurityKey> {
+        self.keys.iter().find(|key| key.key_id == key_id)
+    }
+
+    /// The configuration with only the keys that can be used at `now`
+    ///
+    /// Received messages are accepted with every key within its validity
+    /// window, extended by a few seconds on both ends to cover the differences
+    /// between the clocks of the instances. Sent messages use the key
+    /// [`key_id`](`Self::key_id`) while it is valid, and after that the valid
+    /// key that became valid last. So a key rotates without losing messages
+    /// when the validity windows of the old and new key overlap.
+    pub fn active(&self, now: Time) -> SecurityConfig {
+        let margin = Duration::from_secs(VALIDITY_MARGIN_SECS);
+        let key_id = match self.key(self.key_id) {
+            Some(key) if key.is_valid_at(now, Duration::ZERO) => self.key_id,
+            _ => self
+                .keys
+                .iter()
+                .filter(|key| key.is_valid_at(now, Duration::ZERO))
+                .max_by_key(|key| key.valid_from)
+                .map_or(self.key_id, |key| key.key_id),
+        };
+
+        SecurityConfig {
+            spp: self.spp,
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| key.is_valid_at(now, margin))
+                .cloned()
+                .collect(),
+            key_id,
+        }
+    }
+}
+
+/// A source of the keys of a [`SecurityConfig`], such as a key management
+/// service or a key file
+///
+/// The provider is polled with [`PtpInstance::update_keys`], which selects the
+/// keys that are valid at the time of the poll. A provider that fails to fetch
+/// new keys should keep returning the keys it had, so ports keep working
+/// until those expire.
+pub trait KeyProvider {
+    /// All keys currently known to the provider, including those that are not
+    /// valid yet or no longer valid
+    fn security_config(&mut self) -> &SecurityConfig;
+}
+
+/// Static keys that never change
+impl KeyProvider for SecurityConfig {
+    fn security_config(&mut self) -> &SecurityConfig {
+        self
+    }
+}
+
+/// A shared key of a [`SecurityConfig`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecurityKey {
+    /// Identifies the key in the AUTHENTICATION TLV
+    pub key_id: u32,
+    /// The algorithm computing the integrity check value
+    pub algorithm: IntegrityAlgorithm,
+    /// The secret, which must be 16 bytes for
+    /// [`Aes128Cmac`](`IntegrityAlgorithm::Aes128Cmac`) and 32 bytes for
+    /// [`Aes256Cmac`](`IntegrityAlgorithm::Aes256Cmac`)
+    pub secret: ArrayVec<u8, MAX_KEY_LENGTH>,
+    /// The time from which the key can be used, always when `None`
+    pub valid_from: Option<Time>,
+    /// The time until which the key can be used, always when `None`
+    pub valid_until: Option<Time>,
+}
+
+impl core::fmt::Debug for SecurityKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SecurityKey")
+            .field("key_id", &self.key_id)
+            .field("algorithm", &self.algorithm)
+            .field("valid_from", &self.valid_from)
+            .field("valid_until", &self.valid_until)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecurityKey {
+    /// Whether the secret has a valid length for the algorithm
+    pub fn is_valid(&self) -> bool {
+        match self.algorithm {
+            IntegrityAlgorithm::HmacSha256 | IntegrityAlgorithm::HmacSha256_128 => {
+                !self.secret.is_empty()
+            }
+            IntegrityAlgorithm::Aes128Cmac => self.secret.len() == 16,
+            IntegrityAlgorithm::Aes256Cmac => self.secret.len() == 32,
+        }
+    }
+
+    /// Whether the key can be used at `now`, with its validity window extended
+    /// by `margin` on both ends
+    fn is_valid_at(&self, now: Time, margin: Duration) -> bool {
+        self.valid_from.map_or(true, |from| from <= now + margin)
+            && self.valid_until.map_or(true, |until| now < until + margin)
+    }
+
+    /// Compute the integrity check value of `message`, returns `None` when the
+    /// secret is not valid for the algorithm
+    pub(crate) fn icv(&self, message: &[u8]) -> Option<ArrayVec<u8, MAX_ICV_LENGTH>> {
+        let length = self.algorithm.icv_length();
+        let mut icv = ArrayVec::new();
+        match self.algorithm {
+            IntegrityAlgorithm::HmacSha256 | IntegrityAlgorithm::HmacSha256_128 => {
+                let tag = mac::<Hmac<Sha256>>(&self.secret, message)?.finalize();
+                icv.try_extend_from_slice(&tag.into_bytes()[..length])
+                    .ok()?;
+            }
+            IntegrityAlgorithm::Aes128Cmac => {
+                let tag = mac::<Cmac<Aes128>>(&self.secret, message)?.finalize();
+                icv.try_extend_from_slice(&tag.into_bytes()[..length])
+                    .ok()?;
+            }
+            IntegrityAlgorithm::Aes256Cmac => {
+                let tag = mac::<Cmac<Aes256>>(&self.secret, message)?.finalize();
+                icv.try_extend_from_slice(&tag.into_bytes()[..length])
+                    .ok()?;
+            }
+        }
+        Some(icv)
+    }
+
+    /// Check the integrity check value of `message` in constant time
+    pub(crate) fn verify(&self, message: &[u8], icv: &[u8]) -> bool {
+        if icv.len() != self.algorithm.icv_length() {
+            return false;
+        }
+
+        match self.algorithm {
+            IntegrityAlgorithm::HmacSha256 | IntegrityAlgorithm::HmacSha256_128 => {
+                mac::<Hmac<Sha256>>(&self.secret, message)
+                    .map_or(false, |mac| mac.verify_truncated_left(icv).is_ok())
+            }
+            IntegrityAlgorithm::Aes128Cmac => mac::<Cmac<Aes128>>(&self.secret, message)
+                .map_or(false, |mac| mac.verify_truncated_left(icv).is_ok()),
+            IntegrityAlgorithm::Aes256Cmac => mac::<Cmac<Aes256>>(&self.secret, message)
+                .map_or(false, |mac| mac.verify_truncated_left(icv).is_ok()),
+        }
+    }
+}
+
+/// Start a MAC over `message` with its correctionField set to zero
+fn mac<M: Mac + KeyInit>(secret: &[u8], message: &[u8]) -> Option<M> {
+    if message.len() < CORRECTION_FIELD.end {
+        return None;
+    }
+
+    let mut mac = <M as KeyInit>::new_from_slice(secret).ok()?;
+    mac.update(&message[..CORRECTION_FIELD.start]);
+    mac.update(&[0; CORRECTION_FIELD.end - CORRECTION_FIELD.start]);
+    mac.update(&message[CORRECTION_FIELD.end..]);
+    Some(mac)
+}
+
+/// The algorithm computing the integrity check value (ICV) of a message
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum IntegrityAlgorithm {
+    /// HMAC-SHA256 with a 32 byte ICV
+    HmacSha256,
+    /// HMAC-SHA256 truncated to a 16 byte ICV
+    #[cfg_attr(feature = "serde", serde(rename = "hmac-sha256-128"))]
+    HmacSha256_128,
+    /// AES-CMAC with a 128 bit key and a 16 byte ICV
+    Aes128Cmac,
+    /// AES-CMAC with a 256 bit key and a 16 byte ICV
+    Aes256Cmac,
+}
+
+impl IntegrityAlgorithm {
+    /// The length in bytes of the integrity check value
+    pub fn icv_length(self) -> usize {
+        match self {
+            IntegrityAlgorithm::HmacSha256 => 32,
+            IntegrityAlgorithm::HmacSha256_128
+            | IntegrityAlgorithm::Aes128Cmac
+            | IntegrityAlgorithm::Aes256Cmac => 16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(algorithm: IntegrityAlgorithm, secret: &[u8]) -> SecurityKey {
+        SecurityKey {
+            key_id: 1,
+            algorithm,
+            secret: secret.try_into().unwrap(),
+            valid_from: None,
+            valid_until: None,
+        }
+    }
+
+    #[test]
+    fn hmac_sha256() {
+        let key = key(IntegrityAlgorithm::HmacSha256, b"Jefe");
+        let message = b"what do ya want for nothing?";
+        let icv = key.icv(message).unwrap();
+        assert_eq!(icv.len(), 32);
+        assert!(key.verify(message, &icv));
+
+        let mut other = *message;
+        other[20] ^= 1;
+        assert!(!key.verify(&other, &icv));
+
+        let truncated = SecurityKey {
+            algorithm: IntegrityAlgorithm::HmacSha256_128,
+            ..key.clone()
+        };
+        assert_eq!(truncated.icv(message).unwrap()[..], icv[..16]);
+        assert!(truncated.verify(message, &icv[..16]));
+        assert!(!truncated.verify(message, &icv));
+    }
+
+    #[test]
+    fn correction_field_is_ignored() {
+        let key = key(IntegrityAlgorithm::Aes128Cmac, &[0x2b; 16]);
+        let mut message = [0x55; 44];
+        let icv = key.icv(&message).unwrap();
+        assert_eq!(icv.len(), 16);
+
+        message[CORRECTION_FIELD].fill(0x12);
+        assert!(key.verify(&message, &icv));
+        assert_eq!(key.icv(&message).unwrap(), icv);
+
+        message[CORRECTION_FIELD.end] = 0x12;
+        assert!(!key.verify(&message, &icv));
+    }
+
+    #[test]
+    fn key_length() {
+        assert!(key(IntegrityAlgorithm::Aes128Cmac, &[0; 16]).is_valid());
+        assert!(!key(IntegrityAlgorithm::Aes128Cmac, &[0; 32]).is_valid());
+        assert!(key(IntegrityAlgorithm::Aes256Cmac, &[0; 32]).is_valid());
+        assert!(!key(IntegrityAlgorithm::HmacSha256, &[]).is_valid());
+
+        let invalid = key(IntegrityAlgorithm::Aes256Cmac, &[0; 16]);
+        assert!(invalid.icv(&[0; 44]).is_none());
+        assert!(!invalid.verify(&[0; 44], &[0; 16]));
+    }
+
+    #[test]
+    fn key_rotation() {
+        let window = |key_id, from: Option<u64>, until: Option<u64>| SecurityKey {
+            key_id,
+            valid_from: from.map(Time::from_secs),
+            valid_until: until.map(Time::from_secs),
+            ..key(IntegrityAlgorithm::HmacSha256_128, &[1; 16])
+        };
+        let mut config = SecurityConfig {
+            spp: 0,
+            keys: ArrayVec::new(),
+            key_id: 1,
+        };
+        config.keys.push(window(1, None, Some(1000)));
+        config.keys.push(window(2, Some(900), Some(2000)));
+        config.keys.push(window(3, Some(1900), None));
+
+        let check = |now, key_id, keys: &[u32]| {
+            let active = config.active(Time::from_secs(now));
+            assert_eq!(active.key_id, key_id);
+            assert!(active
+                .keys
+                .iter()
+                .map(|key| key.key_id)
+                .eq(keys.iter().copied()));
+        };
+
+        check(100, 1, &[1]);
+        // During the overlap, the configured key is still used for sending
+        check(950, 1, &[1, 2]);
+        // Shortly after expiry the old key is still accepted
+        check(1001, 2, &[1, 2]);
+        check(1500, 2, &[2]);
+        check(1950, 3, &[2, 3]);
+        check(5000, 3, &[3]);
+    }
+}
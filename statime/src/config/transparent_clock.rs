@@ -0,0 +1,47 @@
+use crate::config::{ClockIdentity, DelayMechanism, SdoId};
+#[cfg(doc)]
+use crate::TransparentClock;
+
+/// Configuration for a [`TransparentClock`]
+///
+/// # Example
+/// A configuration with common default values:
+/// ```
+/// # use statime::config::{ClockIdentity, DelayMechanism, SdoId, TransparentClockConfig};
+/// # use statime::time::Interval;
+/// let config = TransparentClockConfig {
+///     clock_identity: ClockIdentity::from_mac_address([1,2,3,4,5,6]),
+///     primary_domain: 0,
+///     sdo_id: SdoId::default(),
+///     delay_mechanism: DelayMechanism::E2E { interval: Interval::ONE_SECOND },
+///     time_inaccuracy: 0,
+/// };
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TransparentClockConfig {
+    /// The unique identifier for this device within the PTP network.
+    pub clock_identity: ClockIdentity,
+
+    /// The domain used for messages originating from the transparent clock
+    /// itself.
+    ///
+    /// Messages from all domains are forwarded, this only affects messages
+    /// generated by the [`TransparentClock`]. See *IEEE 1588-2019 section
+    /// 8.3.2.2.2*.
+    pub primary_domain: u8,
+
+    /// See [`TransparentClockConfig::primary_domain`].
+    pub sdo_id: SdoId,
+
+    /// Whether to operate as an end-to-end or a peer-to-peer transparent clock
+    ///
+    /// The interval of [`DelayMechanism::E2E`] is unused, the interval of
+    /// [`DelayMechanism::P2P`] determines how often the ports measure the
+    /// link delay to their peer. See *IEEE 1588-2019 sections 10.2 and 10.3*.
+    pub delay_mechanism: DelayMechanism,
+
+    /// The time inaccuracy in nanoseconds this clock adds to the network time
+    /// inaccuracy of forwarded announce messages of the power profile, see
+    /// [`Profile::C37_238`](`crate::config::Profile::C37_238`)
+    pub time_inaccuracy: u32,
+}
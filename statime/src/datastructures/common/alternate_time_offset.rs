@@ -0,0 +1,146 @@
+use arrayvec::ArrayString;
+
+use crate::datastructures::{
+    common::{Tlv, TlvType},
+    WireFormatError,
+};
+
+/// Maximum length in bytes of the display name of an alternate timescale
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 10;
+
+/// The offset of an alternate timescale, such as local time, announced by the
+/// grandmaster in the alternate time offset indicator TLV, see
+/// *IEEE1588-2019 section 16.3*
+///
+/// Times are in seconds of the PTP timescale, offsets are in seconds relative
+/// to the PTP time.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlternateTimeOffset {
+    /// Identifies the alternate timescale
+    pub key_field: u8,
+    /// The current offset of the alternate timescale
+    pub current_offset: i32,
+    /// The change of the offset at the next jump, for instance for daylight
+    /// saving time
+    pub jump_seconds: i32,
+    /// When the next jump happens, only the lower 48 bits are used on the wire
+    pub time_of_next_jump: u64,
+    /// The name of the alternate timescale, such as `"CET"`
+    pub display_name: ArrayString<MAX_DISPLAY_NAME_LENGTH>,
+}
+
+impl AlternateTimeOffset {
+    pub(crate) fn value_size(&self) -> usize {
+        // Padded to an even length
+        (16 + self.display_name.len() + 1) & !1
+    }
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let name = self.display_name.as_bytes();
+        let buffer = buffer
+            .get_mut(..self.value_size())
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer.fill(0);
+        buffer[0] = self.key_field;
+        buffer[1..5].copy_from_slice(&self.current_offset.to_be_bytes());
+        buffer[5..9].copy_from_slice(&self.jump_seconds.to_be_bytes());
+        buffer[9..15].copy_from_slice(&self.time_of_next_jump.to_be_bytes()[2..]);
+        buffer[15] = name.len() as u8;
+        buffer[16..16 + name.len()].copy_from_slice(name);
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not an alternate time
+    /// offset indicator TLV
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        if tlv.tlv_type != TlvType::AlternateTimeOffsetIndicator {
+            return Ok(None);
+        }
+
+        let value: &[u8] = tlv.value.as_ref();
+        let name_length = *value.get(15).ok_or(WireFormatError::BufferTooShort)? as usize;
+        let name = value
+            .get(16..16 + name_length)
+            .ok_or(WireFormatError::BufferTooShort)?;
+        let name = core::str::from_utf8(name).map_err(|_| WireFormatError::Invalid)?;
+
+        let mut time_of_next_jump = [0; 8];
+        time_of_next_jump[2..].copy_from_slice(&value[9..15]);
+
+        Ok(Some(Self {
+            key_field: value[0],
+            current_offset: i32::from_be_bytes(value[1..5].try_into().unwrap()),
+            jump_seconds: i32::from_be_bytes(value[5..9].try_into().unwrap()),
+            time_of_next_jump: u64::from_be_bytes(time_of_next_jump),
+            display_name: ArrayString::from(name).map_err(|_| WireFormatError::CapacityError)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternate_time_offset_tlv_wireformat() {
+        let byte_representation = [
+            0x01, // key field
+            0xff, 0xff, 0xb9, 0xb0, // current offset
+            0x00, 0x00, 0x0e, 0x10, // jump seconds
+            0x00, 0x00, 0x65, 0x5a, 0x8e, 0x40, // time of next jump
+            0x03, b'E', b'S', b'T', // display name
+            0x00, // padding
+        ];
+        let object_representation = AlternateTimeOffset {
+            key_field: 1,
+            current_offset: -18000,
+            jump_seconds: 3600,
+            time_of_next_jump: 1700433472,
+            display_name: ArrayString::from("EST").unwrap(),
+        };
+
+        // Test the serialization output
+        let mut serialization_buffer = [0; 32];
+        let value = object_representation
+            .serialize_value(&mut serialization_buffer)
+            .unwrap();
+        assert_eq!(value, byte_representation);
+
+        // Test the deserialization output
+        let tlv = Tlv {
+            tlv_type: TlvType::AlternateTimeOffsetIndicator,
+            value: byte_representation[..].into(),
+        };
+        let deserialized_data = AlternateTimeOffset::from_tlv(&tlv);
+        assert_eq!(deserialized_data.unwrap(), Some(object_representation));
+
+        // No padding is needed for names of an even length
+        let object_representation = AlternateTimeOffset {
+            display_name: ArrayString::from("CEST").unwrap(),
+            ..object_representation
+        };
+        let value = object_representation
+            .serialize_value(&mut serialization_buffer)
+            .unwrap();
+        assert_eq!(value.len(), 20);
+        assert_eq!(&value[15..], b"\x04CEST");
+
+        // Names that are too long are rejected
+        let mut too_long = [0; 28];
+        too_long[15] = 11;
+        let tlv = Tlv {
+            tlv_type: TlvType::AlternateTimeOffsetIndicator,
+            value: too_long[..].into(),
+        };
+        assert!(matches!(
+            AlternateTimeOffset::from_tlv(&tlv),
+            Err(WireFormatError::CapacityError)
+        ));
+    }
+}
@@ -1,5 +1,7 @@
 use core::cmp::Ordering;
 
+use crate::time::Duration;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// How accurate the underlying clock device is expected to be when not
@@ -139,6 +141,46 @@ impl ClockAccuracy {
         }
     }
 
+    /// The most accurate clock accuracy that still covers the given
+    /// uncertainty of a clock
+    pub fn from_uncertainty(uncertainty: Duration) -> Self {
+        // Upper bounds of the accuracies in nanoseconds
+        const ACCURACIES: [(f64, ClockAccuracy); 26] = [
+            (0.001, ClockAccuracy::PS1),
+            (0.0025, ClockAccuracy::PS2_5),
+            (0.01, ClockAccuracy::PS10),
+            (0.025, ClockAccuracy::PS25),
+            (0.1, ClockAccuracy::PS100),
+            (0.25, ClockAccuracy::PS250),
+            (1.0, ClockAccuracy::NS1),
+            (2.5, ClockAccuracy::NS2_5),
+            (10.0, ClockAccuracy::NS10),
+            (25.0, ClockAccuracy::NS25),
+            (100.0, ClockAccuracy::NS100),
+            (250.0, ClockAccuracy::NS250),
+            (1e3, ClockAccuracy::US1),
+            (2.5e3, ClockAccuracy::US2_5),
+            (10e3, ClockAccuracy::US10),
+            (25e3, ClockAccuracy::US25),
+            (100e3, ClockAccuracy::US100),
+            (250e3, ClockAccuracy::US250),
+            (1e6, ClockAccuracy::MS1),
+            (2.5e6, ClockAccuracy::MS2_5),
+            (10e6, ClockAccuracy::MS10),
+            (25e6, ClockAccuracy::MS25),
+            (100e6, ClockAccuracy::MS100),
+            (250e6, ClockAccuracy::MS250),
+            (1e9, ClockAccuracy::S1),
+            (10e9, ClockAccuracy::S10),
+        ];
+
+        let nanos = uncertainty.abs().nanos_lossy();
+        ACCURACIES
+            .iter()
+            .find(|(limit, _)| nanos <= *limit)
+            .map_or(ClockAccuracy::SGT10, |(_, accuracy)| *accuracy)
+    }
+
     /// high accuracy to low accuracy
     pub(crate) fn cmp_numeric(&self, other: &Self) -> Ordering {
         self.to_primitive().cmp(&other.to_primitive())
@@ -175,4 +217,28 @@ mod tests {
 
         assert_eq!(a.cmp_numeric(&b), Ordering::Less);
     }
+
+    #[test]
+    fn from_uncertainty() {
+        assert_eq!(
+            ClockAccuracy::from_uncertainty(Duration::from_nanos(80)),
+            ClockAccuracy::NS100
+        );
+        assert_eq!(
+            ClockAccuracy::from_uncertainty(Duration::from_micros(1)),
+            ClockAccuracy::US1
+        );
+        assert_eq!(
+            ClockAccuracy::from_uncertainty(Duration::from_fixed_nanos(2.4)),
+            ClockAccuracy::NS2_5
+        );
+        assert_eq!(
+            ClockAccuracy::from_uncertainty(-Duration::from_millis(3)),
+            ClockAccuracy::MS10
+        );
+        assert_eq!(
+            ClockAccuracy::from_uncertainty(Duration::from_secs(60)),
+            ClockAccuracy::SGT10
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use super::clock_accuracy::ClockAccuracy;
 use crate::datastructures::{WireFormat, WireFormatError};
+#[allow(unused_imports)]
+use crate::float_polyfill::FloatPolyfill;
 
 /// A description of the accuracy and type of a clock.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -37,6 +39,37 @@ impl Default for ClockQuality {
     }
 }
 
+impl ClockQuality {
+    /// The offsetScaledLogVariance of a clock with the given variance in
+    /// seconds^2, see *IEEE1588-2019 section 7.6.3.3*
+    pub fn scaled_log_variance(variance: f64) -> u16 {
+        (0x8000 as f64 + variance.log2() * 256.0 + 0.5).clamp(0.0, 0xfffe as f64) as u16
+    }
+
+    /// This quality of a grandmaster as seen through a clock synchronized to
+    /// it with the given accuracy and offsetScaledLogVariance, which is never
+    /// better than that of the grandmaster itself
+    pub(crate) fn degraded_by(
+        self,
+        accuracy: ClockAccuracy,
+        offset_scaled_log_variance: u16,
+    ) -> Self {
+        let clock_accuracy = match self.clock_accuracy {
+            // Not comparable to the accuracy of our clock
+            ClockAccuracy::Reserved | ClockAccuracy::ProfileSpecific(_) => self.clock_accuracy,
+            _ => core::cmp::max_by(self.clock_accuracy, accuracy, ClockAccuracy::cmp_numeric),
+        };
+
+        Self {
+            clock_class: self.clock_class,
+            clock_accuracy,
+            offset_scaled_log_variance: self
+                .offset_scaled_log_variance
+                .max(offset_scaled_log_variance),
+        }
+    }
+}
+
 impl WireFormat for ClockQuality {
     fn wire_size(&self) -> usize {
         4
@@ -86,4 +119,44 @@ mod tests {
             assert_eq!(deserialized_data, object_representation);
         }
     }
+
+    #[test]
+    fn scaled_log_variance() {
+        // The default variance of 2^-23 seconds^2
+        assert_eq!(
+            ClockQuality::scaled_log_variance(2f64.powi(-23)),
+            ClockQuality::default().offset_scaled_log_variance
+        );
+        assert_eq!(ClockQuality::scaled_log_variance(1.0), 0x8000);
+        assert_eq!(ClockQuality::scaled_log_variance(0.0), 0);
+        assert_eq!(ClockQuality::scaled_log_variance(f64::MAX), 0xfffe);
+    }
+
+    #[test]
+    fn degraded_by() {
+        let grandmaster = ClockQuality {
+            clock_class: 6,
+            clock_accuracy: ClockAccuracy::NS100,
+            offset_scaled_log_variance: 0x4e5d,
+        };
+
+        let degraded = grandmaster.degraded_by(ClockAccuracy::US1, 0x5000);
+        assert_eq!(degraded.clock_class, 6);
+        assert_eq!(degraded.clock_accuracy, ClockAccuracy::US1);
+        assert_eq!(degraded.offset_scaled_log_variance, 0x5000);
+
+        // Never better than the grandmaster
+        assert_eq!(grandmaster.degraded_by(ClockAccuracy::NS1, 0), grandmaster);
+
+        let profile_specific = ClockQuality {
+            clock_accuracy: ClockAccuracy::ProfileSpecific(3),
+            ..grandmaster
+        };
+        assert_eq!(
+            profile_specific
+                .degraded_by(ClockAccuracy::US1, 0)
+                .clock_accuracy,
+            ClockAccuracy::ProfileSpecific(3)
+        );
+    }
 }
@@ -1,20 +1,28 @@
 //! Common data structures that are used throughout the protocol
 
+mod alternate_time_offset;
 mod clock_accuracy;
 mod clock_identity;
 mod clock_quality;
 mod leap_indicator;
+mod port_address;
 mod port_identity;
+mod power_profile;
+mod synchronization_metadata;
 mod time_interval;
 mod time_source;
 mod timestamp;
 mod tlv;
 
+pub use alternate_time_offset::*;
 pub use clock_accuracy::*;
 pub use clock_identity::*;
 pub use clock_quality::*;
 pub use leap_indicator::*;
-pub(crate) use port_identity::*;
+pub use port_address::*;
+pub use port_identity::*;
+pub use power_profile::*;
+pub use synchronization_metadata::*;
 pub(crate) use time_interval::*;
 pub use time_source::*;
 pub use timestamp::*;
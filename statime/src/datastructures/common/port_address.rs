@@ -0,0 +1,39 @@
+/// The protocol address of a PTP port, see *IEEE1588-2019 section 5.3.6*
+///
+/// Statime does not do any networking itself. These addresses are only used
+/// to tell the user where unicast messages need to go, and for the user to
+/// tell statime where a received message came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortAddress {
+    /// An address for PTP over UDP/IPv4, see *IEEE1588-2019 annex C*
+    Ipv4([u8; 4]),
+    /// An address for PTP over UDP/IPv6, see *IEEE1588-2019 annex D*
+    Ipv6([u8; 16]),
+    /// A MAC address for PTP over IEEE 802.3, see *IEEE1588-2019 annex E*
+    Ethernet([u8; 6]),
+}
+
+#[cfg(feature = "std")]
+impl From<std::net::Ipv4Addr> for PortAddress {
+    fn from(value: std::net::Ipv4Addr) -> Self {
+        PortAddress::Ipv4(value.octets())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::net::Ipv6Addr> for PortAddress {
+    fn from(value: std::net::Ipv6Addr) -> Self {
+        PortAddress::Ipv6(value.octets())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::net::IpAddr> for PortAddress {
+    fn from(value: std::net::IpAddr) -> Self {
+        match value {
+            std::net::IpAddr::V4(addr) => addr.into(),
+            std::net::IpAddr::V6(addr) => addr.into(),
+        }
+    }
+}
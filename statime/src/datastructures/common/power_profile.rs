@@ -0,0 +1,161 @@
+use crate::datastructures::{
+    common::{Tlv, TlvType},
+    messages::Message,
+    WireFormatError,
+};
+
+/// The organizationId of the IEEE C37.238 power profile
+const IEEE_C37_238_ORGANIZATION_ID: [u8; 3] = [0x1c, 0x12, 0x9d];
+
+/// Settings of an instance following the power profile, see
+/// [`Profile::C37_238`](`crate::config::Profile::C37_238`)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerProfileConfig {
+    /// The identifier of this instance while it is the grandmaster, between 3
+    /// and 254 for grandmaster capable clocks
+    pub grandmaster_id: u16,
+    /// The time inaccuracy of this instance in nanoseconds
+    ///
+    /// As grandmaster this is the grandmaster time inaccuracy, otherwise it is
+    /// added to the network time inaccuracy passed on to the next clock.
+    pub time_inaccuracy: u32,
+}
+
+/// The information carried by the IEEE_C37_238 organization extension TLV in
+/// announce messages, see *IEEE C37.238-2011 section 5.12*
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerProfileInformation {
+    /// The identifier of the grandmaster
+    pub grandmaster_id: u16,
+    /// The time inaccuracy of the grandmaster in nanoseconds
+    pub grandmaster_time_inaccuracy: u32,
+    /// The time inaccuracy added by the network between the grandmaster and
+    /// the sender, in nanoseconds
+    pub network_time_inaccuracy: u32,
+}
+
+impl PowerProfileInformation {
+    const VALUE_SIZE: usize = 18;
+    const ORGANIZATION_SUB_TYPE: [u8; 3] = [0x00, 0x00, 0x01];
+
+    /// The total time inaccuracy of the time received from the sender
+    pub fn total_time_inaccuracy(&self) -> u32 {
+        self.grandmaster_time_inaccuracy
+            .saturating_add(self.network_time_inaccuracy)
+    }
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let buffer = buffer
+            .get_mut(..Self::VALUE_SIZE)
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer[0..3].copy_from_slice(&IEEE_C37_238_ORGANIZATION_ID);
+        buffer[3..6].copy_from_slice(&Self::ORGANIZATION_SUB_TYPE);
+        buffer[6..8].copy_from_slice(&self.grandmaster_id.to_be_bytes());
+        buffer[8..12].copy_from_slice(&self.grandmaster_time_inaccuracy.to_be_bytes());
+        buffer[12..16].copy_from_slice(&self.network_time_inaccuracy.to_be_bytes());
+        buffer[16..18].fill(0);
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not an IEEE_C37_238
+    /// TLV
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        let value: &[u8] = tlv.value.as_ref();
+        if tlv.tlv_type != TlvType::OrganizationExtension
+            || value.get(0..3) != Some(&IEEE_C37_238_ORGANIZATION_ID[..])
+            || value.get(3..6) != Some(&Self::ORGANIZATION_SUB_TYPE[..])
+        {
+            return Ok(None);
+        }
+
+        if value.len() < Self::VALUE_SIZE {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        Ok(Some(Self {
+            grandmaster_id: u16::from_be_bytes(value[6..8].try_into().unwrap()),
+            grandmaster_time_inaccuracy: u32::from_be_bytes(value[8..12].try_into().unwrap()),
+            network_time_inaccuracy: u32::from_be_bytes(value[12..16].try_into().unwrap()),
+        }))
+    }
+
+    /// Add `inaccuracy` to the network time inaccuracy of the IEEE_C37_238 TLV
+    /// in a serialized message, if it has one
+    pub(crate) fn add_to_serialized_network_time_inaccuracy(
+        buffer: &mut [u8],
+        inaccuracy: u32,
+    ) -> Result<(), WireFormatError> {
+        let offset = {
+            let message = Message::deserialize(buffer)?;
+            let mut offset = message.wire_size() - message.suffix.wire_size();
+            let mut found = None;
+            for tlv in message.suffix.tlv() {
+                if Self::from_tlv(&tlv)?.is_some() {
+                    // Skip the type and length of the TLV
+                    found = Some(offset + 4 + 12);
+                    break;
+                }
+                offset += tlv.wire_size();
+            }
+            found
+        };
+
+        if let Some(offset) = offset {
+            let field = &mut buffer[offset..offset + 4];
+            let current = u32::from_be_bytes((&*field).try_into().unwrap());
+            field.copy_from_slice(&current.saturating_add(inaccuracy).to_be_bytes());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_profile_tlv_wireformat() {
+        let byte_representation = [
+            0x1c, 0x12, 0x9d, 0x00, 0x00, 0x01, // organization
+            0x00, 0x05, // grandmaster id
+            0x00, 0x00, 0x00, 0x32, // grandmaster time inaccuracy
+            0x00, 0x00, 0x01, 0x2c, // network time inaccuracy
+            0x00, 0x00,
+        ];
+        let object_representation = PowerProfileInformation {
+            grandmaster_id: 5,
+            grandmaster_time_inaccuracy: 50,
+            network_time_inaccuracy: 300,
+        };
+
+        // Test the serialization output
+        let mut serialization_buffer = [0; 18];
+        let value = object_representation
+            .serialize_value(&mut serialization_buffer)
+            .unwrap();
+        assert_eq!(value, byte_representation);
+
+        // Test the deserialization output
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: byte_representation[..].into(),
+        };
+        let deserialized_data = PowerProfileInformation::from_tlv(&tlv);
+        assert_eq!(deserialized_data.unwrap(), Some(object_representation));
+
+        // Other TLVs are ignored
+        let tlv = Tlv {
+            tlv_type: TlvType::AlternateTimeOffsetIndicator,
+            value: byte_representation[..].into(),
+        };
+        assert!(matches!(PowerProfileInformation::from_tlv(&tlv), Ok(None)));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::datastructures::{
+    common::{Tlv, TlvType},
+    WireFormatError,
+};
+
+/// The organizationId of SMPTE
+const SMPTE_ORGANIZATION_ID: [u8; 3] = [0x68, 0x97, 0xe8];
+
+/// How the grandmaster is locked to its time reference, see *SMPTE ST 2059-2
+/// section 7.2.3*
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MasterLockingStatus {
+    /// The locking status is not provided
+    #[default]
+    NotInUse,
+    /// The grandmaster is not locked to a reference
+    FreeRun,
+    /// The grandmaster is locking to a reference, and its time may jump
+    ColdLocking,
+    /// The grandmaster is locking to a reference by slewing its time
+    WarmLocking,
+    /// The grandmaster is locked to its reference
+    Locked,
+}
+
+impl MasterLockingStatus {
+    /// Converts enum variants back to their primitive values
+    /// as specified in *SMPTE ST 2059-2 section 7.2.3*
+    pub fn to_primitive(self) -> u8 {
+        match self {
+            Self::NotInUse => 0,
+            Self::FreeRun => 1,
+            Self::ColdLocking => 2,
+            Self::WarmLocking => 3,
+            Self::Locked => 4,
+        }
+    }
+
+    pub(crate) fn from_primitive(value: u8) -> Result<Self, WireFormatError> {
+        match value {
+            0 => Ok(Self::NotInUse),
+            1 => Ok(Self::FreeRun),
+            2 => Ok(Self::ColdLocking),
+            3 => Ok(Self::WarmLocking),
+            4 => Ok(Self::Locked),
+            _ => Err(WireFormatError::EnumConversionError),
+        }
+    }
+}
+
+/// The synchronization metadata of the grandmaster, carried by the SMPTE
+/// organization extension TLV in announce messages, see *SMPTE ST 2059-2
+/// section 7.2*
+///
+/// Times are in seconds of the PTP timescale, local offsets are in seconds
+/// relative to the PTP time.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SynchronizationMetadata {
+    /// Numerator of the default video frame rate of the system
+    pub default_system_frame_rate_numerator: u32,
+    /// Denominator of the default video frame rate of the system
+    pub default_system_frame_rate_denominator: u32,
+    /// How the grandmaster is locked to its time reference
+    pub master_locking_status: MasterLockingStatus,
+    /// Whether the time address uses drop frame counting
+    pub drop_frame: bool,
+    /// Whether the time address uses color frame identification
+    pub color_frame_identification: bool,
+    /// Offset of the local time, including the UTC offset
+    pub current_local_offset: i32,
+    /// The change of the local offset at the next jump, for daylight saving or
+    /// leap seconds
+    pub jump_seconds: i32,
+    /// When the next jump of the local offset happens, only the lower 48 bits
+    /// are used on the wire
+    pub time_of_next_jump: u64,
+    /// When time addresses are next resynchronized (jammed), only the lower 48
+    /// bits are used on the wire
+    pub time_of_next_jam: u64,
+    /// When time addresses were last resynchronized (jammed), only the lower
+    /// 48 bits are used on the wire
+    pub time_of_previous_jam: u64,
+    /// The local offset at the last jam
+    pub previous_jam_local_offset: i32,
+    /// Whether daylight saving time is currently in effect
+    pub daylight_saving: bool,
+    /// Whether daylight saving time is in effect after the next jump
+    pub daylight_saving_at_next_jump: bool,
+    /// Whether daylight saving time was in effect at the last jam
+    pub daylight_saving_at_previous_jam: bool,
+    /// Whether the next jump is caused by a leap second
+    pub leap_second_jump: bool,
+}
+
+impl SynchronizationMetadata {
+    const VALUE_SIZE: usize = 48;
+    const ORGANIZATION_SUB_TYPE: [u8; 3] = [0x00, 0x00, 0x01];
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let buffer = buffer
+            .get_mut(..Self::VALUE_SIZE)
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer[0..3].copy_from_slice(&SMPTE_ORGANIZATION_ID);
+        buffer[3..6].copy_from_slice(&Self::ORGANIZATION_SUB_TYPE);
+        buffer[6..10].copy_from_slice(&self.default_system_frame_rate_numerator.to_be_bytes());
+        buffer[10..14].copy_from_slice(&self.default_system_frame_rate_denominator.to_be_bytes());
+        buffer[14] = self.master_locking_status.to_primitive();
+        buffer[15] = self.drop_frame as u8 | (self.color_frame_identification as u8) << 1;
+        buffer[16..20].copy_from_slice(&self.current_local_offset.to_be_bytes());
+        buffer[20..24].copy_from_slice(&self.jump_seconds.to_be_bytes());
+        buffer[24..30].copy_from_slice(&self.time_of_next_jump.to_be_bytes()[2..]);
+        buffer[30..36].copy_from_slice(&self.time_of_next_jam.to_be_bytes()[2..]);
+        buffer[36..42].copy_from_slice(&self.time_of_previous_jam.to_be_bytes()[2..]);
+        buffer[42..46].copy_from_slice(&self.previous_jam_local_offset.to_be_bytes());
+        buffer[46] = self.daylight_saving as u8
+            | (self.daylight_saving_at_next_jump as u8) << 1
+            | (self.daylight_saving_at_previous_jam as u8) << 2;
+        buffer[47] = self.leap_second_jump as u8;
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not a SMPTE
+    /// synchronization metadata TLV
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        let value: &[u8] = tlv.value.as_ref();
+        if tlv.tlv_type != TlvType::OrganizationExtension
+            || value.get(0..3) != Some(&SMPTE_ORGANIZATION_ID[..])
+            || value.get(3..6) != Some(&Self::ORGANIZATION_SUB_TYPE[..])
+        {
+            return Ok(None);
+        }
+
+        if value.len() < Self::VALUE_SIZE {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        let u48 = |bytes: &[u8]| {
+            let mut buffer = [0; 8];
+            buffer[2..].copy_from_slice(bytes);
+            u64::from_be_bytes(buffer)
+        };
+
+        Ok(Some(Self {
+            default_system_frame_rate_numerator: u32::from_be_bytes(
+                value[6..10].try_into().unwrap(),
+            ),
+            default_system_frame_rate_denominator: u32::from_be_bytes(
+                value[10..14].try_into().unwrap(),
+            ),
+            master_locking_status: MasterLockingStatus::from_primitive(value[14])?,
+            drop_frame: value[15] & 1 != 0,
+            color_frame_identification: value[15] & 2 != 0,
+            current_local_offset: i32::from_be_bytes(value[16..20].try_into().unwrap()),
+            jump_seconds: i32::from_be_bytes(value[20..24].try_into().unwrap()),
+            time_of_next_jump: u48(&value[24..30]),
+            time_of_next_jam: u48(&value[30..36]),
+            time_of_previous_jam: u48(&value[36..42]),
+            previous_jam_local_offset: i32::from_be_bytes(value[42..46].try_into().unwrap()),
+            daylight_saving: value[46] & 1 != 0,
+            daylight_saving_at_next_jump: value[46] & 2 != 0,
+            daylight_saving_at_previous_jam: value[46] & 4 != 0,
+            leap_second_jump: value[47] & 1 != 0,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synchronization_metadata_wireformat() {
+        let byte_representation = [
+            0x68, 0x97, 0xe8, 0x00, 0x00, 0x01, // organization
+            0x00, 0x00, 0x75, 0x30, 0x00, 0x00, 0x03, 0xe9, // 30000/1001
+            0x04, 0x01, // locked, drop frame
+            0x00, 0x00, 0x00, 0x25, // current local offset
+            0xff, 0xff, 0xf1, 0xf0, // jump seconds
+            0x00, 0x00, 0x65, 0x5a, 0x8e, 0x40, // time of next jump
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // time of next jam
+            0x00, 0x00, 0x65, 0x12, 0x3c, 0x80, // time of previous jam
+            0x00, 0x00, 0x0e, 0x35, // previous jam local offset
+            0x03, 0x00, // daylight saving
+        ];
+        let object_representation = SynchronizationMetadata {
+            default_system_frame_rate_numerator: 30000,
+            default_system_frame_rate_denominator: 1001,
+            master_locking_status: MasterLockingStatus::Locked,
+            drop_frame: true,
+            color_frame_identification: false,
+            current_local_offset: 37,
+            jump_seconds: -3600,
+            time_of_next_jump: 1700433472,
+            time_of_next_jam: 0,
+            time_of_previous_jam: 1695693952,
+            previous_jam_local_offset: 3637,
+            daylight_saving: true,
+            daylight_saving_at_next_jump: true,
+            daylight_saving_at_previous_jam: false,
+            leap_second_jump: false,
+        };
+
+        // Test the serialization output
+        let mut serialization_buffer = [0; 48];
+        let value = object_representation
+            .serialize_value(&mut serialization_buffer)
+            .unwrap();
+        assert_eq!(value, byte_representation);
+
+        // Test the deserialization output
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: byte_representation[..].into(),
+        };
+        let deserialized_data = SynchronizationMetadata::from_tlv(&tlv);
+        assert_eq!(deserialized_data.unwrap(), Some(object_representation));
+
+        // Other organizations are ignored
+        let mut other = byte_representation;
+        other[0] = 0x00;
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: other[..].into(),
+        };
+        assert!(matches!(SynchronizationMetadata::from_tlv(&tlv), Ok(None)));
+
+        // Unknown locking status
+        let mut invalid = byte_representation;
+        invalid[14] = 5;
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: invalid[..].into(),
+        };
+        assert!(matches!(
+            SynchronizationMetadata::from_tlv(&tlv),
+            Err(WireFormatError::EnumConversionError)
+        ));
+    }
+}
@@ -19,6 +19,11 @@ impl<'a> TlvSetBuilder<'a> {
         Ok(())
     }
 
+    /// The size of the TLVs added so far
+    pub(crate) fn wire_size(&self) -> usize {
+        self.used
+    }
+
     pub(crate) fn build(self) -> TlvSet<'a> {
         TlvSet {
             bytes: &self.buffer[..self.used],
@@ -1,5 +1,5 @@
 use crate::{
-    config::InstanceConfig,
+    config::{InstanceConfig, Profile},
     datastructures::{
         common::{ClockIdentity, ClockQuality},
         messages::SdoId,
@@ -23,6 +23,8 @@ pub(crate) struct InternalDefaultDS {
     pub(crate) domain_number: u8,
     pub(crate) slave_only: bool,
     pub(crate) sdo_id: SdoId,
+    pub(crate) profile: Profile,
+    pub(crate) local_priority: u8,
 }
 
 impl InternalDefaultDS {
@@ -36,6 +38,8 @@ impl InternalDefaultDS {
             domain_number: config.domain_number,
             slave_only: config.slave_only,
             sdo_id: config.sdo_id,
+            profile: config.profile,
+            local_priority: config.local_priority,
         }
     }
 }
@@ -1,4 +1,7 @@
-use crate::datastructures::{common::WireTimestamp, WireFormat, WireFormatError};
+use crate::datastructures::{
+    common::{Tlv, TlvType, WireTimestamp},
+    WireFormat, WireFormatError,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct FollowUpMessage {
@@ -27,6 +30,77 @@ impl FollowUpMessage {
     }
 }
 
+/// The organizationId of IEEE 802.1
+const IEEE_802_1_ORGANIZATION_ID: [u8; 3] = [0x00, 0x80, 0xc2];
+
+/// The Follow_Up information TLV of gPTP, see *IEEE802.1AS-2020 section
+/// 11.4.4.3*
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct FollowUpInformationTlv {
+    /// The rate ratio of the grandmaster to the sender, as `(rateRatio - 1) *
+    /// 2^41`
+    pub(crate) cumulative_scaled_rate_offset: i32,
+    pub(crate) gm_time_base_indicator: u16,
+    /// In units of 2^-16 ns, only the lower 96 bits are used on the wire
+    pub(crate) last_gm_phase_change: i128,
+    pub(crate) scaled_last_gm_freq_change: i32,
+}
+
+impl FollowUpInformationTlv {
+    const VALUE_SIZE: usize = 28;
+    const ORGANIZATION_SUB_TYPE: [u8; 3] = [0x00, 0x00, 0x01];
+
+    /// The rate ratio of the grandmaster clock to the clock of the sender
+    pub(crate) fn rate_ratio(&self) -> f64 {
+        1.0 + self.cumulative_scaled_rate_offset as f64 / (1u64 << 41) as f64
+    }
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let buffer = buffer
+            .get_mut(..Self::VALUE_SIZE)
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer[0..3].copy_from_slice(&IEEE_802_1_ORGANIZATION_ID);
+        buffer[3..6].copy_from_slice(&Self::ORGANIZATION_SUB_TYPE);
+        buffer[6..10].copy_from_slice(&self.cumulative_scaled_rate_offset.to_be_bytes());
+        buffer[10..12].copy_from_slice(&self.gm_time_base_indicator.to_be_bytes());
+        buffer[12..24].copy_from_slice(&self.last_gm_phase_change.to_be_bytes()[4..]);
+        buffer[24..28].copy_from_slice(&self.scaled_last_gm_freq_change.to_be_bytes());
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not a Follow_Up
+    /// information TLV
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        let value: &[u8] = tlv.value.as_ref();
+        if tlv.tlv_type != TlvType::OrganizationExtension
+            || value.get(0..3) != Some(&IEEE_802_1_ORGANIZATION_ID[..])
+            || value.get(3..6) != Some(&Self::ORGANIZATION_SUB_TYPE[..])
+        {
+            return Ok(None);
+        }
+
+        if value.len() < Self::VALUE_SIZE {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        // Sign extend the 96 bit phase change
+        let mut phase_change = [if value[12] & 0x80 != 0 { 0xff } else { 0 }; 16];
+        phase_change[4..].copy_from_slice(&value[12..24]);
+
+        Ok(Some(Self {
+            cumulative_scaled_rate_offset: i32::from_be_bytes(value[6..10].try_into().unwrap()),
+            gm_time_base_indicator: u16::from_be_bytes(value[10..12].try_into().unwrap()),
+            last_gm_phase_change: i128::from_be_bytes(phase_change),
+            scaled_last_gm_freq_change: i32::from_be_bytes(value[24..28].try_into().unwrap()),
+        }))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +142,44 @@ mod tests {
             assert_eq!(deserialized_data, object_representation);
         }
     }
+
+    #[test]
+    fn follow_up_information_tlv_wireformat() {
+        let byte_representation = [
+            0x00, 0x80, 0xc2, 0x00, 0x00, 0x01, 0x00, 0x00, 0x02, 0x00, 0x00, 0x01, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x00, 0x00, 0x00, 0x03u8,
+        ];
+        let object_representation = FollowUpInformationTlv {
+            cumulative_scaled_rate_offset: 512,
+            gm_time_base_indicator: 1,
+            last_gm_phase_change: -2,
+            scaled_last_gm_freq_change: 3,
+        };
+
+        // Test the serialization output
+        let mut serialization_buffer = [0; 28];
+        let value = object_representation
+            .serialize_value(&mut serialization_buffer)
+            .unwrap();
+        assert_eq!(value, byte_representation);
+
+        // Test the deserialization output
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: (&byte_representation[..]).into(),
+        };
+        let deserialized_data = FollowUpInformationTlv::from_tlv(&tlv).unwrap();
+        assert_eq!(deserialized_data, Some(object_representation));
+        assert_eq!(
+            object_representation.rate_ratio(),
+            1.0 + 512.0 / (1u64 << 41) as f64
+        );
+
+        // Other organization extensions are skipped
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: (&[0x00u8, 0x1b, 0x19, 0x00, 0x00, 0x01][..]).into(),
+        };
+        assert!(matches!(FollowUpInformationTlv::from_tlv(&tlv), Ok(None)));
+    }
 }
@@ -132,6 +132,26 @@ impl Header {
             message_length: u16::from_be_bytes(buffer[2..4].try_into().unwrap()),
         })
     }
+
+    /// Add `correction` to the correction field of an already serialized
+    /// message in `buffer`, leaving all other bytes untouched.
+    pub(crate) fn add_to_serialized_correction_field(
+        buffer: &mut [u8],
+        correction: TimeInterval,
+    ) -> Result<(), WireFormatError> {
+        let field = buffer
+            .get_mut(8..16)
+            .ok_or(WireFormatError::BufferTooShort)?;
+        let current = TimeInterval::deserialize(field)?;
+        TimeInterval(current.0 + correction.0).serialize(field)
+    }
+
+    /// Set the two step flag of an already serialized message in `buffer`
+    pub(crate) fn set_serialized_two_step_flag(buffer: &mut [u8]) -> Result<(), WireFormatError> {
+        let flags = buffer.get_mut(6).ok_or(WireFormatError::BufferTooShort)?;
+        *flags |= 1 << 1;
+        Ok(())
+    }
 }
 
 impl Default for Header {
@@ -432,4 +452,40 @@ mod tests {
             "SdoId not in range of 0..=0xFFF: 4096",
         );
     }
+
+    #[test]
+    fn patch_serialized_header() {
+        let header = Header {
+            correction_field: TimeInterval(I48F16::from_num(1.5)),
+            sequence_id: 0x1234,
+            ..Default::default()
+        };
+        let mut buffer = [0; 34];
+        header
+            .serialize_header(MessageType::Sync, 0, &mut buffer)
+            .unwrap();
+
+        Header::add_to_serialized_correction_field(
+            &mut buffer,
+            TimeInterval(I48F16::from_num(2.25)),
+        )
+        .unwrap();
+        Header::set_serialized_two_step_flag(&mut buffer).unwrap();
+
+        let deserialized = Header::deserialize_header(&buffer).unwrap().header;
+        assert_eq!(
+            deserialized,
+            Header {
+                correction_field: TimeInterval(I48F16::from_num(3.75)),
+                two_step_flag: true,
+                ..header
+            }
+        );
+
+        assert!(Header::add_to_serialized_correction_field(
+            &mut buffer[..10],
+            TimeInterval::default()
+        )
+        .is_err());
+    }
 }
@@ -1,11 +1,21 @@
-use crate::datastructures::{common::PortIdentity, WireFormat, WireFormatError};
+use crate::datastructures::{
+    common::{
+        ClockAccuracy, ClockIdentity, ClockQuality, PortIdentity, TimeInterval, TimeSource, Tlv,
+        TlvType, WireTimestamp,
+    },
+    WireFormat, WireFormatError,
+};
+use crate::{
+    observability::performance::{PerformanceRecord, PerformanceStatistics},
+    time::{Duration, Time},
+};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct ManagementMessage {
-    pub(super) target_port_identity: PortIdentity,
-    pub(super) starting_boundary_hops: u8,
-    pub(super) boundary_hops: u8,
-    pub(super) action: ManagementAction,
+    pub(crate) target_port_identity: PortIdentity,
+    pub(crate) starting_boundary_hops: u8,
+    pub(crate) boundary_hops: u8,
+    pub(crate) action: ManagementAction,
 }
 
 impl ManagementMessage {
@@ -17,10 +27,15 @@ impl ManagementMessage {
         &self,
         buffer: &mut [u8],
     ) -> Result<(), crate::datastructures::WireFormatError> {
+        if buffer.len() < 14 {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
         self.target_port_identity.serialize(&mut buffer[0..10])?;
-        buffer[11] = self.starting_boundary_hops;
-        buffer[12] = self.boundary_hops;
-        buffer[13] = self.action.to_primitive();
+        buffer[10] = self.starting_boundary_hops;
+        buffer[11] = self.boundary_hops;
+        buffer[12] = self.action.to_primitive();
+        buffer[13] = 0;
 
         Ok(())
     }
@@ -33,26 +48,34 @@ impl ManagementMessage {
         }
         Ok(Self {
             target_port_identity: PortIdentity::deserialize(&buffer[0..10])?,
-            starting_boundary_hops: buffer[11],
-            boundary_hops: buffer[12],
-            action: ManagementAction::from_primitive(buffer[13]),
+            starting_boundary_hops: buffer[10],
+            boundary_hops: buffer[11],
+            action: ManagementAction::from_primitive(buffer[12] & 0x0f),
         })
     }
 }
 
-/// See: 15.4.1.6
+/// The actionField of a management message, see *IEEE1588-2019 section
+/// 15.4.1.6*
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
-pub(crate) enum ManagementAction {
+pub enum ManagementAction {
+    /// A reserved action value
     Reserved,
+    /// Request the value of the addressed management id
     GET,
+    /// Update the value of the addressed management id
     SET,
+    /// The answer to a GET or SET
     RESPONSE,
+    /// Initiate the event of the addressed management id
     COMMAND,
+    /// The answer to a COMMAND
     ACKNOWLEDGE,
 }
 
 impl ManagementAction {
+    /// The wire representation of this action
     pub fn to_primitive(self) -> u8 {
         match self {
             Self::GET => 0x0,
@@ -64,6 +87,7 @@ impl ManagementAction {
         }
     }
 
+    /// Parse an action from its wire representation
     pub fn from_primitive(value: u8) -> Self {
         match value {
             0x0 => Self::GET,
@@ -75,3 +99,1104 @@ impl ManagementAction {
         }
     }
 }
+
+/// The managementId of a management TLV, see *IEEE1588-2019 table 59*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagementId {
+    /// NULL_PTP_MANAGEMENT
+    NullPtpManagement,
+    /// CLOCK_DESCRIPTION
+    ClockDescription,
+    /// USER_DESCRIPTION
+    UserDescription,
+    /// SAVE_IN_NON_VOLATILE_STORAGE
+    SaveInNonVolatileStorage,
+    /// RESET_NON_VOLATILE_STORAGE
+    ResetNonVolatileStorage,
+    /// INITIALIZE
+    Initialize,
+    /// FAULT_LOG
+    FaultLog,
+    /// FAULT_LOG_RESET
+    FaultLogReset,
+    /// DEFAULT_DATA_SET
+    DefaultDataSet,
+    /// CURRENT_DATA_SET
+    CurrentDataSet,
+    /// PARENT_DATA_SET
+    ParentDataSet,
+    /// TIME_PROPERTIES_DATA_SET
+    TimePropertiesDataSet,
+    /// PORT_DATA_SET
+    PortDataSet,
+    /// PRIORITY1
+    Priority1,
+    /// PRIORITY2
+    Priority2,
+    /// DOMAIN
+    Domain,
+    /// SLAVE_ONLY
+    SlaveOnly,
+    /// LOG_ANNOUNCE_INTERVAL
+    LogAnnounceInterval,
+    /// ANNOUNCE_RECEIPT_TIMEOUT
+    AnnounceReceiptTimeout,
+    /// LOG_SYNC_INTERVAL
+    LogSyncInterval,
+    /// VERSION_NUMBER
+    VersionNumber,
+    /// ENABLE_PORT
+    EnablePort,
+    /// DISABLE_PORT
+    DisablePort,
+    /// TIME
+    Time,
+    /// CLOCK_ACCURACY
+    ClockAccuracy,
+    /// UTC_PROPERTIES
+    UtcProperties,
+    /// TRACEABILITY_PROPERTIES
+    TraceabilityProperties,
+    /// TIMESCALE_PROPERTIES
+    TimescaleProperties,
+    /// UNICAST_NEGOTIATION_ENABLE
+    UnicastNegotiationEnable,
+    /// PATH_TRACE_LIST
+    PathTraceList,
+    /// PATH_TRACE_ENABLE
+    PathTraceEnable,
+    /// GRANDMASTER_CLUSTER_TABLE
+    GrandmasterClusterTable,
+    /// UNICAST_MASTER_TABLE
+    UnicastMasterTable,
+    /// UNICAST_MASTER_MAX_TABLE_SIZE
+    UnicastMasterMaxTableSize,
+    /// ACCEPTABLE_MASTER_TABLE
+    AcceptableMasterTable,
+    /// ACCEPTABLE_MASTER_TABLE_ENABLED
+    AcceptableMasterTableEnabled,
+    /// ACCEPTABLE_MASTER_MAX_TABLE_SIZE
+    AcceptableMasterMaxTableSize,
+    /// ALTERNATE_MASTER
+    AlternateMaster,
+    /// ALTERNATE_TIME_OFFSET_ENABLE
+    AlternateTimeOffsetEnable,
+    /// ALTERNATE_TIME_OFFSET_NAME
+    AlternateTimeOffsetName,
+    /// ALTERNATE_TIME_OFFSET_MAX_KEY
+    AlternateTimeOffsetMaxKey,
+    /// ALTERNATE_TIME_OFFSET_PROPERTIES
+    AlternateTimeOffsetProperties,
+    /// TRANSPARENT_CLOCK_DEFAULT_DATA_SET
+    TransparentClockDefaultDataSet,
+    /// TRANSPARENT_CLOCK_PORT_DATA_SET
+    TransparentClockPortDataSet,
+    /// PRIMARY_DOMAIN
+    PrimaryDomain,
+    /// DELAY_MECHANISM
+    DelayMechanism,
+    /// LOG_MIN_PDELAY_REQ_INTERVAL
+    LogMinPdelayReqInterval,
+    /// PERFORMANCE_MONITORING_RECORD, an implementation specific management
+    /// id for the performance monitoring records of *IEEE1588-2019 annex J*
+    PerformanceMonitoringRecord,
+    /// A reserved or implementation specific management id
+    Reserved(u16),
+}
+
+impl ManagementId {
+    /// The wire representation of this management id
+    pub fn to_primitive(self) -> u16 {
+        match self {
+            Self::NullPtpManagement => 0x0000,
+            Self::ClockDescription => 0x0001,
+            Self::UserDescription => 0x0002,
+            Self::SaveInNonVolatileStorage => 0x0003,
+            Self::ResetNonVolatileStorage => 0x0004,
+            Self::Initialize => 0x0005,
+            Self::FaultLog => 0x0006,
+            Self::FaultLogReset => 0x0007,
+            Self::DefaultDataSet => 0x2000,
+            Self::CurrentDataSet => 0x2001,
+            Self::ParentDataSet => 0x2002,
+            Self::TimePropertiesDataSet => 0x2003,
+            Self::PortDataSet => 0x2004,
+            Self::Priority1 => 0x2005,
+            Self::Priority2 => 0x2006,
+            Self::Domain => 0x2007,
+            Self::SlaveOnly => 0x2008,
+            Self::LogAnnounceInterval => 0x2009,
+            Self::AnnounceReceiptTimeout => 0x200a,
+            Self::LogSyncInterval => 0x200b,
+            Self::VersionNumber => 0x200c,
+            Self::EnablePort => 0x200d,
+            Self::DisablePort => 0x200e,
+            Self::Time => 0x200f,
+            Self::ClockAccuracy => 0x2010,
+            Self::UtcProperties => 0x2011,
+            Self::TraceabilityProperties => 0x2012,
+            Self::TimescaleProperties => 0x2013,
+            Self::UnicastNegotiationEnable => 0x2014,
+            Self::PathTraceList => 0x2015,
+            Self::PathTraceEnable => 0x2016,
+            Self::GrandmasterClusterTable => 0x2017,
+            Self::UnicastMasterTable => 0x2018,
+            Self::UnicastMasterMaxTableSize => 0x2019,
+            Self::AcceptableMasterTable => 0x201a,
+            Self::AcceptableMasterTableEnabled => 0x201b,
+            Self::AcceptableMasterMaxTableSize => 0x201c,
+            Self::AlternateMaster => 0x201d,
+            Self::AlternateTimeOffsetEnable => 0x201e,
+            Self::AlternateTimeOffsetName => 0x201f,
+            Self::AlternateTimeOffsetMaxKey => 0x2020,
+            Self::AlternateTimeOffsetProperties => 0x2021,
+            Self::TransparentClockDefaultDataSet => 0x4000,
+            Self::TransparentClockPortDataSet => 0x4001,
+            Self::PrimaryDomain => 0x4002,
+            Self::DelayMechanism => 0x6000,
+            Self::LogMinPdelayReqInterval => 0x6001,
+            Self::PerformanceMonitoringRecord => 0xc000,
+            Self::Reserved(value) => value,
+        }
+    }
+
+    /// Parse a management id from its wire representation
+    pub fn from_primitive(value: u16) -> Self {
+        match value {
+            0x0000 => Self::NullPtpManagement,
+            0x0001 => Self::ClockDescription,
+            0x0002 => Self::UserDescription,
+            0x0003 => Self::SaveInNonVolatileStorage,
+            0x0004 => Self::ResetNonVolatileStorage,
+            0x0005 => Self::Initialize,
+            0x0006 => Self::FaultLog,
+            0x0007 => Self::FaultLogReset,
+            0x2000 => Self::DefaultDataSet,
+            0x2001 => Self::CurrentDataSet,
+            0x2002 => Self::ParentDataSet,
+            0x2003 => Self::TimePropertiesDataSet,
+            0x2004 => Self::PortDataSet,
+            0x2005 => Self::Priority1,
+            0x2006 => Self::Priority2,
+            0x2007 => Self::Domain,
+            0x2008 => Self::SlaveOnly,
+            0x2009 => Self::LogAnnounceInterval,
+            0x200a => Self::AnnounceReceiptTimeout,
+            0x200b => Self::LogSyncInterval,
+            0x200c => Self::VersionNumber,
+            0x200d => Self::EnablePort,
+            0x200e => Self::DisablePort,
+            0x200f => Self::Time,
+            0x2010 => Self::ClockAccuracy,
+            0x2011 => Self::UtcProperties,
+            0x2012 => Self::TraceabilityProperties,
+            0x2013 => Self::TimescaleProperties,
+            0x2014 => Self::UnicastNegotiationEnable,
+            0x2015 => Self::PathTraceList,
+            0x2016 => Self::PathTraceEnable,
+            0x2017 => Self::GrandmasterClusterTable,
+            0x2018 => Self::UnicastMasterTable,
+            0x2019 => Self::UnicastMasterMaxTableSize,
+            0x201a => Self::AcceptableMasterTable,
+            0x201b => Self::AcceptableMasterTableEnabled,
+            0x201c => Self::AcceptableMasterMaxTableSize,
+            0x201d => Self::AlternateMaster,
+            0x201e => Self::AlternateTimeOffsetEnable,
+            0x201f => Self::AlternateTimeOffsetName,
+            0x2020 => Self::AlternateTimeOffsetMaxKey,
+            0x2021 => Self::AlternateTimeOffsetProperties,
+            0x4000 => Self::TransparentClockDefaultDataSet,
+            0x4001 => Self::TransparentClockPortDataSet,
+            0x4002 => Self::PrimaryDomain,
+            0x6000 => Self::DelayMechanism,
+            0x6001 => Self::LogMinPdelayReqInterval,
+            0xc000 => Self::PerformanceMonitoringRecord,
+            _ => Self::Reserved(value),
+        }
+    }
+}
+
+/// The managementErrorId of a MANAGEMENT_ERROR_STATUS TLV, see
+/// *IEEE1588-2019 table 109*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagementErrorId {
+    /// The response is too big to fit in a message
+    ResponseTooBig,
+    /// The management id is not recognized
+    NoSuchId,
+    /// The dataField has the wrong length
+    WrongLength,
+    /// One or more values in the dataField are out of range
+    WrongValue,
+    /// Some of the values could not be set
+    NotSetable,
+    /// The management id is not supported by the instance
+    NotSupported,
+    /// The target table is not populated
+    Unpopulated,
+    /// An error not covered by the other ids occurred
+    GeneralError,
+    /// A reserved or implementation specific error id
+    Reserved(u16),
+}
+
+impl ManagementErrorId {
+    /// The wire representation of this error id
+    pub fn to_primitive(self) -> u16 {
+        match self {
+            Self::ResponseTooBig => 0x0001,
+            Self::NoSuchId => 0x0002,
+            Self::WrongLength => 0x0003,
+            Self::WrongValue => 0x0004,
+            Self::NotSetable => 0x0005,
+            Self::NotSupported => 0x0006,
+            Self::Unpopulated => 0x0007,
+            Self::GeneralError => 0xfffe,
+            Self::Reserved(value) => value,
+        }
+    }
+
+    /// Parse an error id from its wire representation
+    pub fn from_primitive(value: u16) -> Self {
+        match value {
+            0x0001 => Self::ResponseTooBig,
+            0x0002 => Self::NoSuchId,
+            0x0003 => Self::WrongLength,
+            0x0004 => Self::WrongValue,
+            0x0005 => Self::NotSetable,
+            0x0006 => Self::NotSupported,
+            0x0007 => Self::Unpopulated,
+            0xfffe => Self::GeneralError,
+            _ => Self::Reserved(value),
+        }
+    }
+}
+
+/// The MANAGEMENT TLV carried by a management message, see 15.5.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ManagementTlv<'a> {
+    pub(crate) management_id: ManagementId,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> ManagementTlv<'a> {
+    pub(crate) fn from_tlv(tlv: &'a Tlv<'_>) -> Result<Self, WireFormatError> {
+        if tlv.tlv_type != TlvType::Management {
+            return Err(WireFormatError::Invalid);
+        }
+
+        let value: &[u8] = tlv.value.as_ref();
+        if value.len() < 2 {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        Ok(Self {
+            management_id: ManagementId::from_primitive(u16::from_be_bytes([value[0], value[1]])),
+            data: &value[2..],
+        })
+    }
+}
+
+/// The value of a MANAGEMENT_ERROR_STATUS TLV, see *IEEE1588-2019 section
+/// 15.5.4.4*
+///
+/// We never send display data, but accept it in received messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManagementErrorStatus {
+    /// The reason the request failed
+    pub management_error_id: ManagementErrorId,
+    /// The management id of the failed request
+    pub management_id: ManagementId,
+}
+
+impl ManagementErrorStatus {
+    pub(crate) fn wire_size(&self) -> usize {
+        // An empty display data PTPText plus a pad byte
+        10
+    }
+
+    pub(crate) fn serialize(&self, buffer: &mut [u8]) -> Result<usize, WireFormatError> {
+        let buffer = buffer
+            .get_mut(..self.wire_size())
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer[0..2].copy_from_slice(&self.management_error_id.to_primitive().to_be_bytes());
+        buffer[2..4].copy_from_slice(&self.management_id.to_primitive().to_be_bytes());
+        buffer[4..].fill(0);
+
+        Ok(self.wire_size())
+    }
+
+    pub(crate) fn deserialize(buffer: &[u8]) -> Result<Self, WireFormatError> {
+        if buffer.len() < 8 {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        Ok(Self {
+            management_error_id: ManagementErrorId::from_primitive(u16::from_be_bytes([
+                buffer[0], buffer[1],
+            ])),
+            management_id: ManagementId::from_primitive(u16::from_be_bytes([buffer[2], buffer[3]])),
+        })
+    }
+}
+
+/// The dataField of the management TLVs supported by statime, see
+/// *IEEE1588-2019 section 15.5.3*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)] // We can't box the performance record without alloc
+pub enum ManagementData {
+    /// A management TLV without a dataField, as used for GET requests,
+    /// commands and acknowledgements
+    Empty(ManagementId),
+    /// DEFAULT_DATA_SET, see *IEEE1588-2019 section 15.5.3.3.1*
+    DefaultDataSet {
+        /// See *IEEE1588-2019 section 8.2.1.2.1*.
+        two_step: bool,
+        /// See *IEEE1588-2019 section 8.2.1.4.4*.
+        slave_only: bool,
+        /// See *IEEE1588-2019 section 8.2.1.2.3*.
+        number_ports: u16,
+        /// See *IEEE1588-2019 section 8.2.1.4.1*.
+        priority_1: u8,
+        /// See *IEEE1588-2019 section 8.2.1.3.1*.
+        clock_quality: ClockQuality,
+        /// See *IEEE1588-2019 section 8.2.1.4.2*.
+        priority_2: u8,
+        /// See *IEEE1588-2019 section 8.2.1.2.2*.
+        clock_identity: ClockIdentity,
+        /// See *IEEE1588-2019 section 8.2.1.4.3*.
+        domain_number: u8,
+    },
+    /// CURRENT_DATA_SET, see *IEEE1588-2019 section 15.5.3.4.1*
+    CurrentDataSet {
+        /// See *IEEE1588-2019 section 8.2.2.2*.
+        steps_removed: u16,
+        /// See *IEEE1588-2019 section 8.2.2.3*.
+        offset_from_master: Duration,
+        /// See *IEEE1588-2019 section 8.2.2.4*.
+        mean_path_delay: Duration,
+    },
+    /// PARENT_DATA_SET, see *IEEE1588-2019 section 15.5.3.5.1*
+    ParentDataSet {
+        /// See *IEEE1588-2019 section 8.2.3.2*.
+        parent_port_identity: PortIdentity,
+        /// See *IEEE1588-2019 section 8.2.3.3*.
+        parent_stats: bool,
+        /// See *IEEE1588-2019 section 8.2.3.4*.
+        observed_parent_offset_scaled_log_variance: u16,
+        /// See *IEEE1588-2019 section 8.2.3.5*.
+        observed_parent_clock_phase_change_rate: i32,
+        /// See *IEEE1588-2019 section 8.2.3.8*.
+        grandmaster_priority_1: u8,
+        /// See *IEEE1588-2019 section 8.2.3.7*.
+        grandmaster_clock_quality: ClockQuality,
+        /// See *IEEE1588-2019 section 8.2.3.9*.
+        grandmaster_priority_2: u8,
+        /// See *IEEE1588-2019 section 8.2.3.6*.
+        grandmaster_identity: ClockIdentity,
+    },
+    /// TIME_PROPERTIES_DATA_SET, see *IEEE1588-2019 section 15.5.3.6.1*
+    TimePropertiesDataSet {
+        /// See *IEEE1588-2019 section 8.2.4.2*.
+        current_utc_offset: i16,
+        /// See *IEEE1588-2019 section 8.2.4.4*.
+        leap61: bool,
+        /// See *IEEE1588-2019 section 8.2.4.5*.
+        leap59: bool,
+        /// See *IEEE1588-2019 section 8.2.4.3*.
+        current_utc_offset_valid: bool,
+        /// See *IEEE1588-2019 section 8.2.4.8*.
+        ptp_timescale: bool,
+        /// See *IEEE1588-2019 section 8.2.4.6*.
+        time_traceable: bool,
+        /// See *IEEE1588-2019 section 8.2.4.7*.
+        frequency_traceable: bool,
+        /// See *IEEE1588-2019 section 8.2.4.9*.
+        time_source: TimeSource,
+    },
+    /// PORT_DATA_SET, see *IEEE1588-2019 section 15.5.3.7.1*
+    PortDataSet {
+        /// See *IEEE1588-2019 section 8.2.15.2.1*.
+        port_identity: PortIdentity,
+        /// The port state as encoded in *IEEE1588-2019 table 20*.
+        port_state: u8,
+        /// See *IEEE1588-2019 section 8.2.15.3.2*.
+        log_min_delay_req_interval: i8,
+        /// See *IEEE1588-2019 section 8.2.15.3.3*.
+        peer_mean_path_delay: Duration,
+        /// See *IEEE1588-2019 section 8.2.15.4.1*.
+        log_announce_interval: i8,
+        /// See *IEEE1588-2019 section 8.2.15.4.2*.
+        announce_receipt_timeout: u8,
+        /// See *IEEE1588-2019 section 8.2.15.4.3*.
+        log_sync_interval: i8,
+        /// The delay mechanism as encoded in *IEEE1588-2019 table 21*.
+        delay_mechanism: u8,
+        /// See *IEEE1588-2019 section 8.2.15.3.4*.
+        log_min_pdelay_req_interval: i8,
+        /// See *IEEE1588-2019 section 8.2.15.4.5*.
+        version_number: u8,
+        /// See *IEEE1588-2019 section 8.2.15.4.6*.
+        minor_version_number: u8,
+    },
+    /// PRIORITY1, see *IEEE1588-2019 section 15.5.3.3.2*
+    Priority1(u8),
+    /// PRIORITY2, see *IEEE1588-2019 section 15.5.3.3.3*
+    Priority2(u8),
+    /// DOMAIN, see *IEEE1588-2019 section 15.5.3.3.4*
+    Domain(u8),
+    /// SLAVE_ONLY, see *IEEE1588-2019 section 15.5.3.3.5*
+    SlaveOnly(bool),
+    /// LOG_ANNOUNCE_INTERVAL, see *IEEE1588-2019 section 15.5.3.7.2*
+    LogAnnounceInterval(i8),
+    /// ANNOUNCE_RECEIPT_TIMEOUT, see *IEEE1588-2019 section 15.5.3.7.3*
+    AnnounceReceiptTimeout(u8),
+    /// LOG_SYNC_INTERVAL, see *IEEE1588-2019 section 15.5.3.7.4*
+    LogSyncInterval(i8),
+    /// VERSION_NUMBER, see *IEEE1588-2019 section 15.5.3.7.5*
+    VersionNumber {
+        /// See *IEEE1588-2019 section 8.2.15.4.5*.
+        version_number: u8,
+        /// See *IEEE1588-2019 section 8.2.15.4.6*.
+        minor_version_number: u8,
+    },
+    /// CLOCK_ACCURACY, see *IEEE1588-2019 section 15.5.3.3.6*
+    ClockAccuracy(ClockAccuracy),
+    /// UTC_PROPERTIES, see *IEEE1588-2019 section 15.5.3.6.2*
+    UtcProperties {
+        /// See *IEEE1588-2019 section 8.2.4.2*.
+        current_utc_offset: i16,
+        /// See *IEEE1588-2019 section 8.2.4.4*.
+        leap61: bool,
+        /// See *IEEE1588-2019 section 8.2.4.5*.
+        leap59: bool,
+        /// See *IEEE1588-2019 section 8.2.4.3*.
+        current_utc_offset_valid: bool,
+    },
+    /// TRACEABILITY_PROPERTIES, see *IEEE1588-2019 section 15.5.3.6.3*
+    TraceabilityProperties {
+        /// See *IEEE1588-2019 section 8.2.4.6*.
+        time_traceable: bool,
+        /// See *IEEE1588-2019 section 8.2.4.7*.
+        frequency_traceable: bool,
+    },
+    /// TIMESCALE_PROPERTIES, see *IEEE1588-2019 section 15.5.3.6.4*
+    TimescaleProperties {
+        /// See *IEEE1588-2019 section 8.2.4.8*.
+        ptp_timescale: bool,
+        /// See *IEEE1588-2019 section 8.2.4.9*.
+        time_source: TimeSource,
+    },
+    /// DELAY_MECHANISM as encoded in *IEEE1588-2019 table 21*, see
+    /// *IEEE1588-2019 section 15.5.3.7.18*
+    DelayMechanism(u8),
+    /// LOG_MIN_PDELAY_REQ_INTERVAL, see *IEEE1588-2019 section 15.5.3.7.19*
+    LogMinPdelayReqInterval(i8),
+    /// The dataField of a GET request for PERFORMANCE_MONITORING_RECORD: the
+    /// index of the requested record. A GET request without dataField asks
+    /// for record 0.
+    PerformanceMonitoringRecordIndex(u16),
+    /// PERFORMANCE_MONITORING_RECORD, see
+    /// [`PERFORMANCE_RECORDS`](crate::observability::performance::PERFORMANCE_RECORDS)
+    /// for the numbering of the records
+    PerformanceMonitoringRecord {
+        /// The index of the record
+        index: u16,
+        /// The statistics of the record
+        record: PerformanceRecord,
+    },
+}
+
+/// Size of [`PerformanceStatistics`] on the wire: the number of samples,
+/// followed by the minimum, maximum, mean and standard deviation as
+/// TimeIntervals
+const STATISTICS_SIZE: usize = 36;
+
+fn serialize_statistics(
+    statistics: Option<PerformanceStatistics>,
+    buffer: &mut [u8],
+) -> Result<(), WireFormatError> {
+    // Parameters without measurements are left zero
+    let Some(statistics) = statistics else {
+        return Ok(());
+    };
+
+    buffer[0..4].copy_from_slice(&statistics.samples.to_be_bytes());
+    let values = [
+        statistics.min,
+        statistics.max,
+        statistics.mean,
+        statistics.std_dev,
+    ];
+    for (value, field) in values.into_iter().zip(buffer[4..].chunks_exact_mut(8)) {
+        TimeInterval::from(Duration::from_fixed_nanos(value)).serialize(field)?;
+    }
+    Ok(())
+}
+
+fn deserialize_statistics(buffer: &[u8]) -> Result<Option<PerformanceStatistics>, WireFormatError> {
+    let samples = u32::from_be_bytes(buffer[0..4].try_into().unwrap());
+    if samples == 0 {
+        return Ok(None);
+    }
+
+    let value = |offset: usize| -> Result<i128, WireFormatError> {
+        let interval = TimeInterval::deserialize(&buffer[offset..offset + 8])?;
+        Ok(Duration::from(interval).nanos_rounded())
+    };
+    Ok(Some(PerformanceStatistics {
+        samples,
+        min: value(4)?,
+        max: value(12)?,
+        mean: value(20)?,
+        std_dev: value(28)?,
+    }))
+}
+
+impl ManagementData {
+    /// The management id of the TLV this data belongs in
+    pub fn management_id(&self) -> ManagementId {
+        match self {
+            Self::Empty(management_id) => *management_id,
+            Self::DefaultDataSet { .. } => ManagementId::DefaultDataSet,
+            Self::CurrentDataSet { .. } => ManagementId::CurrentDataSet,
+            Self::ParentDataSet { .. } => ManagementId::ParentDataSet,
+            Self::TimePropertiesDataSet { .. } => ManagementId::TimePropertiesDataSet,
+            Self::PortDataSet { .. } => ManagementId::PortDataSet,
+            Self::Priority1(_) => ManagementId::Priority1,
+            Self::Priority2(_) => ManagementId::Priority2,
+            Self::Domain(_) => ManagementId::Domain,
+            Self::SlaveOnly(_) => ManagementId::SlaveOnly,
+            Self::LogAnnounceInterval(_) => ManagementId::LogAnnounceInterval,
+            Self::AnnounceReceiptTimeout(_) => ManagementId::AnnounceReceiptTimeout,
+            Self::LogSyncInterval(_) => ManagementId::LogSyncInterval,
+            Self::VersionNumber { .. } => ManagementId::VersionNumber,
+            Self::ClockAccuracy(_) => ManagementId::ClockAccuracy,
+            Self::UtcProperties { .. } => ManagementId::UtcProperties,
+            Self::TraceabilityProperties { .. } => ManagementId::TraceabilityProperties,
+            Self::TimescaleProperties { .. } => ManagementId::TimescaleProperties,
+            Self::DelayMechanism(_) => ManagementId::DelayMechanism,
+            Self::LogMinPdelayReqInterval(_) => ManagementId::LogMinPdelayReqInterval,
+            Self::PerformanceMonitoringRecordIndex(_)
+            | Self::PerformanceMonitoringRecord { .. } => ManagementId::PerformanceMonitoringRecord,
+        }
+    }
+
+    /// The size of the dataField, always an even number of octets
+    pub(crate) fn wire_size(&self) -> usize {
+        match self {
+            Self::Empty(_) => 0,
+            Self::DefaultDataSet { .. } => 20,
+            Self::CurrentDataSet { .. } => 18,
+            Self::ParentDataSet { .. } => 32,
+            Self::TimePropertiesDataSet { .. } => 4,
+            Self::PortDataSet { .. } => 26,
+            Self::UtcProperties { .. } => 4,
+            Self::PerformanceMonitoringRecord { .. } => 14 + 4 * STATISTICS_SIZE,
+            Self::Priority1(_)
+            | Self::Priority2(_)
+            | Self::Domain(_)
+            | Self::SlaveOnly(_)
+            | Self::LogAnnounceInterval(_)
+            | Self::AnnounceReceiptTimeout(_)
+            | Self::LogSyncInterval(_)
+            | Self::VersionNumber { .. }
+            | Self::ClockAccuracy(_)
+            | Self::TraceabilityProperties { .. }
+            | Self::TimescaleProperties { .. }
+            | Self::DelayMechanism(_)
+            | Self::LogMinPdelayReqInterval(_)
+            | Self::PerformanceMonitoringRecordIndex(_) => 2,
+        }
+    }
+
+    pub(crate) fn serialize(&self, buffer: &mut [u8]) -> Result<usize, WireFormatError> {
+        let buffer = buffer
+            .get_mut(..self.wire_size())
+            .ok_or(WireFormatError::BufferTooShort)?;
+        buffer.fill(0);
+
+        match *self {
+            Self::Empty(_) => {}
+            Self::DefaultDataSet {
+                two_step,
+                slave_only,
+                number_ports,
+                priority_1,
+                clock_quality,
+                priority_2,
+                clock_identity,
+                domain_number,
+            } => {
+                buffer[0] = two_step as u8 | (slave_only as u8) << 1;
+                buffer[2..4].copy_from_slice(&number_ports.to_be_bytes());
+                buffer[4] = priority_1;
+                clock_quality.serialize(&mut buffer[5..9])?;
+                buffer[9] = priority_2;
+                clock_identity.serialize(&mut buffer[10..18])?;
+                buffer[18] = domain_number;
+            }
+            Self::CurrentDataSet {
+                steps_removed,
+                offset_from_master,
+                mean_path_delay,
+            } => {
+                buffer[0..2].copy_from_slice(&steps_removed.to_be_bytes());
+                TimeInterval::from(offset_from_master).serialize(&mut buffer[2..10])?;
+                TimeInterval::from(mean_path_delay).serialize(&mut buffer[10..18])?;
+            }
+            Self::ParentDataSet {
+                parent_port_identity,
+                parent_stats,
+                observed_parent_offset_scaled_log_variance,
+                observed_parent_clock_phase_change_rate,
+                grandmaster_priority_1,
+                grandmaster_clock_quality,
+                grandmaster_priority_2,
+                grandmaster_identity,
+            } => {
+                parent_port_identity.serialize(&mut buffer[0..10])?;
+                buffer[10] = parent_stats as u8;
+                buffer[12..14]
+                    .copy_from_slice(&observed_parent_offset_scaled_log_variance.to_be_bytes());
+                buffer[14..18]
+                    .copy_from_slice(&observed_parent_clock_phase_change_rate.to_be_bytes());
+                buffer[18] = grandmaster_priority_1;
+                grandmaster_clock_quality.serialize(&mut buffer[19..23])?;
+                buffer[23] = grandmaster_priority_2;
+                grandmaster_identity.serialize(&mut buffer[24..32])?;
+            }
+            Self::TimePropertiesDataSet {
+                current_utc_offset,
+                leap61,
+                leap59,
+                current_utc_offset_valid,
+                ptp_timescale,
+                time_traceable,
+                frequency_traceable,
+                time_source,
+            } => {
+                buffer[0..2].copy_from_slice(&current_utc_offset.to_be_bytes());
+                buffer[2] = leap61 as u8
+                    | (leap59 as u8) << 1
+                    | (current_utc_offset_valid as u8) << 2
+                    | (ptp_timescale as u8) << 3
+                    | (time_traceable as u8) << 4
+                    | (frequency_traceable as u8) << 5;
+                buffer[3] = time_source.to_primitive();
+            }
+            Self::PortDataSet {
+                port_identity,
+                port_state,
+                log_min_delay_req_interval,
+                peer_mean_path_delay,
+                log_announce_interval,
+                announce_receipt_timeout,
+                log_sync_interval,
+                delay_mechanism,
+                log_min_pdelay_req_interval,
+                version_number,
+                minor_version_number,
+            } => {
+                port_identity.serialize(&mut buffer[0..10])?;
+                buffer[10] = port_state;
+                buffer[11] = log_min_delay_req_interval as u8;
+                TimeInterval::from(peer_mean_path_delay).serialize(&mut buffer[12..20])?;
+                buffer[20] = log_announce_interval as u8;
+                buffer[21] = announce_receipt_timeout;
+                buffer[22] = log_sync_interval as u8;
+                buffer[23] = delay_mechanism;
+                buffer[24] = log_min_pdelay_req_interval as u8;
+                buffer[25] = (minor_version_number << 4) | (version_number & 0x0f);
+            }
+            Self::Priority1(value)
+            | Self::Priority2(value)
+            | Self::Domain(value)
+            | Self::AnnounceReceiptTimeout(value)
+            | Self::DelayMechanism(value) => buffer[0] = value,
+            Self::LogAnnounceInterval(value)
+            | Self::LogSyncInterval(value)
+            | Self::LogMinPdelayReqInterval(value) => buffer[0] = value as u8,
+            Self::SlaveOnly(slave_only) => buffer[0] = slave_only as u8,
+            Self::VersionNumber {
+                version_number,
+                minor_version_number,
+            } => buffer[0] = (minor_version_number << 4) | (version_number & 0x0f),
+            Self::ClockAccuracy(clock_accuracy) => buffer[0] = clock_accuracy.to_primitive(),
+            Self::UtcProperties {
+                current_utc_offset,
+                leap61,
+                leap59,
+                current_utc_offset_valid,
+            } => {
+                buffer[0..2].copy_from_slice(&current_utc_offset.to_be_bytes());
+                buffer[2] =
+                    leap61 as u8 | (leap59 as u8) << 1 | (current_utc_offset_valid as u8) << 2;
+            }
+            Self::TraceabilityProperties {
+                time_traceable,
+                frequency_traceable,
+            } => buffer[0] = (time_traceable as u8) << 4 | (frequency_traceable as u8) << 5,
+            Self::TimescaleProperties {
+                ptp_timescale,
+                time_source,
+            } => {
+                buffer[0] = (ptp_timescale as u8) << 3;
+                buffer[1] = time_source.to_primitive();
+            }
+            Self::PerformanceMonitoringRecordIndex(index) => {
+                buffer[0..2].copy_from_slice(&index.to_be_bytes())
+            }
+            Self::PerformanceMonitoringRecord { index, record } => {
+                buffer[0..2].copy_from_slice(&index.to_be_bytes());
+                buffer[2] = record.complete as u8;
+                WireTimestamp::from(Time::from_secs(record.start)).serialize(&mut buffer[4..14])?;
+                let parameters = [
+                    record.offset_from_master,
+                    record.mean_path_delay,
+                    record.master_slave_delay,
+                    record.slave_master_delay,
+                ];
+                for (statistics, field) in parameters
+                    .into_iter()
+                    .zip(buffer[14..].chunks_exact_mut(STATISTICS_SIZE))
+                {
+                    serialize_statistics(statistics, field)?;
+                }
+            }
+        }
+
+        Ok(self.wire_size())
+    }
+
+    /// Parse the dataField of a management TLV with the given id
+    ///
+    /// Returns `Ok(None)` for ids we don't know the format of.
+    pub(crate) fn deserialize(
+        management_id: ManagementId,
+        buffer: &[u8],
+    ) -> Result<Option<Self>, WireFormatError> {
+        let expect = |size: usize| -> Result<&[u8], WireFormatError> {
+            buffer.get(..size).ok_or(WireFormatError::BufferTooShort)
+        };
+
+        let data = match management_id {
+            ManagementId::NullPtpManagement
+            | ManagementId::EnablePort
+            | ManagementId::DisablePort => Self::Empty(management_id),
+            ManagementId::DefaultDataSet => {
+                let buffer = expect(20)?;
+                Self::DefaultDataSet {
+                    two_step: buffer[0] & (1 << 0) != 0,
+                    slave_only: buffer[0] & (1 << 1) != 0,
+                    number_ports: u16::from_be_bytes([buffer[2], buffer[3]]),
+                    priority_1: buffer[4],
+                    clock_quality: ClockQuality::deserialize(&buffer[5..9])?,
+                    priority_2: buffer[9],
+                    clock_identity: ClockIdentity::deserialize(&buffer[10..18])?,
+                    domain_number: buffer[18],
+                }
+            }
+            ManagementId::CurrentDataSet => {
+                let buffer = expect(18)?;
+                Self::CurrentDataSet {
+                    steps_removed: u16::from_be_bytes([buffer[0], buffer[1]]),
+                    offset_from_master: TimeInterval::deserialize(&buffer[2..10])?.into(),
+                    mean_path_delay: TimeInterval::deserialize(&buffer[10..18])?.into(),
+                }
+            }
+            ManagementId::ParentDataSet => {
+                let buffer = expect(32)?;
+                Self::ParentDataSet {
+                    parent_port_identity: PortIdentity::deserialize(&buffer[0..10])?,
+                    parent_stats: buffer[10] & (1 << 0) != 0,
+                    observed_parent_offset_scaled_log_variance: u16::from_be_bytes([
+                        buffer[12], buffer[13],
+                    ]),
+                    observed_parent_clock_phase_change_rate: i32::from_be_bytes(
+                        buffer[14..18].try_into().unwrap(),
+                    ),
+                    grandmaster_priority_1: buffer[18],
+                    grandmaster_clock_quality: ClockQuality::deserialize(&buffer[19..23])?,
+                    grandmaster_priority_2: buffer[23],
+                    grandmaster_identity: ClockIdentity::deserialize(&buffer[24..32])?,
+                }
+            }
+            ManagementId::TimePropertiesDataSet => {
+                let buffer = expect(4)?;
+                Self::TimePropertiesDataSet {
+                    current_utc_offset: i16::from_be_bytes([buffer[0], buffer[1]]),
+                    leap61: buffer[2] & (1 << 0) != 0,
+                    leap59: buffer[2] & (1 << 1) != 0,
+                    current_utc_offset_valid: buffer[2] & (1 << 2) != 0,
+                    ptp_timescale: buffer[2] & (1 << 3) != 0,
+                    time_traceable: buffer[2] & (1 << 4) != 0,
+                    frequency_traceable: buffer[2] & (1 << 5) != 0,
+                    time_source: TimeSource::from_primitive(buffer[3]),
+                }
+            }
+            ManagementId::PortDataSet => {
+                let buffer = expect(26)?;
+                Self::PortDataSet {
+                    port_identity: PortIdentity::deserialize(&buffer[0..10])?,
+                    port_state: buffer[10],
+                    log_min_delay_req_interval: buffer[11] as i8,
+                    peer_mean_path_delay: TimeInterval::deserialize(&buffer[12..20])?.into(),
+                    log_announce_interval: buffer[20] as i8,
+                    announce_receipt_timeout: buffer[21],
+                    log_sync_interval: buffer[22] as i8,
+                    delay_mechanism: buffer[23],
+                    log_min_pdelay_req_interval: buffer[24] as i8,
+                    version_number: buffer[25] & 0x0f,
+                    minor_version_number: buffer[25] >> 4,
+                }
+            }
+            ManagementId::Priority1 => Self::Priority1(expect(2)?[0]),
+            ManagementId::Priority2 => Self::Priority2(expect(2)?[0]),
+            ManagementId::Domain => Self::Domain(expect(2)?[0]),
+            ManagementId::SlaveOnly => Self::SlaveOnly(expect(2)?[0] & (1 << 0) != 0),
+            ManagementId::LogAnnounceInterval => Self::LogAnnounceInterval(expect(2)?[0] as i8),
+            ManagementId::AnnounceReceiptTimeout => Self::AnnounceReceiptTimeout(expect(2)?[0]),
+            ManagementId::LogSyncInterval => Self::LogSyncInterval(expect(2)?[0] as i8),
+            ManagementId::VersionNumber => {
+                let buffer = expect(2)?;
+                Self::VersionNumber {
+                    version_number: buffer[0] & 0x0f,
+                    minor_version_number: buffer[0] >> 4,
+                }
+            }
+            ManagementId::ClockAccuracy => {
+                Self::ClockAccuracy(ClockAccuracy::from_primitive(expect(2)?[0]))
+            }
+            ManagementId::UtcProperties => {
+                let buffer = expect(4)?;
+                Self::UtcProperties {
+                    current_utc_offset: i16::from_be_bytes([buffer[0], buffer[1]]),
+                    leap61: buffer[2] & (1 << 0) != 0,
+                    leap59: buffer[2] & (1 << 1) != 0,
+                    current_utc_offset_valid: buffer[2] & (1 << 2) != 0,
+                }
+            }
+            ManagementId::TraceabilityProperties => {
+                let buffer = expect(2)?;
+                Self::TraceabilityProperties {
+                    time_traceable: buffer[0] & (1 << 4) != 0,
+                    frequency_traceable: buffer[0] & (1 << 5) != 0,
+                }
+            }
+            ManagementId::TimescaleProperties => {
+                let buffer = expect(2)?;
+                Self::TimescaleProperties {
+                    ptp_timescale: buffer[0] & (1 << 3) != 0,
+                    time_source: TimeSource::from_primitive(buffer[1]),
+                }
+            }
+            ManagementId::DelayMechanism => Self::DelayMechanism(expect(2)?[0]),
+            ManagementId::LogMinPdelayReqInterval => {
+                Self::LogMinPdelayReqInterval(expect(2)?[0] as i8)
+            }
+            // Requests only carry the index, responses the whole record
+            ManagementId::PerformanceMonitoringRecord if buffer.len() < 14 => {
+                let buffer = expect(2)?;
+                Self::PerformanceMonitoringRecordIndex(u16::from_be_bytes([buffer[0], buffer[1]]))
+            }
+            ManagementId::PerformanceMonitoringRecord => {
+                let buffer = expect(14 + 4 * STATISTICS_SIZE)?;
+                let statistics = |parameter: usize| {
+                    deserialize_statistics(&buffer[14 + parameter * STATISTICS_SIZE..])
+                };
+                Self::PerformanceMonitoringRecord {
+                    index: u16::from_be_bytes([buffer[0], buffer[1]]),
+                    record: PerformanceRecord {
+                        start: WireTimestamp::deserialize(&buffer[4..14])?.seconds,
+                        complete: buffer[2] & (1 << 0) != 0,
+                        offset_from_master: statistics(0)?,
+                        mean_path_delay: statistics(1)?,
+                        master_slave_delay: statistics(2)?,
+                        slave_master_delay: statistics(3)?,
+                    },
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn management_message_wireformat() {
+        let representations = [(
+            [
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xff, 0xff, 0x03, 0x01, 0x01, 0x00,
+            ],
+            ManagementMessage {
+                target_port_identity: PortIdentity {
+                    clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+                    port_number: 0xffff,
+                },
+                starting_boundary_hops: 3,
+                boundary_hops: 1,
+                action: ManagementAction::SET,
+            },
+        )];
+
+        for (byte_representation, object_representation) in representations {
+            let mut serialization_buffer = [0; 14];
+            object_representation
+                .serialize_content(&mut serialization_buffer)
+                .unwrap();
+            assert_eq!(serialization_buffer, byte_representation);
+
+            let deserialized_data =
+                ManagementMessage::deserialize_content(&byte_representation).unwrap();
+            assert_eq!(deserialized_data, object_representation);
+        }
+    }
+
+    #[test]
+    fn management_data_roundtrip() {
+        let representations = [
+            ManagementData::Empty(ManagementId::NullPtpManagement),
+            ManagementData::DefaultDataSet {
+                two_step: true,
+                slave_only: false,
+                number_ports: 2,
+                priority_1: 128,
+                clock_quality: ClockQuality::default(),
+                priority_2: 127,
+                clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+                domain_number: 3,
+            },
+            ManagementData::CurrentDataSet {
+                steps_removed: 1,
+                offset_from_master: Duration::from_nanos(-1000),
+                mean_path_delay: Duration::from_nanos(2000),
+            },
+            ManagementData::ParentDataSet {
+                parent_port_identity: PortIdentity {
+                    clock_identity: ClockIdentity([8; 8]),
+                    port_number: 1,
+                },
+                parent_stats: false,
+                observed_parent_offset_scaled_log_variance: 0xffff,
+                observed_parent_clock_phase_change_rate: 0x7fffffff,
+                grandmaster_priority_1: 1,
+                grandmaster_clock_quality: ClockQuality::default(),
+                grandmaster_priority_2: 2,
+                grandmaster_identity: ClockIdentity([9; 8]),
+            },
+            ManagementData::TimePropertiesDataSet {
+                current_utc_offset: 37,
+                leap61: false,
+                leap59: true,
+                current_utc_offset_valid: true,
+                ptp_timescale: true,
+                time_traceable: false,
+                frequency_traceable: true,
+                time_source: TimeSource::Gnss,
+            },
+            ManagementData::PortDataSet {
+                port_identity: PortIdentity::default(),
+                port_state: 6,
+                log_min_delay_req_interval: 0,
+                peer_mean_path_delay: Duration::ZERO,
+                log_announce_interval: 1,
+                announce_receipt_timeout: 3,
+                log_sync_interval: -3,
+                delay_mechanism: 1,
+                log_min_pdelay_req_interval: 0,
+                version_number: 2,
+                minor_version_number: 1,
+            },
+            ManagementData::Priority1(100),
+            ManagementData::SlaveOnly(true),
+            ManagementData::LogSyncInterval(-2),
+            ManagementData::VersionNumber {
+                version_number: 2,
+                minor_version_number: 1,
+            },
+            ManagementData::UtcProperties {
+                current_utc_offset: 37,
+                leap61: true,
+                leap59: false,
+                current_utc_offset_valid: true,
+            },
+            ManagementData::TraceabilityProperties {
+                time_traceable: true,
+                frequency_traceable: false,
+            },
+            ManagementData::TimescaleProperties {
+                ptp_timescale: true,
+                time_source: TimeSource::InternalOscillator,
+            },
+            ManagementData::PerformanceMonitoringRecordIndex(96),
+            ManagementData::PerformanceMonitoringRecord {
+                index: 1,
+                record: PerformanceRecord {
+                    start: 1_700_000_100,
+                    complete: true,
+                    offset_from_master: Some(PerformanceStatistics {
+                        samples: 900,
+                        min: -120,
+                        max: 95,
+                        mean: -3,
+                        std_dev: 41,
+                    }),
+                    mean_path_delay: None,
+                    master_slave_delay: Some(PerformanceStatistics {
+                        samples: 900,
+                        min: 1_000_000,
+                        max: 1_000_300,
+                        mean: 1_000_150,
+                        std_dev: 60,
+                    }),
+                    slave_master_delay: None,
+                },
+            },
+        ];
+
+        for data in representations {
+            let mut buffer = [0; 160];
+            let size = data.serialize(&mut buffer).unwrap();
+            assert_eq!(size, data.wire_size());
+            assert_eq!(size % 2, 0);
+
+            let deserialized = ManagementData::deserialize(data.management_id(), &buffer[..size])
+                .unwrap()
+                .unwrap();
+            assert_eq!(deserialized, data);
+        }
+    }
+
+    #[test]
+    fn management_error_status_wireformat() {
+        let status = ManagementErrorStatus {
+            management_error_id: ManagementErrorId::NotSetable,
+            management_id: ManagementId::DefaultDataSet,
+        };
+
+        let mut buffer = [0xff; 10];
+        assert_eq!(status.serialize(&mut buffer).unwrap(), 10);
+        assert_eq!(buffer, [0x00, 0x05, 0x20, 0x00, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(ManagementErrorStatus::deserialize(&buffer).unwrap(), status);
+    }
+
+    #[test]
+    fn management_ids() {
+        for value in [
+            0x0000, 0x2000, 0x200e, 0x2021, 0x4002, 0x6000, 0x6001, 0x1234,
+        ] {
+            assert_eq!(ManagementId::from_primitive(value).to_primitive(), value);
+        }
+        assert_eq!(
+            ManagementId::from_primitive(0x1234),
+            ManagementId::Reserved(0x1234)
+        );
+    }
+}
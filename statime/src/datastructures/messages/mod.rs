@@ -5,12 +5,13 @@ pub(crate) use delay_req::*;
 pub(crate) use delay_resp::*;
 pub(crate) use follow_up::*;
 pub use header::*;
+pub use management::*;
 pub(crate) use p_delay_req::*;
 pub(crate) use p_delay_resp::*;
 pub(crate) use p_delay_resp_follow_up::*;
+pub(crate) use signalling::*;
 pub(crate) use sync::*;
 
-use self::{management::ManagementMessage, signalling::SignalingMessage};
 use super::{
     common::{PortIdentity, TimeInterval, TlvSet, WireTimestamp},
     datasets::InternalDefaultDS,
@@ -151,7 +152,7 @@ impl MessageBody {
         }
     }
 
-    fn content_type(&self) -> MessageType {
+    pub(crate) fn content_type(&self) -> MessageType {
         match self {
             MessageBody::Sync(_) => MessageType::Sync,
             MessageBody::DelayReq(_) => MessageType::DelayReq,
@@ -299,7 +300,7 @@ impl Message<'_> {
             origin_timestamp: Default::default(),
             current_utc_offset: time_properties_ds.current_utc_offset.unwrap_or_default(),
             grandmaster_priority_1: global.parent_ds.grandmaster_priority_1,
-            grandmaster_clock_quality: global.parent_ds.grandmaster_clock_quality,
+            grandmaster_clock_quality: global.announced_clock_quality(),
             grandmaster_priority_2: global.parent_ds.grandmaster_priority_2,
             grandmaster_identity: global.parent_ds.grandmaster_identity,
             steps_removed: global.current_ds.steps_removed,
@@ -418,6 +419,81 @@ impl Message<'_> {
 }
 
 impl<'a> Message<'a> {
+    pub(crate) fn management_request(
+        sdo_id: SdoId,
+        domain_number: u8,
+        port_identity: PortIdentity,
+        sequence_id: u16,
+        management: ManagementMessage,
+        suffix: TlvSet<'a>,
+    ) -> Self {
+        let header = Header {
+            sdo_id,
+            domain_number,
+            source_port_identity: port_identity,
+            sequence_id,
+            log_message_interval: 0x7f,
+            ..Default::default()
+        };
+
+        Message {
+            header,
+            body: MessageBody::Management(management),
+            suffix,
+        }
+    }
+
+    pub(crate) fn management_response(
+        default_ds: &InternalDefaultDS,
+        port_identity: PortIdentity,
+        request_header: Header,
+        request: &ManagementMessage,
+        action: ManagementAction,
+        suffix: TlvSet<'a>,
+    ) -> Self {
+        let header = Header {
+            log_message_interval: 0x7f,
+            ..base_header(default_ds, port_identity, request_header.sequence_id)
+        };
+
+        let hops_used = request
+            .starting_boundary_hops
+            .saturating_sub(request.boundary_hops);
+
+        Message {
+            header,
+            body: MessageBody::Management(ManagementMessage {
+                target_port_identity: request_header.source_port_identity,
+                starting_boundary_hops: hops_used,
+                boundary_hops: hops_used,
+                action,
+            }),
+            suffix,
+        }
+    }
+
+    pub(crate) fn signaling(
+        default_ds: &InternalDefaultDS,
+        port_identity: PortIdentity,
+        target_port_identity: PortIdentity,
+        sequence_id: u16,
+        suffix: TlvSet<'a>,
+    ) -> Self {
+        let header = Header {
+            unicast_flag: true,
+            log_message_interval: 0x7f,
+            ..base_header(default_ds, port_identity, sequence_id)
+        };
+
+        Message {
+            header,
+            body: MessageBody::Signaling(SignalingMessage {
+                target_port_identity,
+            }),
+            suffix,
+        }
+    }
+
     pub(crate) fn header(&self) -> &Header {
         &self.header
     }
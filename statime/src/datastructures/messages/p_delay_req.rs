@@ -2,7 +2,7 @@ use crate::datastructures::{common::WireTimestamp, WireFormat, WireFormatError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct PDelayReqMessage {
-    pub(super) origin_timestamp: WireTimestamp,
+    pub(crate) origin_timestamp: WireTimestamp,
 }
 
 impl PDelayReqMessage {
@@ -1,8 +1,20 @@
-use crate::datastructures::{common::PortIdentity, WireFormat, WireFormatError};
+use arrayvec::ArrayVec;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use super::MessageType;
+use crate::{
+    datastructures::{
+        common::{PortIdentity, TimeInterval, Tlv, TlvType, WireTimestamp},
+        WireFormat, WireFormatError,
+    },
+    observability::slave_event_monitoring::{
+        SyncComputedRecord, SyncTimingRecord, TxEventRecord, MAX_SLAVE_EVENT_RECORDS,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct SignalingMessage {
-    pub(super) target_port_identity: PortIdentity,
+    pub(crate) target_port_identity: PortIdentity,
 }
 
 impl SignalingMessage {
@@ -31,3 +43,551 @@ impl SignalingMessage {
         })
     }
 }
+
+/// The TLVs used for unicast message negotiation, see *IEEE1588-2019 section
+/// 16.1.4*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnicastNegotiationTlv {
+    Request {
+        message_type: MessageType,
+        log_inter_message_period: i8,
+        duration: u32,
+    },
+    Grant {
+        message_type: MessageType,
+        log_inter_message_period: i8,
+        duration: u32,
+        renewal_invited: bool,
+    },
+    Cancel {
+        message_type: MessageType,
+    },
+    AcknowledgeCancel {
+        message_type: MessageType,
+    },
+}
+
+impl UnicastNegotiationTlv {
+    pub(crate) fn tlv_type(&self) -> TlvType {
+        match self {
+            Self::Request { .. } => TlvType::RequestUnicastTransmission,
+            Self::Grant { .. } => TlvType::GrantUnicastTransmission,
+            Self::Cancel { .. } => TlvType::CancelUnicastTransmission,
+            Self::AcknowledgeCancel { .. } => TlvType::AcknowledgeCancelUnicastTransmission,
+        }
+    }
+
+    pub(crate) fn message_type(&self) -> MessageType {
+        match *self {
+            Self::Request { message_type, .. }
+            | Self::Grant { message_type, .. }
+            | Self::Cancel { message_type }
+            | Self::AcknowledgeCancel { message_type } => message_type,
+        }
+    }
+
+    pub(crate) fn value_size(&self) -> usize {
+        match self {
+            Self::Request { .. } => 6,
+            Self::Grant { .. } => 8,
+            Self::Cancel { .. } | Self::AcknowledgeCancel { .. } => 2,
+        }
+    }
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let buffer = buffer
+            .get_mut(..self.value_size())
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer.fill(0);
+        buffer[0] = (self.message_type() as u8) << 4;
+
+        match *self {
+            Self::Request {
+                log_inter_message_period,
+                duration,
+                ..
+            } => {
+                buffer[1] = log_inter_message_period as u8;
+                buffer[2..6].copy_from_slice(&duration.to_be_bytes());
+            }
+            Self::Grant {
+                log_inter_message_period,
+                duration,
+                renewal_invited,
+                ..
+            } => {
+                buffer[1] = log_inter_message_period as u8;
+                buffer[2..6].copy_from_slice(&duration.to_be_bytes());
+                buffer[7] = renewal_invited as u8;
+            }
+            Self::Cancel { .. } | Self::AcknowledgeCancel { .. } => {}
+        }
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not used in unicast
+    /// negotiation
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        let value: &[u8] = tlv.value.as_ref();
+        let message_type = || -> Result<MessageType, WireFormatError> {
+            let byte = value.first().ok_or(WireFormatError::BufferTooShort)?;
+            Ok(MessageType::try_from(byte >> 4)?)
+        };
+
+        let result = match tlv.tlv_type {
+            TlvType::RequestUnicastTransmission => {
+                if value.len() < 6 {
+                    return Err(WireFormatError::BufferTooShort);
+                }
+                Self::Request {
+                    message_type: message_type()?,
+                    log_inter_message_period: value[1] as i8,
+                    duration: u32::from_be_bytes(value[2..6].try_into().unwrap()),
+                }
+            }
+            TlvType::GrantUnicastTransmission => {
+                if value.len() < 8 {
+                    return Err(WireFormatError::BufferTooShort);
+                }
+                Self::Grant {
+                    message_type: message_type()?,
+                    log_inter_message_period: value[1] as i8,
+                    duration: u32::from_be_bytes(value[2..6].try_into().unwrap()),
+                    renewal_invited: value[7] & 1 != 0,
+                }
+            }
+            TlvType::CancelUnicastTransmission => Self::Cancel {
+                message_type: message_type()?,
+            },
+            TlvType::AcknowledgeCancelUnicastTransmission => Self::AcknowledgeCancel {
+                message_type: message_type()?,
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(result))
+    }
+}
+
+/// The TLVs used for slave event monitoring, see *IEEE1588-2019 section
+/// 16.11.4*
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SlaveEventMonitoringTlv {
+    RxSyncTimingData {
+        sync_source_port_identity: PortIdentity,
+        records: ArrayVec<SyncTimingRecord, MAX_SLAVE_EVENT_RECORDS>,
+    },
+    RxSyncComputedData {
+        source_port_identity: PortIdentity,
+        records: ArrayVec<SyncComputedRecord, MAX_SLAVE_EVENT_RECORDS>,
+    },
+    TxEventTimestamps {
+        source_port_identity: PortIdentity,
+        event_message_type: MessageType,
+        records: ArrayVec<TxEventRecord, MAX_SLAVE_EVENT_RECORDS>,
+    },
+}
+
+const SYNC_TIMING_RECORD_SIZE: usize = 34;
+const SYNC_COMPUTED_RECORD_SIZE: usize = 22;
+const TX_EVENT_RECORD_SIZE: usize = 12;
+
+const SCALED_NEIGHBOR_RATE_RATIO_VALID: u8 = 1 << 0;
+const MEAN_PATH_DELAY_VALID: u8 = 1 << 1;
+const OFFSET_FROM_MASTER_VALID: u8 = 1 << 2;
+
+fn serialize_timestamp(nanos: u128, buffer: &mut [u8]) -> Result<(), WireFormatError> {
+    WireTimestamp {
+        seconds: (nanos / 1_000_000_000) as u64,
+        nanos: (nanos % 1_000_000_000) as u32,
+    }
+    .serialize(buffer)
+}
+
+fn deserialize_timestamp(buffer: &[u8]) -> Result<u128, WireFormatError> {
+    let timestamp = WireTimestamp::deserialize(buffer)?;
+    Ok(timestamp.seconds as u128 * 1_000_000_000 + timestamp.nanos as u128)
+}
+
+fn serialize_time_interval(nanos: i128, buffer: &mut [u8]) -> Result<(), WireFormatError> {
+    TimeInterval::from(Duration::from_fixed_nanos(nanos)).serialize(buffer)
+}
+
+fn deserialize_time_interval(buffer: &[u8]) -> Result<i128, WireFormatError> {
+    Ok(Duration::from(TimeInterval::deserialize(buffer)?).nanos_rounded())
+}
+
+impl SlaveEventMonitoringTlv {
+    /// The largest value of these TLVs, a full SLAVE_RX_SYNC_TIMING_DATA TLV
+    pub(crate) const MAX_VALUE_SIZE: usize = 10 + MAX_SLAVE_EVENT_RECORDS * SYNC_TIMING_RECORD_SIZE;
+
+    pub(crate) fn tlv_type(&self) -> TlvType {
+        match self {
+            Self::RxSyncTimingData { .. } => TlvType::SlaveRxSyncTimingData,
+            Self::RxSyncComputedData { .. } => TlvType::SlaveRxSyncComputedData,
+            Self::TxEventTimestamps { .. } => TlvType::SlaveTxEventTimestamps,
+        }
+    }
+
+    pub(crate) fn value_size(&self) -> usize {
+        match self {
+            Self::RxSyncTimingData { records, .. } => 10 + records.len() * SYNC_TIMING_RECORD_SIZE,
+            Self::RxSyncComputedData { records, .. } => {
+                12 + records.len() * SYNC_COMPUTED_RECORD_SIZE
+            }
+            Self::TxEventTimestamps { records, .. } => 12 + records.len() * TX_EVENT_RECORD_SIZE,
+        }
+    }
+
+    pub(crate) fn serialize_value<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], WireFormatError> {
+        let buffer = buffer
+            .get_mut(..self.value_size())
+            .ok_or(WireFormatError::BufferTooShort)?;
+
+        buffer.fill(0);
+
+        match self {
+            Self::RxSyncTimingData {
+                sync_source_port_identity,
+                records,
+            } => {
+                sync_source_port_identity.serialize(&mut buffer[0..10])?;
+                for (record, buffer) in records
+                    .iter()
+                    .zip(buffer[10..].chunks_exact_mut(SYNC_TIMING_RECORD_SIZE))
+                {
+                    buffer[0..2].copy_from_slice(&record.sequence_id.to_be_bytes());
+                    serialize_timestamp(record.sync_origin_timestamp, &mut buffer[2..12])?;
+                    serialize_time_interval(record.total_correction, &mut buffer[12..20])?;
+                    buffer[20..24]
+                        .copy_from_slice(&record.scaled_cumulative_rate_offset.to_be_bytes());
+                    serialize_timestamp(record.sync_event_ingress_timestamp, &mut buffer[24..34])?;
+                }
+            }
+            Self::RxSyncComputedData {
+                source_port_identity,
+                records,
+            } => {
+                source_port_identity.serialize(&mut buffer[0..10])?;
+
+                // The values are only valid when they are valid in every record
+                let mut flags = SCALED_NEIGHBOR_RATE_RATIO_VALID
+                    | MEAN_PATH_DELAY_VALID
+                    | OFFSET_FROM_MASTER_VALID;
+                for record in records {
+                    if record.scaled_neighbor_rate_ratio.is_none() {
+                        flags &= !SCALED_NEIGHBOR_RATE_RATIO_VALID;
+                    }
+                    if record.mean_path_delay.is_none() {
+                        flags &= !MEAN_PATH_DELAY_VALID;
+                    }
+                    if record.offset_from_master.is_none() {
+                        flags &= !OFFSET_FROM_MASTER_VALID;
+                    }
+                }
+                buffer[10] = flags;
+
+                for (record, buffer) in records
+                    .iter()
+                    .zip(buffer[12..].chunks_exact_mut(SYNC_COMPUTED_RECORD_SIZE))
+                {
+                    buffer[0..2].copy_from_slice(&record.sequence_id.to_be_bytes());
+                    serialize_time_interval(
+                        record.offset_from_master.unwrap_or_default(),
+                        &mut buffer[2..10],
+                    )?;
+                    serialize_time_interval(
+                        record.mean_path_delay.unwrap_or_default(),
+                        &mut buffer[10..18],
+                    )?;
+                    buffer[18..22].copy_from_slice(
+                        &record
+                            .scaled_neighbor_rate_ratio
+                            .unwrap_or_default()
+                            .to_be_bytes(),
+                    );
+                }
+            }
+            Self::TxEventTimestamps {
+                source_port_identity,
+                event_message_type,
+                records,
+            } => {
+                source_port_identity.serialize(&mut buffer[0..10])?;
+                buffer[10] = (*event_message_type as u8) << 4;
+                for (record, buffer) in records
+                    .iter()
+                    .zip(buffer[12..].chunks_exact_mut(TX_EVENT_RECORD_SIZE))
+                {
+                    buffer[0..2].copy_from_slice(&record.sequence_id.to_be_bytes());
+                    serialize_timestamp(record.event_egress_timestamp, &mut buffer[2..12])?;
+                }
+            }
+        }
+
+        Ok(buffer)
+    }
+
+    /// Parse a TLV, returns `Ok(None)` for TLVs that are not used in slave
+    /// event monitoring
+    ///
+    /// Records beyond [`MAX_SLAVE_EVENT_RECORDS`] are ignored.
+    pub(crate) fn from_tlv(tlv: &Tlv<'_>) -> Result<Option<Self>, WireFormatError> {
+        let value: &[u8] = tlv.value.as_ref();
+        let header_size = match tlv.tlv_type {
+            TlvType::SlaveRxSyncTimingData => 10,
+            TlvType::SlaveRxSyncComputedData | TlvType::SlaveTxEventTimestamps => 12,
+            _ => return Ok(None),
+        };
+        if value.len() < header_size {
+            return Err(WireFormatError::BufferTooShort);
+        }
+        let port_identity = PortIdentity::deserialize(&value[0..10])?;
+        let records = &value[header_size..];
+
+        let result = match tlv.tlv_type {
+            TlvType::SlaveRxSyncTimingData => Self::RxSyncTimingData {
+                sync_source_port_identity: port_identity,
+                records: records
+                    .chunks_exact(SYNC_TIMING_RECORD_SIZE)
+                    .take(MAX_SLAVE_EVENT_RECORDS)
+                    .map(|record| {
+                        Ok(SyncTimingRecord {
+                            sequence_id: u16::from_be_bytes([record[0], record[1]]),
+                            sync_origin_timestamp: deserialize_timestamp(&record[2..12])?,
+                            total_correction: deserialize_time_interval(&record[12..20])?,
+                            scaled_cumulative_rate_offset: i32::from_be_bytes(
+                                record[20..24].try_into().unwrap(),
+                            ),
+                            sync_event_ingress_timestamp: deserialize_timestamp(&record[24..34])?,
+                        })
+                    })
+                    .collect::<Result<_, WireFormatError>>()?,
+            },
+            TlvType::SlaveRxSyncComputedData => {
+                let flags = value[10];
+                let valid = |flag: u8| flags & flag != 0;
+                Self::RxSyncComputedData {
+                    source_port_identity: port_identity,
+                    records: records
+                        .chunks_exact(SYNC_COMPUTED_RECORD_SIZE)
+                        .take(MAX_SLAVE_EVENT_RECORDS)
+                        .map(|record| {
+                            let offset_from_master = deserialize_time_interval(&record[2..10])?;
+                            let mean_path_delay = deserialize_time_interval(&record[10..18])?;
+                            let scaled_neighbor_rate_ratio =
+                                i32::from_be_bytes(record[18..22].try_into().unwrap());
+                            Ok(SyncComputedRecord {
+                                sequence_id: u16::from_be_bytes([record[0], record[1]]),
+                                offset_from_master: valid(OFFSET_FROM_MASTER_VALID)
+                                    .then_some(offset_from_master),
+                                mean_path_delay: valid(MEAN_PATH_DELAY_VALID)
+                                    .then_some(mean_path_delay),
+                                scaled_neighbor_rate_ratio: valid(SCALED_NEIGHBOR_RATE_RATIO_VALID)
+                                    .then_some(scaled_neighbor_rate_ratio),
+                            })
+                        })
+                        .collect::<Result<_, WireFormatError>>()?,
+                }
+            }
+            _ => Self::TxEventTimestamps {
+                source_port_identity: port_identity,
+                event_message_type: MessageType::try_from(value[10] >> 4)?,
+                records: records
+                    .chunks_exact(TX_EVENT_RECORD_SIZE)
+                    .take(MAX_SLAVE_EVENT_RECORDS)
+                    .map(|record| {
+                        Ok(TxEventRecord {
+                            sequence_id: u16::from_be_bytes([record[0], record[1]]),
+                            event_egress_timestamp: deserialize_timestamp(&record[2..12])?,
+                        })
+                    })
+                    .collect::<Result<_, WireFormatError>>()?,
+            },
+        };
+
+        Ok(Some(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unicast_negotiation_tlv_wireformat() {
+        let representations = [
+            (
+                &[0x00u8, 0xfe, 0x00, 0x00, 0x01, 0x2c][..],
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period: -2,
+                    duration: 300,
+                },
+            ),
+            (
+                &[0xb0, 0x01, 0x00, 0x00, 0x00, 0x3c, 0x00, 0x01],
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::Announce,
+                    log_inter_message_period: 1,
+                    duration: 60,
+                    renewal_invited: true,
+                },
+            ),
+            (
+                &[0x90, 0x00],
+                UnicastNegotiationTlv::Cancel {
+                    message_type: MessageType::DelayResp,
+                },
+            ),
+            (
+                &[0x90, 0x00],
+                UnicastNegotiationTlv::AcknowledgeCancel {
+                    message_type: MessageType::DelayResp,
+                },
+            ),
+        ];
+
+        for (byte_representation, object_representation) in representations {
+            // Test the serialization output
+            let mut serialization_buffer = [0; 8];
+            let value = object_representation
+                .serialize_value(&mut serialization_buffer)
+                .unwrap();
+            assert_eq!(value, byte_representation);
+
+            // Test the deserialization output
+            let tlv = Tlv {
+                tlv_type: object_representation.tlv_type(),
+                value: byte_representation.into(),
+            };
+            let deserialized_data = UnicastNegotiationTlv::from_tlv(&tlv);
+            assert_eq!(deserialized_data.unwrap(), Some(object_representation));
+        }
+    }
+
+    #[test]
+    fn slave_event_monitoring_tlv_wireformat() {
+        let source_port_identity = PortIdentity {
+            clock_identity: crate::config::ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+            port_number: 9,
+        };
+
+        let timing = SlaveEventMonitoringTlv::RxSyncTimingData {
+            sync_source_port_identity: source_port_identity,
+            records: [SyncTimingRecord {
+                sequence_id: 0x0102,
+                sync_origin_timestamp: 3_000_000_004,
+                total_correction: -5,
+                scaled_cumulative_rate_offset: 6,
+                sync_event_ingress_timestamp: 7_000_000_008,
+            }]
+            .into_iter()
+            .collect(),
+        };
+        let mut buffer = [0; SlaveEventMonitoringTlv::MAX_VALUE_SIZE];
+        let value = timing.serialize_value(&mut buffer).unwrap();
+        assert_eq!(
+            value,
+            [
+                1, 2, 3, 4, 5, 6, 7, 8, 0, 9, // syncSourcePortIdentity
+                1, 2, // sequenceId
+                0, 0, 0, 0, 0, 3, 0, 0, 0, 4, // syncOriginTimestamp
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xfb, 0, 0, // totalCorrectionField
+                0, 0, 0, 6, // scaledCumulativeRateOffset
+                0, 0, 0, 0, 0, 7, 0, 0, 0, 8, // syncEventIngressTimestamp
+            ]
+        );
+
+        let records = [
+            SyncComputedRecord {
+                sequence_id: 1,
+                offset_from_master: Some(-100),
+                mean_path_delay: Some(200),
+                scaled_neighbor_rate_ratio: None,
+            },
+            SyncComputedRecord {
+                sequence_id: 2,
+                offset_from_master: Some(100),
+                mean_path_delay: None,
+                scaled_neighbor_rate_ratio: None,
+            },
+        ];
+        let computed = SlaveEventMonitoringTlv::RxSyncComputedData {
+            source_port_identity,
+            records: records.into_iter().collect(),
+        };
+        let value = computed.serialize_value(&mut buffer).unwrap();
+        assert_eq!(value.len(), 12 + 2 * 22);
+        // Only the offset from master is valid in every record
+        assert_eq!(value[10], 0b100);
+
+        let tx_events = SlaveEventMonitoringTlv::TxEventTimestamps {
+            source_port_identity,
+            event_message_type: MessageType::DelayReq,
+            records: [TxEventRecord {
+                sequence_id: 3,
+                event_egress_timestamp: 1_000_000_002,
+            }]
+            .into_iter()
+            .collect(),
+        };
+
+        for tlv in [timing, computed, tx_events] {
+            let value = tlv.serialize_value(&mut buffer).unwrap();
+            let parsed = SlaveEventMonitoringTlv::from_tlv(&Tlv {
+                tlv_type: tlv.tlv_type(),
+                value: value.into(),
+            })
+            .unwrap()
+            .unwrap();
+
+            match (&tlv, parsed) {
+                (
+                    SlaveEventMonitoringTlv::RxSyncComputedData { .. },
+                    SlaveEventMonitoringTlv::RxSyncComputedData { records, .. },
+                ) => {
+                    assert_eq!(records[0].offset_from_master, Some(-100));
+                    assert_eq!(records[0].mean_path_delay, None);
+                    assert_eq!(records[1].offset_from_master, Some(100));
+                }
+                (_, parsed) => assert_eq!(parsed, tlv),
+            }
+        }
+    }
+
+    #[test]
+    fn unicast_negotiation_tlv_invalid() {
+        let tlv = Tlv {
+            tlv_type: TlvType::RequestUnicastTransmission,
+            value: (&[0x00u8, 0x00, 0x00][..]).into(),
+        };
+        assert!(matches!(
+            UnicastNegotiationTlv::from_tlv(&tlv),
+            Err(WireFormatError::BufferTooShort)
+        ));
+
+        let tlv = Tlv {
+            tlv_type: TlvType::CancelUnicastTransmission,
+            value: (&[0x40u8, 0x00][..]).into(),
+        };
+        assert!(matches!(
+            UnicastNegotiationTlv::from_tlv(&tlv),
+            Err(WireFormatError::EnumConversionError)
+        ));
+
+        let tlv = Tlv {
+            tlv_type: TlvType::PathTrace,
+            value: (&[0x00u8, 0x00][..]).into(),
+        };
+        assert!(matches!(UnicastNegotiationTlv::from_tlv(&tlv), Ok(None)));
+    }
+}
@@ -8,11 +8,17 @@ pub mod common;
 pub mod datasets;
 pub mod messages;
 
+/// Error encountered while converting PTP messages to or from their wire
+/// format
 #[derive(Clone, Debug)]
-pub(crate) enum WireFormatError {
+pub enum WireFormatError {
+    /// A field contained a value that is not valid for its type
     EnumConversionError,
+    /// A buffer is too short to hold the data
     BufferTooShort,
+    /// A container has insufficient capacity
     CapacityError,
+    /// The data violates an invariant of the message
     Invalid,
 }
 
@@ -2,7 +2,7 @@
 
 use fixed::traits::LossyInto;
 
-use super::{Filter, FilterUpdate};
+use super::{ClockUncertainty, Filter, FilterUpdate};
 #[allow(unused_imports)]
 use crate::float_polyfill::FloatPolyfill;
 use crate::{
@@ -154,4 +154,14 @@ impl Filter for BasicFilter {
         // ignore
         Default::default()
     }
+
+    fn uncertainty(&self) -> Option<ClockUncertainty> {
+        // The confidence intervals only mean something once we are steering
+        self.last_step.as_ref()?;
+
+        Some(ClockUncertainty {
+            offset: self.offset_confidence,
+            frequency: self.freq_confidence,
+        })
+    }
 }
@@ -64,6 +64,17 @@ pub struct KalmanConfiguration {
     /// peer delay measurements (to compensate for there being multiple path
     /// segments).
     pub peer_delay_factor: f64,
+
+    /// How close a jump in the measured offset needs to be to exactly one
+    /// second to be considered a leap second.
+    pub leap_second_tolerance: Duration,
+    /// How long measurements that jumped by a leap second are ignored.
+    ///
+    /// A leap second applied by the remote is expected to be applied to our
+    /// clock by the system as well, after which the measurements return to
+    /// the old offset. When the jump persists beyond this time it is treated
+    /// as a normal offset and corrected by a step.
+    pub leap_second_holdoff: Duration,
 }
 
 impl Default for KalmanConfiguration {
@@ -84,6 +95,8 @@ impl Default for KalmanConfiguration {
             difference_estimation_boundary: 4,
             statistical_estimation_boundary: 8,
             peer_delay_factor: 2.0,
+            leap_second_tolerance: Duration::from_millis(1),
+            leap_second_holdoff: Duration::from_secs(10),
         }
     }
 }
@@ -489,6 +502,10 @@ impl BaseFilter {
             .unwrap_or((0.0, sqr(config.step_threshold.seconds())))
     }
 
+    fn initialized(&self) -> bool {
+        self.0.is_some()
+    }
+
     fn after_filter_time(&self, time: Time) -> bool {
         match &self.0 {
             Some(inner) => time >= inner.filter_time,
@@ -517,6 +534,7 @@ pub struct KalmanFilter {
     wander_measurement_error: f64,
     measurement_error_estimator: MeasurementErrorEstimator,
     cur_frequency: Option<f64>,
+    leap_second_since: Option<Time>,
 }
 
 impl Filter for KalmanFilter {
@@ -534,6 +552,7 @@ impl Filter for KalmanFilter {
                 .sqrt(),
             measurement_error_estimator,
             cur_frequency: None,
+            leap_second_since: None,
             config,
         }
     }
@@ -547,6 +566,10 @@ impl Filter for KalmanFilter {
             return super::FilterUpdate::default();
         }
 
+        if self.is_leap_second(&m) {
+            return super::FilterUpdate::default();
+        }
+
         self.measurement_error_estimator.absorb_measurement(
             m,
             self.running_filter.freq_offset(),
@@ -622,6 +645,16 @@ impl Filter for KalmanFilter {
         // of correct
         self.change_frequency(0.0, clock);
     }
+
+    fn uncertainty(&self) -> Option<super::ClockUncertainty> {
+        // Without steering the clock we have no estimate of its frequency to hold
+        self.cur_frequency?;
+
+        Some(super::ClockUncertainty {
+            offset: Duration::from_seconds(self.running_filter.offset_uncertainty(&self.config)),
+            frequency: self.running_filter.freq_offset_uncertainty(&self.config),
+        })
+    }
 }
 
 impl KalmanFilter {
@@ -665,6 +698,50 @@ impl KalmanFilter {
         }
     }
 
+    /// Whether the measurement should be ignored because the offset jumped by
+    /// a leap second
+    ///
+    /// Around a leap second the clocks of the remote and of the system are
+    /// not necessarily updated at the same moment, so the measured offset
+    /// jumps by one second for a short while. Steering on that would step the
+    /// clock twice.
+    fn is_leap_second(&mut self, m: &Measurement) -> bool {
+        if !self.running_filter.initialized() {
+            return false;
+        }
+
+        let jump = match (m.raw_sync_offset, m.raw_delay_offset) {
+            (Some(sync_offset), _) => {
+                sync_offset.seconds() - self.running_filter.predict_sync_offset(&self.config).0
+            }
+            (None, Some(delay_offset)) => {
+                delay_offset.seconds() - self.running_filter.predict_delay_offset(&self.config).0
+            }
+            (None, None) => return false,
+        };
+
+        if (jump.abs() - 1.0).abs() > self.config.leap_second_tolerance.seconds() {
+            if self.leap_second_since.take().is_some() {
+                log::info!("Offset returned after leap second");
+            }
+            return false;
+        }
+
+        match self.leap_second_since {
+            None => {
+                log::warn!("Offset jumped by {jump:.3}s, ignoring measurements during leap second");
+                self.leap_second_since = Some(m.event_time);
+                true
+            }
+            Some(since) if m.event_time - since < self.config.leap_second_holdoff => true,
+            Some(_) => {
+                log::warn!("Offset jump of {jump:.3}s persists, correcting it");
+                self.leap_second_since = None;
+                false
+            }
+        }
+    }
+
     fn display_state(&self) {
         log::info!(
             "Estimated offset {}ns+-{}ns, freq {}+-{}, delay {}+-{}",
@@ -779,3 +856,72 @@ impl KalmanFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sync(event_time: Time, offset: Duration) -> Measurement {
+        Measurement {
+            event_time,
+            raw_sync_offset: Some(offset),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn leap_second_is_ignored() {
+        let config = KalmanConfiguration::default();
+        let mut filter = KalmanFilter::new(config);
+        let start = Time::from_secs(1000);
+        filter
+            .running_filter
+            .progress_filtertime(start, filter.wander, &config);
+
+        let normal = sync(start, Duration::from_micros(10));
+        assert!(!filter.is_leap_second(&normal));
+
+        let leap = sync(
+            start + Duration::from_secs(1),
+            Duration::from_secs(-1) + Duration::from_micros(10),
+        );
+        assert!(filter.is_leap_second(&leap));
+        assert!(filter.is_leap_second(&sync(
+            start + Duration::from_secs(5),
+            Duration::from_secs(1)
+        )));
+
+        // Returning to the old offset ends the leap second
+        assert!(!filter.is_leap_second(&sync(start + Duration::from_secs(6), Duration::ZERO)));
+        assert!(filter.leap_second_since.is_none());
+    }
+
+    #[test]
+    fn persistent_jump_is_corrected() {
+        let config = KalmanConfiguration::default();
+        let mut filter = KalmanFilter::new(config);
+        let start = Time::from_secs(1000);
+
+        // Without a filter state there is nothing to compare against
+        assert!(!filter.is_leap_second(&sync(start, Duration::from_secs(1))));
+
+        filter
+            .running_filter
+            .progress_filtertime(start, filter.wander, &config);
+        assert!(filter.is_leap_second(&sync(start, Duration::from_secs(1))));
+        assert!(filter.is_leap_second(&sync(
+            start + Duration::from_secs(9),
+            Duration::from_secs(1)
+        )));
+        assert!(!filter.is_leap_second(&sync(
+            start + Duration::from_secs(10),
+            Duration::from_secs(1)
+        )));
+
+        // A jump that is not a whole second is a normal offset
+        assert!(!filter.is_leap_second(&sync(
+            start + Duration::from_secs(11),
+            Duration::from_millis(1500)
+        )));
+    }
+}
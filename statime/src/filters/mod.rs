@@ -7,7 +7,7 @@ mod matrix;
 pub use basic::BasicFilter;
 pub use kalman::{KalmanConfiguration, KalmanFilter};
 
-use crate::{port::Measurement, time::Duration, Clock};
+use crate::{config::ClockAccuracy, port::Measurement, time::Duration, Clock};
 
 /// Information on the result of the [`Filter`] and the actions it needs from
 /// its environment
@@ -23,6 +23,28 @@ pub struct FilterUpdate {
     pub mean_delay: Option<Duration>,
 }
 
+/// Estimate of the uncertainty of a clock steered by a [`Filter`], see
+/// [`Filter::uncertainty`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClockUncertainty {
+    /// Uncertainty of the offset of the clock
+    pub offset: Duration,
+    /// Uncertainty of the frequency of the clock, as a fractional frequency
+    pub frequency: f64,
+}
+
+impl ClockUncertainty {
+    /// Variance of the offset of the clock in seconds^2
+    pub fn offset_variance(&self) -> f64 {
+        self.offset.seconds() * self.offset.seconds()
+    }
+
+    /// The accuracy of the clock, see [`ClockAccuracy::from_uncertainty`]
+    pub fn clock_accuracy(&self) -> ClockAccuracy {
+        ClockAccuracy::from_uncertainty(self.offset)
+    }
+}
+
 /// A filter for post-processing time measurements.
 ///
 /// Filters are responsible for dealing with the network noise, and should
@@ -51,4 +73,16 @@ pub trait Filter {
     /// Handle ending of time synchronization from the source
     /// associated with this filter.
     fn demobilize<C: Clock>(self, clock: &mut C);
+
+    /// Current estimate of the uncertainty of the clock steered by this
+    /// filter.
+    ///
+    /// This determines the clock accuracy and offsetScaledLogVariance announced
+    /// while the clock is synchronized, and is the starting point of the
+    /// holdover of the clock once time synchronization from the source ends.
+    /// Filters without such an estimate return `None`, in which case the clock
+    /// is never considered to be within its holdover specification.
+    fn uncertainty(&self) -> Option<ClockUncertainty> {
+        None
+    }
 }
@@ -9,6 +9,8 @@ pub(crate) trait FloatPolyfill {
     fn powi(self, n: i32) -> Self;
     #[cfg(not(feature = "std"))]
     fn exp(self) -> Self;
+    #[cfg(not(feature = "std"))]
+    fn log2(self) -> Self;
 }
 
 impl FloatPolyfill for f64 {
@@ -42,4 +44,9 @@ impl FloatPolyfill for f64 {
     fn exp(self) -> Self {
         libm::exp(self)
     }
+
+    #[cfg(not(feature = "std"))]
+    fn log2(self) -> Self {
+        libm::log2(self)
+    }
 }
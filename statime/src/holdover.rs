@@ -0,0 +1,208 @@
+use crate::{
+    config::{ClockAccuracy, ClockQuality, HoldoverConfig, TimePropertiesDS},
+    filters::ClockUncertainty,
+    observability::holdover::HoldoverState,
+    time::Duration,
+};
+
+/// The clockClass of a clock in holdover within its specification, see
+/// *IEEE1588-2019 table 4*
+const HOLDOVER_CLOCK_CLASS: u8 = 7;
+
+/// The state of the clock of an instance since it lost a grandmaster with a
+/// traceable time
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Holdover {
+    /// Time since the grandmaster was lost
+    elapsed: Duration,
+    /// Uncertainty of the clock when the grandmaster was lost
+    initial: Option<ClockUncertainty>,
+    /// The time properties of the lost grandmaster
+    time_properties_ds: TimePropertiesDS,
+}
+
+impl Holdover {
+    pub(crate) fn new(
+        initial: Option<ClockUncertainty>,
+        time_properties_ds: TimePropertiesDS,
+    ) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            initial,
+            time_properties_ds,
+        }
+    }
+
+    pub(crate) fn step(&mut self, step: Duration) {
+        self.elapsed += step;
+    }
+
+    /// Bound on the uncertainty of the clock, which grows with the frequency
+    /// uncertainty at the start of the holdover and the drift of the local
+    /// oscillator since then
+    fn uncertainty(&self, config: &HoldoverConfig) -> Option<Duration> {
+        let initial = self.initial?;
+        let elapsed = self.elapsed.seconds();
+
+        let drift =
+            initial.frequency.abs() * elapsed + 0.5 * config.frequency_drift * elapsed * elapsed;
+
+        Some(initial.offset.abs() + Duration::from_fixed_nanos(drift * 1e9))
+    }
+
+    fn in_specification(&self, config: &HoldoverConfig) -> bool {
+        self.elapsed <= config.duration
+            && matches!(
+                self.uncertainty(config),
+                Some(uncertainty) if uncertainty <= config.max_uncertainty
+            )
+    }
+
+    pub(crate) fn clock_quality(&self, config: &HoldoverConfig) -> ClockQuality {
+        let uncertainty = self.uncertainty(config);
+        let clock_accuracy = uncertainty.map_or(ClockAccuracy::Unknown, |uncertainty| {
+            ClockAccuracy::from_uncertainty(uncertainty)
+        });
+
+        if self.in_specification(config) {
+            ClockQuality {
+                clock_class: HOLDOVER_CLOCK_CLASS,
+                clock_accuracy,
+                offset_scaled_log_variance: uncertainty.map_or(u16::MAX, |uncertainty| {
+                    ClockQuality::scaled_log_variance(uncertainty.seconds() * uncertainty.seconds())
+                }),
+            }
+        } else {
+            ClockQuality {
+                clock_class: config.degradation.clock_class(),
+                clock_accuracy,
+                offset_scaled_log_variance: u16::MAX,
+            }
+        }
+    }
+
+    /// The time properties of the lost grandmaster, which are no longer
+    /// traceable once the holdover specification is exceeded
+    pub(crate) fn time_properties_ds(&self, config: &HoldoverConfig) -> TimePropertiesDS {
+        let mut time_properties_ds = self.time_properties_ds;
+        if !self.in_specification(config) {
+            time_properties_ds.time_traceable = false;
+            time_properties_ds.frequency_traceable = false;
+        }
+        time_properties_ds
+    }
+
+    pub(crate) fn state(&self, config: &HoldoverConfig) -> HoldoverState {
+        HoldoverState {
+            duration: self.elapsed.nanos_rounded(),
+            uncertainty: self
+                .uncertainty(config)
+                .map(|uncertainty| uncertainty.nanos_rounded()),
+            in_specification: self.in_specification(config),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClockClassDegradation, LeapIndicator, TimeSource};
+
+    fn traceable() -> TimePropertiesDS {
+        TimePropertiesDS::new_ptp_time(
+            Some(37),
+            LeapIndicator::NoLeap,
+            true,
+            true,
+            TimeSource::Gnss,
+        )
+    }
+
+    fn initial() -> Option<ClockUncertainty> {
+        Some(ClockUncertainty {
+            offset: Duration::from_nanos(50),
+            frequency: 1e-9,
+        })
+    }
+
+    #[test]
+    fn uncertainty_grows() {
+        let config = HoldoverConfig {
+            frequency_drift: 1e-11,
+            ..Default::default()
+        };
+        let mut holdover = Holdover::new(initial(), traceable());
+        assert_eq!(
+            holdover.uncertainty(&config),
+            Some(Duration::from_nanos(50))
+        );
+
+        // 50ns + 1e-9 * 100s + 0.5 * 1e-11 * 100s^2
+        holdover.step(Duration::from_secs(100));
+        let uncertainty = holdover.uncertainty(&config).unwrap();
+        assert!((uncertainty.nanos_lossy() - 200.0).abs() < 1e-3);
+
+        assert!(Holdover::new(None, traceable())
+            .uncertainty(&config)
+            .is_none());
+    }
+
+    #[test]
+    fn clock_quality_degrades() {
+        let config = HoldoverConfig {
+            duration: Duration::from_secs(300),
+            max_uncertainty: Duration::from_micros(1),
+            frequency_drift: 1e-11,
+            degradation: ClockClassDegradation::AlternativeB,
+        };
+        let mut holdover = Holdover::new(initial(), traceable());
+
+        let quality = holdover.clock_quality(&config);
+        assert_eq!(quality.clock_class, 7);
+        assert_eq!(quality.clock_accuracy, ClockAccuracy::NS100);
+        assert!(holdover.time_properties_ds(&config).time_traceable);
+
+        holdover.step(Duration::from_secs(100));
+        let later = holdover.clock_quality(&config);
+        assert_eq!(later.clock_class, 7);
+        assert_eq!(later.clock_accuracy, ClockAccuracy::NS250);
+        assert!(later.offset_scaled_log_variance > quality.offset_scaled_log_variance);
+
+        // The uncertainty exceeds the specification after about 400 seconds
+        holdover.step(Duration::from_secs(300));
+        let degraded = holdover.clock_quality(&config);
+        assert_eq!(degraded.clock_class, 187);
+        assert_eq!(degraded.clock_accuracy, ClockAccuracy::US2_5);
+        assert_eq!(degraded.offset_scaled_log_variance, u16::MAX);
+
+        let time_properties_ds = holdover.time_properties_ds(&config);
+        assert!(!time_properties_ds.time_traceable);
+        assert!(!time_properties_ds.frequency_traceable);
+        assert_eq!(time_properties_ds.current_utc_offset, Some(37));
+    }
+
+    #[test]
+    fn clock_quality_degrades_after_duration() {
+        let config = HoldoverConfig {
+            duration: Duration::from_secs(10),
+            max_uncertainty: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let mut holdover = Holdover::new(initial(), traceable());
+        holdover.step(Duration::from_secs(10));
+        assert_eq!(holdover.clock_quality(&config).clock_class, 7);
+        holdover.step(Duration::from_secs(1));
+        assert_eq!(holdover.clock_quality(&config).clock_class, 52);
+    }
+
+    #[test]
+    fn no_estimate_is_out_of_specification() {
+        let config = HoldoverConfig::default();
+        let holdover = Holdover::new(None, traceable());
+
+        let quality = holdover.clock_quality(&config);
+        assert_eq!(quality.clock_class, 52);
+        assert_eq!(quality.clock_accuracy, ClockAccuracy::Unknown);
+        assert!(!holdover.state(&config).in_specification);
+    }
+}
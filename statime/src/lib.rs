@@ -1,6 +1,6 @@
 //! Statime is a library providing an implementation of PTP version 2.1
-//! (IEEE1588-2019). It provides all the building blocks to setup PTP ordinary
-//! and boundary clocks.
+//! (IEEE1588-2019). It provides all the building blocks to setup PTP ordinary,
+//! boundary and transparent clocks.
 //!
 //! `statime` is designed to be able to work with many different underlying
 //! platforms, including embedded targets. This does mean that it cannot use the
@@ -57,6 +57,21 @@
 //! [`Port`](`port::Port`) expects to be performed are returned in the form of
 //! [`PortAction`](`port::PortAction`)s.
 //!
+//! # Transparent clocks
+//! A transparent clock is set up from a [`TransparentClock`], configured with
+//! a [`TransparentClockConfig`](`config::TransparentClockConfig`). Its
+//! [`TransparentPort`](`port::TransparentPort`)s are created using
+//! [`TransparentClock::add_port`] and need no BMCA. Each message a port
+//! receives is returned as a
+//! [`TransparentPortAction::Forward`](`port::TransparentPortAction::Forward`)
+//! which the user passes on to all other ports with
+//! [`TransparentPort::handle_forwarded_message`](`port::TransparentPort::handle_forwarded_message`).
+//!
+//! # Management
+//! Ports answer PTP management messages (IEEE1588-2019 section 15) as part of
+//! their normal message handling. The [`management`] module provides the
+//! types needed to build a management node that queries or configures them.
+//!
 //! # Testing a new implementation
 //! A basic option for testing is to run `statime-linux` on your developer
 //! machine and connecting your new implementation to a dedicated network port.
@@ -89,13 +104,17 @@ pub mod config;
 pub(crate) mod datastructures;
 pub mod filters;
 mod float_polyfill;
+mod holdover;
+pub mod management;
 pub mod observability;
 pub mod port;
 mod ptp_instance;
 pub mod time;
+mod transparent_clock;
 
 pub use clock::Clock;
 pub use ptp_instance::PtpInstance;
+pub use transparent_clock::TransparentClock;
 
 /// Helper types used for fuzzing
 ///
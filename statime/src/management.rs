@@ -0,0 +1,289 @@
+//! Building and parsing PTP management messages (IEEE1588-2019 section 15)
+//!
+//! A [`PtpInstance`](`crate::PtpInstance`) answers management messages on its
+//! own. The types in this module are meant for the other side of that
+//! exchange: a management node that sends a [`ManagementRequest`] to one or
+//! more instances and interprets the [`ManagementResponse`]s they send back.
+
+pub use crate::datastructures::{
+    common::PortIdentity,
+    messages::{
+        ManagementAction, ManagementData, ManagementErrorId, ManagementErrorStatus, ManagementId,
+    },
+    WireFormatError,
+};
+use crate::{
+    config::{ClockIdentity, SdoId, SecurityConfig},
+    datastructures::{
+        common::{Tlv, TlvSetBuilder, TlvType},
+        messages::{ManagementMessage, ManagementTlv, Message, MessageBody},
+    },
+};
+
+// Enough room for the largest management TLV we know of
+// (PERFORMANCE_MONITORING_RECORD)
+const MANAGEMENT_TLV_SIZE: usize = 160;
+
+/// The targetPortIdentity addressing all ports of all PTP instances, see
+/// *IEEE1588-2019 section 15.4.1.2*
+pub const ALL_PORTS: PortIdentity = PortIdentity {
+    clock_identity: ClockIdentity([0xff; 8]),
+    port_number: 0xffff,
+};
+
+/// A management message sent by a management node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManagementRequest {
+    /// The sdoId of the PTP domain the request is sent in
+    pub sdo_id: SdoId,
+    /// The number of the PTP domain the request is sent in
+    pub domain_number: u8,
+    /// The identity of the sending management node
+    pub source_port_identity: PortIdentity,
+    /// The sequence id, used to match responses to this request
+    pub sequence_id: u16,
+    /// The port(s) that should act on this request, use [`ALL_PORTS`] to
+    /// address every port
+    pub target_port_identity: PortIdentity,
+    /// The number of boundary clocks the request may be forwarded through
+    pub boundary_hops: u8,
+    /// What the target should do with the management TLV
+    pub action: ManagementAction,
+    /// The management TLV to send. GET and COMMAND requests normally use
+    /// [`ManagementData::Empty`]
+    pub data: ManagementData,
+}
+
+impl ManagementRequest {
+    /// Serializes the request into the PTP wire format.
+    ///
+    /// Returns the used buffer size that contains the message or an error.
+    pub fn serialize(&self, buffer: &mut [u8]) -> Result<usize, WireFormatError> {
+        let mut value = [0; MANAGEMENT_TLV_SIZE];
+        value[..2].copy_from_slice(&self.data.management_id().to_primitive().to_be_bytes());
+        let length = 2 + self.data.serialize(&mut value[2..])?;
+
+        let mut tlv_buffer = [0; MANAGEMENT_TLV_SIZE + 4];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        tlv_builder.add(Tlv {
+            tlv_type: TlvType::Management,
+            value: value[..length].into(),
+        })?;
+
+        let message = Message::management_request(
+            self.sdo_id,
+            self.domain_number,
+            self.source_port_identity,
+            self.sequence_id,
+            ManagementMessage {
+                target_port_identity: self.target_port_identity,
+                starting_boundary_hops: self.boundary_hops,
+                boundary_hops: self.boundary_hops,
+                action: self.action,
+            },
+            tlv_builder.build(),
+        );
+
+        if buffer.len() < message.wire_size() {
+            return Err(WireFormatError::BufferTooShort);
+        }
+
+        message.serialize(buffer)
+    }
+
+    /// Serializes the request like [`serialize`](`Self::serialize`), followed
+    /// by an AUTHENTICATION TLV for instances that have a [`SecurityConfig`].
+    pub fn serialize_authenticated(
+        &self,
+        buffer: &mut [u8],
+        security: &SecurityConfig,
+    ) -> Result<usize, WireFormatError> {
+        let length = self.serialize(buffer)?;
+        crate::port::security::append_authentication_tlv(security, buffer, length)
+            .ok_or(WireFormatError::Invalid)
+    }
+}
+
+/// A RESPONSE or ACKNOWLEDGE management message sent by a PTP instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManagementResponse {
+    /// The sdoId of the PTP domain the response was sent in
+    pub sdo_id: SdoId,
+    /// The number of the PTP domain the response was sent in
+    pub domain_number: u8,
+    /// The identity of the responding port
+    pub source_port_identity: PortIdentity,
+    /// The sequence id of the request this response answers
+    pub sequence_id: u16,
+    /// The identity of the management node that sent the request
+    pub target_port_identity: PortIdentity,
+    /// Either [`ManagementAction::RESPONSE`] or
+    /// [`ManagementAction::ACKNOWLEDGE`]
+    pub action: ManagementAction,
+    /// The returned management TLV, or the reason the request failed
+    pub result: Result<ManagementData, ManagementErrorStatus>,
+}
+
+impl ManagementResponse {
+    /// Deserializes a management response from the PTP wire format.
+    ///
+    /// Returns `Ok(None)` for valid PTP messages that are not management
+    /// responses, such as other management nodes' requests.
+    pub fn deserialize(buffer: &[u8]) -> Result<Option<Self>, WireFormatError> {
+        let message = Message::deserialize(buffer)?;
+
+        let MessageBody::Management(management) = message.body else {
+            return Ok(None);
+        };
+        if !matches!(
+            management.action,
+            ManagementAction::RESPONSE | ManagementAction::ACKNOWLEDGE
+        ) {
+            return Ok(None);
+        }
+
+        let tlv = message
+            .suffix
+            .tlv()
+            .find(|tlv| {
+                matches!(
+                    tlv.tlv_type,
+                    TlvType::Management | TlvType::ManagementErrorStatus
+                )
+            })
+            .ok_or(WireFormatError::Invalid)?;
+
+        let result = match tlv.tlv_type {
+            TlvType::Management => {
+                let management_tlv = ManagementTlv::from_tlv(&tlv)?;
+                let data =
+                    ManagementData::deserialize(management_tlv.management_id, management_tlv.data)?
+                        .ok_or(WireFormatError::Invalid)?;
+                Ok(data)
+            }
+            _ => Err(ManagementErrorStatus::deserialize(tlv.value.as_ref())?),
+        };
+
+        Ok(Some(Self {
+            sdo_id: message.header.sdo_id,
+            domain_number: message.header.domain_number,
+            source_port_identity: message.header.source_port_identity,
+            sequence_id: message.header.sequence_id,
+            target_port_identity: management.target_port_identity,
+            action: management.action,
+            result,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Duration;
+
+    #[test]
+    fn authenticated_request() {
+        use crate::config::{IntegrityAlgorithm, SecurityKey};
+
+        let request = ManagementRequest {
+            sdo_id: SdoId::default(),
+            domain_number: 0,
+            source_port_identity: PortIdentity::default(),
+            sequence_id: 1,
+            target_port_identity: ALL_PORTS,
+            boundary_hops: 1,
+            action: ManagementAction::GET,
+            data: ManagementData::Empty(ManagementId::DefaultDataSet),
+        };
+        let mut security = SecurityConfig {
+            spp: 0,
+            keys: Default::default(),
+            key_id: 1,
+        };
+        security.keys.push(SecurityKey {
+            key_id: 1,
+            algorithm: IntegrityAlgorithm::HmacSha256_128,
+            secret: [1; 32][..].try_into().unwrap(),
+            valid_from: None,
+            valid_until: None,
+        });
+
+        let mut buffer = [0; 128];
+        let length = request
+            .serialize_authenticated(&mut buffer, &security)
+            .unwrap();
+        let message = Message::deserialize(&buffer[..length]).unwrap();
+        assert_eq!(message.wire_size(), length);
+        assert_eq!(
+            crate::port::security::verify_authentication(&security, &buffer, &message),
+            Ok(())
+        );
+
+        security.key_id = 2;
+        assert!(matches!(
+            request.serialize_authenticated(&mut buffer, &security),
+            Err(WireFormatError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn request_response_roundtrip() {
+        let request = ManagementRequest {
+            sdo_id: SdoId::default(),
+            domain_number: 3,
+            source_port_identity: PortIdentity {
+                clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+                port_number: 1,
+            },
+            sequence_id: 42,
+            target_port_identity: ALL_PORTS,
+            boundary_hops: 1,
+            action: ManagementAction::SET,
+            data: ManagementData::Priority1(12),
+        };
+
+        let mut buffer = [0; 128];
+        let length = request.serialize(&mut buffer).unwrap();
+
+        // A request is not a response
+        assert_eq!(
+            ManagementResponse::deserialize(&buffer[..length]).unwrap(),
+            None
+        );
+
+        // Turn the request into a response by changing the actionField
+        buffer[34 + 12] = ManagementAction::RESPONSE.to_primitive();
+        let response = ManagementResponse::deserialize(&buffer[..length])
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.domain_number, 3);
+        assert_eq!(response.sequence_id, 42);
+        assert_eq!(response.source_port_identity, request.source_port_identity);
+        assert_eq!(response.target_port_identity, ALL_PORTS);
+        assert_eq!(response.result, Ok(ManagementData::Priority1(12)));
+    }
+
+    #[test]
+    fn request_too_large_for_buffer() {
+        let request = ManagementRequest {
+            sdo_id: SdoId::default(),
+            domain_number: 0,
+            source_port_identity: PortIdentity::default(),
+            sequence_id: 0,
+            target_port_identity: ALL_PORTS,
+            boundary_hops: 0,
+            action: ManagementAction::SET,
+            data: ManagementData::CurrentDataSet {
+                steps_removed: 0,
+                offset_from_master: Duration::ZERO,
+                mean_path_delay: Duration::ZERO,
+            },
+        };
+
+        let mut buffer = [0; 40];
+        assert!(matches!(
+            request.serialize(&mut buffer),
+            Err(WireFormatError::BufferTooShort)
+        ));
+    }
+}
@@ -0,0 +1,12 @@
+/// The state of the clock of an instance in holdover, see
+/// [`PtpInstance::holdover`](`crate::PtpInstance::holdover`)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HoldoverState {
+    /// Time since the grandmaster was lost in nanoseconds
+    pub duration: i128,
+    /// Bound on the uncertainty of the clock in nanoseconds, if known
+    pub uncertainty: Option<i128>,
+    /// Whether the clock is still within its holdover specification
+    pub in_specification: bool,
+}
@@ -3,5 +3,20 @@
 pub mod current;
 /// A concrete implementation of the PTP Default dataset (IEEE1588-2019 section 8.2.1)
 pub mod default;
+/// The state of the clock while in holdover
+pub mod holdover;
 /// A concrete implementation of the PTP Parent dataset (IEEE1588-2019 section 8.2.3)
 pub mod parent;
+/// Performance monitoring records of a port (IEEE1588-2019 annex J)
+pub mod performance;
+/// A concrete implementation of the PTP Port dataset (IEEE1588-2019 section 8.2.15)
+pub mod port;
+/// Counters of messages dropped because of their authentication
+pub mod security;
+/// Slave event monitoring data received from slave ports (IEEE1588-2019 section 16.11)
+pub mod slave_event_monitoring;
+/// The SMPTE ST 2059-2 synchronization metadata of the grandmaster, see
+/// [`PtpInstance::synchronization_metadata`](`crate::PtpInstance::synchronization_metadata`)
+pub use crate::datastructures::common::{
+    AlternateTimeOffset, MasterLockingStatus, PowerProfileInformation, SynchronizationMetadata,
+};
@@ -0,0 +1,56 @@
+/// The number of 15-minute records of a port: the current one and those of the
+/// past 24 hours, see *IEEE1588-2019 section J.5.1*
+pub const FIFTEEN_MINUTE_RECORDS: u16 = 96;
+/// The number of 24-hour records of a port: the current and the previous one
+pub const TWENTY_FOUR_HOUR_RECORDS: u16 = 2;
+/// The number of performance monitoring records of a port
+///
+/// Records are numbered like the `index` of *IEEE1588-2019 section J.5.1*:
+/// index 0 is the current 15-minute record and indices 1 through 95 are the
+/// previous ones, most recent first. Index 96 is the current 24-hour record
+/// and 97 the previous one.
+pub const PERFORMANCE_RECORDS: u16 = FIFTEEN_MINUTE_RECORDS + TWENTY_FOUR_HOUR_RECORDS;
+
+/// A performance monitoring record of a port, with the statistics of the
+/// measurements during a 15-minute or 24-hour period, see *IEEE1588-2019
+/// annex J*
+///
+/// Records start with the first measurement in their period, so there are no
+/// records for periods without measurements.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceRecord {
+    /// Start of the period of this record, in seconds in the timescale of the
+    /// clock of the port. Periods are aligned to multiples of their length.
+    pub start: u64,
+    /// Whether the period of this record has ended
+    pub complete: bool,
+    /// The offset of the clock to its master
+    pub offset_from_master: Option<PerformanceStatistics>,
+    /// The mean path delay, measured with either the delay request-response
+    /// or the peer delay mechanism
+    pub mean_path_delay: Option<PerformanceStatistics>,
+    /// The difference between the receive and send timestamps of sync
+    /// messages
+    pub master_slave_delay: Option<PerformanceStatistics>,
+    /// The difference between the receive and send timestamps of delay
+    /// requests
+    pub slave_master_delay: Option<PerformanceStatistics>,
+}
+
+/// Statistics of a parameter over the period of a [`PerformanceRecord`], all
+/// in nanoseconds
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceStatistics {
+    /// The number of measurements
+    pub samples: u32,
+    /// The smallest measurement
+    pub min: i128,
+    /// The largest measurement
+    pub max: i128,
+    /// The mean of the measurements
+    pub mean: i128,
+    /// The (population) standard deviation of the measurements
+    pub std_dev: i128,
+}
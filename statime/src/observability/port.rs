@@ -0,0 +1,175 @@
+use crate::{
+    datastructures::{common::PortIdentity, messages::MessageType},
+    port::state,
+};
+
+/// A concrete implementation of the PTP Port dataset (IEEE1588-2019 section
+/// 8.2.15)
+///
+/// The `enable` field is missing, ports that are not enabled are in the
+/// [`PortState::Disabled`] state.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortDS {
+    /// See *IEEE1588-2019 section 8.2.15.2.1*.
+    pub port_identity: PortIdentity,
+    /// See *IEEE1588-2019 section 8.2.15.3.1*.
+    pub port_state: PortState,
+    /// See *IEEE1588-2019 section 8.2.15.3.2*. Set to 0x7f when the port uses
+    /// the peer-to-peer delay mechanism.
+    pub log_min_delay_req_interval: i8,
+    /// See *IEEE1588-2019 section 8.2.15.3.3*. Zero when the port uses the
+    /// end-to-end delay mechanism.
+    pub peer_mean_path_delay: i128,
+    /// See *IEEE1588-2019 section 8.2.15.4.1*.
+    pub log_announce_interval: i8,
+    /// See *IEEE1588-2019 section 8.2.15.4.2*.
+    pub announce_receipt_timeout: u8,
+    /// See *IEEE1588-2019 section 8.2.15.4.3*.
+    pub log_sync_interval: i8,
+    /// See *IEEE1588-2019 section 8.2.15.4.4*.
+    pub delay_mechanism: DelayMechanism,
+    /// See *IEEE1588-2019 section 8.2.15.3.4*. Set to 0x7f when the port uses
+    /// the end-to-end delay mechanism.
+    pub log_min_pdelay_req_interval: i8,
+    /// See *IEEE1588-2019 section 8.2.15.4.5*.
+    pub version_number: u8,
+    /// See *IEEE1588-2019 section 8.2.15.4.6*.
+    pub minor_version_number: u8,
+}
+
+/// The state of a port, see *IEEE1588-2019 section 9.2.5*
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PortState {
+    /// The port is in a fault state
+    Faulty,
+    /// The port is disabled and does not send or receive messages
+    Disabled,
+    /// The port waits for announce messages
+    Listening,
+    /// The port is the source of time on its path
+    Master,
+    /// The port does not send or synchronize to messages, to avoid timing
+    /// loops
+    Passive,
+    /// The port synchronizes the clock to its master
+    Slave,
+}
+
+impl PortState {
+    /// The port state as encoded in *IEEE1588-2019 table 20*
+    pub fn to_primitive(self) -> u8 {
+        match self {
+            PortState::Faulty => 2,
+            PortState::Disabled => 3,
+            PortState::Listening => 4,
+            PortState::Master => 6,
+            PortState::Passive => 7,
+            PortState::Slave => 9,
+        }
+    }
+}
+
+impl From<&state::PortState> for PortState {
+    fn from(v: &state::PortState) -> Self {
+        match v {
+            state::PortState::Faulty => PortState::Faulty,
+            state::PortState::Disabled => PortState::Disabled,
+            state::PortState::Listening => PortState::Listening,
+            state::PortState::Master => PortState::Master,
+            state::PortState::Passive => PortState::Passive,
+            state::PortState::Slave(_) => PortState::Slave,
+        }
+    }
+}
+
+/// The delay mechanism of a port, see *IEEE1588-2019 section 8.2.15.4.4*
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DelayMechanism {
+    /// End-to-end delay mechanism
+    E2E,
+    /// Peer-to-peer delay mechanism
+    P2P,
+}
+
+impl DelayMechanism {
+    /// The delay mechanism as encoded in *IEEE1588-2019 table 21*
+    pub fn to_primitive(self) -> u8 {
+        match self {
+            DelayMechanism::E2E => 0x01,
+            DelayMechanism::P2P => 0x02,
+        }
+    }
+}
+
+/// Counters of the messages handled by a port, for performance monitoring
+/// like *IEEE1588-2019 annex J*
+///
+/// Received messages are counted once they are parsed and are meant for the
+/// domain of the port. All counters start at zero when the port is created.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortCounters {
+    /// Received messages, per message type
+    pub rx: MessageCounters,
+    /// Sent messages, per message type
+    pub tx: MessageCounters,
+    /// Received packets that could not be parsed as a PTP message
+    pub parse_errors: u64,
+    /// Received messages for another sdoId or domain
+    pub domain_mismatches: u64,
+    /// Follow_Up, Delay_Resp and peer delay responses that did not match the
+    /// sequence id of the message they respond to
+    pub sequence_id_mismatches: u64,
+    /// Two-step Sync messages whose Follow_Up never arrived
+    pub missing_follow_ups: u64,
+    /// Send timestamps of (peer) delay requests that did not arrive before
+    /// the next request
+    pub timestamp_timeouts: u64,
+}
+
+/// Number of messages per message type, see *IEEE1588-2019 section 13.3.2.3*
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageCounters {
+    /// Sync messages
+    pub sync: u64,
+    /// Delay_Req messages
+    pub delay_req: u64,
+    /// Pdelay_Req messages
+    pub pdelay_req: u64,
+    /// Pdelay_Resp messages
+    pub pdelay_resp: u64,
+    /// Follow_Up messages
+    pub follow_up: u64,
+    /// Delay_Resp messages
+    pub delay_resp: u64,
+    /// Pdelay_Resp_Follow_Up messages
+    pub pdelay_resp_follow_up: u64,
+    /// Announce messages
+    pub announce: u64,
+    /// Signaling messages
+    pub signaling: u64,
+    /// Management messages
+    pub management: u64,
+}
+
+impl MessageCounters {
+    pub(crate) fn count(&mut self, message_type: MessageType) {
+        let counter = match message_type {
+            MessageType::Sync => &mut self.sync,
+            MessageType::DelayReq => &mut self.delay_req,
+            MessageType::PDelayReq => &mut self.pdelay_req,
+            MessageType::PDelayResp => &mut self.pdelay_resp,
+            MessageType::FollowUp => &mut self.follow_up,
+            MessageType::DelayResp => &mut self.delay_resp,
+            MessageType::PDelayRespFollowUp => &mut self.pdelay_resp_follow_up,
+            MessageType::Announce => &mut self.announce,
+            MessageType::Signaling => &mut self.signaling,
+            MessageType::Management => &mut self.management,
+        };
+        *counter += 1;
+    }
+}
@@ -0,0 +1,17 @@
+/// Messages dropped by a port because of their authentication, see
+/// [`Port::authentication_counters`](`crate::port::Port::authentication_counters`)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthenticationCounters {
+    /// Messages without an AUTHENTICATION TLV
+    pub unauthenticated: u64,
+    /// Messages with an AUTHENTICATION TLV that could not be verified
+    pub authentication_failed: u64,
+}
+
+impl core::ops::AddAssign for AuthenticationCounters {
+    fn add_assign(&mut self, rhs: Self) {
+        self.unauthenticated += rhs.unauthenticated;
+        self.authentication_failed += rhs.authentication_failed;
+    }
+}
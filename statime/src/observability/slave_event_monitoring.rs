@@ -0,0 +1,84 @@
+use arrayvec::ArrayVec;
+
+use crate::datastructures::common::PortIdentity;
+
+/// The maximum number of records of each kind in a [`SlaveEventReport`]
+pub const MAX_SLAVE_EVENT_RECORDS: usize = 8;
+/// The maximum number of slave ports whose last [`SlaveEventReport`] a port
+/// keeps. When more slaves send reports, the least recently updated one is
+/// dropped.
+pub const MAX_SLAVE_EVENT_REPORTS: usize = 8;
+
+/// The timing data of a Sync message received by a slave port, a record of
+/// the SLAVE_RX_SYNC_TIMING_DATA TLV (*IEEE1588-2019 section 16.11.4.1*)
+///
+/// Timestamps are in nanoseconds since the start of the timescale of the
+/// clock, durations in nanoseconds.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncTimingRecord {
+    /// The sequence id of the Sync message
+    pub sequence_id: u16,
+    /// The origin timestamp of the Sync message, or the precise origin
+    /// timestamp of its Follow_Up
+    pub sync_origin_timestamp: u128,
+    /// The sum of the correction fields of the Sync message and its Follow_Up
+    pub total_correction: i128,
+    /// The cumulative rate offset of the Sync message, scaled by 2^41. Statime
+    /// does not track this, so it sends 0.
+    pub scaled_cumulative_rate_offset: i32,
+    /// The receive timestamp of the Sync message
+    pub sync_event_ingress_timestamp: u128,
+}
+
+/// The values computed by a slave port from a Sync message, a record of the
+/// SLAVE_RX_SYNC_COMPUTED_DATA TLV (*IEEE1588-2019 section 16.11.4.2*)
+///
+/// The TLV marks values as valid for all its records at once, so a value that
+/// is missing in one record is missing in all records of the same message.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncComputedRecord {
+    /// The sequence id of the Sync message
+    pub sequence_id: u16,
+    /// The offset of the slave from its master, in nanoseconds
+    pub offset_from_master: Option<i128>,
+    /// The mean path delay to the master, in nanoseconds
+    pub mean_path_delay: Option<i128>,
+    /// The neighbor rate ratio minus one, scaled by 2^41
+    pub scaled_neighbor_rate_ratio: Option<i32>,
+}
+
+/// The transmit timestamp of an event message sent by a slave port, a record
+/// of the SLAVE_TX_EVENT_TIMESTAMPS TLV (*IEEE1588-2019 section 16.11.4.3*)
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxEventRecord {
+    /// The sequence id of the event message
+    pub sequence_id: u16,
+    /// The transmit timestamp of the event message, in nanoseconds since the
+    /// start of the timescale of the clock
+    pub event_egress_timestamp: u128,
+}
+
+/// The slave event monitoring data last received from a slave port, see
+/// *IEEE1588-2019 section 16.11*
+///
+/// Records that are not part of the last received Signaling message of a
+/// slave are kept from earlier messages of that slave.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlaveEventReport {
+    /// The slave port that sent the report
+    pub source_port_identity: PortIdentity,
+    /// The master port the Sync messages of the
+    /// [`sync_timing`](`Self::sync_timing`) records came from
+    pub sync_source_port_identity: Option<PortIdentity>,
+    /// Timing data of received Sync messages, oldest first
+    pub sync_timing: ArrayVec<SyncTimingRecord, MAX_SLAVE_EVENT_RECORDS>,
+    /// Values computed from received Sync messages, oldest first
+    pub sync_computed: ArrayVec<SyncComputedRecord, MAX_SLAVE_EVENT_RECORDS>,
+    /// Transmit timestamps of the Delay_Req or Pdelay_Req messages of the
+    /// slave, oldest first
+    pub tx_events: ArrayVec<TxEventRecord, MAX_SLAVE_EVENT_RECORDS>,
+}
@@ -2,8 +2,9 @@ use core::iter::Fuse;
 
 use arrayvec::ArrayVec;
 
+use super::transparent::MessageKey;
 use crate::{
-    datastructures::common::{PortIdentity, Tlv, TlvSetIterator},
+    datastructures::common::{PortAddress, PortIdentity, Tlv, TlvSetIterator},
     filters::FilterUpdate,
 };
 
@@ -51,7 +52,10 @@ impl ForwardedTLVProvider for NoForwardedTLVs {
 /// The caller receives this from a [`PortAction::SendEvent`] and should return
 /// it to the [`Port`](`super::Port`) with
 /// [`Port::handle_send_timestamp`](`super::Port::handle_send_timestamp`) once
-/// the transmit timestamp of that packet is known.
+/// the transmit timestamp of that packet is known. Likewise for a
+/// [`TransparentPortAction::SendEvent`](`super::TransparentPortAction::SendEvent`)
+/// it should be returned with
+/// [`TransparentPort::handle_send_timestamp`](`super::TransparentPort::handle_send_timestamp`).
 ///
 /// This type is non-copy and non-clone on purpose to ensures a single
 /// [`handle_send_timestamp`](`super::Port::handle_send_timestamp`) per
@@ -65,6 +69,7 @@ pub struct TimestampContext {
 pub(super) enum TimestampContextInner {
     Sync {
         id: u16,
+        destination: Option<PortAddress>,
     },
     DelayReq {
         id: u16,
@@ -76,6 +81,40 @@ pub(super) enum TimestampContextInner {
         id: u16,
         requestor_identity: PortIdentity,
     },
+    OneStep,
+    Forward {
+        key: MessageKey,
+    },
+}
+
+/// Where the hardware needs to insert timing information into a one-step event
+/// message while sending it
+///
+/// See [`PortConfig::one_step`](`crate::config::PortConfig::one_step`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OneStepInsertion {
+    /// Byte offset of the 10 byte timestamp field that should be set to the
+    /// transmit time. This is the origin timestamp of a Sync message, and
+    /// `None` for a Pdelay_Resp message.
+    pub timestamp_offset: Option<usize>,
+    /// Byte offset of the 8 byte correction field. For a Pdelay_Resp message
+    /// the turnaround time, between receiving the Pdelay_Req and sending the
+    /// response, should be added to it.
+    pub correction_offset: usize,
+}
+
+impl OneStepInsertion {
+    /// The correction field starts at byte 8 of the header, the body of a Sync
+    /// message with the origin timestamp follows the 34 byte header.
+    pub(super) const SYNC: Self = Self {
+        timestamp_offset: Some(34),
+        correction_offset: 8,
+    };
+
+    pub(super) const PDELAY_RESP: Self = Self {
+        timestamp_offset: None,
+        correction_offset: 8,
+    };
 }
 
 /// An action the [`Port`](`super::Port`) needs the user to perform
@@ -92,10 +131,23 @@ pub enum PortAction<'a> {
     /// Packets marked as link local should be sent per the instructions
     /// for sending peer to peer delay mechanism messages of the relevant
     /// transport specification of PTP.
+    ///
+    /// Packets with a `destination` should be sent unicast to that address
+    /// instead of to the multicast address.
+    ///
+    /// Packets with `one_step` set are one-step messages, which the hardware
+    /// should complete while sending them as described by the
+    /// [`OneStepInsertion`]: for a Sync it sets the origin timestamp at
+    /// `timestamp_offset` to the transmit time, for a Pdelay_Resp, which has
+    /// no `timestamp_offset`, it adds the turnaround time to the correction
+    /// field at `correction_offset`. These don't need a transmit timestamp,
+    /// their context can be dropped.
     SendEvent {
         context: TimestampContext,
         data: &'a [u8],
         link_local: bool,
+        destination: Option<PortAddress>,
+        one_step: Option<OneStepInsertion>,
     },
     /// Send a general packet
     ///
@@ -104,7 +156,14 @@ pub enum PortAction<'a> {
     /// Packets marked as link local should be sent per the instructions
     /// for sending peer to peer delay mechanism messages of the relevant
     /// transport specification of PTP.
-    SendGeneral { data: &'a [u8], link_local: bool },
+    ///
+    /// Packets with a `destination` should be sent unicast to that address
+    /// instead of to the multicast address.
+    SendGeneral {
+        data: &'a [u8],
+        link_local: bool,
+        destination: Option<PortAddress>,
+    },
     /// Call [`Port::handle_announce_timer`](`super::Port::handle_announce_timer`) in `duration` from now
     ResetAnnounceTimer { duration: core::time::Duration },
     /// Call [`Port::handle_sync_timer`](`super::Port::handle_sync_timer`) in
@@ -116,6 +175,8 @@ pub enum PortAction<'a> {
     ResetAnnounceReceiptTimer { duration: core::time::Duration },
     /// Call [`Port::handle_filter_update_timer`](`super::Port::handle_filter_update_timer`) in `duration` from now
     ResetFilterUpdateTimer { duration: core::time::Duration },
+    /// Call [`Port::handle_unicast_timer`](`super::Port::handle_unicast_timer`) in `duration` from now
+    ResetUnicastTimer { duration: core::time::Duration },
     /// Forward this TLV to the announce timer call of all other ports.
     /// The receiver must ensure the TLV is yielded only once to the announce
     /// method of a port.
@@ -3,13 +3,14 @@ use rand::Rng;
 use super::{InBmca, Port, PortActionIterator, Running};
 use crate::{
     bmc::bmca::{BestAnnounceMessage, RecommendedState},
-    config::{AcceptableMasterList, LeapIndicator, TimePropertiesDS, TimeSource},
+    config::{AcceptableMasterList, TimePropertiesDS},
     datastructures::{
         datasets::{InternalCurrentDS, InternalDefaultDS, InternalParentDS},
         messages::Message,
     },
     filters::Filter,
     port::{
+        management::DefaultDSChanges,
         state::{PortState, SlaveState},
         PortAction,
     },
@@ -23,10 +24,18 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
         message: &Message<'b>,
         announce: crate::datastructures::messages::AnnounceMessage,
     ) -> PortActionIterator<'b> {
+        if self.announce_has_looped(message) {
+            log::debug!("Ignoring announce message that passed through this instance before");
+            return actions![];
+        }
+
         if self
             .bmca
             .register_announce_message(&message.header, &announce)
         {
+            self.handle_synchronization_metadata(message);
+            self.handle_power_profile_tlvs(message);
+
             actions![PortAction::ResetAnnounceReceiptTimer {
                 duration: self.config.announce_duration(&mut self.rng),
             }]
@@ -54,7 +63,9 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
         // in the global operation of the best master clock algorithm or in the update
         // of data sets. We still need them during the calculation of the recommended
         // port state though to avoid getting multiple masters in the segment.
-        if self.config.master_only || matches!(self.port_state, PortState::Faulty) {
+        if self.config.master_only
+            || matches!(self.port_state, PortState::Faulty | PortState::Disabled)
+        {
             None
         } else {
             self.lifecycle.local_best
@@ -69,6 +80,10 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
         self.lifecycle.local_best
     }
 
+    pub(crate) fn take_default_ds_changes(&mut self) -> DefaultDSChanges {
+        core::mem::take(&mut self.default_ds_changes)
+    }
+
     pub(crate) fn set_recommended_state(
         &mut self,
         recommended_state: RecommendedState,
@@ -94,12 +109,8 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
                 parent_ds.grandmaster_priority_1 = defaultds.priority_1;
                 parent_ds.grandmaster_priority_2 = defaultds.priority_2;
 
-                time_properties_ds.leap_indicator = LeapIndicator::NoLeap;
-                time_properties_ds.current_utc_offset = None;
-                time_properties_ds.ptp_timescale = true;
-                time_properties_ds.time_traceable = false;
-                time_properties_ds.frequency_traceable = false;
-                time_properties_ds.time_source = TimeSource::InternalOscillator;
+                // The time properties of the instance itself are applied by the
+                // instance once it knows it is the grandmaster
             }
             RecommendedState::M3(_) | RecommendedState::P1(_) | RecommendedState::P2(_) => {}
             RecommendedState::S1(announce_message) => {
@@ -145,7 +156,7 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
                 let remote_master = announce_message.header.source_port_identity;
 
                 let update_state = match &self.port_state {
-                    PortState::Faulty => false,
+                    PortState::Faulty | PortState::Disabled => false,
                     PortState::Listening | PortState::Master | PortState::Passive => true,
                     PortState::Slave(old_state) => old_state.remote_master() != remote_master,
                 };
@@ -165,7 +176,9 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
             RecommendedState::M1(_) | RecommendedState::M2(_) | RecommendedState::M3(_) => {
                 if default_ds.slave_only {
                     match self.port_state {
-                        PortState::Listening | PortState::Faulty => { /* do nothing */ }
+                        PortState::Listening | PortState::Faulty | PortState::Disabled => {
+                            /* do nothing */
+                        }
                         PortState::Slave(_) | PortState::Passive => {
                             self.set_forced_port_state(PortState::Listening);
 
@@ -192,7 +205,9 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
                                 PortAction::ResetSyncTimer { duration }
                             ];
                         }
-                        PortState::Master | PortState::Faulty => { /* do nothing */ }
+                        PortState::Master | PortState::Faulty | PortState::Disabled => {
+                            /* do nothing */
+                        }
                     }
                 }
             }
@@ -200,7 +215,7 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
                 PortState::Listening | PortState::Slave(_) | PortState::Master => {
                     self.set_forced_port_state(PortState::Passive)
                 }
-                PortState::Passive | PortState::Faulty => {}
+                PortState::Passive | PortState::Faulty | PortState::Disabled => {}
             },
         }
     }
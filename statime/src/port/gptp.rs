@@ -0,0 +1,457 @@
+//! Port behaviour specific to gPTP, see [`Profile::Gptp`]
+//!
+//! A gPTP port only exchanges time with its peer while it is asCapable, which
+//! is determined from the peer delay measurements. Loops of announce messages
+//! are detected with the path trace TLV.
+//!
+//! Sync and Follow_Up messages are not forwarded between the ports of a relay.
+//! Each master port sends its own, with the time of the local clock. That clock
+//! is synchronized to the grandmaster, so the cumulativeScaledRateOffset of
+//! the Follow_Up information TLV is not accumulated over the path but always
+//! zero.
+
+use arrayvec::ArrayVec;
+
+use super::{peer_delay::NeighborRateRatio, ForwardedTLV, Port, Running};
+#[cfg(doc)]
+use crate::config::Profile;
+use crate::{
+    config::ClockIdentity,
+    datastructures::{
+        common::{PortIdentity, Tlv, TlvSetBuilder, TlvType},
+        messages::{FollowUpInformationTlv, Message},
+    },
+    filters::Filter,
+    time::Duration,
+};
+
+/// Peer delays above this make a port not asCapable, the default
+/// neighborPropDelayThresh
+const NEIGHBOR_PROP_DELAY_THRESH_NANOS: i64 = 800;
+/// Number of peer delay requests in a row that may go unanswered before a port
+/// is no longer asCapable, the default allowedLostResponses
+const ALLOWED_LOST_RESPONSES: u8 = 3;
+/// Maximum number of clock identities in the path trace of our announce
+/// messages
+const MAX_PATH_TRACE_LENGTH: usize = 64;
+
+#[derive(Debug, Default)]
+pub(super) struct GptpState {
+    as_capable: bool,
+    lost_responses: u8,
+    pub(super) neighbor_rate_ratio: NeighborRateRatio,
+    /// The last path trace forwarded to this port, and who sent it
+    upstream_path_trace: Option<(PortIdentity, Option<PathTrace>)>,
+}
+
+type PathTrace = ArrayVec<ClockIdentity, MAX_PATH_TRACE_LENGTH>;
+
+impl GptpState {
+    /// Update asCapable with a completed peer delay measurement, see
+    /// *IEEE802.1AS-2020 section 11.2.2*
+    pub(super) fn handle_peer_delay(&mut self, peer_delay: Duration) {
+        self.lost_responses = 0;
+
+        let as_capable = peer_delay <= Duration::from_nanos(NEIGHBOR_PROP_DELAY_THRESH_NANOS);
+        if as_capable != self.as_capable {
+            if as_capable {
+                log::info!("Port became asCapable");
+            } else {
+                log::warn!("Port no longer asCapable, peer delay {:?}", peer_delay);
+            }
+        }
+        self.as_capable = as_capable;
+    }
+
+    /// Register that a peer delay request was not answered
+    pub(super) fn handle_lost_response(&mut self) {
+        self.lost_responses = self.lost_responses.saturating_add(1);
+        if self.as_capable && self.lost_responses > ALLOWED_LOST_RESPONSES {
+            log::warn!("Port no longer asCapable, peer stopped responding");
+            self.as_capable = false;
+        }
+    }
+}
+
+fn path_trace_identities<'a>(tlv: &'a Tlv<'_>) -> impl Iterator<Item = ClockIdentity> + 'a {
+    let value: &[u8] = tlv.value.as_ref();
+    value
+        .chunks_exact(8)
+        .map(|chunk| ClockIdentity(chunk.try_into().unwrap()))
+}
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Whether this port exchanges time with its peer, always true outside of
+    /// gPTP
+    pub(super) fn as_capable(&self) -> bool {
+        self.gptp.as_ref().map_or(true, |gptp| gptp.as_capable)
+    }
+
+    /// Whether an announce message already passed through this instance, see
+    /// *IEEE802.1AS-2020 section 10.3.11.2.1*
+    pub(super) fn announce_has_looped(&self, message: &Message<'_>) -> bool {
+        if self.gptp.is_none() {
+            return false;
+        }
+
+        let own_identity = self.lifecycle.state.default_ds.clock_identity;
+        message
+            .suffix
+            .tlv()
+            .filter(|tlv| tlv.tlv_type == TlvType::PathTrace)
+            .any(|tlv| path_trace_identities(&tlv).any(|identity| identity == own_identity))
+    }
+
+    /// Remember the path trace in a forwarded TLV, it is extended with our own
+    /// identity before sending it on. Returns false for other TLVs.
+    pub(super) fn take_forwarded_path_trace(&mut self, tlv: &ForwardedTLV<'_>) -> bool {
+        let Some(gptp) = &mut self.gptp else {
+            return false;
+        };
+        if tlv.tlv.tlv_type != TlvType::PathTrace {
+            return false;
+        }
+
+        let mut path = PathTrace::new();
+        let complete =
+            path_trace_identities(&tlv.tlv).all(|identity| path.try_push(identity).is_ok());
+        gptp.upstream_path_trace = Some((tlv.sender_identity, complete.then_some(path)));
+
+        true
+    }
+
+    /// Add the path trace TLV to an announce message, see *IEEE802.1AS-2020
+    /// section 10.3.8.23*
+    ///
+    /// The TLV is left out when it would not fit in `margin` bytes.
+    pub(super) fn add_path_trace(&self, tlv_builder: &mut TlvSetBuilder<'_>, margin: usize) {
+        let Some(gptp) = &self.gptp else {
+            return;
+        };
+
+        let mut path = match &gptp.upstream_path_trace {
+            Some((sender, path))
+                if *sender == self.lifecycle.state.parent_ds.parent_port_identity =>
+            {
+                match path {
+                    Some(path) => path.clone(),
+                    None => return,
+                }
+            }
+            // We are the grandmaster, or have not heard from upstream yet
+            _ => PathTrace::new(),
+        };
+        if path
+            .try_push(self.lifecycle.state.default_ds.clock_identity)
+            .is_err()
+        {
+            return;
+        }
+
+        let mut value = [0; MAX_PATH_TRACE_LENGTH * 8];
+        for (identity, buffer) in path.iter().zip(value.chunks_exact_mut(8)) {
+            buffer.copy_from_slice(&identity.0);
+        }
+        let tlv = Tlv {
+            tlv_type: TlvType::PathTrace,
+            value: value[..path.len() * 8].into(),
+        };
+
+        if tlv.wire_size() >= margin {
+            return;
+        }
+        // Will not fail as we checked there is enough space in the buffer
+        tlv_builder.add(tlv).unwrap();
+    }
+
+    /// Add the Follow_Up information TLV to a follow up message
+    ///
+    /// Time is passed on from the local clock, which is synchronized to the
+    /// grandmaster. So for both a grandmaster and a relay the rate ratio to
+    /// the grandmaster is one, and the TLV only holds zeroes. The rate ratio
+    /// reported by our own master is not accumulated into it.
+    pub(super) fn add_follow_up_information(&self, tlv_builder: &mut TlvSetBuilder<'_>) {
+        if self.gptp.is_none() {
+            return;
+        }
+
+        let mut value = [0; 28];
+        let result = FollowUpInformationTlv::default()
+            .serialize_value(&mut value)
+            .and_then(|value| {
+                tlv_builder.add(Tlv {
+                    tlv_type: TlvType::OrganizationExtension,
+                    value: value.into(),
+                })
+            });
+        if let Err(error) = result {
+            log::error!(
+                "Statime bug: Could not build follow up information TLV: {:?}",
+                error
+            );
+        }
+    }
+
+    /// Log the rate ratio our master reports in a follow up message
+    ///
+    /// The local clock is steered by the filter, so the rate ratio is not used
+    /// to correct the measurements.
+    pub(super) fn handle_follow_up_information(&self, message: &Message<'_>) {
+        if self.gptp.is_none() {
+            return;
+        }
+
+        let information = message
+            .suffix
+            .tlv()
+            .find_map(|tlv| FollowUpInformationTlv::from_tlv(&tlv).ok().flatten());
+        match information {
+            Some(information) => log::trace!(
+                "Rate ratio of grandmaster to master: {}",
+                information.rate_ratio()
+            ),
+            None => log::debug!("Follow up without follow up information TLV"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{AcceptAnyMaster, DelayMechanism, Profile},
+        datastructures::messages::{MessageBody, PDelayRespMessage},
+        filters::BasicFilter,
+        port::{
+            state::PortState,
+            tests::{setup_test_port, setup_test_state, TestClock},
+            ForwardedTLVProvider, NoForwardedTLVs, PortAction,
+        },
+        ptp_instance::PtpInstanceState,
+        time::{Interval, Time},
+    };
+
+    type TestPort<'a> =
+        Port<Running<'a>, AcceptAnyMaster, rand::rngs::mock::StepRng, TestClock, BasicFilter>;
+
+    const OWN_IDENTITY: ClockIdentity = ClockIdentity([1; 8]);
+
+    fn setup_gptp_state() -> atomic_refcell::AtomicRefCell<PtpInstanceState> {
+        let state = setup_test_state();
+        let mut state_ref = state.borrow_mut();
+        state_ref.default_ds.profile = Profile::Gptp;
+        state_ref.default_ds.clock_identity = OWN_IDENTITY;
+        state_ref.parent_ds.parent_port_identity.clock_identity = OWN_IDENTITY;
+        drop(state_ref);
+        state
+    }
+
+    fn setup_gptp_port(state: &atomic_refcell::AtomicRefCell<PtpInstanceState>) -> TestPort<'_> {
+        let mut port = setup_test_port(state);
+        port.config.delay_mechanism = DelayMechanism::P2P {
+            interval: Interval::from_log_2(0),
+        };
+        port.set_forced_port_state(PortState::Master);
+        port
+    }
+
+    // Run a peer delay measurement with a one-step responder
+    fn measure_peer_delay(port: &mut TestPort<'_>, start_nanos: u64, delay_nanos: u64) {
+        let mut actions = port.send_delay_request();
+        let Some(PortAction::ResetDelayRequestTimer { .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+        let Some(PortAction::SendEvent { context, data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+        let request_header = Message::deserialize(data).unwrap().header;
+        drop(actions);
+
+        let mut actions = port.handle_send_timestamp(context, Time::from_nanos(start_nanos));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        let mut actions = port.handle_peer_delay_response(
+            crate::datastructures::messages::Header {
+                sequence_id: request_header.sequence_id,
+                ..Default::default()
+            },
+            PDelayRespMessage {
+                request_receive_timestamp: Time::from_nanos(start_nanos + delay_nanos).into(),
+                requesting_port_identity: request_header.source_port_identity,
+            },
+            Time::from_nanos(start_nanos + 2 * delay_nanos),
+        );
+        assert!(actions.next().is_none());
+    }
+
+    #[test]
+    fn test_as_capable() {
+        let state = setup_gptp_state();
+        let mut port = setup_gptp_port(&state);
+
+        // Nothing is sent before the peer delay is known
+        assert!(!port.as_capable());
+        let mut actions = port.send_sync();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetSyncTimer { .. })
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        measure_peer_delay(&mut port, 1_000_000, 500);
+        assert!(port.as_capable());
+
+        let mut actions = port.send_sync();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetSyncTimer { .. })
+        ));
+        let Some(PortAction::SendEvent {
+            context,
+            link_local: true,
+            ..
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        drop(actions);
+
+        let mut actions = port.handle_send_timestamp(context, Time::from_micros(2_000));
+        let Some(PortAction::SendGeneral {
+            data,
+            link_local: true,
+            destination: None,
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        let follow_up = Message::deserialize(data).unwrap();
+        assert!(matches!(follow_up.body, MessageBody::FollowUp(_)));
+        let information = follow_up
+            .suffix
+            .tlv()
+            .find_map(|tlv| FollowUpInformationTlv::from_tlv(&tlv).unwrap())
+            .unwrap();
+        // Time is sent from the synchronized local clock, not forwarded
+        assert_eq!(information.cumulative_scaled_rate_offset, 0);
+        assert_eq!(information, FollowUpInformationTlv::default());
+        drop(actions);
+
+        // A link delay above the threshold
+        measure_peer_delay(&mut port, 2_000_000, 1_000);
+        assert!(!port.as_capable());
+    }
+
+    #[test]
+    fn test_lost_responses() {
+        let state = setup_gptp_state();
+        let mut port = setup_gptp_port(&state);
+
+        measure_peer_delay(&mut port, 1_000_000, 500);
+        assert!(port.as_capable());
+
+        for _ in 0..=ALLOWED_LOST_RESPONSES {
+            assert!(port.as_capable());
+            let _ = port.send_delay_request();
+        }
+        let _ = port.send_delay_request();
+        assert!(!port.as_capable());
+
+        measure_peer_delay(&mut port, 2_000_000, 500);
+        assert!(port.as_capable());
+    }
+
+    struct SingleTlv(Option<ForwardedTLV<'static>>);
+
+    impl ForwardedTLVProvider for SingleTlv {
+        fn next_if_smaller(&mut self, max_size: usize) -> Option<ForwardedTLV<'_>> {
+            self.0.take().filter(|tlv| tlv.size() <= max_size)
+        }
+    }
+
+    fn announce_path_trace(
+        port: &mut TestPort<'_>,
+        provider: &mut impl ForwardedTLVProvider,
+    ) -> PathTrace {
+        let mut actions = port.send_announce(provider);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral {
+            data,
+            link_local: true,
+            destination: None,
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+
+        let announce = Message::deserialize(data).unwrap();
+        let mut tlvs = announce.suffix.tlv();
+        let tlv = tlvs.next().unwrap();
+        assert_eq!(tlv.tlv_type, TlvType::PathTrace);
+        assert!(tlvs.next().is_none());
+        path_trace_identities(&tlv).collect()
+    }
+
+    #[test]
+    fn test_path_trace() {
+        let state = setup_gptp_state();
+        let mut port = setup_gptp_port(&state);
+        port.gptp.as_mut().unwrap().as_capable = true;
+
+        // As grandmaster the path only contains us
+        let path = announce_path_trace(&mut port, &mut NoForwardedTLVs);
+        assert_eq!(path.as_slice(), &[OWN_IDENTITY]);
+
+        // An announce that passed us before is ignored
+        let mut announce = Message::announce(&state.borrow(), PortIdentity::default(), 0);
+        let MessageBody::Announce(announce_body) = announce.body else {
+            panic!("Unexpected message");
+        };
+        let mut tlv_buffer = [0; 64];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        tlv_builder
+            .add(Tlv {
+                tlv_type: TlvType::PathTrace,
+                value: (&[[2; 8], OWN_IDENTITY.0].concat()[..]).into(),
+            })
+            .unwrap();
+        announce.suffix = tlv_builder.build();
+        assert!(port
+            .handle_announce(&announce, announce_body)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_path_trace_relay() {
+        let state = setup_gptp_state();
+        let upstream = PortIdentity {
+            clock_identity: ClockIdentity([2; 8]),
+            port_number: 1,
+        };
+        state.borrow_mut().parent_ds.parent_port_identity = upstream;
+
+        let mut port = setup_gptp_port(&state);
+        port.gptp.as_mut().unwrap().as_capable = true;
+
+        // The path from upstream is extended with our identity
+        let mut provider = SingleTlv(Some(ForwardedTLV {
+            tlv: Tlv {
+                tlv_type: TlvType::PathTrace,
+                value: (&[3u8; 8][..]).into(),
+            },
+            sender_identity: upstream,
+        }));
+        let path = announce_path_trace(&mut port, &mut provider);
+        assert_eq!(path.as_slice(), &[ClockIdentity([3; 8]), OWN_IDENTITY]);
+
+        // And remembered for the next announce
+        let path = announce_path_trace(&mut port, &mut NoForwardedTLVs);
+        assert_eq!(path.as_slice(), &[ClockIdentity([3; 8]), OWN_IDENTITY]);
+    }
+}
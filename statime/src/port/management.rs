@@ -0,0 +1,986 @@
+use core::ops::RangeInclusive;
+
+use rand::Rng;
+
+use super::{state::PortState, Port, PortAction, PortActionIterator, Running};
+#[cfg(doc)]
+use crate::PtpInstance;
+use crate::{
+    bmc::acceptable_master::AcceptableMasterList,
+    clock::Clock,
+    config::{DelayMechanism, LeapIndicator, PortConfig},
+    datastructures::{
+        common::{ClockIdentity, PortIdentity, Tlv, TlvSetBuilder, TlvType},
+        datasets::InternalDefaultDS,
+        messages::{
+            Header, ManagementAction, ManagementData, ManagementErrorId, ManagementErrorStatus,
+            ManagementId, ManagementMessage, ManagementTlv, Message,
+        },
+    },
+    filters::Filter,
+    observability::performance::PERFORMANCE_RECORDS,
+    time::{Duration, Interval},
+};
+
+/// Changes to the instance wide default dataset requested through management
+/// messages.
+///
+/// A running [`Port`] can't modify the instance state, so these are applied by
+/// the [`PtpInstance`] at the start of its next BMCA.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DefaultDSChanges {
+    priority_1: Option<u8>,
+    priority_2: Option<u8>,
+    domain_number: Option<u8>,
+    slave_only: Option<bool>,
+}
+
+impl DefaultDSChanges {
+    pub(crate) fn apply(&self, default_ds: &mut InternalDefaultDS) {
+        if let Some(priority_1) = self.priority_1 {
+            default_ds.priority_1 = priority_1;
+        }
+        if let Some(priority_2) = self.priority_2 {
+            default_ds.priority_2 = priority_2;
+        }
+        if let Some(domain_number) = self.domain_number {
+            default_ds.domain_number = domain_number;
+        }
+        if let Some(slave_only) = self.slave_only {
+            default_ds.slave_only = slave_only;
+        }
+    }
+}
+
+// Enough room for the largest management TLV we send
+// (PERFORMANCE_MONITORING_RECORD)
+const MANAGEMENT_TLV_SIZE: usize = 160;
+
+/// The log message intervals that can be set through management messages,
+/// from a message per 128 seconds to 128 messages per second. The profile of
+/// the instance may limit these further.
+const LOG_INTERVALS: RangeInclusive<i8> = -7..=7;
+
+// Management message handling of the port, see IEEE1588-2019 section 15
+impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
+    pub(super) fn handle_management<'b>(
+        &'b mut self,
+        message: &Message<'b>,
+        management: ManagementMessage,
+    ) -> PortActionIterator<'b> {
+        if !self.is_management_target(management.target_port_identity) {
+            return actions![];
+        }
+
+        let response_action = match management.action {
+            ManagementAction::GET | ManagementAction::SET => ManagementAction::RESPONSE,
+            ManagementAction::COMMAND => ManagementAction::ACKNOWLEDGE,
+            // Responses are meant for management nodes, which we are not
+            ManagementAction::RESPONSE
+            | ManagementAction::ACKNOWLEDGE
+            | ManagementAction::Reserved => return actions![],
+        };
+
+        let Some(tlv) = message
+            .suffix
+            .tlv()
+            .find(|tlv| tlv.tlv_type == TlvType::Management)
+        else {
+            log::warn!("Received management message without management TLV");
+            return actions![];
+        };
+        let management_tlv = match ManagementTlv::from_tlv(&tlv) {
+            Ok(management_tlv) => management_tlv,
+            Err(error) => {
+                log::warn!("Could not parse management TLV: {:?}", error);
+                return actions![];
+            }
+        };
+
+        log::debug!(
+            "Received management {:?} for {:?}",
+            management.action,
+            management_tlv.management_id
+        );
+
+        let mut extra_action = None;
+        let result = match management.action {
+            ManagementAction::GET => self.management_get_tlv(management_tlv),
+            ManagementAction::SET => self.management_set(management_tlv),
+            _ => self.management_command(management_tlv.management_id, &mut extra_action),
+        };
+
+        self.send_management_response(
+            message.header,
+            &management,
+            response_action,
+            management_tlv.management_id,
+            result,
+            extra_action,
+        )
+    }
+
+    fn is_management_target(&self, target: PortIdentity) -> bool {
+        (target.clock_identity == self.port_identity.clock_identity
+            || target.clock_identity == ClockIdentity([0xff; 8]))
+            && (target.port_number == self.port_identity.port_number
+                || target.port_number == 0xffff)
+    }
+
+    // The default dataset including the changes that are not yet applied
+    fn management_default_ds(&self) -> InternalDefaultDS {
+        let mut default_ds = self.lifecycle.state.default_ds;
+        self.default_ds_changes.apply(&mut default_ds);
+        default_ds
+    }
+
+    fn management_get_tlv(
+        &self,
+        management_tlv: ManagementTlv,
+    ) -> Result<ManagementData, ManagementErrorId> {
+        match management_tlv.management_id {
+            // The only GET request with a dataField, which selects the record
+            ManagementId::PerformanceMonitoringRecord => {
+                let index = match management_tlv.data.get(..2) {
+                    Some(index) => u16::from_be_bytes([index[0], index[1]]),
+                    None => 0,
+                };
+                if index >= PERFORMANCE_RECORDS {
+                    return Err(ManagementErrorId::WrongValue);
+                }
+                let record = self
+                    .performance_record(index)
+                    .ok_or(ManagementErrorId::Unpopulated)?;
+                Ok(ManagementData::PerformanceMonitoringRecord { index, record })
+            }
+            management_id => self.management_get(management_id),
+        }
+    }
+
+    fn management_get(
+        &self,
+        management_id: ManagementId,
+    ) -> Result<ManagementData, ManagementErrorId> {
+        let default_ds = self.management_default_ds();
+        let state = &self.lifecycle.state;
+        let time_properties_ds = &state.time_properties_ds;
+
+        let data = match management_id {
+            ManagementId::NullPtpManagement => ManagementData::Empty(management_id),
+            ManagementId::DefaultDataSet => ManagementData::DefaultDataSet {
+                two_step: !self.config.one_step,
+                slave_only: default_ds.slave_only,
+                number_ports: default_ds.number_ports,
+                priority_1: default_ds.priority_1,
+                clock_quality: default_ds.clock_quality,
+                priority_2: default_ds.priority_2,
+                clock_identity: default_ds.clock_identity,
+                domain_number: default_ds.domain_number,
+            },
+            ManagementId::CurrentDataSet => ManagementData::CurrentDataSet {
+                steps_removed: state.current_ds.steps_removed,
+                offset_from_master: state.current_ds.offset_from_master,
+                mean_path_delay: self.mean_delay.unwrap_or(Duration::ZERO),
+            },
+            ManagementId::ParentDataSet => ManagementData::ParentDataSet {
+                parent_port_identity: state.parent_ds.parent_port_identity,
+                // We don't compute parent statistics, see 8.2.3.3 to 8.2.3.5
+                parent_stats: false,
+                observed_parent_offset_scaled_log_variance: 0xffff,
+                observed_parent_clock_phase_change_rate: 0x7fffffff,
+                grandmaster_priority_1: state.parent_ds.grandmaster_priority_1,
+                grandmaster_clock_quality: state.parent_ds.grandmaster_clock_quality,
+                grandmaster_priority_2: state.parent_ds.grandmaster_priority_2,
+                grandmaster_identity: state.parent_ds.grandmaster_identity,
+            },
+            ManagementId::TimePropertiesDataSet => ManagementData::TimePropertiesDataSet {
+                current_utc_offset: time_properties_ds.current_utc_offset.unwrap_or_default(),
+                leap61: time_properties_ds.leap_indicator == LeapIndicator::Leap61,
+                leap59: time_properties_ds.leap_indicator == LeapIndicator::Leap59,
+                current_utc_offset_valid: time_properties_ds.current_utc_offset.is_some(),
+                ptp_timescale: time_properties_ds.ptp_timescale,
+                time_traceable: time_properties_ds.time_traceable,
+                frequency_traceable: time_properties_ds.frequency_traceable,
+                time_source: time_properties_ds.time_source,
+            },
+            ManagementId::PortDataSet => {
+                let port_ds = self.port_ds();
+                // Unlike the PortDS, the management TLV has sub-nanosecond precision
+                let peer_mean_path_delay = match self.config.delay_mechanism {
+                    DelayMechanism::E2E { .. } => Duration::ZERO,
+                    DelayMechanism::P2P { .. } => self.mean_delay.unwrap_or(Duration::ZERO),
+                };
+
+                ManagementData::PortDataSet {
+                    port_identity: port_ds.port_identity,
+                    port_state: port_ds.port_state.to_primitive(),
+                    log_min_delay_req_interval: port_ds.log_min_delay_req_interval,
+                    peer_mean_path_delay,
+                    log_announce_interval: port_ds.log_announce_interval,
+                    announce_receipt_timeout: port_ds.announce_receipt_timeout,
+                    log_sync_interval: port_ds.log_sync_interval,
+                    delay_mechanism: port_ds.delay_mechanism.to_primitive(),
+                    log_min_pdelay_req_interval: port_ds.log_min_pdelay_req_interval,
+                    version_number: port_ds.version_number,
+                    minor_version_number: port_ds.minor_version_number,
+                }
+            }
+            ManagementId::Priority1 => ManagementData::Priority1(default_ds.priority_1),
+            ManagementId::Priority2 => ManagementData::Priority2(default_ds.priority_2),
+            ManagementId::Domain => ManagementData::Domain(default_ds.domain_number),
+            ManagementId::SlaveOnly => ManagementData::SlaveOnly(default_ds.slave_only),
+            ManagementId::LogAnnounceInterval => {
+                ManagementData::LogAnnounceInterval(self.config.announce_interval.as_log_2())
+            }
+            ManagementId::AnnounceReceiptTimeout => {
+                ManagementData::AnnounceReceiptTimeout(self.config.announce_receipt_timeout)
+            }
+            ManagementId::LogSyncInterval => {
+                ManagementData::LogSyncInterval(self.config.sync_interval.as_log_2())
+            }
+            ManagementId::VersionNumber => ManagementData::VersionNumber {
+                version_number: 2,
+                minor_version_number: 1,
+            },
+            ManagementId::ClockAccuracy => {
+                ManagementData::ClockAccuracy(default_ds.clock_quality.clock_accuracy)
+            }
+            ManagementId::UtcProperties => ManagementData::UtcProperties {
+                current_utc_offset: time_properties_ds.current_utc_offset.unwrap_or_default(),
+                leap61: time_properties_ds.leap_indicator == LeapIndicator::Leap61,
+                leap59: time_properties_ds.leap_indicator == LeapIndicator::Leap59,
+                current_utc_offset_valid: time_properties_ds.current_utc_offset.is_some(),
+            },
+            ManagementId::TraceabilityProperties => ManagementData::TraceabilityProperties {
+                time_traceable: time_properties_ds.time_traceable,
+                frequency_traceable: time_properties_ds.frequency_traceable,
+            },
+            ManagementId::TimescaleProperties => ManagementData::TimescaleProperties {
+                ptp_timescale: time_properties_ds.ptp_timescale,
+                time_source: time_properties_ds.time_source,
+            },
+            ManagementId::DelayMechanism => {
+                ManagementData::DelayMechanism(self.port_ds().delay_mechanism.to_primitive())
+            }
+            ManagementId::LogMinPdelayReqInterval => match self.config.delay_mechanism {
+                DelayMechanism::P2P { interval } => {
+                    ManagementData::LogMinPdelayReqInterval(interval.as_log_2())
+                }
+                DelayMechanism::E2E { .. } => return Err(ManagementErrorId::NotSupported),
+            },
+            ManagementId::Reserved(_) => return Err(ManagementErrorId::NoSuchId),
+            _ => return Err(ManagementErrorId::NotSupported),
+        };
+
+        Ok(data)
+    }
+
+    fn management_set(
+        &mut self,
+        management_tlv: ManagementTlv,
+    ) -> Result<ManagementData, ManagementErrorId> {
+        let management_id = management_tlv.management_id;
+
+        match management_id {
+            ManagementId::NullPtpManagement
+            | ManagementId::Priority1
+            | ManagementId::Priority2
+            | ManagementId::Domain
+            | ManagementId::SlaveOnly
+            | ManagementId::LogAnnounceInterval
+            | ManagementId::AnnounceReceiptTimeout
+            | ManagementId::LogSyncInterval
+            | ManagementId::LogMinPdelayReqInterval => {}
+            ManagementId::DefaultDataSet
+            | ManagementId::CurrentDataSet
+            | ManagementId::ParentDataSet
+            | ManagementId::TimePropertiesDataSet
+            | ManagementId::PortDataSet
+            | ManagementId::VersionNumber
+            | ManagementId::ClockAccuracy
+            | ManagementId::UtcProperties
+            | ManagementId::TraceabilityProperties
+            | ManagementId::TimescaleProperties
+            | ManagementId::DelayMechanism
+            | ManagementId::PerformanceMonitoringRecord => {
+                return Err(ManagementErrorId::NotSetable)
+            }
+            ManagementId::Reserved(_) => return Err(ManagementErrorId::NoSuchId),
+            _ => return Err(ManagementErrorId::NotSupported),
+        }
+
+        let data = match ManagementData::deserialize(management_id, management_tlv.data) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Err(ManagementErrorId::NotSupported),
+            Err(_) => return Err(ManagementErrorId::WrongLength),
+        };
+
+        match data {
+            ManagementData::Priority1(priority_1) => {
+                self.default_ds_changes.priority_1 = Some(priority_1)
+            }
+            ManagementData::Priority2(priority_2) => {
+                self.default_ds_changes.priority_2 = Some(priority_2)
+            }
+            ManagementData::Domain(domain_number) => {
+                // Domains 128 through 255 are reserved, see table 2
+                if domain_number >= 128 {
+                    return Err(ManagementErrorId::WrongValue);
+                }
+                self.default_ds_changes.domain_number = Some(domain_number)
+            }
+            ManagementData::SlaveOnly(slave_only) => {
+                self.default_ds_changes.slave_only = Some(slave_only)
+            }
+            ManagementData::LogAnnounceInterval(log_interval) => {
+                self.set_log_interval(log_interval, |config| &mut config.announce_interval)?
+            }
+            ManagementData::AnnounceReceiptTimeout(timeout) => {
+                // See 7.7.3.1
+                if timeout < 2 {
+                    return Err(ManagementErrorId::WrongValue);
+                }
+                self.config.announce_receipt_timeout = timeout
+            }
+            ManagementData::LogSyncInterval(log_interval) => {
+                self.set_log_interval(log_interval, |config| &mut config.sync_interval)?
+            }
+            ManagementData::LogMinPdelayReqInterval(log_interval) => {
+                if let DelayMechanism::E2E { .. } = self.config.delay_mechanism {
+                    return Err(ManagementErrorId::NotSupported);
+                }
+                self.set_log_interval(log_interval, |config| match &mut config.delay_mechanism {
+                    DelayMechanism::P2P { interval } | DelayMechanism::E2E { interval } => interval,
+                })?
+            }
+            _ => {}
+        }
+
+        // The response contains the new values
+        self.management_get(management_id)
+    }
+
+    /// Change an interval of the port configuration, refusing values outside
+    /// of [`LOG_INTERVALS`] or the limits of the profile
+    fn set_log_interval(
+        &mut self,
+        log_interval: i8,
+        interval: fn(&mut PortConfig<()>) -> &mut Interval,
+    ) -> Result<(), ManagementErrorId> {
+        if !LOG_INTERVALS.contains(&log_interval) {
+            return Err(ManagementErrorId::WrongValue);
+        }
+
+        let previous = core::mem::replace(
+            interval(&mut self.config),
+            Interval::from_log_2(log_interval),
+        );
+        let profile = self.lifecycle.state.default_ds.profile;
+        if profile.check_port_config(&self.config).is_err() {
+            *interval(&mut self.config) = previous;
+            return Err(ManagementErrorId::WrongValue);
+        }
+
+        Ok(())
+    }
+
+    fn management_command(
+        &mut self,
+        management_id: ManagementId,
+        extra_action: &mut Option<PortAction<'static>>,
+    ) -> Result<ManagementData, ManagementErrorId> {
+        match management_id {
+            ManagementId::NullPtpManagement => {}
+            ManagementId::EnablePort => {
+                if matches!(self.port_state, PortState::Disabled) {
+                    log::info!("Enabling port {}", self.port_identity.port_number);
+                    self.set_forced_port_state(PortState::Listening);
+                    // consistent with Port<InBmca>::new()
+                    *extra_action = Some(PortAction::ResetAnnounceReceiptTimer {
+                        duration: self.config.announce_duration(&mut self.rng),
+                    });
+                }
+            }
+            ManagementId::DisablePort => {
+                if !matches!(self.port_state, PortState::Disabled) {
+                    log::info!("Disabling port {}", self.port_identity.port_number);
+                    self.set_forced_port_state(PortState::Disabled);
+                }
+            }
+            ManagementId::Reserved(_) => return Err(ManagementErrorId::NoSuchId),
+            _ => return Err(ManagementErrorId::NotSupported),
+        }
+
+        Ok(ManagementData::Empty(management_id))
+    }
+
+    fn send_management_response(
+        &mut self,
+        request_header: Header,
+        request: &ManagementMessage,
+        action: ManagementAction,
+        management_id: ManagementId,
+        result: Result<ManagementData, ManagementErrorId>,
+        extra_action: Option<PortAction<'static>>,
+    ) -> PortActionIterator<'_> {
+        let mut value = [0; MANAGEMENT_TLV_SIZE];
+        let serialized = match result {
+            Ok(data) => {
+                value[..2].copy_from_slice(&data.management_id().to_primitive().to_be_bytes());
+                data.serialize(&mut value[2..])
+                    .map(|length| (TlvType::Management, 2 + length))
+            }
+            Err(management_error_id) => {
+                log::debug!("Management request failed: {:?}", management_error_id);
+                ManagementErrorStatus {
+                    management_error_id,
+                    management_id,
+                }
+                .serialize(&mut value)
+                .map(|length| (TlvType::ManagementErrorStatus, length))
+            }
+        };
+        let (tlv_type, length) = match serialized {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                log::error!("Could not serialize management TLV: {:?}", error);
+                return actions![];
+            }
+        };
+
+        let mut tlv_buffer = [0; MANAGEMENT_TLV_SIZE + 4];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        if let Err(error) = tlv_builder.add(Tlv {
+            tlv_type,
+            value: value[..length].into(),
+        }) {
+            log::error!("Could not serialize management TLV: {:?}", error);
+            return actions![];
+        }
+
+        let response = Message::management_response(
+            &self.lifecycle.state.default_ds,
+            self.port_identity,
+            request_header,
+            request,
+            action,
+            tlv_builder.build(),
+        );
+
+        let packet_length = match response.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!("Could not serialize management response: {:?}", error);
+                return actions![];
+            }
+        };
+        let Some(packet_length) = self.finish_message(packet_length) else {
+            return actions![];
+        };
+
+        let send = PortAction::SendGeneral {
+            data: &self.packet_buffer[..packet_length],
+            link_local: false,
+            destination: None,
+        };
+        match extra_action {
+            Some(extra_action) => actions![send, extra_action],
+            None => actions![send],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Profile,
+        datastructures::messages::MessageBody,
+        port::tests::{setup_test_port, setup_test_state},
+    };
+
+    const WILDCARD: PortIdentity = PortIdentity {
+        clock_identity: ClockIdentity([0xff; 8]),
+        port_number: 0xffff,
+    };
+
+    fn management_request<'a>(
+        buffer: &'a mut [u8],
+        target_port_identity: PortIdentity,
+        action: ManagementAction,
+        management_id: ManagementId,
+        data: &[u8],
+    ) -> Message<'a> {
+        let mut value = [0; MANAGEMENT_TLV_SIZE];
+        value[..2].copy_from_slice(&management_id.to_primitive().to_be_bytes());
+        value[2..2 + data.len()].copy_from_slice(data);
+
+        let mut tlv_builder = TlvSetBuilder::new(buffer);
+        tlv_builder
+            .add(Tlv {
+                tlv_type: TlvType::Management,
+                value: value[..2 + data.len()].into(),
+            })
+            .unwrap();
+
+        Message {
+            header: Header {
+                sequence_id: 17,
+                source_port_identity: PortIdentity {
+                    port_number: 5,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            body: MessageBody::Management(ManagementMessage {
+                target_port_identity,
+                starting_boundary_hops: 3,
+                boundary_hops: 1,
+                action,
+            }),
+            suffix: tlv_builder.build(),
+        }
+    }
+
+    fn check_response_header(message: &Message<'_>, action: ManagementAction) {
+        assert_eq!(message.header.sequence_id, 17);
+        let MessageBody::Management(management) = message.body else {
+            panic!("Unexpected message type");
+        };
+        assert_eq!(management.action, action);
+        assert_eq!(
+            management.target_port_identity,
+            PortIdentity {
+                port_number: 5,
+                ..Default::default()
+            }
+        );
+        assert_eq!(management.starting_boundary_hops, 2);
+        assert_eq!(management.boundary_hops, 2);
+    }
+
+    fn response_data(message: &Message<'_>) -> ManagementData {
+        let tlv = message.suffix.tlv().next().unwrap();
+        assert_eq!(tlv.tlv_type, TlvType::Management);
+        let management_tlv = ManagementTlv::from_tlv(&tlv).unwrap();
+        ManagementData::deserialize(management_tlv.management_id, management_tlv.data)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn response_error(message: &Message<'_>) -> ManagementErrorStatus {
+        let tlv = message.suffix.tlv().next().unwrap();
+        assert_eq!(tlv.tlv_type, TlvType::ManagementErrorStatus);
+        ManagementErrorStatus::deserialize(&tlv.value).unwrap()
+    }
+
+    #[test]
+    fn test_management_get() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::GET,
+            ManagementId::DefaultDataSet,
+            &[],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral {
+            data,
+            link_local: false,
+            destination: None,
+        }) = actions.next()
+        else {
+            panic!("Unexpected resulting action");
+        };
+        assert!(actions.next().is_none());
+
+        let response = Message::deserialize(data).unwrap();
+        check_response_header(&response, ManagementAction::RESPONSE);
+        let ManagementData::DefaultDataSet {
+            priority_1,
+            priority_2,
+            domain_number,
+            slave_only,
+            ..
+        } = response_data(&response)
+        else {
+            panic!("Unexpected management data");
+        };
+        assert_eq!(priority_1, 255);
+        assert_eq!(priority_2, 255);
+        assert_eq!(domain_number, 0);
+        assert!(!slave_only);
+    }
+
+    #[test]
+    fn test_management_two_step() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let two_step = |port: &mut Port<Running<'_>, _, _, _, _>| {
+            let mut buffer = [0; 128];
+            let request = management_request(
+                &mut buffer,
+                WILDCARD,
+                ManagementAction::GET,
+                ManagementId::DefaultDataSet,
+                &[],
+            );
+            let MessageBody::Management(management) = request.body else {
+                unreachable!()
+            };
+            let mut actions = port.handle_management(&request, management);
+            let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+                panic!("Unexpected resulting action");
+            };
+            let ManagementData::DefaultDataSet { two_step, .. } =
+                response_data(&Message::deserialize(data).unwrap())
+            else {
+                panic!("Unexpected management data");
+            };
+            two_step
+        };
+
+        assert!(two_step(&mut port));
+        port.config.one_step = true;
+        assert!(!two_step(&mut port));
+    }
+
+    #[test]
+    fn test_port_ds() {
+        use crate::observability::port as ds;
+
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let port_ds = port.port_ds();
+        assert_eq!(port_ds.port_state, ds::PortState::Listening);
+        assert_eq!(port_ds.delay_mechanism, ds::DelayMechanism::E2E);
+        assert_eq!(port_ds.log_min_delay_req_interval, 1);
+        assert_eq!(port_ds.log_min_pdelay_req_interval, 0x7f);
+        assert_eq!(port_ds.log_announce_interval, 1);
+        assert_eq!(port_ds.announce_receipt_timeout, 3);
+        assert_eq!(port_ds.log_sync_interval, 0);
+        assert_eq!(port_ds.peer_mean_path_delay, 0);
+
+        port.set_forced_port_state(PortState::Master);
+        let port = port.start_bmca();
+        assert_eq!(port.port_ds().port_state, ds::PortState::Master);
+    }
+
+    #[test]
+    fn test_management_performance_record() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let get_record = |port: &mut Port<_, _, _, _, _>, data: &[u8]| {
+            let mut buffer = [0; 128];
+            let request = management_request(
+                &mut buffer,
+                WILDCARD,
+                ManagementAction::GET,
+                ManagementId::PerformanceMonitoringRecord,
+                data,
+            );
+            let MessageBody::Management(management) = request.body else {
+                unreachable!()
+            };
+
+            let mut actions = port.handle_management(&request, management);
+            let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+                panic!("Unexpected resulting action");
+            };
+            let response = Message::deserialize(data).unwrap();
+            match response.suffix.tlv().next().unwrap().tlv_type {
+                TlvType::Management => Ok(response_data(&response)),
+                _ => Err(response_error(&response).management_error_id),
+            }
+        };
+
+        assert_eq!(
+            get_record(&mut port, &[]),
+            Err(ManagementErrorId::Unpopulated)
+        );
+
+        port.performance.add(&crate::port::Measurement {
+            event_time: crate::time::Time::from_secs(1000),
+            offset: Some(Duration::from_nanos(-40)),
+            ..Default::default()
+        });
+
+        let Ok(ManagementData::PerformanceMonitoringRecord { index, record }) =
+            get_record(&mut port, &[0, 96])
+        else {
+            panic!("Unexpected response");
+        };
+        assert_eq!(index, 96);
+        assert_eq!(record.start, 0);
+        assert_eq!(record.offset_from_master.unwrap().mean, -40);
+        assert_eq!(record.mean_path_delay, None);
+
+        assert_eq!(
+            get_record(&mut port, &[0, 98]),
+            Err(ManagementErrorId::WrongValue)
+        );
+    }
+
+    #[test]
+    fn test_management_set() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::SET,
+            ManagementId::Priority1,
+            &[12, 0],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        let response = Message::deserialize(data).unwrap();
+        check_response_header(&response, ManagementAction::RESPONSE);
+        assert_eq!(response_data(&response), ManagementData::Priority1(12));
+
+        // The change is only applied to the instance at the next BMCA
+        assert_eq!(state.borrow().default_ds.priority_1, 255);
+        let mut port = port.start_bmca();
+        let mut default_ds = state.borrow().default_ds;
+        port.take_default_ds_changes().apply(&mut default_ds);
+        assert_eq!(default_ds.priority_1, 12);
+        assert_eq!(port.take_default_ds_changes(), DefaultDSChanges::default());
+    }
+
+    fn set(
+        port: &mut Port<Running<'_>, impl AcceptableMasterList, impl Rng, impl Clock, impl Filter>,
+        management_id: ManagementId,
+        data: &[u8],
+    ) -> Result<ManagementData, ManagementErrorId> {
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::SET,
+            management_id,
+            data,
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        let response = Message::deserialize(data).unwrap();
+        match response.suffix.tlv().next().unwrap().tlv_type {
+            TlvType::Management => Ok(response_data(&response)),
+            _ => Err(response_error(&response).management_error_id),
+        }
+    }
+
+    #[test]
+    fn test_management_set_intervals() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        let announce_interval = port.config.announce_interval;
+        let sync_interval = port.config.sync_interval;
+
+        for log_interval in [127, -128, 8, -8] {
+            let data = [log_interval as u8, 0];
+            assert_eq!(
+                set(&mut port, ManagementId::LogAnnounceInterval, &data),
+                Err(ManagementErrorId::WrongValue)
+            );
+            assert_eq!(
+                set(&mut port, ManagementId::LogSyncInterval, &data),
+                Err(ManagementErrorId::WrongValue)
+            );
+        }
+        assert_eq!(port.config.announce_interval, announce_interval);
+        assert_eq!(port.config.sync_interval, sync_interval);
+
+        assert_eq!(
+            set(&mut port, ManagementId::LogSyncInterval, &[(-3i8) as u8, 0]),
+            Ok(ManagementData::LogSyncInterval(-3))
+        );
+        assert_eq!(port.config.sync_interval, Interval::from_log_2(-3));
+
+        // The profile limits the intervals further
+        let state = setup_test_state();
+        state.borrow_mut().default_ds.profile = Profile::Aes67;
+        let mut port = setup_test_port(&state);
+        assert_eq!(
+            set(&mut port, ManagementId::LogSyncInterval, &[2, 0]),
+            Err(ManagementErrorId::WrongValue)
+        );
+        assert_eq!(
+            set(&mut port, ManagementId::LogSyncInterval, &[1, 0]),
+            Ok(ManagementData::LogSyncInterval(1))
+        );
+    }
+
+    #[test]
+    fn test_management_errors() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::GET,
+            ManagementId::Reserved(0x1234),
+            &[],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        drop(actions);
+        let response = Message::deserialize(data).unwrap();
+        check_response_header(&response, ManagementAction::RESPONSE);
+        assert_eq!(
+            response_error(&response),
+            ManagementErrorStatus {
+                management_error_id: ManagementErrorId::NoSuchId,
+                management_id: ManagementId::Reserved(0x1234),
+            }
+        );
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::SET,
+            ManagementId::CurrentDataSet,
+            &[0; 18],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        let response = Message::deserialize(data).unwrap();
+        assert_eq!(
+            response_error(&response),
+            ManagementErrorStatus {
+                management_error_id: ManagementErrorId::NotSetable,
+                management_id: ManagementId::CurrentDataSet,
+            }
+        );
+    }
+
+    #[test]
+    fn test_management_disable_enable() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::COMMAND,
+            ManagementId::DisablePort,
+            &[],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        assert!(actions.next().is_none());
+        drop(actions);
+        let response = Message::deserialize(data).unwrap();
+        check_response_header(&response, ManagementAction::ACKNOWLEDGE);
+        assert_eq!(
+            response_data(&response),
+            ManagementData::Empty(ManagementId::DisablePort)
+        );
+        assert!(matches!(port.port_state, PortState::Disabled));
+
+        // A disabled port no longer participates in the protocol
+        assert!(port.handle_announce_receipt_timer().next().is_none());
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            WILDCARD,
+            ManagementAction::COMMAND,
+            ManagementId::EnablePort,
+            &[],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        let mut actions = port.handle_management(&request, management);
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected resulting action");
+        };
+        let response = Message::deserialize(data).unwrap();
+        check_response_header(&response, ManagementAction::ACKNOWLEDGE);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceReceiptTimer { .. })
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+        assert!(matches!(port.port_state, PortState::Listening));
+    }
+
+    #[test]
+    fn test_management_other_target() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut buffer = [0; 128];
+        let request = management_request(
+            &mut buffer,
+            PortIdentity {
+                clock_identity: ClockIdentity([1; 8]),
+                port_number: 0xffff,
+            },
+            ManagementAction::GET,
+            ManagementId::DefaultDataSet,
+            &[],
+        );
+        let MessageBody::Management(management) = request.body else {
+            unreachable!()
+        };
+
+        assert!(port
+            .handle_management(&request, management)
+            .next()
+            .is_none());
+    }
+}
@@ -1,11 +1,15 @@
-use super::{state::PortState, ForwardedTLVProvider, Port, PortActionIterator, Running};
+use super::{
+    state::PortState, unicast::UnicastMessage, ForwardedTLVProvider, Port, PortActionIterator,
+    Running,
+};
 use crate::{
+    config::Profile,
     datastructures::{
-        common::{PortIdentity, TlvSetBuilder},
-        messages::{DelayReqMessage, Header, Message, MAX_DATA_LEN},
+        common::{PortAddress, PortIdentity, TlvSetBuilder},
+        messages::{DelayReqMessage, Header, Message, MessageBody, MAX_DATA_LEN},
     },
     filters::Filter,
-    port::{actions::TimestampContextInner, PortAction, TimestampContext},
+    port::{actions::TimestampContextInner, OneStepInsertion, PortAction, TimestampContext},
     time::Time,
 };
 
@@ -14,28 +18,63 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         if matches!(self.port_state, PortState::Master) {
             log::trace!("sending sync message");
 
-            let seq_id = self.sync_seq_ids.generate();
-            let packet_length =
-                match Message::sync(&self.lifecycle.state.default_ds, self.port_identity, seq_id)
-                    .serialize(&mut self.packet_buffer)
+            let mut duration = self.config.sync_interval.as_core_duration();
+            if !self.as_capable() {
+                return actions![PortAction::ResetSyncTimer { duration }];
+            }
+
+            let (seq_id, destination) = if self.unicast.is_some() {
+                // With unicast negotiation, syncs only go to the ports that asked for them
+                if !self.unicast_period_in_progress(UnicastMessage::Sync)
+                    && !self.start_unicast_period(UnicastMessage::Sync)
                 {
-                    Ok(message) => message,
-                    Err(error) => {
-                        log::error!("Statime bug: Could not serialize sync: {:?}", error);
-                        return actions![];
-                    }
+                    return actions![PortAction::ResetSyncTimer { duration }];
+                }
+                let Some((address, seq_id, more)) = self.next_unicast_client(UnicastMessage::Sync)
+                else {
+                    return actions![PortAction::ResetSyncTimer { duration }];
+                };
+                if more {
+                    duration = core::time::Duration::ZERO;
+                }
+                (seq_id, Some(address))
+            } else {
+                (self.sync_seq_ids.generate(), None)
+            };
+
+            let mut message =
+                Message::sync(&self.lifecycle.state.default_ds, self.port_identity, seq_id);
+            message.header.unicast_flag = destination.is_some();
+            message.header.two_step_flag = !self.config.one_step;
+            let packet_length = match message.serialize(&mut self.packet_buffer) {
+                Ok(message) => message,
+                Err(error) => {
+                    log::error!("Statime bug: Could not serialize sync: {:?}", error);
+                    return actions![];
+                }
+            };
+            let Some(packet_length) = self.finish_message(packet_length) else {
+                return actions![PortAction::ResetSyncTimer { duration }];
+            };
+
+            let (inner, one_step) = if self.config.one_step {
+                (TimestampContextInner::OneStep, Some(OneStepInsertion::SYNC))
+            } else {
+                let inner = TimestampContextInner::Sync {
+                    id: seq_id,
+                    destination,
                 };
+                (inner, None)
+            };
 
             actions![
-                PortAction::ResetSyncTimer {
-                    duration: self.config.sync_interval.as_core_duration(),
-                },
+                PortAction::ResetSyncTimer { duration },
                 PortAction::SendEvent {
-                    context: TimestampContext {
-                        inner: TimestampContextInner::Sync { id: seq_id },
-                    },
+                    context: TimestampContext { inner },
                     data: &self.packet_buffer[..packet_length],
-                    link_local: false,
+                    link_local: self.gptp.is_some(),
+                    destination,
+                    one_step,
                 }
             ]
         } else {
@@ -43,16 +82,27 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         }
     }
 
-    pub(super) fn handle_sync_timestamp(&mut self, id: u16, timestamp: Time) -> PortActionIterator {
+    pub(super) fn handle_sync_timestamp(
+        &mut self,
+        id: u16,
+        destination: Option<PortAddress>,
+        timestamp: Time,
+    ) -> PortActionIterator {
         if matches!(self.port_state, PortState::Master) {
-            let packet_length = match Message::follow_up(
+            let mut message = Message::follow_up(
                 &self.lifecycle.state.default_ds,
                 self.port_identity,
                 id,
                 timestamp,
-            )
-            .serialize(&mut self.packet_buffer)
-            {
+            );
+            message.header.unicast_flag = destination.is_some();
+
+            let mut tlv_buffer = [0; 32];
+            let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+            self.add_follow_up_information(&mut tlv_builder);
+            message.suffix = tlv_builder.build();
+
+            let packet_length = match message.serialize(&mut self.packet_buffer) {
                 Ok(length) => length,
                 Err(error) => {
                     log::error!(
@@ -62,10 +112,14 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                     return actions![];
                 }
             };
+            let Some(packet_length) = self.finish_message(packet_length) else {
+                return actions![];
+            };
 
             actions![PortAction::SendGeneral {
                 data: &self.packet_buffer[..packet_length],
-                link_local: false,
+                link_local: self.gptp.is_some(),
+                destination,
             }]
         } else {
             actions![]
@@ -77,6 +131,26 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         tlv_provider: &mut impl ForwardedTLVProvider,
     ) -> PortActionIterator {
         if matches!(self.port_state, PortState::Master) {
+            if self.unicast_period_in_progress(UnicastMessage::Announce) {
+                return self.send_unicast_announce();
+            }
+
+            // Without multicast, announces only go to the ports that asked for them
+            if self.lifecycle.state.default_ds.profile == Profile::G8275_2 {
+                if self.start_unicast_period(UnicastMessage::Announce) {
+                    return self.send_unicast_announce();
+                }
+                return actions![PortAction::ResetAnnounceTimer {
+                    duration: self.config.announce_interval.as_core_duration(),
+                }];
+            }
+
+            if !self.as_capable() {
+                return actions![PortAction::ResetAnnounceTimer {
+                    duration: self.config.announce_interval.as_core_duration(),
+                }];
+            }
+
             log::trace!("sending announce message");
 
             let mut tlv_buffer = [0; MAX_DATA_LEN];
@@ -96,20 +170,30 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                     continue;
                 }
 
+                if self.take_forwarded_path_trace(&tlv) {
+                    // Sent below, with our own identity added
+                    continue;
+                }
+
+                if self.is_synchronization_metadata(&tlv) || self.is_power_profile_tlv(&tlv) {
+                    // Sent below, from the instance state
+                    continue;
+                }
+
                 tlv_margin -= tlv.size();
                 // Will not fail as previous checks ensure sufficient space in buffer.
                 tlv_builder.add(tlv.tlv).unwrap();
             }
 
+            self.add_path_trace(&mut tlv_builder, tlv_margin);
+            let tlv_margin = MAX_DATA_LEN - message.wire_size() - tlv_builder.wire_size();
+            self.add_synchronization_metadata(&mut tlv_builder, tlv_margin);
+            let tlv_margin = MAX_DATA_LEN - message.wire_size() - tlv_builder.wire_size();
+            self.add_power_profile_tlvs(&mut tlv_builder, tlv_margin);
+
             message.suffix = tlv_builder.build();
 
-            let packet_length = match Message::announce(
-                &self.lifecycle.state,
-                self.port_identity,
-                self.announce_seq_ids.generate(),
-            )
-            .serialize(&mut self.packet_buffer)
-            {
+            let packet_length = match message.serialize(&mut self.packet_buffer) {
                 Ok(length) => length,
                 Err(error) => {
                     log::error!(
@@ -119,14 +203,25 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                     return actions![];
                 }
             };
+            let Some(packet_length) = self.finish_message(packet_length) else {
+                return actions![PortAction::ResetAnnounceTimer {
+                    duration: self.config.announce_interval.as_core_duration(),
+                }];
+            };
+
+            // Announces to ports that negotiated unicast follow right after this one
+            let duration = if self.start_unicast_period(UnicastMessage::Announce) {
+                core::time::Duration::ZERO
+            } else {
+                self.config.announce_interval.as_core_duration()
+            };
 
             actions![
-                PortAction::ResetAnnounceTimer {
-                    duration: self.config.announce_interval.as_core_duration(),
-                },
+                PortAction::ResetAnnounceTimer { duration },
                 PortAction::SendGeneral {
                     data: &self.packet_buffer[..packet_length],
-                    link_local: false,
+                    link_local: self.gptp.is_some(),
+                    destination: None,
                 }
             ]
         } else {
@@ -134,15 +229,67 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         }
     }
 
+    fn send_unicast_announce(&mut self) -> PortActionIterator<'_> {
+        let Some((address, seq_id, more)) = self.next_unicast_client(UnicastMessage::Announce)
+        else {
+            return actions![];
+        };
+        log::trace!("sending unicast announce message to {:?}", address);
+
+        let mut message = Message::announce(&self.lifecycle.state, self.port_identity, seq_id);
+        message.header.unicast_flag = true;
+
+        let mut tlv_buffer = [0; MAX_DATA_LEN];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        self.add_synchronization_metadata(&mut tlv_builder, MAX_DATA_LEN - message.wire_size());
+        let tlv_margin = MAX_DATA_LEN - message.wire_size() - tlv_builder.wire_size();
+        self.add_power_profile_tlvs(&mut tlv_builder, tlv_margin);
+        message.suffix = tlv_builder.build();
+
+        let packet_length = match message.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!(
+                    "Statime bug: Could not serialize announce message {:?}",
+                    error
+                );
+                return actions![];
+            }
+        };
+        let duration = if more {
+            core::time::Duration::ZERO
+        } else {
+            self.config.announce_interval.as_core_duration()
+        };
+        let Some(packet_length) = self.finish_message(packet_length) else {
+            return actions![PortAction::ResetAnnounceTimer { duration }];
+        };
+
+        actions![
+            PortAction::ResetAnnounceTimer { duration },
+            PortAction::SendGeneral {
+                data: &self.packet_buffer[..packet_length],
+                link_local: false,
+                destination: Some(address),
+            }
+        ]
+    }
+
     pub(super) fn handle_delay_req(
         &mut self,
         header: Header,
         message: DelayReqMessage,
         timestamp: Time,
+        source: Option<PortAddress>,
     ) -> PortActionIterator {
         if matches!(self.port_state, PortState::Master) {
             log::debug!("Received DelayReq");
-            let delay_resp_message = Message::delay_resp(
+            let destination = if self.config.hybrid_mode {
+                source
+            } else {
+                self.unicast_delay_resp_destination(source)
+            };
+            let mut delay_resp_message = Message::delay_resp(
                 header,
                 message,
                 self.port_identity,
@@ -150,6 +297,8 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                 timestamp,
             );
 
+            delay_resp_message.header.unicast_flag = destination.is_some();
+
             let packet_length = match delay_resp_message.serialize(&mut self.packet_buffer) {
                 Ok(length) => length,
                 Err(error) => {
@@ -157,10 +306,14 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                     return actions![];
                 }
             };
+            let Some(packet_length) = self.finish_message(packet_length) else {
+                return actions![];
+            };
 
             actions![PortAction::SendGeneral {
                 data: &self.packet_buffer[..packet_length],
                 link_local: false,
+                destination,
             }]
         } else {
             actions![]
@@ -173,13 +326,22 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         timestamp: Time,
     ) -> PortActionIterator {
         log::debug!("Received PDelayReq");
-        let pdelay_resp_message = Message::pdelay_resp(
+        let mut pdelay_resp_message = Message::pdelay_resp(
             &self.lifecycle.state.default_ds,
             self.port_identity,
             header,
             timestamp,
         );
 
+        // For a one-step response the hardware adds the turnaround time to the
+        // correction field, so the receive time is not sent
+        if self.config.one_step {
+            if let MessageBody::PDelayResp(body) = &mut pdelay_resp_message.body {
+                body.request_receive_timestamp = Default::default();
+            }
+            pdelay_resp_message.header.two_step_flag = false;
+        }
+
         let packet_length = match pdelay_resp_message.serialize(&mut self.packet_buffer) {
             Ok(length) => length,
             Err(error) => {
@@ -187,16 +349,29 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                 return actions![];
             }
         };
+        let Some(packet_length) = self.finish_message(packet_length) else {
+            return actions![];
+        };
+
+        let (inner, one_step) = if self.config.one_step {
+            (
+                TimestampContextInner::OneStep,
+                Some(OneStepInsertion::PDELAY_RESP),
+            )
+        } else {
+            let inner = TimestampContextInner::PDelayResp {
+                id: header.sequence_id,
+                requestor_identity: header.source_port_identity,
+            };
+            (inner, None)
+        };
 
         actions![PortAction::SendEvent {
             data: &self.packet_buffer[..packet_length],
-            context: TimestampContext {
-                inner: TimestampContextInner::PDelayResp {
-                    id: header.sequence_id,
-                    requestor_identity: header.source_port_identity
-                }
-            },
+            context: TimestampContext { inner },
             link_local: true,
+            destination: None,
+            one_step,
         }]
     }
 
@@ -221,10 +396,14 @@ impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                 return actions![];
             }
         };
+        let Some(packet_length) = self.finish_message(packet_length) else {
+            return actions![];
+        };
 
         actions![PortAction::SendGeneral {
             data: &self.packet_buffer[..packet_length],
             link_local: true,
+            destination: None,
         }]
     }
 }
@@ -235,16 +414,17 @@ mod tests {
 
     use super::*;
     use crate::{
-        config::DelayMechanism,
+        config::{ClockAccuracy, ClockIdentity, ClockQuality, DelayMechanism},
         datastructures::{
             common::{PortIdentity, TimeInterval},
-            messages::{Header, MessageBody},
+            messages::{Header, MessageBody, MAX_DATA_LEN},
         },
+        filters::ClockUncertainty,
         port::{
             tests::{setup_test_port, setup_test_state},
             NoForwardedTLVs,
         },
-        time::Interval,
+        time::{Duration, Interval},
     };
 
     #[test]
@@ -273,11 +453,13 @@ mod tests {
                 origin_timestamp: Time::from_micros(0).into(),
             },
             Time::from_fixed_nanos(U96F32::from_bits((200000 << 32) + (500 << 16))),
+            None,
         );
 
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = action.next()
         else {
             panic!("Unexpected resulting action");
@@ -326,11 +508,13 @@ mod tests {
                 origin_timestamp: Time::from_micros(0).into(),
             },
             Time::from_fixed_nanos(U96F32::from_bits((220000 << 32) + (300 << 16))),
+            None,
         );
 
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = action.next()
         else {
             panic!("Unexpected resulting action");
@@ -361,6 +545,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hybrid_delay_response() {
+        let state = setup_test_state();
+
+        let mut port = setup_test_port(&state);
+        port.config.hybrid_mode = true;
+
+        port.set_forced_port_state(PortState::Master);
+
+        let source = PortAddress::Ipv4([192, 168, 1, 2]);
+        let mut action = port.handle_delay_req(
+            Header {
+                sequence_id: 42,
+                unicast_flag: true,
+                ..Default::default()
+            },
+            DelayReqMessage {
+                origin_timestamp: Time::from_micros(0).into(),
+            },
+            Time::from_micros(200),
+            Some(source),
+        );
+
+        let Some(PortAction::SendGeneral {
+            data,
+            link_local: false,
+            destination: Some(destination),
+        }) = action.next()
+        else {
+            panic!("Unexpected resulting action");
+        };
+        assert!(action.next().is_none());
+        assert_eq!(destination, source);
+
+        let msg = Message::deserialize(data).unwrap();
+        assert!(msg.header.unicast_flag);
+        assert_eq!(msg.header.sequence_id, 42);
+    }
+
     #[test]
     fn test_announce() {
         let state = setup_test_state();
@@ -386,6 +609,7 @@ mod tests {
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -412,6 +636,7 @@ mod tests {
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -430,6 +655,59 @@ mod tests {
         assert_ne!(msg2_header.sequence_id, msg_header.sequence_id);
     }
 
+    #[test]
+    fn test_announce_boundary_clock_quality() {
+        let state = setup_test_state();
+
+        let grandmaster_clock_quality = ClockQuality {
+            clock_class: 6,
+            clock_accuracy: ClockAccuracy::NS25,
+            offset_scaled_log_variance: 0x4e5d,
+        };
+
+        let mut state_ref = state.borrow_mut();
+        state_ref.parent_ds.grandmaster_identity = ClockIdentity([1; 8]);
+        state_ref.parent_ds.grandmaster_clock_quality = grandmaster_clock_quality;
+        state_ref.synchronized_uncertainty = Some(ClockUncertainty {
+            offset: Duration::from_nanos(200),
+            frequency: 1e-9,
+        });
+        drop(state_ref);
+
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        let mut actions = port.send_announce(&mut NoForwardedTLVs);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+
+        let MessageBody::Announce(msg) = Message::deserialize(data).unwrap().body else {
+            panic!("Unexpected message type");
+        };
+
+        // The grandmaster as seen through our clock
+        assert_eq!(msg.grandmaster_clock_quality.clock_class, 6);
+        assert_eq!(
+            msg.grandmaster_clock_quality.clock_accuracy,
+            ClockAccuracy::NS250
+        );
+        assert_eq!(
+            msg.grandmaster_clock_quality.offset_scaled_log_variance,
+            ClockQuality::scaled_log_variance(200e-9 * 200e-9)
+        );
+
+        // The parentDS keeps the quality of the grandmaster itself
+        assert_eq!(
+            state.borrow().parent_ds.grandmaster_clock_quality,
+            grandmaster_clock_quality
+        );
+    }
+
     #[test]
     fn test_sync() {
         let state = setup_test_state();
@@ -455,6 +733,8 @@ mod tests {
             context,
             data,
             link_local: false,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -471,18 +751,20 @@ mod tests {
         };
 
         let id = match context.inner {
-            TimestampContextInner::Sync { id } => id,
+            TimestampContextInner::Sync { id, .. } => id,
             _ => panic!("Wrong type of context"),
         };
 
         let mut actions = port.handle_sync_timestamp(
             id,
+            None,
             Time::from_fixed_nanos(U96F32::from_bits((601300 << 32) + (230 << 16))),
         );
 
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -522,6 +804,8 @@ mod tests {
             context,
             data,
             link_local: false,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -538,18 +822,20 @@ mod tests {
         };
 
         let id = match context.inner {
-            TimestampContextInner::Sync { id } => id,
+            TimestampContextInner::Sync { id, .. } => id,
             _ => panic!("wrong type of context"),
         };
 
         let mut actions = port.handle_sync_timestamp(
             id,
+            None,
             Time::from_fixed_nanos(U96F32::from_bits((1000601300 << 32) + (543 << 16))),
         );
 
         let Some(PortAction::SendGeneral {
             data,
             link_local: false,
+            destination: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -592,6 +878,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -614,6 +902,7 @@ mod tests {
         let Some(PortAction::SendGeneral {
             data,
             link_local: true,
+            destination: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -631,4 +920,109 @@ mod tests {
         assert!(actions.next().is_none());
         drop(actions);
     }
+
+    #[test]
+    fn test_one_step_sync() {
+        let state = setup_test_state();
+
+        let mut port = setup_test_port(&state);
+        port.config.one_step = true;
+
+        port.set_forced_port_state(PortState::Master);
+        let mut actions = port.send_sync();
+
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetSyncTimer { .. })
+        ));
+        let Some(PortAction::SendEvent {
+            context,
+            data,
+            link_local: false,
+            destination: None,
+            one_step: Some(OneStepInsertion::SYNC),
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        let sync = Message::deserialize(data).unwrap();
+        assert!(!sync.header.two_step_flag);
+        assert!(matches!(sync.body, MessageBody::Sync(_)));
+        drop(sync);
+
+        // No follow up is needed for a one-step sync
+        let mut actions = port.handle_send_timestamp(context, Time::from_micros(550));
+        assert!(actions.next().is_none());
+    }
+
+    #[test]
+    fn test_one_step_peer_delay() {
+        let state = setup_test_state();
+
+        let mut port = setup_test_port(&state);
+        port.config.one_step = true;
+
+        let mut actions = port.handle_pdelay_req(Header::default(), Time::from_micros(500));
+
+        let Some(PortAction::SendEvent {
+            context,
+            data,
+            link_local: true,
+            destination: None,
+            one_step: Some(OneStepInsertion::PDELAY_RESP),
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        let response = Message::deserialize(data).unwrap();
+        assert!(!response.header.two_step_flag);
+        let MessageBody::PDelayResp(response_body) = response.body else {
+            panic!("Unexpected message sent by port");
+        };
+        assert_eq!(
+            response_body.request_receive_timestamp,
+            Time::from_micros(0).into()
+        );
+        drop(response);
+
+        let mut actions = port.handle_send_timestamp(context, Time::from_micros(550));
+        assert!(actions.next().is_none());
+    }
+
+    #[test]
+    fn test_port_counters() {
+        let state = setup_test_state();
+
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        let mut buffer = [0; MAX_DATA_LEN];
+        let delay_req = Message::delay_req(&state.borrow().default_ds, PortIdentity::default(), 3);
+        let length = delay_req.serialize(&mut buffer).unwrap();
+
+        let mut actions = port.handle_event_receive(&buffer[..length], Time::from_micros(100));
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::SendGeneral { .. })
+        ));
+        drop(actions);
+
+        // Another domain
+        buffer[4] = 1;
+        drop(port.handle_event_receive(&buffer[..length], Time::from_micros(200)));
+
+        drop(port.handle_general_receive(&buffer[..10]));
+
+        let counters = port.port_counters();
+        assert_eq!(counters.rx.delay_req, 1);
+        assert_eq!(counters.tx.delay_resp, 1);
+        assert_eq!(counters.domain_mismatches, 1);
+        assert_eq!(counters.parse_errors, 1);
+    }
 }
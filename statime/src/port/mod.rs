@@ -5,15 +5,23 @@
 use core::ops::ControlFlow;
 
 pub use actions::{
-    ForwardedTLV, ForwardedTLVProvider, NoForwardedTLVs, PortAction, PortActionIterator,
-    TimestampContext,
+    ForwardedTLV, ForwardedTLVProvider, NoForwardedTLVs, OneStepInsertion, PortAction,
+    PortActionIterator, TimestampContext,
 };
+use arrayvec::ArrayVec;
 use atomic_refcell::{AtomicRef, AtomicRefCell};
 pub use measurement::Measurement;
 use rand::Rng;
 use state::PortState;
+pub use transparent::{
+    ForwardedMessage, TransparentPort, TransparentPortAction, TransparentPortActionIterator,
+};
 
-use self::sequence_id::SequenceIdGenerator;
+use self::{
+    gptp::GptpState, management::DefaultDSChanges, peer_delay::PeerDelayState,
+    performance::PerformanceMonitor, sequence_id::SequenceIdGenerator,
+    slave_event_monitoring::SlaveEventMonitor, unicast::UnicastState,
+};
 pub use crate::datastructures::messages::MAX_DATA_LEN;
 #[cfg(doc)]
 use crate::PtpInstance;
@@ -23,12 +31,18 @@ use crate::{
         bmca::{BestAnnounceMessage, Bmca},
     },
     clock::Clock,
-    config::PortConfig,
+    config::{AlternateTimeOffset, DelayMechanism, PortConfig, Profile, SynchronizationMetadata},
     datastructures::{
-        common::PortIdentity,
-        messages::{Message, MessageBody},
+        common::{PortAddress, PortIdentity, PowerProfileInformation},
+        messages::{Message, MessageBody, MessageType},
+    },
+    filters::{ClockUncertainty, Filter},
+    observability::{
+        performance::PerformanceRecord,
+        port::{self as port_ds, PortCounters, PortDS},
+        security::AuthenticationCounters,
+        slave_event_monitoring::{SlaveEventReport, MAX_SLAVE_EVENT_REPORTS},
     },
-    filters::Filter,
     ptp_instance::PtpInstanceState,
     time::{Duration, Time},
 };
@@ -59,11 +73,21 @@ macro_rules! actions {
 
 mod actions;
 mod bmca;
+mod gptp;
+mod management;
 mod master;
 mod measurement;
+mod peer_delay;
+mod performance;
+mod power;
+pub(crate) mod security;
 mod sequence_id;
 mod slave;
+mod slave_event_monitoring;
+mod smpte;
 pub(crate) mod state;
+mod transparent;
+mod unicast;
 
 /// A single port of the PTP instance
 ///
@@ -133,7 +157,13 @@ pub(crate) mod state;
 ///     announce_receipt_timeout: 0,
 ///     sync_interval: interval,
 ///     master_only: false,
+///     local_priority: 128,
 ///     delay_asymmetry: Default::default(),
+///     unicast_negotiation: None,
+///     unicast_master_table: Default::default(),
+///     hybrid_mode: false,
+///     one_step: false,
+///     slave_event_monitoring: None,
 /// };
 /// let filter_config = 1.0;
 /// let clock = system::Clock {};
@@ -167,7 +197,7 @@ pub(crate) mod state;
 /// #     }
 /// #     pub struct UdpSocket;
 /// #     impl UdpSocket {
-/// #         pub fn send(&mut self, buf: &[u8], link_local: bool) -> statime::time::Time { unimplemented!() }
+/// #         pub fn send(&mut self, buf: &[u8], link_local: bool, destination: Option<statime::config::PortAddress>) -> statime::time::Time { unimplemented!() }
 /// #     }
 /// # }
 /// struct MyPortResources {
@@ -176,6 +206,7 @@ pub(crate) mod state;
 ///     delay_req_timer: system::Timer,
 ///     announce_receipt_timer: system::Timer,
 ///     filter_update_timer: system::Timer,
+///     unicast_timer: system::Timer,
 ///     time_critical_socket: system::UdpSocket,
 ///     general_socket: system::UdpSocket,
 ///     send_timestamp: Option<(TimestampContext, Time)>
@@ -184,12 +215,13 @@ pub(crate) mod state;
 /// fn handle_actions(resources: &mut MyPortResources, actions: PortActionIterator) {
 ///     for action in actions {
 ///         match action {
-///             PortAction::SendEvent { context, data, link_local } => {
-///                 let timestamp = resources.time_critical_socket.send(data, link_local);
+///             // One-step messages are only sent when enabled in the `PortConfig`
+///             PortAction::SendEvent { context, data, link_local, destination, .. } => {
+///                 let timestamp = resources.time_critical_socket.send(data, link_local, destination);
 ///                 resources.send_timestamp = Some((context, timestamp));
 ///             }
-///             PortAction::SendGeneral { data, link_local } => {
-///                 resources.general_socket.send(data, link_local);
+///             PortAction::SendGeneral { data, link_local, destination } => {
+///                 resources.general_socket.send(data, link_local, destination);
 ///             }
 ///             PortAction::ResetAnnounceTimer { duration } => {
 ///                 resources.announce_timer.expire_in(duration)
@@ -204,6 +236,9 @@ pub(crate) mod state;
 ///             PortAction::ResetFilterUpdateTimer { duration } => {
 ///                 resources.filter_update_timer.expire_in(duration)
 ///             }
+///             PortAction::ResetUnicastTimer { duration } => {
+///                 resources.unicast_timer.expire_in(duration)
+///             }
 ///             PortAction::ForwardTLV { .. } => {}
 ///         }
 ///     }
@@ -232,6 +267,7 @@ pub(crate) mod state;
 /// #     delay_req_timer: system::Timer,
 /// #     announce_receipt_timer: system::Timer,
 /// #     filter_update_timer: system::Timer,
+/// #     unicast_timer: system::Timer,
 /// #     time_critical_socket: system::UdpSocket,
 /// #     general_socket: system::UdpSocket,
 /// #     send_timestamp: Option<(statime::port::TimestampContext, statime::time::Time)>
@@ -254,6 +290,8 @@ pub(crate) mod state;
 ///         running_port.handle_announce_receipt_timer()
 ///     } else if resources.filter_update_timer.has_expired() {
 ///         running_port.handle_filter_update_timer()
+///     } else if resources.unicast_timer.has_expired() {
+///         running_port.handle_unicast_timer()
 ///     } else if let Some((data, timestamp)) = resources.time_critical_socket.recv() {
 ///         running_port.handle_event_receive(data, timestamp)
 ///     } else if let Some((data, _timestamp)) = resources.general_socket.recv() {
@@ -286,29 +324,29 @@ pub struct Port<L, A, R, C, F: Filter> {
     sync_seq_ids: SequenceIdGenerator,
     delay_seq_ids: SequenceIdGenerator,
     pdelay_seq_ids: SequenceIdGenerator,
+    signaling_seq_ids: SequenceIdGenerator,
 
     filter: F,
     /// Mean delay means either `mean_path_delay` when DelayMechanism is E2E,
     /// or `mean_link_delay` when DelayMechanism is P2P.
     mean_delay: Option<Duration>,
     peer_delay_state: PeerDelayState,
-}
+    unicast: Option<UnicastState>,
+    gptp: Option<GptpState>,
+    /// The last synchronization metadata received, and who sent it
+    synchronization_metadata: Option<(PortIdentity, SynchronizationMetadata)>,
+    /// The last power profile information received, and who sent it
+    power_profile: Option<(PortIdentity, PowerProfileInformation)>,
+    /// The last alternate time offset received, and who sent it
+    alternate_time_offset: Option<(PortIdentity, AlternateTimeOffset)>,
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum PeerDelayState {
-    Empty,
-    Measuring {
-        id: u16,
-        responder_identity: Option<PortIdentity>,
-        request_send_time: Option<Time>,
-        request_recv_time: Option<Time>,
-        response_send_time: Option<Time>,
-        response_recv_time: Option<Time>,
-    },
-    PostMeasurement {
-        id: u16,
-        responder_identity: PortIdentity,
-    },
+    default_ds_changes: DefaultDSChanges,
+    authentication_counters: AuthenticationCounters,
+    counters: PortCounters,
+    performance: PerformanceMonitor,
+    slave_event_monitor: Option<SlaveEventMonitor>,
+    /// The last slave event monitoring records received from each slave
+    slave_event_reports: ArrayVec<SlaveEventReport, MAX_SLAVE_EVENT_REPORTS>,
 }
 
 /// Type state of [`Port`] entered by [`Port::end_bmca`]
@@ -337,8 +375,8 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
         timestamp: Time,
     ) -> PortActionIterator<'_> {
         match context.inner {
-            actions::TimestampContextInner::Sync { id } => {
-                self.handle_sync_timestamp(id, timestamp)
+            actions::TimestampContextInner::Sync { id, destination } => {
+                self.handle_sync_timestamp(id, destination, timestamp)
             }
             actions::TimestampContextInner::DelayReq { id } => {
                 self.handle_delay_timestamp(id, timestamp)
@@ -350,6 +388,12 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
                 id,
                 requestor_identity,
             } => self.handle_pdelay_response_timestamp(id, requestor_identity, timestamp),
+            // One-step messages are completed by the hardware
+            actions::TimestampContextInner::OneStep => actions![],
+            actions::TimestampContextInner::Forward { .. } => {
+                log::error!("Port received timestamp context of a transparent port");
+                actions![]
+            }
         }
     }
 
@@ -376,6 +420,7 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
         // we didn't hear announce messages from other masters, so become master
         // ourselves
         match self.port_state {
+            PortState::Disabled => return actions![],
             PortState::Master => (),
             _ => self.set_forced_port_state(PortState::Master),
         }
@@ -421,10 +466,22 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
             sync_seq_ids: self.sync_seq_ids,
             delay_seq_ids: self.delay_seq_ids,
             pdelay_seq_ids: self.pdelay_seq_ids,
+            signaling_seq_ids: self.signaling_seq_ids,
 
             filter: self.filter,
             mean_delay: self.mean_delay,
             peer_delay_state: self.peer_delay_state,
+            unicast: self.unicast,
+            gptp: self.gptp,
+            synchronization_metadata: self.synchronization_metadata,
+            power_profile: self.power_profile,
+            alternate_time_offset: self.alternate_time_offset,
+            default_ds_changes: self.default_ds_changes,
+            authentication_counters: self.authentication_counters,
+            counters: self.counters,
+            performance: self.performance,
+            slave_event_monitor: self.slave_event_monitor,
+            slave_event_reports: self.slave_event_reports,
         }
     }
 
@@ -437,11 +494,32 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
             Ok(message) => message,
             Err(error) => {
                 log::warn!("Could not parse packet: {:?}", error);
+                self.counters.parse_errors += 1;
                 return ControlFlow::Break(actions![]);
             }
         };
         if message.header().sdo_id != self.lifecycle.state.default_ds.sdo_id
             || message.header().domain_number != self.lifecycle.state.default_ds.domain_number
+        {
+            self.counters.domain_mismatches += 1;
+            return ControlFlow::Break(actions![]);
+        }
+        self.counters.rx.count(message.body.content_type());
+        if !self.check_authentication(data, &message) {
+            return ControlFlow::Break(actions![]);
+        }
+        // A disabled port only answers management messages
+        if matches!(self.port_state, PortState::Disabled)
+            && !matches!(message.body, MessageBody::Management(_))
+        {
+            return ControlFlow::Break(actions![]);
+        }
+        // A gPTP port only takes time from an asCapable peer
+        if !self.as_capable()
+            && matches!(
+                message.body,
+                MessageBody::Sync(_) | MessageBody::FollowUp(_) | MessageBody::Announce(_)
+            )
         {
             return ControlFlow::Break(actions![]);
         }
@@ -453,6 +531,44 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
         &'b mut self,
         data: &'b [u8],
         timestamp: Time,
+    ) -> PortActionIterator<'b> {
+        self.handle_event_receive_internal(data, timestamp, None)
+    }
+
+    /// Handle a message over the event channel that was received from
+    /// `source`
+    ///
+    /// Knowing the source of messages is needed for unicast communication.
+    pub fn handle_event_receive_from<'b>(
+        &'b mut self,
+        data: &'b [u8],
+        timestamp: Time,
+        source: PortAddress,
+    ) -> PortActionIterator<'b> {
+        self.handle_event_receive_internal(data, timestamp, Some(source))
+    }
+
+    /// Handle a general ptp message
+    pub fn handle_general_receive<'b>(&'b mut self, data: &'b [u8]) -> PortActionIterator<'b> {
+        self.handle_general_receive_internal(data, None)
+    }
+
+    /// Handle a general ptp message that was received from `source`
+    ///
+    /// Knowing the source of messages is needed for unicast communication.
+    pub fn handle_general_receive_from<'b>(
+        &'b mut self,
+        data: &'b [u8],
+        source: PortAddress,
+    ) -> PortActionIterator<'b> {
+        self.handle_general_receive_internal(data, Some(source))
+    }
+
+    fn handle_event_receive_internal<'b>(
+        &'b mut self,
+        data: &'b [u8],
+        timestamp: Time,
+        source: Option<PortAddress>,
     ) -> PortActionIterator<'b> {
         let message = match self.parse_and_filter(data) {
             ControlFlow::Continue(value) => value,
@@ -460,32 +576,52 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
         };
 
         match message.body {
-            MessageBody::Sync(sync) => self.handle_sync(message.header, sync, timestamp),
+            MessageBody::Sync(sync) => {
+                if let Some(source) = source {
+                    self.learn_sync_source(&message.header, source);
+                }
+                self.handle_sync(message.header, sync, timestamp)
+            }
             MessageBody::DelayReq(delay_request) => {
-                self.handle_delay_req(message.header, delay_request, timestamp)
+                self.handle_delay_req(message.header, delay_request, timestamp, source)
             }
             MessageBody::PDelayReq(_) => self.handle_pdelay_req(message.header, timestamp),
             MessageBody::PDelayResp(peer_delay_response) => {
                 self.handle_peer_delay_response(message.header, peer_delay_response, timestamp)
             }
-            _ => self.handle_general_internal(message),
+            _ => self.handle_general_internal(message, source),
         }
     }
 
-    /// Handle a general ptp message
-    pub fn handle_general_receive<'b>(&'b mut self, data: &'b [u8]) -> PortActionIterator<'b> {
+    fn handle_general_receive_internal<'b>(
+        &'b mut self,
+        data: &'b [u8],
+        source: Option<PortAddress>,
+    ) -> PortActionIterator<'b> {
         let message = match self.parse_and_filter(data) {
             ControlFlow::Continue(value) => value,
             ControlFlow::Break(value) => return value,
         };
 
-        self.handle_general_internal(message)
+        self.handle_general_internal(message, source)
     }
 
-    fn handle_general_internal<'b>(&'b mut self, message: Message<'b>) -> PortActionIterator<'b> {
+    fn handle_general_internal<'b>(
+        &'b mut self,
+        message: Message<'b>,
+        source: Option<PortAddress>,
+    ) -> PortActionIterator<'b> {
         match message.body {
-            MessageBody::Announce(announce) => self.handle_announce(&message, announce),
-            MessageBody::FollowUp(follow_up) => self.handle_follow_up(message.header, follow_up),
+            MessageBody::Announce(announce) => {
+                if let Some(source) = source {
+                    self.learn_unicast_master(source, message.header.source_port_identity);
+                }
+                self.handle_announce(&message, announce)
+            }
+            MessageBody::FollowUp(follow_up) => {
+                self.handle_follow_up_information(&message);
+                self.handle_follow_up(message.header, follow_up)
+            }
             MessageBody::DelayResp(delay_response) => {
                 self.handle_delay_resp(message.header, delay_response)
             }
@@ -499,11 +635,30 @@ impl<'a, A: AcceptableMasterList, C: Clock, F: Filter, R: Rng> Port<Running<'a>,
                 log::warn!("Received event message over general interface");
                 actions![]
             }
-            MessageBody::Management(_) | MessageBody::Signaling(_) => actions![],
+            MessageBody::Management(management) => self.handle_management(&message, management),
+            MessageBody::Signaling(signaling) => {
+                self.handle_slave_event_report(&message, signaling);
+                self.handle_signaling(&message, signaling, source)
+            }
         }
     }
 }
 
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Authenticate and count the message of `length` bytes in the packet
+    /// buffer before it is sent, returns the final length of the message
+    ///
+    /// Returns `None` when the message cannot be authenticated, it should then
+    /// be dropped instead of sent.
+    fn finish_message(&mut self, length: usize) -> Option<usize> {
+        let length = self.authenticate(length)?;
+        if let Ok(message_type) = MessageType::try_from(self.packet_buffer[0] & 0x0f) {
+            self.counters.tx.count(message_type);
+        }
+        Some(length)
+    }
+}
+
 impl<'a, A, C, F: Filter, R> Port<InBmca<'a>, A, R, C, F> {
     /// End a BMCA cycle and make the
     /// [`handle_*`](`Port::handle_send_timestamp`) methods available again
@@ -526,9 +681,21 @@ impl<'a, A, C, F: Filter, R> Port<InBmca<'a>, A, R, C, F> {
                 sync_seq_ids: self.sync_seq_ids,
                 delay_seq_ids: self.delay_seq_ids,
                 pdelay_seq_ids: self.pdelay_seq_ids,
+                signaling_seq_ids: self.signaling_seq_ids,
                 filter: self.filter,
                 mean_delay: self.mean_delay,
                 peer_delay_state: self.peer_delay_state,
+                unicast: self.unicast,
+                gptp: self.gptp,
+                synchronization_metadata: self.synchronization_metadata,
+                power_profile: self.power_profile,
+                alternate_time_offset: self.alternate_time_offset,
+                default_ds_changes: self.default_ds_changes,
+                authentication_counters: self.authentication_counters,
+                counters: self.counters,
+                performance: self.performance,
+                slave_event_monitor: self.slave_event_monitor,
+                slave_event_reports: self.slave_event_reports,
             },
             self.lifecycle.pending_action,
         )
@@ -565,10 +732,75 @@ impl<L, A, R, C, F: Filter> Port<L, A, R, C, F> {
         matches!(self.port_state, PortState::Master)
     }
 
+    /// The message and error counters of this [`Port`]
+    pub fn port_counters(&self) -> PortCounters {
+        self.counters
+    }
+
+    /// The performance monitoring record with the given `index`, see
+    /// [`PERFORMANCE_RECORDS`](crate::observability::performance::PERFORMANCE_RECORDS)
+    /// for their numbering
+    ///
+    /// Returns `None` when the port has no measurements for the record yet.
+    pub fn performance_record(&self, index: u16) -> Option<PerformanceRecord> {
+        self.performance.record(index)
+    }
+
+    /// The last slave event monitoring records received from each slave, the
+    /// most recently updated last
+    ///
+    /// Slave ports send these when configured with
+    /// [`PortConfig::slave_event_monitoring`].
+    pub fn slave_event_reports(&self) -> &[SlaveEventReport] {
+        &self.slave_event_reports
+    }
+
+    /// Get a snapshot of the port dataset of this [`Port`]
+    pub fn port_ds(&self) -> PortDS {
+        let (delay_mechanism, log_min_delay_req_interval, log_min_pdelay_req_interval, peer_delay) =
+            match self.config.delay_mechanism {
+                DelayMechanism::E2E { interval } => (
+                    port_ds::DelayMechanism::E2E,
+                    interval.as_log_2(),
+                    0x7f,
+                    Duration::ZERO,
+                ),
+                DelayMechanism::P2P { interval } => (
+                    port_ds::DelayMechanism::P2P,
+                    0x7f,
+                    interval.as_log_2(),
+                    self.mean_delay.unwrap_or(Duration::ZERO),
+                ),
+            };
+
+        PortDS {
+            port_identity: self.port_identity,
+            port_state: (&self.port_state).into(),
+            log_min_delay_req_interval,
+            peer_mean_path_delay: peer_delay.nanos_rounded(),
+            log_announce_interval: self.config.announce_interval.as_log_2(),
+            announce_receipt_timeout: self.config.announce_receipt_timeout,
+            log_sync_interval: self.config.sync_interval.as_log_2(),
+            delay_mechanism,
+            log_min_pdelay_req_interval,
+            version_number: 2,
+            minor_version_number: 1,
+        }
+    }
+
     pub(crate) fn state(&self) -> &PortState {
         &self.port_state
     }
 
+    /// Uncertainty of the clock while this [`Port`] is steering it
+    pub(crate) fn clock_uncertainty(&self) -> Option<ClockUncertainty> {
+        if self.is_steering() {
+            self.filter.uncertainty()
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn number(&self) -> u16 {
         self.port_identity.port_number
     }
@@ -585,14 +817,38 @@ impl<'a, A, C, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
         mut rng: R,
     ) -> Self {
         let duration = config.announce_duration(&mut rng);
+        let profile = state_refcell.borrow().default_ds.profile;
         let bmca = Bmca::new(
             config.acceptable_master_list,
             config.announce_interval.as_duration().into(),
             port_identity,
+            config.local_priority,
+            profile,
         );
 
         let filter = F::new(filter_config.clone());
 
+        // The unicast master table can only be used through unicast negotiation
+        let unicast_negotiation = if config.unicast_master_table.is_empty() {
+            config.unicast_negotiation
+        } else {
+            Some(config.unicast_negotiation.unwrap_or_default())
+        };
+        let unicast = unicast_negotiation.map(|_| UnicastState::new(&config.unicast_master_table));
+
+        let gptp = (profile == Profile::Gptp).then(GptpState::default);
+
+        let reset_announce_receipt = PortAction::ResetAnnounceReceiptTimer { duration };
+        let pending_action = match unicast_negotiation {
+            Some(_) => actions![
+                reset_announce_receipt,
+                PortAction::ResetUnicastTimer {
+                    duration: core::time::Duration::ZERO,
+                }
+            ],
+            None => actions![reset_announce_receipt],
+        };
+
         Port {
             config: PortConfig {
                 acceptable_master_list: (),
@@ -601,7 +857,13 @@ impl<'a, A, C, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
                 announce_receipt_timeout: config.announce_receipt_timeout,
                 sync_interval: config.sync_interval,
                 master_only: config.master_only,
+                local_priority: config.local_priority,
                 delay_asymmetry: config.delay_asymmetry,
+                unicast_negotiation,
+                unicast_master_table: config.unicast_master_table,
+                hybrid_mode: config.hybrid_mode,
+                one_step: config.one_step,
+                slave_event_monitoring: config.slave_event_monitoring,
             },
             filter_config,
             clock,
@@ -611,7 +873,7 @@ impl<'a, A, C, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
             rng,
             packet_buffer: [0; MAX_DATA_LEN],
             lifecycle: InBmca {
-                pending_action: actions![PortAction::ResetAnnounceReceiptTimer { duration }],
+                pending_action,
                 local_best: None,
                 state_refcell,
             },
@@ -619,9 +881,21 @@ impl<'a, A, C, F: Filter, R: Rng> Port<InBmca<'a>, A, R, C, F> {
             sync_seq_ids: SequenceIdGenerator::new(),
             delay_seq_ids: SequenceIdGenerator::new(),
             pdelay_seq_ids: SequenceIdGenerator::new(),
+            signaling_seq_ids: SequenceIdGenerator::new(),
             filter,
             mean_delay: None,
             peer_delay_state: PeerDelayState::Empty,
+            unicast,
+            gptp,
+            synchronization_metadata: None,
+            power_profile: None,
+            alternate_time_offset: None,
+            default_ds_changes: DefaultDSChanges::default(),
+            authentication_counters: AuthenticationCounters::default(),
+            counters: PortCounters::default(),
+            performance: PerformanceMonitor::default(),
+            slave_event_monitor: config.slave_event_monitoring.map(SlaveEventMonitor::new),
+            slave_event_reports: ArrayVec::new(),
         }
     }
 }
@@ -679,7 +953,13 @@ mod tests {
                 announce_receipt_timeout: 3,
                 sync_interval: Interval::from_log_2(0),
                 master_only: false,
+                local_priority: 128,
                 delay_asymmetry: Duration::ZERO,
+                unicast_negotiation: None,
+                unicast_master_table: Default::default(),
+                hybrid_mode: false,
+                one_step: false,
+                slave_event_monitoring: None,
             },
             0.25,
             TestClock,
@@ -706,7 +986,13 @@ mod tests {
                 announce_receipt_timeout: 3,
                 sync_interval: Interval::from_log_2(0),
                 master_only: false,
+                local_priority: 128,
                 delay_asymmetry: Duration::ZERO,
+                unicast_negotiation: None,
+                unicast_master_table: Default::default(),
+                hybrid_mode: false,
+                one_step: false,
+                slave_event_monitoring: None,
             },
             filter_config,
             TestClock,
@@ -726,6 +1012,8 @@ mod tests {
             domain_number: 0,
             slave_only: false,
             sdo_id: Default::default(),
+            profile: Default::default(),
+            local_priority: 128,
         });
 
         let parent_ds = InternalParentDS::new(default_ds);
@@ -735,6 +1023,18 @@ mod tests {
             current_ds: Default::default(),
             parent_ds,
             time_properties_ds: Default::default(),
+            local_time_properties_ds: Default::default(),
+            local_clock_quality: Default::default(),
+            holdover_config: None,
+            holdover: None,
+            synchronized_uncertainty: None,
+            synchronization_metadata: None,
+            local_synchronization_metadata: None,
+            power_profile: None,
+            local_power_profile: None,
+            alternate_time_offset: None,
+            local_alternate_time_offset: None,
+            security_config: None,
         });
         state
     }
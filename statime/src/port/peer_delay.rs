@@ -0,0 +1,339 @@
+use crate::{
+    datastructures::{
+        common::PortIdentity,
+        messages::{Header, PDelayRespFollowUpMessage, PDelayRespMessage},
+    },
+    time::{Duration, Time},
+};
+
+/// State of the peer delay measurement of a port, shared between the ports of
+/// ordinary/boundary clocks and those of peer-to-peer transparent clocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PeerDelayState {
+    Empty,
+    Measuring {
+        id: u16,
+        responder_identity: Option<PortIdentity>,
+        request_send_time: Option<Time>,
+        request_recv_time: Option<Time>,
+        response_send_time: Option<Time>,
+        response_recv_time: Option<Time>,
+    },
+    PostMeasurement {
+        id: u16,
+        responder_identity: PortIdentity,
+    },
+}
+
+/// Estimate of the rate of the clock of our peer relative to our own clock,
+/// from the timestamps of successive peer delay measurements, see
+/// *IEEE802.1AS-2020 section 11.2.19.3.3*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct NeighborRateRatio {
+    previous: Option<(PortIdentity, Time, Time)>,
+    ratio: f64,
+}
+
+impl Default for NeighborRateRatio {
+    fn default() -> Self {
+        Self {
+            previous: None,
+            ratio: 1.0,
+        }
+    }
+}
+
+impl NeighborRateRatio {
+    pub(super) fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    fn update(
+        &mut self,
+        responder: PortIdentity,
+        response_send_time: Time,
+        response_recv_time: Time,
+    ) {
+        match self.previous {
+            Some((previous_responder, previous_send_time, previous_recv_time))
+                if previous_responder == responder =>
+            {
+                let remote_interval = response_send_time - previous_send_time;
+                let local_interval = response_recv_time - previous_recv_time;
+                if remote_interval > Duration::ZERO && local_interval > Duration::ZERO {
+                    self.ratio = remote_interval.nanos_lossy() / local_interval.nanos_lossy();
+                }
+            }
+            // A different peer, start over
+            Some(_) => self.ratio = 1.0,
+            None => {}
+        }
+
+        self.previous = Some((responder, response_send_time, response_recv_time));
+    }
+}
+
+/// Reasons for a peer delay message not to contribute to a measurement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PeerDelayError {
+    /// The message is a duplicate, late, or not meant for us
+    Ignored,
+    /// More than one device responded to our request
+    MultipleResponders,
+    /// The response is for another request than the one we are measuring
+    SequenceMismatch,
+}
+
+impl PeerDelayState {
+    /// Whether the send timestamp of the request of the current measurement
+    /// is still missing
+    pub(super) fn awaiting_request_timestamp(&self) -> bool {
+        matches!(
+            self,
+            PeerDelayState::Measuring {
+                request_send_time: None,
+                ..
+            }
+        )
+    }
+
+    /// The error for a response that matches no request of this measurement
+    fn unexpected_response(&self) -> PeerDelayError {
+        match self {
+            PeerDelayState::Measuring { .. } => PeerDelayError::SequenceMismatch,
+            _ => PeerDelayError::Ignored,
+        }
+    }
+
+    /// Start a new measurement with the request with sequence id `id`
+    pub(super) fn new_measurement(id: u16) -> Self {
+        PeerDelayState::Measuring {
+            id,
+            responder_identity: None,
+            request_send_time: None,
+            request_recv_time: None,
+            response_send_time: None,
+            response_recv_time: None,
+        }
+    }
+
+    /// Record the send timestamp of our peer delay request
+    pub(super) fn handle_request_timestamp(
+        &mut self,
+        timestamp_id: u16,
+        timestamp: Time,
+    ) -> Result<(), PeerDelayError> {
+        match self {
+            PeerDelayState::Measuring {
+                id,
+                request_send_time: Some(_),
+                ..
+            } if *id == timestamp_id => {
+                log::error!("Double send timestamp for pdelay request");
+                Err(PeerDelayError::Ignored)
+            }
+            PeerDelayState::Measuring {
+                id,
+                request_send_time,
+                ..
+            } if *id == timestamp_id => {
+                *request_send_time = Some(timestamp);
+                Ok(())
+            }
+            _ => {
+                log::warn!("Late timestamp for pdelay request ignored");
+                Err(PeerDelayError::Ignored)
+            }
+        }
+    }
+
+    fn check_responder(&self, header: &Header) -> Result<(), PeerDelayError> {
+        match *self {
+            PeerDelayState::PostMeasurement {
+                id,
+                responder_identity,
+            } if id == header.sequence_id && responder_identity != header.source_port_identity => {
+                Err(PeerDelayError::MultipleResponders)
+            }
+            PeerDelayState::Measuring {
+                id,
+                responder_identity: Some(identity),
+                ..
+            } if id == header.sequence_id && identity != header.source_port_identity => {
+                Err(PeerDelayError::MultipleResponders)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a peer delay response to a request sent by `own_identity`
+    pub(super) fn handle_response(
+        &mut self,
+        own_identity: PortIdentity,
+        header: Header,
+        message: PDelayRespMessage,
+        recv_time: Time,
+    ) -> Result<(), PeerDelayError> {
+        if own_identity != message.requesting_port_identity {
+            return Err(PeerDelayError::Ignored);
+        }
+
+        self.check_responder(&header)?;
+
+        match self {
+            PeerDelayState::Measuring {
+                id,
+                response_recv_time: Some(_),
+                ..
+            } if *id == header.sequence_id => {
+                log::warn!("Duplicate PDelayResp message");
+                Err(PeerDelayError::Ignored)
+            }
+            PeerDelayState::Measuring {
+                id,
+                request_recv_time,
+                response_recv_time,
+                response_send_time,
+                responder_identity,
+                ..
+            } if *id == header.sequence_id => {
+                *response_recv_time = Some(recv_time - Duration::from(header.correction_field));
+                *request_recv_time = Some(message.request_receive_timestamp.into());
+                *responder_identity = Some(header.source_port_identity);
+
+                if !header.two_step_flag {
+                    *response_send_time = Some(message.request_receive_timestamp.into());
+                }
+                Ok(())
+            }
+            _ => {
+                log::warn!("Unexpected PDelayResp message");
+                Err(self.unexpected_response())
+            }
+        }
+    }
+
+    /// Record a peer delay response follow up to a request sent by
+    /// `own_identity`
+    pub(super) fn handle_response_follow_up(
+        &mut self,
+        own_identity: PortIdentity,
+        header: Header,
+        message: PDelayRespFollowUpMessage,
+    ) -> Result<(), PeerDelayError> {
+        if own_identity != message.requesting_port_identity {
+            return Err(PeerDelayError::Ignored);
+        }
+
+        self.check_responder(&header)?;
+
+        match self {
+            PeerDelayState::Measuring {
+                id,
+                response_send_time: Some(_),
+                ..
+            } if *id == header.sequence_id => {
+                log::warn!("Duplicate PDelayRespFollowUp message");
+                Err(PeerDelayError::Ignored)
+            }
+            PeerDelayState::Measuring {
+                id,
+                response_send_time,
+                responder_identity,
+                ..
+            } if *id == header.sequence_id => {
+                *response_send_time = Some(
+                    Time::from(message.response_origin_timestamp)
+                        + Duration::from(header.correction_field),
+                );
+                *responder_identity = Some(header.source_port_identity);
+                Ok(())
+            }
+            _ => {
+                log::warn!("Unexpected PDelayRespFollowUp message");
+                Err(self.unexpected_response())
+            }
+        }
+    }
+
+    /// Take the result of a completed measurement
+    ///
+    /// Returns the time the measurement completed and the measured peer delay.
+    /// When given a `neighbor_rate_ratio` it is updated with this measurement,
+    /// and used to express the peer delay in the time base of the peer.
+    pub(super) fn extract_measurement(
+        &mut self,
+        neighbor_rate_ratio: Option<&mut NeighborRateRatio>,
+    ) -> Option<(Time, Duration)> {
+        if let PeerDelayState::Measuring {
+            request_send_time: Some(request_send_time),
+            request_recv_time: Some(request_recv_time),
+            response_send_time: Some(response_send_time),
+            response_recv_time: Some(response_recv_time),
+            responder_identity: Some(responder_identity),
+            id,
+        } = *self
+        {
+            let round_trip = match neighbor_rate_ratio {
+                Some(neighbor_rate_ratio) => {
+                    neighbor_rate_ratio.update(
+                        responder_identity,
+                        response_send_time,
+                        response_recv_time,
+                    );
+                    (response_recv_time - request_send_time) * neighbor_rate_ratio.ratio()
+                }
+                None => response_recv_time - request_send_time,
+            };
+            let peer_delay = (round_trip - (response_send_time - request_recv_time)) / 2.0;
+            *self = PeerDelayState::PostMeasurement {
+                id,
+                responder_identity,
+            };
+
+            Some((response_recv_time, peer_delay))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure(
+        neighbor_rate_ratio: &mut NeighborRateRatio,
+        [t1, t2, t3, t4]: [u64; 4],
+    ) -> Option<Duration> {
+        let mut state = PeerDelayState::Measuring {
+            id: 0,
+            responder_identity: Some(PortIdentity::default()),
+            request_send_time: Some(Time::from_nanos(t1)),
+            request_recv_time: Some(Time::from_nanos(t2)),
+            response_send_time: Some(Time::from_nanos(t3)),
+            response_recv_time: Some(Time::from_nanos(t4)),
+        };
+        state
+            .extract_measurement(Some(neighbor_rate_ratio))
+            .map(|(_, peer_delay)| peer_delay)
+    }
+
+    #[test]
+    fn test_neighbor_rate_ratio() {
+        let mut neighbor_rate_ratio = NeighborRateRatio::default();
+
+        // The first measurement can't tell the rate of the peer
+        let peer_delay = measure(&mut neighbor_rate_ratio, [0, 1_000, 1_000, 2_000]);
+        assert_eq!(peer_delay, Some(Duration::from_nanos(1_000)));
+        assert_eq!(neighbor_rate_ratio.ratio(), 1.0);
+
+        // The clock of the peer runs twice as fast as ours
+        let peer_delay = measure(
+            &mut neighbor_rate_ratio,
+            [1_000_000, 2_001_000, 2_001_000, 1_002_000],
+        );
+        assert_eq!(neighbor_rate_ratio.ratio(), 2.0);
+        assert_eq!(peer_delay, Some(Duration::from_nanos(2_000)));
+    }
+}
@@ -0,0 +1,272 @@
+//! Performance monitoring of a port, see *IEEE1588-2019 annex J*
+//!
+//! The statistics of the measurements of the port are kept in 15-minute and
+//! 24-hour records of fixed size, so a port always uses the same amount of
+//! memory for them.
+
+use arrayvec::ArrayVec;
+
+use super::Measurement;
+#[allow(unused_imports)]
+use crate::float_polyfill::FloatPolyfill;
+use crate::{
+    observability::performance::{
+        PerformanceRecord, PerformanceStatistics, FIFTEEN_MINUTE_RECORDS, TWENTY_FOUR_HOUR_RECORDS,
+    },
+    time::Duration,
+};
+
+const FIFTEEN_MINUTES: u64 = 15 * 60;
+const TWENTY_FOUR_HOURS: u64 = 24 * 60 * 60;
+
+/// Running statistics of one parameter, using Welford's algorithm for the
+/// variance
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Accumulator {
+    samples: u32,
+    min: Duration,
+    max: Duration,
+    mean: f64,
+    m2: f64,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Duration) {
+        if self.samples == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.samples = self.samples.saturating_add(1);
+
+        let value = value.nanos_lossy();
+        let delta = value - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn statistics(&self) -> Option<PerformanceStatistics> {
+        if self.samples == 0 {
+            return None;
+        }
+
+        let variance = self.m2 / self.samples as f64;
+        Some(PerformanceStatistics {
+            samples: self.samples,
+            min: self.min.nanos_rounded(),
+            max: self.max.nanos_rounded(),
+            mean: Duration::from_fixed_nanos(self.mean).nanos_rounded(),
+            std_dev: Duration::from_fixed_nanos(variance.sqrt()).nanos_rounded(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Record {
+    start: u64,
+    offset_from_master: Accumulator,
+    mean_path_delay: Accumulator,
+    master_slave_delay: Accumulator,
+    slave_master_delay: Accumulator,
+}
+
+impl Record {
+    fn new(start: u64) -> Self {
+        Record {
+            start,
+            offset_from_master: Accumulator::default(),
+            mean_path_delay: Accumulator::default(),
+            master_slave_delay: Accumulator::default(),
+            slave_master_delay: Accumulator::default(),
+        }
+    }
+
+    fn add(&mut self, measurement: &Measurement) {
+        if let Some(offset) = measurement.offset {
+            self.offset_from_master.add(offset);
+        }
+        if let Some(delay) = measurement.delay.or(measurement.peer_delay) {
+            self.mean_path_delay.add(delay);
+        }
+        // t2 - t1
+        if let Some(raw_sync_offset) = measurement.raw_sync_offset {
+            self.master_slave_delay.add(raw_sync_offset);
+        }
+        // t4 - t3, the raw delay offset is t3 - t4
+        if let Some(raw_delay_offset) = measurement.raw_delay_offset {
+            self.slave_master_delay.add(-raw_delay_offset);
+        }
+    }
+
+    fn to_record(self, complete: bool) -> PerformanceRecord {
+        PerformanceRecord {
+            start: self.start,
+            complete,
+            offset_from_master: self.offset_from_master.statistics(),
+            mean_path_delay: self.mean_path_delay.statistics(),
+            master_slave_delay: self.master_slave_delay.statistics(),
+            slave_master_delay: self.slave_master_delay.statistics(),
+        }
+    }
+}
+
+/// The records of one period length, the most recent one last
+#[derive(Clone, Debug, PartialEq)]
+struct Records<const N: usize> {
+    length: u64,
+    records: ArrayVec<Record, N>,
+}
+
+impl<const N: usize> Records<N> {
+    fn new(length: u64) -> Self {
+        Records {
+            length,
+            records: ArrayVec::new(),
+        }
+    }
+
+    fn add(&mut self, measurement: &Measurement) {
+        let secs = measurement.event_time.secs();
+        let start = secs - secs % self.length;
+
+        match self.records.last_mut() {
+            Some(record) if record.start == start => record.add(measurement),
+            _ => {
+                if self.records.is_full() {
+                    self.records.remove(0);
+                }
+                let mut record = Record::new(start);
+                record.add(measurement);
+                self.records.push(record);
+            }
+        }
+    }
+
+    /// The record `index` periods back, 0 being the current one
+    fn get(&self, index: usize) -> Option<PerformanceRecord> {
+        let record = self.records.iter().rev().nth(index)?;
+        Some(record.to_record(index != 0))
+    }
+}
+
+/// The performance monitoring records of a port
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct PerformanceMonitor {
+    fifteen_minutes: Records<{ FIFTEEN_MINUTE_RECORDS as usize }>,
+    twenty_four_hours: Records<{ TWENTY_FOUR_HOUR_RECORDS as usize }>,
+}
+
+impl Default for PerformanceMonitor {
+    fn default() -> Self {
+        PerformanceMonitor {
+            fifteen_minutes: Records::new(FIFTEEN_MINUTES),
+            twenty_four_hours: Records::new(TWENTY_FOUR_HOURS),
+        }
+    }
+}
+
+impl PerformanceMonitor {
+    pub(super) fn add(&mut self, measurement: &Measurement) {
+        self.fifteen_minutes.add(measurement);
+        self.twenty_four_hours.add(measurement);
+    }
+
+    /// The record with the given index, see
+    /// [`PERFORMANCE_RECORDS`](crate::observability::performance::PERFORMANCE_RECORDS)
+    pub(super) fn record(&self, index: u16) -> Option<PerformanceRecord> {
+        match index.checked_sub(FIFTEEN_MINUTE_RECORDS) {
+            None => self.fifteen_minutes.get(index.into()),
+            Some(index) => self.twenty_four_hours.get(index.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Time;
+
+    fn measurement(secs: u64, offset: i64) -> Measurement {
+        Measurement {
+            event_time: Time::from_secs(secs),
+            offset: Some(Duration::from_nanos(offset)),
+            raw_sync_offset: Some(Duration::from_nanos(offset + 1000)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let mut accumulator = Accumulator::default();
+        assert_eq!(accumulator.statistics(), None);
+
+        for value in [2, 4, 4, 4, 5, 5, 7, 9] {
+            accumulator.add(Duration::from_nanos(value));
+        }
+        assert_eq!(
+            accumulator.statistics(),
+            Some(PerformanceStatistics {
+                samples: 8,
+                min: 2,
+                max: 9,
+                mean: 5,
+                std_dev: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn records() {
+        let mut monitor = PerformanceMonitor::default();
+        assert_eq!(monitor.record(0), None);
+
+        monitor.add(&measurement(100, 10));
+        monitor.add(&measurement(200, -10));
+        monitor.add(&Measurement {
+            event_time: Time::from_secs(300),
+            delay: Some(Duration::from_nanos(500)),
+            raw_delay_offset: Some(Duration::from_nanos(-600)),
+            ..Default::default()
+        });
+
+        let current = monitor.record(0).unwrap();
+        assert_eq!(current.start, 0);
+        assert!(!current.complete);
+        let offset = current.offset_from_master.unwrap();
+        assert_eq!((offset.samples, offset.min, offset.max), (2, -10, 10));
+        assert_eq!(offset.mean, 0);
+        assert_eq!(current.master_slave_delay.unwrap().mean, 1000);
+        assert_eq!(current.mean_path_delay.unwrap().mean, 500);
+        assert_eq!(current.slave_master_delay.unwrap().mean, 600);
+
+        // The next 15 minutes
+        monitor.add(&measurement(FIFTEEN_MINUTES + 1, 30));
+        let current = monitor.record(0).unwrap();
+        assert_eq!(current.start, FIFTEEN_MINUTES);
+        assert_eq!(current.offset_from_master.unwrap().samples, 1);
+        let previous = monitor.record(1).unwrap();
+        assert_eq!(previous.start, 0);
+        assert!(previous.complete);
+        assert_eq!(monitor.record(2), None);
+
+        let day = monitor.record(FIFTEEN_MINUTE_RECORDS).unwrap();
+        assert_eq!(day.start, 0);
+        assert_eq!(day.offset_from_master.unwrap().samples, 3);
+        assert_eq!(monitor.record(FIFTEEN_MINUTE_RECORDS + 1), None);
+
+        // Only the records of the last 24 hours are kept
+        for period in 2..=FIFTEEN_MINUTE_RECORDS as u64 {
+            monitor.add(&measurement(period * FIFTEEN_MINUTES, 0));
+        }
+        assert_eq!(
+            monitor.record(FIFTEEN_MINUTE_RECORDS - 1).unwrap().start,
+            FIFTEEN_MINUTES
+        );
+        assert_eq!(monitor.record(0).unwrap().start, TWENTY_FOUR_HOURS);
+        let previous_day = monitor.record(FIFTEEN_MINUTE_RECORDS + 1).unwrap();
+        assert_eq!(previous_day.start, 0);
+        assert!(previous_day.complete);
+    }
+}
@@ -0,0 +1,245 @@
+//! Port behaviour for the TLVs of the power profile, see
+//! [`Profile::C37_238`]
+//!
+//! The grandmaster adds the IEEE_C37_238 TLV, and optionally the alternate
+//! time offset indicator TLV, to announce messages. Slaves remember these for
+//! their master, which the instance takes over during the BMCA. Boundary
+//! clocks pass them on in their own announce messages, adding their time
+//! inaccuracy to the network time inaccuracy.
+
+use super::{state::PortState, ForwardedTLV, InBmca, Port, Running};
+#[cfg(doc)]
+use crate::config::Profile;
+use crate::{
+    config::AlternateTimeOffset,
+    datastructures::{
+        common::{PortIdentity, PowerProfileInformation, Tlv, TlvSetBuilder, TlvType},
+        messages::Message,
+    },
+    filters::Filter,
+};
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Remember the power profile TLVs in an announce message
+    ///
+    /// Once we are a slave, only the TLVs of our master are kept.
+    pub(super) fn handle_power_profile_tlvs(&mut self, message: &Message<'_>) {
+        let sender = message.header.source_port_identity;
+        if let PortState::Slave(slave) = &self.port_state {
+            if slave.remote_master() != sender {
+                return;
+            }
+        }
+
+        let information = message
+            .suffix
+            .tlv()
+            .find_map(|tlv| PowerProfileInformation::from_tlv(&tlv).ok().flatten());
+        let offset = message
+            .suffix
+            .tlv()
+            .find_map(|tlv| AlternateTimeOffset::from_tlv(&tlv).ok().flatten());
+
+        // Forget what the sender stopped sending
+        self.power_profile = information
+            .map(|information| (sender, information))
+            .or(self.power_profile.filter(|(old, _)| *old != sender));
+        self.alternate_time_offset = offset
+            .map(|offset| (sender, offset))
+            .or(self.alternate_time_offset.filter(|(old, _)| *old != sender));
+    }
+
+    /// Whether a forwarded TLV is one of the power profile TLVs, which are not
+    /// forwarded as is. Those of the instance are added to the announce
+    /// messages instead.
+    pub(super) fn is_power_profile_tlv(&self, tlv: &ForwardedTLV<'_>) -> bool {
+        matches!(PowerProfileInformation::from_tlv(&tlv.tlv), Ok(Some(_)))
+            || tlv.tlv.tlv_type == TlvType::AlternateTimeOffsetIndicator
+    }
+
+    /// Add the power profile TLVs of the grandmaster to an announce message,
+    /// see *IEEE C37.238-2011 section 5.12*
+    ///
+    /// A TLV is left out when it would not fit in `margin` bytes.
+    pub(super) fn add_power_profile_tlvs(
+        &self,
+        tlv_builder: &mut TlvSetBuilder<'_>,
+        mut margin: usize,
+    ) {
+        let state = &self.lifecycle.state;
+
+        if let Some(mut information) = state.power_profile {
+            // Our own inaccuracy adds to that of the network, unless we are the
+            // grandmaster
+            if state.parent_ds.grandmaster_identity != state.default_ds.clock_identity {
+                let own = state.local_power_profile.map_or(0, |c| c.time_inaccuracy);
+                information.network_time_inaccuracy =
+                    information.network_time_inaccuracy.saturating_add(own);
+            }
+
+            let mut value = [0; 18];
+            match information.serialize_value(&mut value) {
+                Ok(value) => {
+                    margin =
+                        Self::add_tlv(tlv_builder, margin, TlvType::OrganizationExtension, value)
+                }
+                Err(error) => log::error!(
+                    "Statime bug: Could not build power profile TLV: {:?}",
+                    error
+                ),
+            }
+        }
+
+        if let Some(offset) = state.alternate_time_offset {
+            let mut value = [0; 32];
+            match offset.serialize_value(&mut value) {
+                Ok(value) => {
+                    Self::add_tlv(
+                        tlv_builder,
+                        margin,
+                        TlvType::AlternateTimeOffsetIndicator,
+                        value,
+                    );
+                }
+                Err(error) => log::error!(
+                    "Statime bug: Could not build alternate time offset TLV: {:?}",
+                    error
+                ),
+            }
+        }
+    }
+
+    /// Add a TLV if it fits in `margin` bytes, returns the remaining margin
+    fn add_tlv(
+        tlv_builder: &mut TlvSetBuilder<'_>,
+        margin: usize,
+        tlv_type: TlvType,
+        value: &[u8],
+    ) -> usize {
+        let tlv = Tlv {
+            tlv_type,
+            value: value.into(),
+        };
+
+        let size = tlv.wire_size();
+        if size >= margin {
+            return margin;
+        }
+        // Will not fail as we checked there is enough space in the buffer
+        tlv_builder.add(tlv).unwrap();
+        margin - size
+    }
+}
+
+impl<'a, A, C, F: Filter, R> Port<InBmca<'a>, A, R, C, F> {
+    /// The power profile information last received from `sender`
+    pub(crate) fn power_profile_of(&self, sender: PortIdentity) -> Option<PowerProfileInformation> {
+        match self.power_profile {
+            Some((identity, information)) if identity == sender => Some(information),
+            _ => None,
+        }
+    }
+
+    /// The alternate time offset last received from `sender`
+    pub(crate) fn alternate_time_offset_of(
+        &self,
+        sender: PortIdentity,
+    ) -> Option<AlternateTimeOffset> {
+        match self.alternate_time_offset {
+            Some((identity, offset)) if identity == sender => Some(offset),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{ClockIdentity, PowerProfileConfig},
+        datastructures::messages::MessageBody,
+        port::{
+            tests::{setup_test_port, setup_test_state},
+            NoForwardedTLVs, PortAction,
+        },
+    };
+
+    const INFORMATION: PowerProfileInformation = PowerProfileInformation {
+        grandmaster_id: 5,
+        grandmaster_time_inaccuracy: 50,
+        network_time_inaccuracy: 200,
+    };
+
+    const UPSTREAM: PortIdentity = PortIdentity {
+        clock_identity: ClockIdentity([2; 8]),
+        port_number: 1,
+    };
+
+    #[test]
+    fn test_send_power_profile_tlv() {
+        let state = setup_test_state();
+        state.borrow_mut().parent_ds.grandmaster_identity = UPSTREAM.clock_identity;
+        state.borrow_mut().power_profile = Some(INFORMATION);
+        state.borrow_mut().local_power_profile = Some(PowerProfileConfig {
+            grandmaster_id: 6,
+            time_inaccuracy: 100,
+        });
+
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        let mut actions = port.send_announce(&mut NoForwardedTLVs);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+
+        // As a boundary clock our inaccuracy adds to that of the network
+        let announce = Message::deserialize(data).unwrap();
+        let tlv = announce.suffix.tlv().next().unwrap();
+        assert_eq!(
+            PowerProfileInformation::from_tlv(&tlv).unwrap(),
+            Some(PowerProfileInformation {
+                network_time_inaccuracy: 300,
+                ..INFORMATION
+            })
+        );
+    }
+
+    #[test]
+    fn test_receive_power_profile_tlv() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut announce = Message::announce(&state.borrow(), UPSTREAM, 0);
+        let MessageBody::Announce(announce_body) = announce.body else {
+            panic!("Unexpected message");
+        };
+
+        let mut value = [0; 18];
+        let mut tlv_buffer = [0; 32];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        tlv_builder
+            .add(Tlv {
+                tlv_type: TlvType::OrganizationExtension,
+                value: INFORMATION.serialize_value(&mut value).unwrap().into(),
+            })
+            .unwrap();
+        announce.suffix = tlv_builder.build();
+        drop(port.handle_announce(&announce, announce_body));
+
+        let port = port.start_bmca();
+        assert_eq!(port.power_profile_of(UPSTREAM), Some(INFORMATION));
+        assert_eq!(port.alternate_time_offset_of(UPSTREAM), None);
+        let (mut port, _) = port.end_bmca();
+
+        // The information is forgotten when the master stops sending it
+        announce.suffix = Default::default();
+        drop(port.handle_announce(&announce, announce_body));
+        let port = port.start_bmca();
+        assert_eq!(port.power_profile_of(UPSTREAM), None);
+    }
+}
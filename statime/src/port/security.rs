@@ -0,0 +1,373 @@
+//! Port behaviour for the AUTHENTICATION TLV, see *IEEE1588-2019 annex P*
+//!
+//! With a [`SecurityConfig`] on the instance, the port appends an
+//! AUTHENTICATION TLV to every message it sends, and drops received messages
+//! that are not authenticated with one of the configured keys. Only immediate
+//! security processing is supported: the TLV has no disclosed key, sequence
+//! number or RES field.
+
+use super::{Port, Running};
+use crate::{
+    config::{SecurityConfig, MAX_ICV_LENGTH},
+    datastructures::{
+        common::{Tlv, TlvType},
+        messages::Message,
+    },
+    filters::Filter,
+    observability::security::AuthenticationCounters,
+};
+
+/// Size of the fields of the TLV value before the ICV: the security parameter
+/// pointer, the secParamIndicator and the keyID
+const PARAMETERS_LENGTH: usize = 6;
+
+/// Why a received message failed authentication
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AuthenticationError {
+    /// The message has no AUTHENTICATION TLV as its last TLV
+    Missing,
+    /// The TLV is for another security association
+    UnknownSecurityParameterPointer(u8),
+    /// The key of the TLV is not configured
+    UnknownKey(u32),
+    /// The TLV uses delayed security processing
+    Unsupported,
+    /// The ICV does not match the message
+    InvalidIcv,
+}
+
+impl AuthenticationCounters {
+    fn count(&mut self, error: AuthenticationError) {
+        match error {
+            AuthenticationError::Missing => self.unauthenticated += 1,
+            _ => self.authentication_failed += 1,
+        }
+    }
+}
+
+/// Append an AUTHENTICATION TLV to the message of `length` bytes at the start
+/// of `buffer`, returns the new length of the message
+pub(crate) fn append_authentication_tlv(
+    config: &SecurityConfig,
+    buffer: &mut [u8],
+    length: usize,
+) -> Option<usize> {
+    let key = config.key(config.key_id)?;
+    let icv_length = key.algorithm.icv_length();
+    let value_length = PARAMETERS_LENGTH + icv_length;
+    let new_length = length + 4 + value_length;
+
+    let tlv = buffer.get_mut(length..new_length)?;
+    tlv[0..2].copy_from_slice(&TlvType::Authentication.to_primitive().to_be_bytes());
+    tlv[2..4].copy_from_slice(&(value_length as u16).to_be_bytes());
+    tlv[4] = config.spp;
+    // Immediate security processing, no optional fields
+    tlv[5] = 0;
+    tlv[6..10].copy_from_slice(&config.key_id.to_be_bytes());
+
+    // The ICV covers the header with its final messageLength
+    buffer[2..4].copy_from_slice(&u16::try_from(new_length).ok()?.to_be_bytes());
+    let icv = key.icv(&buffer[..new_length - icv_length])?;
+    buffer[new_length - icv_length..new_length].copy_from_slice(&icv);
+
+    Some(new_length)
+}
+
+/// Check the AUTHENTICATION TLV of `message`, which was parsed from `data`
+pub(crate) fn verify_authentication(
+    config: &SecurityConfig,
+    data: &[u8],
+    message: &Message<'_>,
+) -> Result<(), AuthenticationError> {
+    let tlv = message
+        .suffix
+        .tlv()
+        .last()
+        .filter(|tlv| tlv.tlv_type == TlvType::Authentication)
+        .ok_or(AuthenticationError::Missing)?;
+    let (spp, sec_param_indicator, key_id, icv) = parse_authentication_tlv(&tlv)?;
+
+    if spp != config.spp {
+        return Err(AuthenticationError::UnknownSecurityParameterPointer(spp));
+    }
+    if sec_param_indicator != 0 {
+        return Err(AuthenticationError::Unsupported);
+    }
+    let key = config
+        .key(key_id)
+        .ok_or(AuthenticationError::UnknownKey(key_id))?;
+
+    // The TLV is the last one, so the ICV ends the message
+    let message_length = message.wire_size();
+    let covered = data
+        .get(..message_length)
+        .and_then(|message| message.get(..message.len().checked_sub(icv.len())?))
+        .ok_or(AuthenticationError::InvalidIcv)?;
+
+    if key.verify(covered, icv) {
+        Ok(())
+    } else {
+        Err(AuthenticationError::InvalidIcv)
+    }
+}
+
+fn parse_authentication_tlv<'a>(
+    tlv: &'a Tlv<'_>,
+) -> Result<(u8, u8, u32, &'a [u8]), AuthenticationError> {
+    let value: &[u8] = tlv.value.as_ref();
+    if value.len() < PARAMETERS_LENGTH || value.len() > PARAMETERS_LENGTH + MAX_ICV_LENGTH {
+        return Err(AuthenticationError::InvalidIcv);
+    }
+
+    let key_id = u32::from_be_bytes(value[2..6].try_into().unwrap());
+    Ok((value[0], value[1], key_id, &value[PARAMETERS_LENGTH..]))
+}
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Authenticate the message of `length` bytes in the packet buffer,
+    /// returns the length of the authenticated message
+    ///
+    /// Without a security configuration the message is left as is. Returns
+    /// `None` when the message cannot be authenticated, such a message should
+    /// not be sent.
+    pub(super) fn authenticate(&mut self, length: usize) -> Option<usize> {
+        let Some(config) = &self.lifecycle.state.security_config else {
+            return Some(length);
+        };
+
+        let length = append_authentication_tlv(config, &mut self.packet_buffer, length);
+        if length.is_none() {
+            log::error!("Could not authenticate message with key {}", config.key_id);
+        }
+        length
+    }
+
+    /// Whether a received message passes the security processing, counting
+    /// the messages that do not
+    pub(super) fn check_authentication(&mut self, data: &[u8], message: &Message<'_>) -> bool {
+        let Some(config) = &self.lifecycle.state.security_config else {
+            return true;
+        };
+
+        match verify_authentication(config, data, message) {
+            Ok(()) => true,
+            Err(error) => {
+                log::debug!(
+                    "Dropping message from {:?}: {:?}",
+                    message.header.source_port_identity,
+                    error
+                );
+                self.authentication_counters.count(error);
+                false
+            }
+        }
+    }
+}
+
+impl<L, A, R, C, F: Filter> Port<L, A, R, C, F> {
+    /// The number of messages this port dropped because of their
+    /// authentication, see [`SecurityConfig`]
+    pub fn authentication_counters(&self) -> AuthenticationCounters {
+        self.authentication_counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrayvec::ArrayVec;
+
+    use super::*;
+    use crate::{
+        config::{IntegrityAlgorithm, SecurityKey},
+        datastructures::{common::PortIdentity, messages::MAX_DATA_LEN},
+        port::{
+            state::PortState,
+            tests::{setup_test_port, setup_test_state},
+            NoForwardedTLVs, PortAction,
+        },
+        time::Time,
+    };
+
+    fn config(algorithm: IntegrityAlgorithm) -> SecurityConfig {
+        let mut keys = ArrayVec::new();
+        keys.push(SecurityKey {
+            key_id: 7,
+            algorithm,
+            secret: [0x42; 16][..].try_into().unwrap(),
+            valid_from: None,
+            valid_until: None,
+        });
+        SecurityConfig {
+            spp: 3,
+            keys,
+            key_id: 7,
+        }
+    }
+
+    fn authenticated_sync(config: &SecurityConfig, buffer: &mut [u8]) -> usize {
+        let state = setup_test_state();
+        let message = Message::sync(&state.borrow().default_ds, PortIdentity::default(), 5);
+        let length = message.serialize(buffer).unwrap();
+        append_authentication_tlv(config, buffer, length).unwrap()
+    }
+
+    #[test]
+    fn authentication_tlv_roundtrip() {
+        for algorithm in [
+            IntegrityAlgorithm::HmacSha256,
+            IntegrityAlgorithm::HmacSha256_128,
+            IntegrityAlgorithm::Aes128Cmac,
+        ] {
+            let config = config(algorithm);
+            let mut buffer = [0; MAX_DATA_LEN];
+            let length = authenticated_sync(&config, &mut buffer);
+            assert_eq!(length, 44 + 10 + algorithm.icv_length());
+
+            let data = &buffer[..length];
+            let message = Message::deserialize(data).unwrap();
+            assert_eq!(verify_authentication(&config, data, &message), Ok(()));
+
+            let tlv = message.suffix.tlv().last().unwrap();
+            assert_eq!(tlv.tlv_type, TlvType::Authentication);
+            assert_eq!(&tlv.value[..6], &[3, 0, 0, 0, 0, 7]);
+        }
+    }
+
+    #[test]
+    fn authentication_failures() {
+        let config = config(IntegrityAlgorithm::HmacSha256_128);
+        let mut buffer = [0; MAX_DATA_LEN];
+        let length = authenticated_sync(&config, &mut buffer);
+
+        // Transparent clocks may change the correctionField
+        let mut corrected = buffer;
+        corrected[8..16].copy_from_slice(&1000i64.to_be_bytes());
+        let message = Message::deserialize(&corrected[..length]).unwrap();
+        assert_eq!(verify_authentication(&config, &corrected, &message), Ok(()));
+
+        let mut tampered = buffer;
+        tampered[40] ^= 1;
+        let message = Message::deserialize(&tampered[..length]).unwrap();
+        assert_eq!(
+            verify_authentication(&config, &tampered, &message),
+            Err(AuthenticationError::InvalidIcv)
+        );
+
+        let mut other_key = config.clone();
+        other_key.keys[0].key_id = 8;
+        let message = Message::deserialize(&buffer[..length]).unwrap();
+        assert_eq!(
+            verify_authentication(&other_key, &buffer, &message),
+            Err(AuthenticationError::UnknownKey(7))
+        );
+
+        let other_spp = SecurityConfig {
+            spp: 4,
+            ..config.clone()
+        };
+        assert_eq!(
+            verify_authentication(&other_spp, &buffer, &message),
+            Err(AuthenticationError::UnknownSecurityParameterPointer(3))
+        );
+
+        let mut delayed = buffer;
+        delayed[44 + 5] = 1;
+        let message = Message::deserialize(&delayed[..length]).unwrap();
+        assert_eq!(
+            verify_authentication(&config, &delayed, &message),
+            Err(AuthenticationError::Unsupported)
+        );
+
+        let state = setup_test_state();
+        let message = Message::sync(&state.borrow().default_ds, PortIdentity::default(), 5);
+        let length = message.serialize(&mut buffer).unwrap();
+        let message = Message::deserialize(&buffer[..length]).unwrap();
+        assert_eq!(
+            verify_authentication(&config, &buffer, &message),
+            Err(AuthenticationError::Missing)
+        );
+    }
+
+    #[test]
+    fn port_authentication() {
+        let config = config(IntegrityAlgorithm::HmacSha256_128);
+        let state = setup_test_state();
+        state.borrow_mut().security_config = Some(config.clone());
+
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        let mut buffer = [0; MAX_DATA_LEN];
+        let mut actions = port.send_announce(&mut NoForwardedTLVs);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+        let length = data.len();
+        buffer[..length].copy_from_slice(data);
+        drop(actions);
+
+        let announce = &buffer[..length];
+        let message = Message::deserialize(announce).unwrap();
+        assert_eq!(verify_authentication(&config, announce, &message), Ok(()));
+
+        drop(port.handle_general_receive(announce));
+        assert_eq!(port.authentication_counters(), Default::default());
+
+        let mut tampered = buffer;
+        tampered[50] ^= 1;
+        drop(port.handle_general_receive(&tampered[..length]));
+
+        let sync = Message::sync(&state.borrow().default_ds, PortIdentity::default(), 1);
+        let length = sync.serialize(&mut buffer).unwrap();
+        drop(port.handle_event_receive(&buffer[..length], Time::from_secs(1)));
+
+        assert_eq!(
+            port.authentication_counters(),
+            AuthenticationCounters {
+                unauthenticated: 1,
+                authentication_failed: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn no_valid_key() {
+        let mut expired = config(IntegrityAlgorithm::HmacSha256_128);
+        expired.keys[0].valid_until = Some(Time::from_secs(10));
+
+        let mut missing = config(IntegrityAlgorithm::HmacSha256_128);
+        missing.key_id = 8;
+
+        for config in [expired.active(Time::from_secs(100)), missing] {
+            let state = setup_test_state();
+            state.borrow_mut().security_config = Some(config);
+
+            let mut port = setup_test_port(&state);
+            port.set_forced_port_state(PortState::Master);
+
+            // Messages that cannot be authenticated are not sent, but the
+            // timers keep running
+            let mut actions = port.send_announce(&mut NoForwardedTLVs);
+            assert!(matches!(
+                actions.next(),
+                Some(PortAction::ResetAnnounceTimer { .. })
+            ));
+            assert!(actions.next().is_none());
+            drop(actions);
+
+            let mut actions = port.send_sync();
+            assert!(matches!(
+                actions.next(),
+                Some(PortAction::ResetSyncTimer { .. })
+            ));
+            assert!(actions.next().is_none());
+            drop(actions);
+
+            assert_eq!(port.counters.tx, Default::default());
+        }
+    }
+}
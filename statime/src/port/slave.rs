@@ -1,14 +1,19 @@
+use arrayvec::ArrayVec;
 use rand::Rng;
 
 use super::{
+    peer_delay::{PeerDelayError, PeerDelayState},
     state::{DelayState, PortState},
-    Measurement, PeerDelayState, Port, PortActionIterator, Running,
+    Measurement, Port, PortActionIterator, Running,
 };
 use crate::{
     config::DelayMechanism,
-    datastructures::messages::{
-        DelayRespMessage, FollowUpMessage, Header, Message, PDelayRespFollowUpMessage,
-        PDelayRespMessage, SyncMessage,
+    datastructures::{
+        common::PortAddress,
+        messages::{
+            DelayRespMessage, FollowUpMessage, Header, Message, PDelayRespFollowUpMessage,
+            PDelayRespMessage, SyncMessage,
+        },
     },
     filters::Filter,
     port::{actions::TimestampContextInner, state::SyncState, PortAction, TimestampContext},
@@ -17,18 +22,27 @@ use crate::{
 };
 
 impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
-    pub(super) fn handle_time_measurement<'b>(&mut self) -> PortActionIterator<'b> {
+    pub(super) fn handle_time_measurement(&mut self) -> PortActionIterator<'_> {
+        let mut actions = ArrayVec::new();
         if let Some(measurement) = self.extract_measurement() {
+            self.performance.add(&measurement);
             // If the received message allowed the (slave) state to calculate its offset
             // from the master, update the local clock
             let filter_updates = self.filter.measurement(measurement, &mut self.clock);
             if let Some(mean_delay) = filter_updates.mean_delay {
                 self.mean_delay = Some(mean_delay);
             }
-            PortActionIterator::from_filter(filter_updates)
-        } else {
-            actions![]
+            if let Some(offset) = measurement.offset {
+                self.monitor_offset(offset);
+            }
+            if let Some(duration) = filter_updates.next_update {
+                actions.push(PortAction::ResetFilterUpdateTimer { duration });
+            }
+        }
+        if let Some(action) = self.send_slave_event_report() {
+            actions.push(action);
         }
+        PortActionIterator::from(actions)
     }
 
     pub(super) fn handle_delay_timestamp(
@@ -52,6 +66,7 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                     ..
                 } if id == timestamp_id => {
                     *send_time = Some(timestamp);
+                    self.monitor_send_timestamp(timestamp_id, timestamp);
                     self.handle_time_measurement()
                 }
                 _ => {
@@ -68,26 +83,24 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         timestamp_id: u16,
         timestamp: Time,
     ) -> PortActionIterator {
-        match self.peer_delay_state {
-            PeerDelayState::Measuring {
-                id,
-                request_send_time: Some(_),
-                ..
-            } if id == timestamp_id => {
-                log::error!("Double send timestamp for pdelay request");
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                ref mut request_send_time,
-                ..
-            } if id == timestamp_id => {
-                *request_send_time = Some(timestamp);
+        match self
+            .peer_delay_state
+            .handle_request_timestamp(timestamp_id, timestamp)
+        {
+            Ok(()) => {
+                self.monitor_send_timestamp(timestamp_id, timestamp);
                 self.handle_time_measurement()
             }
-            _ => {
-                log::warn!("Late timestamp for pdelay request ignored");
-                actions![]
+            Err(error) => self.handle_peer_delay_error(error),
+        }
+    }
+
+    /// Remember the address our master sends its sync messages from, needed
+    /// for hybrid mode
+    pub(super) fn learn_sync_source(&mut self, header: &Header, source: PortAddress) {
+        if let PortState::Slave(state) = &mut self.port_state {
+            if header.source_port_identity == state.remote_master {
+                state.remote_master_address = Some(source);
             }
         }
     }
@@ -106,6 +119,12 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                 // time
                 let corrected_recv_time = recv_time - Duration::from(header.correction_field);
 
+                if let Some(monitor) = &mut self.slave_event_monitor {
+                    let origin_timestamp =
+                        (!header.two_step_flag).then(|| Time::from(message.origin_timestamp));
+                    monitor.handle_sync(&header, origin_timestamp, recv_time);
+                }
+
                 if header.two_step_flag {
                     match state.sync_state {
                         SyncState::Measuring {
@@ -126,6 +145,9 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                             self.handle_time_measurement()
                         }
                         _ => {
+                            if state.awaiting_follow_up() {
+                                self.counters.missing_follow_ups += 1;
+                            }
                             state.sync_state = SyncState::Measuring {
                                 id: header.sequence_id,
                                 send_time: None,
@@ -142,6 +164,9 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                             actions![]
                         }
                         _ => {
+                            if state.awaiting_follow_up() {
+                                self.counters.missing_follow_ups += 1;
+                            }
                             state.sync_state = SyncState::Measuring {
                                 id: header.sequence_id,
                                 send_time: Some(Time::from(message.origin_timestamp)),
@@ -168,6 +193,10 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                 let packet_send_time = Time::from(message.precise_origin_timestamp)
                     + Duration::from(header.correction_field);
 
+                if let Some(monitor) = &mut self.slave_event_monitor {
+                    monitor.handle_follow_up(&header, Time::from(message.precise_origin_timestamp));
+                }
+
                 match state.sync_state {
                     SyncState::Measuring {
                         id,
@@ -187,6 +216,10 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                         self.handle_time_measurement()
                     }
                     _ => {
+                        // A follow up for another sync than the one we wait for
+                        if state.awaiting_follow_up() {
+                            self.counters.sequence_id_mismatches += 1;
+                        }
                         state.sync_state = SyncState::Measuring {
                             id: header.sequence_id,
                             send_time: Some(packet_send_time),
@@ -233,6 +266,11 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
                         );
                         self.handle_time_measurement()
                     }
+                    DelayState::Measuring { .. } => {
+                        log::warn!("DelayResp for another delay request");
+                        self.counters.sequence_id_mismatches += 1;
+                        actions![]
+                    }
                     _ => {
                         log::warn!("Unexpected DelayResp message");
                         // Ignore the Delay response
@@ -250,61 +288,12 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         message: PDelayRespMessage,
         recv_time: Time,
     ) -> PortActionIterator {
-        if self.port_identity != message.requesting_port_identity {
-            return actions![];
-        }
-
-        match self.peer_delay_state {
-            PeerDelayState::PostMeasurement {
-                id,
-                responder_identity,
-            } if id == header.sequence_id && responder_identity != header.source_port_identity => {
-                log::error!(
-                    "Responses from multiple devices to peer delay request, disabling port!"
-                );
-                self.set_forced_port_state(PortState::Faulty);
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                responder_identity: Some(identity),
-                ..
-            } if id == header.sequence_id && identity != header.source_port_identity => {
-                log::error!(
-                    "Responses from multiple devices to peer delay request, disabling port!"
-                );
-                self.set_forced_port_state(PortState::Faulty);
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                response_recv_time: Some(_),
-                ..
-            } if id == header.sequence_id => {
-                log::warn!("Duplicate PDelayResp message");
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                ref mut request_recv_time,
-                ref mut response_recv_time,
-                ref mut response_send_time,
-                ref mut responder_identity,
-                ..
-            } if id == header.sequence_id => {
-                *response_recv_time = Some(recv_time - Duration::from(header.correction_field));
-                *request_recv_time = Some(message.request_receive_timestamp.into());
-                *responder_identity = Some(header.source_port_identity);
-
-                if !header.two_step_flag {
-                    *response_send_time = Some(message.request_receive_timestamp.into());
-                }
-                self.handle_time_measurement()
-            }
-            _ => {
-                log::warn!("Unexpected PDelayResp message");
-                actions![]
-            }
+        match self
+            .peer_delay_state
+            .handle_response(self.port_identity, header, message, recv_time)
+        {
+            Ok(()) => self.handle_time_measurement(),
+            Err(error) => self.handle_peer_delay_error(error),
         }
     }
 
@@ -313,82 +302,43 @@ impl<'a, A, C: Clock, F: Filter, R> Port<Running<'a>, A, R, C, F> {
         header: Header,
         message: PDelayRespFollowUpMessage,
     ) -> PortActionIterator {
-        if self.port_identity != message.requesting_port_identity {
-            return actions![];
+        match self
+            .peer_delay_state
+            .handle_response_follow_up(self.port_identity, header, message)
+        {
+            Ok(()) => self.handle_time_measurement(),
+            Err(error) => self.handle_peer_delay_error(error),
         }
+    }
 
-        match self.peer_delay_state {
-            PeerDelayState::PostMeasurement {
-                id,
-                responder_identity,
-            } if id == header.sequence_id && responder_identity != header.source_port_identity => {
+    fn handle_peer_delay_error<'b>(&mut self, error: PeerDelayError) -> PortActionIterator<'b> {
+        match error {
+            PeerDelayError::MultipleResponders => {
                 log::error!(
                     "Responses from multiple devices to peer delay request, disabling port!"
                 );
                 self.set_forced_port_state(PortState::Faulty);
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                responder_identity: Some(identity),
-                ..
-            } if id == header.sequence_id && identity != header.source_port_identity => {
-                log::error!(
-                    "Responses from multiple devices to peer delay request, disabling port!"
-                );
-                self.set_forced_port_state(PortState::Faulty);
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                response_send_time: Some(_),
-                ..
-            } if id == header.sequence_id => {
-                log::warn!("Duplicate PDelayRespFollowUp message");
-                actions![]
-            }
-            PeerDelayState::Measuring {
-                id,
-                ref mut response_send_time,
-                ref mut responder_identity,
-                ..
-            } if id == header.sequence_id => {
-                *response_send_time = Some(
-                    Time::from(message.response_origin_timestamp)
-                        + Duration::from(header.correction_field),
-                );
-                *responder_identity = Some(header.source_port_identity);
-                self.handle_time_measurement()
-            }
-            _ => {
-                log::warn!("Unexpected PDelayRespFollowUp message");
-                actions![]
             }
+            PeerDelayError::SequenceMismatch => self.counters.sequence_id_mismatches += 1,
+            PeerDelayError::Ignored => {}
         }
+        actions![]
     }
 
     fn extract_measurement(&mut self) -> Option<Measurement> {
         let mut result = Measurement::default();
 
-        if let PeerDelayState::Measuring {
-            request_send_time: Some(request_send_time),
-            request_recv_time: Some(request_recv_time),
-            response_send_time: Some(response_send_time),
-            response_recv_time: Some(response_recv_time),
-            responder_identity: Some(responder_identity),
-            id,
-        } = self.peer_delay_state
+        let neighbor_rate_ratio = self.gptp.as_mut().map(|gptp| &mut gptp.neighbor_rate_ratio);
+        if let Some((event_time, peer_delay)) = self
+            .peer_delay_state
+            .extract_measurement(neighbor_rate_ratio)
         {
-            result.event_time = response_recv_time;
-            result.peer_delay = Some(
-                ((response_recv_time - request_send_time)
-                    - (response_send_time - request_recv_time))
-                    / 2.0,
-            );
-            self.peer_delay_state = PeerDelayState::PostMeasurement {
-                id,
-                responder_identity,
-            };
+            if let Some(gptp) = &mut self.gptp {
+                gptp.handle_peer_delay(peer_delay);
+            }
+
+            result.event_time = event_time;
+            result.peer_delay = Some(peer_delay);
 
             log::info!("Measurement: {:?}", result);
 
@@ -459,6 +409,27 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
         &mut self,
         log_min_pdelay_req_interval: Interval,
     ) -> PortActionIterator {
+        let random = self.rng.sample::<f64, _>(rand::distributions::Open01);
+        let factor = random * 2.0f64;
+        let duration = log_min_pdelay_req_interval
+            .as_core_duration()
+            .mul_f64(factor);
+
+        // Keep the timer running so measurements resume once the port is enabled
+        if matches!(self.port_state, PortState::Disabled) {
+            return actions![PortAction::ResetDelayRequestTimer { duration }];
+        }
+
+        if self.peer_delay_state.awaiting_request_timestamp() {
+            self.counters.timestamp_timeouts += 1;
+        }
+
+        if let (Some(gptp), PeerDelayState::Measuring { .. }) =
+            (&mut self.gptp, self.peer_delay_state)
+        {
+            gptp.handle_lost_response();
+        }
+
         let pdelay_id = self.pdelay_seq_ids.generate();
 
         let pdelay_req = Message::pdelay_req(
@@ -473,21 +444,11 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
                 return actions![];
             }
         };
-
-        self.peer_delay_state = PeerDelayState::Measuring {
-            id: pdelay_id,
-            responder_identity: None,
-            request_send_time: None,
-            request_recv_time: None,
-            response_send_time: None,
-            response_recv_time: None,
+        let Some(message_length) = self.finish_message(message_length) else {
+            return actions![PortAction::ResetDelayRequestTimer { duration }];
         };
 
-        let random = self.rng.sample::<f64, _>(rand::distributions::Open01);
-        let factor = random * 2.0f64;
-        let duration = log_min_pdelay_req_interval
-            .as_core_duration()
-            .mul_f64(factor);
+        self.peer_delay_state = PeerDelayState::new_measurement(pdelay_id);
 
         actions![
             PortAction::ResetDelayRequestTimer { duration },
@@ -497,6 +458,8 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
                 },
                 data: &self.packet_buffer[..message_length],
                 link_local: true,
+                destination: None,
+                one_step: None,
             }
         ]
     }
@@ -505,16 +468,30 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
         &mut self,
         log_min_delay_req_interval: Interval,
     ) -> PortActionIterator {
+        let destination = self.unicast_delay_req_destination();
         match self.port_state {
             PortState::Slave(ref mut state) => {
                 log::debug!("Starting new delay measurement");
 
+                let destination = if self.config.hybrid_mode {
+                    destination.or(state.remote_master_address)
+                } else {
+                    destination
+                };
+
                 let delay_id = self.delay_seq_ids.generate();
-                let delay_req = Message::delay_req(
+                let mut delay_req = Message::delay_req(
                     &self.lifecycle.state.default_ds,
                     self.port_identity,
                     delay_id,
                 );
+                delay_req.header.unicast_flag = destination.is_some();
+
+                let random = self.rng.sample::<f64, _>(rand::distributions::Open01);
+                let factor = random * 2.0f64;
+                let duration = log_min_delay_req_interval
+                    .as_core_duration()
+                    .mul_f64(factor);
 
                 let message_length = match delay_req.serialize(&mut self.packet_buffer) {
                     Ok(length) => length,
@@ -524,17 +501,23 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
                     }
                 };
 
+                if matches!(
+                    state.delay_state,
+                    DelayState::Measuring {
+                        send_time: None,
+                        ..
+                    }
+                ) {
+                    self.counters.timestamp_timeouts += 1;
+                }
                 state.delay_state = DelayState::Measuring {
                     id: delay_id,
                     send_time: None,
                     recv_time: None,
                 };
-
-                let random = self.rng.sample::<f64, _>(rand::distributions::Open01);
-                let factor = random * 2.0f64;
-                let duration = log_min_delay_req_interval
-                    .as_core_duration()
-                    .mul_f64(factor);
+                let Some(message_length) = self.finish_message(message_length) else {
+                    return actions![PortAction::ResetDelayRequestTimer { duration }];
+                };
 
                 actions![
                     PortAction::ResetDelayRequestTimer { duration },
@@ -544,6 +527,8 @@ impl<'a, A, C: Clock, F: Filter, R: Rng> Port<Running<'a>, A, R, C, F> {
                         },
                         data: &self.packet_buffer[..message_length],
                         link_local: false,
+                        destination,
+                        one_step: None,
                     }
                 ]
             }
@@ -770,6 +755,8 @@ mod tests {
             context,
             data,
             link_local: false,
+            destination: None,
+            one_step: None,
         }) = action.next()
         else {
             panic!("Unexpected action");
@@ -853,6 +840,8 @@ mod tests {
             context,
             data,
             link_local: false,
+            destination: None,
+            one_step: None,
         }) = action.next()
         else {
             panic!("Unexpected action");
@@ -933,6 +922,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hybrid_delay_request() {
+        let state = setup_test_state();
+
+        let mut port = setup_test_port_custom_filter::<TestFilter>(&state, ());
+        port.config.hybrid_mode = true;
+
+        let master = PortIdentity {
+            port_number: 2,
+            ..Default::default()
+        };
+        port.set_forced_port_state(PortState::Slave(SlaveState::new(master)));
+
+        let master_address = PortAddress::Ipv4([192, 168, 1, 1]);
+        let other_address = PortAddress::Ipv4([192, 168, 1, 3]);
+
+        // Only syncs from our master tell us its address
+        for (sequence_id, source_port_identity, address) in [
+            (1, master, master_address),
+            (2, PortIdentity::default(), other_address),
+        ] {
+            let sync = Message::sync(
+                &state.borrow().default_ds,
+                source_port_identity,
+                sequence_id,
+            );
+            let mut buffer = [0; 128];
+            let length = sync.serialize(&mut buffer).unwrap();
+            let _ =
+                port.handle_event_receive_from(&buffer[..length], Time::from_micros(50), address);
+        }
+
+        let mut action = port.send_delay_request();
+
+        let Some(PortAction::ResetDelayRequestTimer { .. }) = action.next() else {
+            panic!("Unexpected action");
+        };
+
+        let Some(PortAction::SendEvent {
+            data,
+            link_local: false,
+            destination: Some(destination),
+            one_step: None,
+            ..
+        }) = action.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert_eq!(destination, master_address);
+
+        let req = Message::deserialize(data).unwrap();
+        assert!(req.header.unicast_flag);
+        assert!(matches!(req.body, MessageBody::DelayReq(_)));
+    }
+
     #[test]
     fn test_follow_up_before_sync() {
         let state = setup_test_state();
@@ -1119,6 +1163,7 @@ mod tests {
                 raw_delay_offset: None,
             })
         );
+        assert_eq!(port.port_counters().missing_follow_ups, 1);
     }
 
     #[test]
@@ -1168,6 +1213,8 @@ mod tests {
             context,
             data,
             link_local: false,
+            destination: None,
+            one_step: None,
         }) = action.next()
         else {
             panic!("Unexpected action");
@@ -1259,6 +1306,11 @@ mod tests {
                 raw_delay_offset: Some(Duration::from_micros(-151)),
             })
         );
+
+        let counters = port.port_counters();
+        assert_eq!(counters.tx.delay_req, 1);
+        assert_eq!(counters.sequence_id_mismatches, 1);
+        assert_eq!(counters.timestamp_timeouts, 0);
     }
 
     #[test]
@@ -1284,6 +1336,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -1349,6 +1403,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -1431,6 +1487,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -1514,6 +1572,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
@@ -1587,6 +1647,8 @@ mod tests {
             context,
             data,
             link_local: true,
+            destination: None,
+            one_step: None,
         }) = actions.next()
         else {
             panic!("Unexpected action");
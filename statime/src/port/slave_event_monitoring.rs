@@ -0,0 +1,490 @@
+//! Slave event monitoring, see *IEEE1588-2019 section 16.11*
+//!
+//! A slave port collects records about the Sync messages it receives and the
+//! delay requests it sends, and sends them in a Signaling message once enough
+//! of them are collected. The records received from other ports are kept per
+//! slave, for monitoring.
+
+use arrayvec::ArrayVec;
+
+use super::{state::PortState, unicast::ALL_PORTS, Port, PortAction, Running};
+use crate::{
+    config::{DelayMechanism, SlaveEventMonitoringConfig},
+    datastructures::{
+        common::{PortIdentity, Tlv, TlvSetBuilder},
+        messages::{
+            Header, Message, MessageType, SignalingMessage, SlaveEventMonitoringTlv, MAX_DATA_LEN,
+        },
+    },
+    filters::Filter,
+    observability::slave_event_monitoring::{
+        SlaveEventReport, SyncComputedRecord, SyncTimingRecord, TxEventRecord,
+        MAX_SLAVE_EVENT_RECORDS,
+    },
+    time::{Duration, Time},
+};
+
+type Records<T> = ArrayVec<T, MAX_SLAVE_EVENT_RECORDS>;
+
+fn time_nanos(time: Time) -> u128 {
+    time.secs() as u128 * 1_000_000_000 + time.subsec_nanos() as u128
+}
+
+/// Add a record, dropping the oldest one when there is no room
+fn push_record<T>(records: &mut Records<T>, record: T) {
+    if records.is_full() {
+        records.remove(0);
+    }
+    records.push(record);
+}
+
+/// A two-step Sync message waiting for its Follow_Up
+#[derive(Clone, Copy, Debug)]
+struct PendingSync {
+    id: u16,
+    correction: Duration,
+    recv_time: Time,
+}
+
+/// The records a slave port collected, but did not send yet
+#[derive(Clone, Debug)]
+pub(super) struct SlaveEventMonitor {
+    config: SlaveEventMonitoringConfig,
+    pending_sync: Option<PendingSync>,
+    /// Sequence id of the last complete Sync message, which the next offset
+    /// measurement is computed from
+    last_sync_id: u16,
+    sync_source: PortIdentity,
+    sync_timing: Records<SyncTimingRecord>,
+    sync_computed: Records<SyncComputedRecord>,
+    tx_events: Records<TxEventRecord>,
+}
+
+impl SlaveEventMonitor {
+    pub(super) fn new(config: SlaveEventMonitoringConfig) -> Self {
+        SlaveEventMonitor {
+            config,
+            pending_sync: None,
+            last_sync_id: 0,
+            sync_source: PortIdentity::default(),
+            sync_timing: ArrayVec::new(),
+            sync_computed: ArrayVec::new(),
+            tx_events: ArrayVec::new(),
+        }
+    }
+
+    fn records_per_message(&self) -> usize {
+        (self.config.records_per_message as usize).clamp(1, MAX_SLAVE_EVENT_RECORDS)
+    }
+
+    /// Register a received Sync message, `origin_timestamp` is `None` for
+    /// two-step messages
+    pub(super) fn handle_sync(
+        &mut self,
+        header: &Header,
+        origin_timestamp: Option<Time>,
+        recv_time: Time,
+    ) {
+        let correction = Duration::from(header.correction_field);
+        match origin_timestamp {
+            Some(origin_timestamp) => {
+                self.pending_sync = None;
+                self.complete_sync(header, origin_timestamp, correction, recv_time);
+            }
+            None => {
+                self.pending_sync = Some(PendingSync {
+                    id: header.sequence_id,
+                    correction,
+                    recv_time,
+                })
+            }
+        }
+    }
+
+    /// Register a received Follow_Up message, completing the matching Sync
+    pub(super) fn handle_follow_up(&mut self, header: &Header, precise_origin_timestamp: Time) {
+        if let Some(sync) = self.pending_sync {
+            if sync.id == header.sequence_id {
+                self.pending_sync = None;
+                let correction = sync.correction + Duration::from(header.correction_field);
+                self.complete_sync(header, precise_origin_timestamp, correction, sync.recv_time);
+            }
+        }
+    }
+
+    fn complete_sync(
+        &mut self,
+        header: &Header,
+        origin_timestamp: Time,
+        correction: Duration,
+        recv_time: Time,
+    ) {
+        self.last_sync_id = header.sequence_id;
+
+        if !self.config.rx_sync_timing_data {
+            return;
+        }
+
+        // All records of a message are from the same master
+        if self.sync_source != header.source_port_identity {
+            self.sync_source = header.source_port_identity;
+            self.sync_timing.clear();
+        }
+        push_record(
+            &mut self.sync_timing,
+            SyncTimingRecord {
+                sequence_id: header.sequence_id,
+                sync_origin_timestamp: time_nanos(origin_timestamp),
+                total_correction: correction.nanos_rounded(),
+                scaled_cumulative_rate_offset: 0,
+                sync_event_ingress_timestamp: time_nanos(recv_time),
+            },
+        );
+    }
+
+    /// Register the offset computed from the last complete Sync message
+    pub(super) fn handle_offset(
+        &mut self,
+        offset: Duration,
+        mean_delay: Option<Duration>,
+        neighbor_rate_ratio: Option<f64>,
+    ) {
+        if !self.config.rx_sync_computed_data {
+            return;
+        }
+
+        push_record(
+            &mut self.sync_computed,
+            SyncComputedRecord {
+                sequence_id: self.last_sync_id,
+                offset_from_master: Some(offset.nanos_rounded()),
+                mean_path_delay: mean_delay.map(|delay| delay.nanos_rounded()),
+                scaled_neighbor_rate_ratio: neighbor_rate_ratio
+                    .map(|ratio| ((ratio - 1.0) * (1u64 << 41) as f64) as i32),
+            },
+        );
+    }
+
+    /// Register the send timestamp of a (peer) delay request
+    pub(super) fn handle_send_timestamp(&mut self, id: u16, timestamp: Time) {
+        if !self.config.tx_event_timestamps {
+            return;
+        }
+
+        push_record(
+            &mut self.tx_events,
+            TxEventRecord {
+                sequence_id: id,
+                event_egress_timestamp: time_nanos(timestamp),
+            },
+        );
+    }
+
+    /// Take the collected records once enough of one kind are collected
+    fn take_tlvs(
+        &mut self,
+        source_port_identity: PortIdentity,
+        event_message_type: MessageType,
+    ) -> Option<ArrayVec<SlaveEventMonitoringTlv, 3>> {
+        let records_per_message = self.records_per_message();
+        if self.sync_timing.len() < records_per_message
+            && self.sync_computed.len() < records_per_message
+            && self.tx_events.len() < records_per_message
+        {
+            return None;
+        }
+
+        let mut tlvs = ArrayVec::new();
+        if !self.sync_timing.is_empty() {
+            tlvs.push(SlaveEventMonitoringTlv::RxSyncTimingData {
+                sync_source_port_identity: self.sync_source,
+                records: core::mem::take(&mut self.sync_timing),
+            });
+        }
+        if !self.sync_computed.is_empty() {
+            tlvs.push(SlaveEventMonitoringTlv::RxSyncComputedData {
+                source_port_identity,
+                records: core::mem::take(&mut self.sync_computed),
+            });
+        }
+        if !self.tx_events.is_empty() {
+            tlvs.push(SlaveEventMonitoringTlv::TxEventTimestamps {
+                source_port_identity,
+                event_message_type,
+                records: core::mem::take(&mut self.tx_events),
+            });
+        }
+        Some(tlvs)
+    }
+}
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Send the collected slave event monitoring records once there are
+    /// enough of them
+    pub(super) fn send_slave_event_report(&mut self) -> Option<PortAction<'_>> {
+        let monitor = self.slave_event_monitor.as_mut()?;
+        let event_message_type = match self.config.delay_mechanism {
+            DelayMechanism::E2E { .. } => MessageType::DelayReq,
+            DelayMechanism::P2P { .. } => MessageType::PDelayReq,
+        };
+        let tlvs = monitor.take_tlvs(self.port_identity, event_message_type)?;
+        let destination = monitor.config.destination;
+
+        let mut tlv_buffer = [0; MAX_DATA_LEN];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        for tlv in &tlvs {
+            let mut value = [0; SlaveEventMonitoringTlv::MAX_VALUE_SIZE];
+            let result = tlv.serialize_value(&mut value).and_then(|value| {
+                tlv_builder.add(Tlv {
+                    tlv_type: tlv.tlv_type(),
+                    value: value.into(),
+                })
+            });
+            if let Err(error) = result {
+                log::error!(
+                    "Statime bug: Could not build slave event monitoring TLVs: {:?}",
+                    error
+                );
+                return None;
+            }
+        }
+
+        let mut message = Message::signaling(
+            &self.lifecycle.state.default_ds,
+            self.port_identity,
+            ALL_PORTS,
+            self.signaling_seq_ids.generate(),
+            tlv_builder.build(),
+        );
+        message.header.unicast_flag = destination.is_some();
+
+        let length = match message.serialize(&mut self.packet_buffer) {
+            Ok(length) => self.finish_message(length)?,
+            Err(error) => {
+                log::error!(
+                    "Statime bug: Could not serialize slave event monitoring: {:?}",
+                    error
+                );
+                return None;
+            }
+        };
+
+        Some(PortAction::SendGeneral {
+            data: &self.packet_buffer[..length],
+            link_local: false,
+            destination,
+        })
+    }
+
+    /// Keep the slave event monitoring records of a received Signaling message
+    pub(super) fn handle_slave_event_report(
+        &mut self,
+        message: &Message<'_>,
+        signaling: SignalingMessage,
+    ) {
+        if !self.is_signaling_target(signaling.target_port_identity) {
+            return;
+        }
+
+        let source = message.header.source_port_identity;
+        let mut report = None;
+        for tlv in message.suffix.tlv() {
+            let tlv = match SlaveEventMonitoringTlv::from_tlv(&tlv) {
+                Ok(Some(tlv)) => tlv,
+                Ok(None) => continue,
+                Err(error) => {
+                    log::warn!("Could not parse slave event monitoring TLV: {:?}", error);
+                    continue;
+                }
+            };
+
+            let report = report.get_or_insert_with(|| {
+                // Keep the records of the previous report that are not replaced
+                match self
+                    .slave_event_reports
+                    .iter()
+                    .position(|report| report.source_port_identity == source)
+                {
+                    Some(index) => self.slave_event_reports.remove(index),
+                    None => SlaveEventReport {
+                        source_port_identity: source,
+                        ..Default::default()
+                    },
+                }
+            });
+
+            match tlv {
+                SlaveEventMonitoringTlv::RxSyncTimingData {
+                    sync_source_port_identity,
+                    records,
+                } => {
+                    report.sync_source_port_identity = Some(sync_source_port_identity);
+                    report.sync_timing = records;
+                }
+                SlaveEventMonitoringTlv::RxSyncComputedData { records, .. } => {
+                    report.sync_computed = records;
+                }
+                SlaveEventMonitoringTlv::TxEventTimestamps { records, .. } => {
+                    report.tx_events = records;
+                }
+            }
+        }
+
+        if let Some(report) = report {
+            if self.slave_event_reports.is_full() {
+                self.slave_event_reports.remove(0);
+            }
+            self.slave_event_reports.push(report);
+        }
+    }
+
+    /// Collect the slave event monitoring records of a completed measurement
+    pub(super) fn monitor_offset(&mut self, offset: Duration) {
+        let neighbor_rate_ratio = self
+            .gptp
+            .as_ref()
+            .map(|gptp| gptp.neighbor_rate_ratio.ratio());
+        if let Some(monitor) = &mut self.slave_event_monitor {
+            monitor.handle_offset(offset, self.mean_delay, neighbor_rate_ratio);
+        }
+    }
+
+    /// Collect the send timestamp of a (peer) delay request of a slave
+    pub(super) fn monitor_send_timestamp(&mut self, id: u16, timestamp: Time) {
+        if let (Some(monitor), PortState::Slave(_)) =
+            (&mut self.slave_event_monitor, &self.port_state)
+        {
+            monitor.handle_send_timestamp(id, timestamp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        datastructures::{
+            common::TimeInterval,
+            messages::{FollowUpMessage, SyncMessage},
+        },
+        port::{
+            state::SlaveState,
+            tests::{setup_test_port, setup_test_state},
+        },
+    };
+
+    #[test]
+    fn test_slave_event_monitoring() {
+        let state = setup_test_state();
+
+        let mut slave = setup_test_port(&state);
+        slave.slave_event_monitor = Some(SlaveEventMonitor::new(SlaveEventMonitoringConfig {
+            tx_event_timestamps: false,
+            records_per_message: 2,
+            ..Default::default()
+        }));
+        slave.mean_delay = Some(Duration::from_micros(100));
+        slave.set_forced_port_state(PortState::Slave(SlaveState::new(Default::default())));
+
+        let actions = slave.handle_sync(
+            Header {
+                two_step_flag: true,
+                sequence_id: 1,
+                correction_field: TimeInterval(1000.into()),
+                ..Default::default()
+            },
+            SyncMessage {
+                origin_timestamp: Default::default(),
+            },
+            Time::from_micros(50),
+        );
+        assert!(!actions
+            .into_iter()
+            .any(|action| matches!(action, PortAction::SendGeneral { .. })));
+
+        let actions = slave.handle_follow_up(
+            Header {
+                sequence_id: 1,
+                ..Default::default()
+            },
+            FollowUpMessage {
+                precise_origin_timestamp: Time::from_micros(0).into(),
+            },
+        );
+        assert!(!actions
+            .into_iter()
+            .any(|action| matches!(action, PortAction::SendGeneral { .. })));
+
+        // The second sync completes the records
+        let mut buffer = [0; MAX_DATA_LEN];
+        let mut length = 0;
+        for action in slave.handle_sync(
+            Header {
+                two_step_flag: false,
+                sequence_id: 2,
+                ..Default::default()
+            },
+            SyncMessage {
+                origin_timestamp: Time::from_micros(1000).into(),
+            },
+            Time::from_micros(1050),
+        ) {
+            if let PortAction::SendGeneral {
+                data, destination, ..
+            } = action
+            {
+                assert_eq!(destination, None);
+                buffer[..data.len()].copy_from_slice(data);
+                length = data.len();
+            }
+        }
+        assert_ne!(length, 0);
+        assert_eq!(slave.port_counters().tx.signaling, 1);
+
+        let mut master = setup_test_port(&state);
+        master.set_forced_port_state(PortState::Master);
+        drop(master.handle_general_receive(&buffer[..length]));
+
+        let reports = master.slave_event_reports();
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.source_port_identity, slave.port_identity);
+        assert_eq!(
+            report.sync_timing.as_slice(),
+            &[
+                SyncTimingRecord {
+                    sequence_id: 1,
+                    sync_origin_timestamp: 0,
+                    total_correction: 1000,
+                    scaled_cumulative_rate_offset: 0,
+                    sync_event_ingress_timestamp: 50_000,
+                },
+                SyncTimingRecord {
+                    sequence_id: 2,
+                    sync_origin_timestamp: 1_000_000,
+                    total_correction: 0,
+                    scaled_cumulative_rate_offset: 0,
+                    sync_event_ingress_timestamp: 1_050_000,
+                },
+            ]
+        );
+        assert_eq!(
+            report.sync_computed.as_slice(),
+            &[
+                SyncComputedRecord {
+                    sequence_id: 1,
+                    offset_from_master: Some(-51_000),
+                    mean_path_delay: Some(100_000),
+                    scaled_neighbor_rate_ratio: None,
+                },
+                SyncComputedRecord {
+                    sequence_id: 2,
+                    offset_from_master: Some(-50_000),
+                    mean_path_delay: Some(100_000),
+                    scaled_neighbor_rate_ratio: None,
+                },
+            ]
+        );
+        assert!(report.tx_events.is_empty());
+    }
+}
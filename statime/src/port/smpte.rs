@@ -0,0 +1,226 @@
+//! Port behaviour for the synchronization metadata of SMPTE ST 2059-2, see
+//! [`Profile::St2059_2`]
+//!
+//! The grandmaster adds its synchronization metadata to announce messages.
+//! Slaves remember the metadata of their master, which the instance takes over
+//! during the BMCA, and boundary clocks pass it on in their own announce
+//! messages.
+
+use super::{state::PortState, ForwardedTLV, InBmca, Port, Running};
+#[cfg(doc)]
+use crate::config::Profile;
+use crate::{
+    config::SynchronizationMetadata,
+    datastructures::{
+        common::{PortIdentity, Tlv, TlvSetBuilder, TlvType},
+        messages::Message,
+    },
+    filters::Filter,
+};
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Remember the synchronization metadata in an announce message
+    ///
+    /// Once we are a slave, only the metadata of our master is kept.
+    pub(super) fn handle_synchronization_metadata(&mut self, message: &Message<'_>) {
+        let sender = message.header.source_port_identity;
+        if let PortState::Slave(slave) = &self.port_state {
+            if slave.remote_master() != sender {
+                return;
+            }
+        }
+
+        let metadata = message
+            .suffix
+            .tlv()
+            .find_map(|tlv| SynchronizationMetadata::from_tlv(&tlv).ok().flatten());
+        match metadata {
+            Some(metadata) => self.synchronization_metadata = Some((sender, metadata)),
+            None => {
+                // The sender stopped sending metadata
+                if matches!(self.synchronization_metadata, Some((old, _)) if old == sender) {
+                    self.synchronization_metadata = None;
+                }
+            }
+        }
+    }
+
+    /// Whether a forwarded TLV carries synchronization metadata, which is not
+    /// forwarded as is. The metadata of the instance is added to the announce
+    /// messages instead.
+    pub(super) fn is_synchronization_metadata(&self, tlv: &ForwardedTLV<'_>) -> bool {
+        matches!(SynchronizationMetadata::from_tlv(&tlv.tlv), Ok(Some(_)))
+    }
+
+    /// Add the synchronization metadata of the grandmaster to an announce
+    /// message, see *SMPTE ST 2059-2 section 7.2*
+    ///
+    /// The TLV is left out when it would not fit in `margin` bytes.
+    pub(super) fn add_synchronization_metadata(
+        &self,
+        tlv_builder: &mut TlvSetBuilder<'_>,
+        margin: usize,
+    ) {
+        let Some(metadata) = self.lifecycle.state.synchronization_metadata else {
+            return;
+        };
+
+        let mut value = [0; 48];
+        let value = match metadata.serialize_value(&mut value) {
+            Ok(value) => value,
+            Err(error) => {
+                log::error!(
+                    "Statime bug: Could not build synchronization metadata TLV: {:?}",
+                    error
+                );
+                return;
+            }
+        };
+        let tlv = Tlv {
+            tlv_type: TlvType::OrganizationExtension,
+            value: value.into(),
+        };
+
+        if tlv.wire_size() >= margin {
+            return;
+        }
+        // Will not fail as we checked there is enough space in the buffer
+        tlv_builder.add(tlv).unwrap();
+    }
+}
+
+impl<'a, A, C, F: Filter, R> Port<InBmca<'a>, A, R, C, F> {
+    /// The synchronization metadata last received from `sender`
+    pub(crate) fn synchronization_metadata_of(
+        &self,
+        sender: PortIdentity,
+    ) -> Option<SynchronizationMetadata> {
+        match self.synchronization_metadata {
+            Some((identity, metadata)) if identity == sender => Some(metadata),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{ClockIdentity, MasterLockingStatus},
+        datastructures::messages::MessageBody,
+        port::{
+            tests::{setup_test_port, setup_test_state},
+            ForwardedTLVProvider, PortAction,
+        },
+    };
+
+    const METADATA: SynchronizationMetadata = SynchronizationMetadata {
+        default_system_frame_rate_numerator: 25,
+        default_system_frame_rate_denominator: 1,
+        master_locking_status: MasterLockingStatus::Locked,
+        drop_frame: false,
+        color_frame_identification: false,
+        current_local_offset: 37,
+        jump_seconds: 0,
+        time_of_next_jump: 0,
+        time_of_next_jam: 0,
+        time_of_previous_jam: 0,
+        previous_jam_local_offset: 37,
+        daylight_saving: false,
+        daylight_saving_at_next_jump: false,
+        daylight_saving_at_previous_jam: false,
+        leap_second_jump: false,
+    };
+
+    const UPSTREAM: PortIdentity = PortIdentity {
+        clock_identity: ClockIdentity([2; 8]),
+        port_number: 1,
+    };
+
+    struct SingleTlv<'a>(Option<ForwardedTLV<'a>>);
+
+    impl<'a> ForwardedTLVProvider for SingleTlv<'a> {
+        fn next_if_smaller(&mut self, max_size: usize) -> Option<ForwardedTLV<'_>> {
+            self.0.take().filter(|tlv| tlv.size() <= max_size)
+        }
+    }
+
+    #[test]
+    fn test_send_synchronization_metadata() {
+        let state = setup_test_state();
+        state.borrow_mut().parent_ds.parent_port_identity = UPSTREAM;
+        state.borrow_mut().synchronization_metadata = Some(METADATA);
+
+        let mut port = setup_test_port(&state);
+        port.set_forced_port_state(PortState::Master);
+
+        // Metadata forwarded from upstream is replaced by that of the instance
+        let mut value = [0; 48];
+        let forwarded = SynchronizationMetadata {
+            master_locking_status: MasterLockingStatus::FreeRun,
+            ..METADATA
+        };
+        let mut provider = SingleTlv(Some(ForwardedTLV {
+            tlv: Tlv {
+                tlv_type: TlvType::OrganizationExtension,
+                value: forwarded.serialize_value(&mut value).unwrap().into(),
+            },
+            sender_identity: UPSTREAM,
+        }));
+
+        let mut actions = port.send_announce(&mut provider);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+
+        let announce = Message::deserialize(data).unwrap();
+        let mut tlvs = announce.suffix.tlv();
+        let tlv = tlvs.next().unwrap();
+        assert!(matches!(
+            SynchronizationMetadata::from_tlv(&tlv),
+            Ok(Some(METADATA))
+        ));
+        assert!(tlvs.next().is_none());
+    }
+
+    #[test]
+    fn test_receive_synchronization_metadata() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+
+        let mut announce = Message::announce(&state.borrow(), UPSTREAM, 0);
+        let MessageBody::Announce(announce_body) = announce.body else {
+            panic!("Unexpected message");
+        };
+
+        let mut value = [0; 48];
+        let mut tlv_buffer = [0; 64];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        tlv_builder
+            .add(Tlv {
+                tlv_type: TlvType::OrganizationExtension,
+                value: METADATA.serialize_value(&mut value).unwrap().into(),
+            })
+            .unwrap();
+        announce.suffix = tlv_builder.build();
+        drop(port.handle_announce(&announce, announce_body));
+
+        let port = port.start_bmca();
+        assert_eq!(port.synchronization_metadata_of(UPSTREAM), Some(METADATA));
+        assert_eq!(
+            port.synchronization_metadata_of(PortIdentity::default()),
+            None
+        );
+        let (mut port, _) = port.end_bmca();
+
+        // The metadata is forgotten when the master stops sending it
+        announce.suffix = Default::default();
+        drop(port.handle_announce(&announce, announce_body));
+        let port = port.start_bmca();
+        assert_eq!(port.synchronization_metadata_of(UPSTREAM), None);
+    }
+}
@@ -1,7 +1,7 @@
 use core::fmt::{Display, Formatter};
 
 use crate::{
-    datastructures::common::PortIdentity,
+    datastructures::common::{PortAddress, PortIdentity},
     time::{Duration, Time},
 };
 
@@ -10,6 +10,7 @@ use crate::{
 pub(crate) enum PortState {
     #[default]
     Faulty,
+    Disabled,
     Listening,
     Master,
     Passive,
@@ -24,6 +25,7 @@ impl Display for PortState {
             PortState::Passive => write!(f, "Passive"),
             PortState::Slave(_) => write!(f, "Slave"),
             PortState::Faulty => write!(f, "Faulty"),
+            PortState::Disabled => write!(f, "Disabled"),
         }
     }
 }
@@ -31,6 +33,8 @@ impl Display for PortState {
 #[derive(Debug)]
 pub(crate) struct SlaveState {
     pub(super) remote_master: PortIdentity,
+    /// Where the remote master sends its sync messages from
+    pub(super) remote_master_address: Option<PortAddress>,
 
     pub(super) sync_state: SyncState,
     pub(super) delay_state: DelayState,
@@ -68,9 +72,22 @@ impl SlaveState {
     pub(super) fn new(remote_master: PortIdentity) -> Self {
         SlaveState {
             remote_master,
+            remote_master_address: None,
             sync_state: SyncState::Empty,
             delay_state: DelayState::Empty,
             last_raw_sync_offset: None,
         }
     }
+
+    /// Whether we received a two-step sync whose follow up did not arrive yet
+    pub(super) fn awaiting_follow_up(&self) -> bool {
+        matches!(
+            self.sync_state,
+            SyncState::Measuring {
+                send_time: None,
+                recv_time: Some(_),
+                ..
+            }
+        )
+    }
 }
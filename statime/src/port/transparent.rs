@@ -0,0 +1,1531 @@
+//! Ports of a [`TransparentClock`](`crate::TransparentClock`)
+
+use core::iter::Fuse;
+
+use arrayvec::ArrayVec;
+
+use super::{
+    actions::TimestampContextInner,
+    peer_delay::{PeerDelayError, PeerDelayState},
+    sequence_id::SequenceIdGenerator,
+    TimestampContext,
+};
+#[cfg(doc)]
+use crate::TransparentClock;
+use crate::{
+    config::{DelayMechanism, TransparentClockConfig},
+    datastructures::{
+        common::{PortIdentity, PowerProfileInformation, TimeInterval, TlvSet, WireTimestamp},
+        messages::{
+            FollowUpMessage, Header, Message, MessageBody, MessageType, PDelayReqMessage,
+            PDelayRespFollowUpMessage, PDelayRespMessage, MAX_DATA_LEN,
+        },
+    },
+    time::{Duration, Time},
+};
+
+/// Number of messages for which the residence time is remembered per port
+const RESIDENCE_TABLE_SIZE: usize = 16;
+/// Number of messages remembered per port to recognize our own transmissions
+const SENT_MESSAGES_SIZE: usize = 16;
+/// Number of follow up and delay response messages per port that can wait for
+/// the send timestamp of their event message
+const WAITING_MESSAGES_SIZE: usize = 4;
+
+/// A message received by one [`TransparentPort`] that needs to be handled by
+/// all other ports of the same [`TransparentClock`].
+///
+/// The user should pass this to
+/// [`TransparentPort::handle_forwarded_message`] of every port of the
+/// [`TransparentClock`]. Passing it back to the port that received it is
+/// allowed, that port will ignore it.
+#[derive(Clone)]
+pub struct ForwardedMessage {
+    data: [u8; MAX_DATA_LEN],
+    length: usize,
+    ingress_port: u16,
+    ingress_timestamp: Option<Time>,
+    ingress_link_delay: Duration,
+}
+
+impl ForwardedMessage {
+    fn new(
+        data: &[u8],
+        ingress_port: u16,
+        ingress_timestamp: Option<Time>,
+        ingress_link_delay: Duration,
+    ) -> Option<Self> {
+        let mut buffer = [0; MAX_DATA_LEN];
+        buffer.get_mut(..data.len())?.copy_from_slice(data);
+
+        Some(Self {
+            data: buffer,
+            length: data.len(),
+            ingress_port,
+            ingress_timestamp,
+            ingress_link_delay,
+        })
+    }
+
+    /// The message as it was received
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.length]
+    }
+
+    /// The number of the port that received the message
+    pub fn ingress_port(&self) -> u16 {
+        self.ingress_port
+    }
+}
+
+impl core::fmt::Debug for ForwardedMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ForwardedMessage")
+            .field("data", &self.data())
+            .field("ingress_port", &self.ingress_port)
+            .field("ingress_timestamp", &self.ingress_timestamp)
+            .field("ingress_link_delay", &self.ingress_link_delay)
+            .finish()
+    }
+}
+
+/// Identification of a message passing through a transparent clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct MessageKey {
+    message_type: MessageType,
+    domain_number: u8,
+    source_port_identity: PortIdentity,
+    sequence_id: u16,
+}
+
+impl MessageKey {
+    fn new(message_type: MessageType, header: &Header) -> Self {
+        Self {
+            message_type,
+            domain_number: header.domain_number,
+            source_port_identity: header.source_port_identity,
+            sequence_id: header.sequence_id,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Residence {
+    Pending {
+        ingress_timestamp: Time,
+        ingress_link_delay: Duration,
+        // Header and origin timestamp of a one-step sync that we send on as
+        // two-step, used to construct the follow up carrying the residence time.
+        one_step_sync: Option<(Header, WireTimestamp)>,
+    },
+    /// The correction to apply: the residence time, plus the mean link delay
+    /// of the ingress port for peer-to-peer transparent clocks.
+    Measured(Duration),
+}
+
+/// A general message that arrived before the send timestamp of its event
+/// message, passed on once the residence time is known
+#[derive(Debug)]
+enum WaitingMessage {
+    /// A follow up forwarded by another port, to send on this port
+    FollowUp(ForwardedMessage),
+    /// A delay response received by this port, to forward to the other ports
+    DelayResp(ForwardedMessage),
+}
+
+#[derive(Debug)]
+struct WaitingEntry {
+    /// The event message whose residence time is needed
+    key: MessageKey,
+    message: WaitingMessage,
+}
+
+#[derive(Debug)]
+struct ResidenceEntry {
+    key: MessageKey,
+    residence: Residence,
+}
+
+/// An action the [`TransparentPort`] needs the user to perform
+#[derive(Debug)]
+#[must_use]
+#[allow(missing_docs)] // Explaining the fields as well as the variants does not add value
+#[allow(clippy::large_enum_variant)] // We can't box the forwarded message without alloc
+pub enum TransparentPortAction<'a> {
+    /// Send a time-critical packet
+    ///
+    /// Once the packet is sent and the transmit timestamp known the user should
+    /// return the given [`TimestampContext`] using
+    /// [`TransparentPort::handle_send_timestamp`].
+    ///
+    /// Packets marked as link local should be sent per the instructions
+    /// for sending peer to peer delay mechanism messages of the relevant
+    /// transport specification of PTP.
+    SendEvent {
+        context: TimestampContext,
+        data: &'a [u8],
+        link_local: bool,
+    },
+    /// Send a general packet
+    ///
+    /// For a packet sent this way no timestamp needs to be captured.
+    ///
+    /// Packets marked as link local should be sent per the instructions
+    /// for sending peer to peer delay mechanism messages of the relevant
+    /// transport specification of PTP.
+    SendGeneral { data: &'a [u8], link_local: bool },
+    /// Call [`TransparentPort::handle_delay_request_timer`] in `duration` from
+    /// now
+    ResetDelayRequestTimer { duration: core::time::Duration },
+    /// Pass this message to
+    /// [`TransparentPort::handle_forwarded_message`] of all other ports of
+    /// the [`TransparentClock`].
+    Forward { message: ForwardedMessage },
+}
+
+const MAX_ACTIONS: usize = 2;
+
+/// An Iterator over [`TransparentPortAction`]s
+///
+/// These are returned by [`TransparentPort`] when ever the library needs the
+/// user to perform actions to the system.
+///
+/// **Guarantees to end user:** Any set of actions will only ever contain a
+/// single event send
+#[derive(Debug)]
+#[must_use]
+pub struct TransparentPortActionIterator<'a> {
+    internal: Fuse<<ArrayVec<TransparentPortAction<'a>, MAX_ACTIONS> as IntoIterator>::IntoIter>,
+}
+
+impl<'a> TransparentPortActionIterator<'a> {
+    /// Get an empty Iterator
+    ///
+    /// This can for example be used to have a default value in chained `if`
+    /// statements.
+    pub fn empty() -> Self {
+        Self {
+            internal: ArrayVec::new().into_iter().fuse(),
+        }
+    }
+
+    fn single(action: TransparentPortAction<'a>) -> Self {
+        let mut list = ArrayVec::new();
+        list.push(action);
+        Self {
+            internal: list.into_iter().fuse(),
+        }
+    }
+
+    fn pair(action1: TransparentPortAction<'a>, action2: TransparentPortAction<'a>) -> Self {
+        let mut list = ArrayVec::new();
+        list.push(action1);
+        list.push(action2);
+        Self {
+            internal: list.into_iter().fuse(),
+        }
+    }
+}
+
+impl<'a> Iterator for TransparentPortActionIterator<'a> {
+    type Item = TransparentPortAction<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.internal.next()
+    }
+}
+
+/// A single port of a [`TransparentClock`]
+///
+/// One of these needs to be created per network interface of the device using
+/// [`TransparentClock::add_port`].
+///
+/// Messages received by a port are reported back to the user as
+/// [`TransparentPortAction::Forward`], which should be handed to all other
+/// ports using [`TransparentPort::handle_forwarded_message`]. The ports
+/// measure the residence time of event messages between the receive
+/// timestamp on the ingress port and the send timestamp on the egress port,
+/// and add it to the correction field of the corresponding `Follow_Up` and
+/// `Delay_Resp` messages. One-step `Sync` messages are forwarded as two-step
+/// with a generated `Follow_Up`.
+///
+/// When the [`TransparentClock`] uses the peer-to-peer delay mechanism, each
+/// port measures the mean link delay to its peer, and the mean link delay of
+/// the ingress port is added to the correction of `Sync` messages as well.
+/// `Delay_Req` and `Delay_Resp` messages are not forwarded in that case. The
+/// user should call [`TransparentPort::start`] once to start the peer delay
+/// measurements.
+///
+/// All ports of a [`TransparentClock`] need to timestamp messages using the
+/// same timescale for the residence time to be accurate.
+#[derive(Debug)]
+pub struct TransparentPort {
+    config: TransparentClockConfig,
+    port_identity: PortIdentity,
+    packet_buffer: [u8; MAX_DATA_LEN],
+    residence_times: ArrayVec<ResidenceEntry, RESIDENCE_TABLE_SIZE>,
+    sent_messages: ArrayVec<MessageKey, SENT_MESSAGES_SIZE>,
+    waiting_messages: ArrayVec<WaitingEntry, WAITING_MESSAGES_SIZE>,
+
+    pdelay_seq_ids: SequenceIdGenerator,
+    peer_delay_state: PeerDelayState,
+    mean_link_delay: Option<Duration>,
+}
+
+impl TransparentPort {
+    pub(crate) fn new(config: TransparentClockConfig, port_identity: PortIdentity) -> Self {
+        Self {
+            config,
+            port_identity,
+            packet_buffer: [0; MAX_DATA_LEN],
+            residence_times: ArrayVec::new(),
+            waiting_messages: ArrayVec::new(),
+            sent_messages: ArrayVec::new(),
+            pdelay_seq_ids: SequenceIdGenerator::new(),
+            peer_delay_state: PeerDelayState::Empty,
+            mean_link_delay: None,
+        }
+    }
+
+    /// The number of this port within its [`TransparentClock`]
+    pub fn number(&self) -> u16 {
+        self.port_identity.port_number
+    }
+
+    /// The mean link delay to the peer of this port
+    ///
+    /// This is only measured when using the peer-to-peer delay mechanism.
+    pub fn mean_link_delay(&self) -> Option<Duration> {
+        self.mean_link_delay
+    }
+
+    /// Get the actions needed to start operating the port
+    pub fn start(&mut self) -> TransparentPortActionIterator<'_> {
+        match self.config.delay_mechanism {
+            DelayMechanism::E2E { .. } => TransparentPortActionIterator::empty(),
+            DelayMechanism::P2P { .. } => TransparentPortActionIterator::single(
+                TransparentPortAction::ResetDelayRequestTimer {
+                    duration: core::time::Duration::ZERO,
+                },
+            ),
+        }
+    }
+
+    /// Handle the delay request timer going off
+    pub fn handle_delay_request_timer(&mut self) -> TransparentPortActionIterator<'_> {
+        let DelayMechanism::P2P { interval } = self.config.delay_mechanism else {
+            return TransparentPortActionIterator::empty();
+        };
+
+        let pdelay_id = self.pdelay_seq_ids.generate();
+        let pdelay_req = Message {
+            header: self.header(pdelay_id),
+            body: MessageBody::PDelayReq(PDelayReqMessage {
+                origin_timestamp: WireTimestamp::default(),
+            }),
+            suffix: TlvSet::default(),
+        };
+        let message_length = match pdelay_req.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!("Could not serialize pdelay request: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+
+        self.peer_delay_state = PeerDelayState::new_measurement(pdelay_id);
+        Self::push_evicting(
+            &mut self.sent_messages,
+            MessageKey::new(MessageType::PDelayReq, &pdelay_req.header),
+        );
+
+        TransparentPortActionIterator::pair(
+            TransparentPortAction::ResetDelayRequestTimer {
+                duration: interval.as_core_duration(),
+            },
+            TransparentPortAction::SendEvent {
+                context: TimestampContext {
+                    inner: TimestampContextInner::PDelayReq { id: pdelay_id },
+                },
+                data: &self.packet_buffer[..message_length],
+                link_local: true,
+            },
+        )
+    }
+
+    fn header(&self, sequence_id: u16) -> Header {
+        Header {
+            sdo_id: self.config.sdo_id,
+            domain_number: self.config.primary_domain,
+            source_port_identity: self.port_identity,
+            sequence_id,
+            ..Default::default()
+        }
+    }
+
+    fn is_p2p(&self) -> bool {
+        matches!(self.config.delay_mechanism, DelayMechanism::P2P { .. })
+    }
+
+    /// Handle a message over the event channel
+    pub fn handle_event_receive(
+        &mut self,
+        data: &[u8],
+        timestamp: Time,
+    ) -> TransparentPortActionIterator<'_> {
+        self.handle_receive(data, Some(timestamp))
+    }
+
+    /// Handle a general ptp message
+    pub fn handle_general_receive(&mut self, data: &[u8]) -> TransparentPortActionIterator<'_> {
+        self.handle_receive(data, None)
+    }
+
+    fn handle_receive(
+        &mut self,
+        data: &[u8],
+        timestamp: Option<Time>,
+    ) -> TransparentPortActionIterator<'_> {
+        let message = match Message::deserialize(data) {
+            Ok(message) => message,
+            Err(error) => {
+                log::warn!("Could not parse packet: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+
+        let key = MessageKey::new(message.body.content_type(), &message.header);
+        if self.sent_messages.contains(&key) {
+            // Our own transmission looped back to us
+            return TransparentPortActionIterator::empty();
+        }
+
+        match message.body {
+            MessageBody::Sync(_)
+            | MessageBody::DelayReq(_)
+            | MessageBody::PDelayReq(_)
+            | MessageBody::PDelayResp(_)
+                if timestamp.is_none() =>
+            {
+                log::warn!("Received event message over general interface");
+                TransparentPortActionIterator::empty()
+            }
+            MessageBody::Sync(_) if self.is_p2p() => match self.mean_link_delay {
+                Some(link_delay) => self.forward(data, timestamp, link_delay),
+                None => {
+                    log::warn!("Dropping sync, the link delay of the port is not yet known");
+                    TransparentPortActionIterator::empty()
+                }
+            },
+            MessageBody::DelayReq(_) | MessageBody::DelayResp(_) if self.is_p2p() => {
+                log::debug!("Ignoring end-to-end delay message in peer-to-peer mode");
+                TransparentPortActionIterator::empty()
+            }
+            MessageBody::Sync(_) | MessageBody::DelayReq(_) => {
+                self.forward(data, timestamp, Duration::ZERO)
+            }
+            // Peer delay messages are link local and never forwarded
+            MessageBody::PDelayReq(_) if self.is_p2p() => match timestamp {
+                Some(timestamp) => self.handle_pdelay_req(message.header, timestamp),
+                None => TransparentPortActionIterator::empty(),
+            },
+            MessageBody::PDelayResp(peer_delay_response) if self.is_p2p() => match timestamp {
+                Some(timestamp) => {
+                    let result = self.peer_delay_state.handle_response(
+                        self.port_identity,
+                        message.header,
+                        peer_delay_response,
+                        timestamp,
+                    );
+                    self.handle_peer_delay_result(result)
+                }
+                None => TransparentPortActionIterator::empty(),
+            },
+            MessageBody::PDelayRespFollowUp(peer_delay_follow_up) if self.is_p2p() => {
+                let result = self.peer_delay_state.handle_response_follow_up(
+                    self.port_identity,
+                    message.header,
+                    peer_delay_follow_up,
+                );
+                self.handle_peer_delay_result(result)
+            }
+            MessageBody::PDelayReq(_)
+            | MessageBody::PDelayResp(_)
+            | MessageBody::PDelayRespFollowUp(_) => TransparentPortActionIterator::empty(),
+            MessageBody::DelayResp(delay_resp) => {
+                let request_key = MessageKey {
+                    message_type: MessageType::DelayReq,
+                    source_port_identity: delay_resp.requesting_port_identity,
+                    ..key
+                };
+
+                match self.residence_time(request_key) {
+                    Some(Residence::Measured(residence)) => {
+                        let residence = *residence;
+                        self.forward_with_correction(data, residence)
+                    }
+                    Some(Residence::Pending { .. }) => {
+                        // Forwarded once the send timestamp of the request arrives
+                        match ForwardedMessage::new(data, self.number(), None, Duration::ZERO) {
+                            Some(message) => Self::push_evicting(
+                                &mut self.waiting_messages,
+                                WaitingEntry {
+                                    key: request_key,
+                                    message: WaitingMessage::DelayResp(message),
+                                },
+                            ),
+                            None => log::warn!("Received packet too large to forward"),
+                        }
+                        TransparentPortActionIterator::empty()
+                    }
+                    // The request did not pass through this port, nothing to correct
+                    None => self.forward(data, None, Duration::ZERO),
+                }
+            }
+            MessageBody::FollowUp(_)
+            | MessageBody::Announce(_)
+            | MessageBody::Signaling(_)
+            | MessageBody::Management(_) => self.forward(data, None, Duration::ZERO),
+        }
+    }
+
+    fn handle_pdelay_req(
+        &mut self,
+        header: Header,
+        timestamp: Time,
+    ) -> TransparentPortActionIterator<'_> {
+        log::debug!("Received PDelayReq");
+        // We implement Option B from IEEE 1588-2019 page 202
+        let pdelay_resp = Message {
+            header: Header {
+                two_step_flag: true,
+                correction_field: header.correction_field,
+                ..self.header(header.sequence_id)
+            },
+            body: MessageBody::PDelayResp(PDelayRespMessage {
+                request_receive_timestamp: timestamp.into(),
+                requesting_port_identity: header.source_port_identity,
+            }),
+            suffix: TlvSet::default(),
+        };
+
+        let packet_length = match pdelay_resp.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!("Could not serialize pdelay response: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+        Self::push_evicting(
+            &mut self.sent_messages,
+            MessageKey::new(MessageType::PDelayResp, &pdelay_resp.header),
+        );
+
+        TransparentPortActionIterator::single(TransparentPortAction::SendEvent {
+            context: TimestampContext {
+                inner: TimestampContextInner::PDelayResp {
+                    id: header.sequence_id,
+                    requestor_identity: header.source_port_identity,
+                },
+            },
+            data: &self.packet_buffer[..packet_length],
+            link_local: true,
+        })
+    }
+
+    fn handle_pdelay_response_timestamp(
+        &mut self,
+        id: u16,
+        requestor_identity: PortIdentity,
+        timestamp: Time,
+    ) -> TransparentPortActionIterator<'_> {
+        let pdelay_resp_follow_up = Message {
+            header: self.header(id),
+            body: MessageBody::PDelayRespFollowUp(PDelayRespFollowUpMessage {
+                response_origin_timestamp: timestamp.into(),
+                requesting_port_identity: requestor_identity,
+            }),
+            suffix: TlvSet::default(),
+        };
+
+        let packet_length = match pdelay_resp_follow_up.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!("Could not serialize pdelay_response_followup: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+        Self::push_evicting(
+            &mut self.sent_messages,
+            MessageKey::new(
+                MessageType::PDelayRespFollowUp,
+                &pdelay_resp_follow_up.header,
+            ),
+        );
+
+        TransparentPortActionIterator::single(TransparentPortAction::SendGeneral {
+            data: &self.packet_buffer[..packet_length],
+            link_local: true,
+        })
+    }
+
+    fn handle_peer_delay_result(
+        &mut self,
+        result: Result<(), PeerDelayError>,
+    ) -> TransparentPortActionIterator<'_> {
+        match result {
+            Ok(()) => {
+                if let Some((_, peer_delay)) = self.peer_delay_state.extract_measurement(None) {
+                    log::debug!("Port {} peer delay: {}", self.number(), peer_delay);
+                    // Average the measurements to suppress timestamping noise
+                    self.mean_link_delay = Some(match self.mean_link_delay {
+                        Some(mean_link_delay) => {
+                            mean_link_delay + (peer_delay - mean_link_delay) / 4
+                        }
+                        None => peer_delay,
+                    });
+                }
+            }
+            Err(PeerDelayError::MultipleResponders) => {
+                log::error!(
+                    "Responses from multiple devices to peer delay request, not forwarding syncs \
+                     received on port {}",
+                    self.number()
+                );
+                self.mean_link_delay = None;
+            }
+            Err(PeerDelayError::Ignored | PeerDelayError::SequenceMismatch) => {}
+        }
+
+        TransparentPortActionIterator::empty()
+    }
+
+    fn forward(
+        &self,
+        data: &[u8],
+        ingress_timestamp: Option<Time>,
+        ingress_link_delay: Duration,
+    ) -> TransparentPortActionIterator<'_> {
+        match ForwardedMessage::new(data, self.number(), ingress_timestamp, ingress_link_delay) {
+            Some(message) => {
+                TransparentPortActionIterator::single(TransparentPortAction::Forward { message })
+            }
+            None => {
+                log::warn!("Received packet too large to forward");
+                TransparentPortActionIterator::empty()
+            }
+        }
+    }
+
+    fn forward_with_correction(
+        &self,
+        data: &[u8],
+        correction: Duration,
+    ) -> TransparentPortActionIterator<'_> {
+        let Some(mut message) = ForwardedMessage::new(data, self.number(), None, Duration::ZERO)
+        else {
+            log::warn!("Received packet too large to forward");
+            return TransparentPortActionIterator::empty();
+        };
+
+        if let Err(error) =
+            Header::add_to_serialized_correction_field(&mut message.data, correction.into())
+        {
+            log::error!("Could not update correction field: {:?}", error);
+            return TransparentPortActionIterator::empty();
+        }
+
+        TransparentPortActionIterator::single(TransparentPortAction::Forward { message })
+    }
+
+    /// Handle a message received by another port of the
+    /// [`TransparentClock`]
+    pub fn handle_forwarded_message(
+        &mut self,
+        message: &ForwardedMessage,
+    ) -> TransparentPortActionIterator<'_> {
+        if message.ingress_port == self.number() {
+            return TransparentPortActionIterator::empty();
+        }
+
+        let data = message.data();
+        let header_data = match Header::deserialize_header(data) {
+            Ok(header_data) => header_data,
+            Err(error) => {
+                log::error!("Could not parse forwarded packet: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+        let header = header_data.header;
+        let key = MessageKey::new(header_data.message_type, &header);
+
+        self.packet_buffer[..data.len()].copy_from_slice(data);
+        let buffer = &mut self.packet_buffer[..data.len()];
+
+        match header_data.message_type {
+            MessageType::Sync | MessageType::DelayReq => {
+                let Some(ingress_timestamp) = message.ingress_timestamp else {
+                    log::error!("Forwarded event message without ingress timestamp");
+                    return TransparentPortActionIterator::empty();
+                };
+
+                let mut one_step_sync = None;
+                if header_data.message_type == MessageType::Sync && !header.two_step_flag {
+                    let origin_timestamp = match Message::deserialize(data) {
+                        Ok(Message {
+                            body: MessageBody::Sync(sync),
+                            ..
+                        }) => sync.origin_timestamp,
+                        _ => {
+                            log::error!("Could not parse forwarded sync message");
+                            return TransparentPortActionIterator::empty();
+                        }
+                    };
+
+                    // We can only add the residence time in a follow up, so
+                    // the sync continues as a two-step sync
+                    one_step_sync = Some((header, origin_timestamp));
+                    if let Err(error) = Header::set_serialized_two_step_flag(buffer) {
+                        log::error!("Could not set two step flag: {:?}", error);
+                        return TransparentPortActionIterator::empty();
+                    }
+                }
+
+                Self::push_evicting(
+                    &mut self.residence_times,
+                    ResidenceEntry {
+                        key,
+                        residence: Residence::Pending {
+                            ingress_timestamp,
+                            ingress_link_delay: message.ingress_link_delay,
+                            one_step_sync,
+                        },
+                    },
+                );
+                Self::push_evicting(&mut self.sent_messages, key);
+
+                TransparentPortActionIterator::single(TransparentPortAction::SendEvent {
+                    context: TimestampContext {
+                        inner: TimestampContextInner::Forward { key },
+                    },
+                    data: &self.packet_buffer[..data.len()],
+                    link_local: false,
+                })
+            }
+            MessageType::FollowUp => {
+                let sync_key = MessageKey {
+                    message_type: MessageType::Sync,
+                    ..key
+                };
+
+                let residence = match self.residence_time(sync_key) {
+                    Some(Residence::Measured(residence)) => *residence,
+                    Some(Residence::Pending { .. }) => {
+                        // Sent once the send timestamp of the sync arrives
+                        Self::push_evicting(
+                            &mut self.waiting_messages,
+                            WaitingEntry {
+                                key: sync_key,
+                                message: WaitingMessage::FollowUp(message.clone()),
+                            },
+                        );
+                        return TransparentPortActionIterator::empty();
+                    }
+                    None => {
+                        log::debug!("Received follow up for unknown sync");
+                        return TransparentPortActionIterator::empty();
+                    }
+                };
+
+                self.send_follow_up(message, residence)
+            }
+            MessageType::PDelayReq | MessageType::PDelayResp | MessageType::PDelayRespFollowUp => {
+                TransparentPortActionIterator::empty()
+            }
+            MessageType::Announce => {
+                if self.config.time_inaccuracy != 0 {
+                    if let Err(error) =
+                        PowerProfileInformation::add_to_serialized_network_time_inaccuracy(
+                            buffer,
+                            self.config.time_inaccuracy,
+                        )
+                    {
+                        log::error!("Could not update network time inaccuracy: {:?}", error);
+                        return TransparentPortActionIterator::empty();
+                    }
+                }
+                Self::push_evicting(&mut self.sent_messages, key);
+
+                TransparentPortActionIterator::single(TransparentPortAction::SendGeneral {
+                    data: &self.packet_buffer[..data.len()],
+                    link_local: false,
+                })
+            }
+            MessageType::DelayResp | MessageType::Signaling | MessageType::Management => {
+                Self::push_evicting(&mut self.sent_messages, key);
+
+                TransparentPortActionIterator::single(TransparentPortAction::SendGeneral {
+                    data: &self.packet_buffer[..data.len()],
+                    link_local: false,
+                })
+            }
+        }
+    }
+
+    /// Inform the port about a transmit timestamp being available
+    ///
+    /// `context` is the handle of the packet that was send from the
+    /// [`TransparentPortAction::SendEvent`] that caused the send.
+    pub fn handle_send_timestamp(
+        &mut self,
+        context: TimestampContext,
+        timestamp: Time,
+    ) -> TransparentPortActionIterator<'_> {
+        let key = match context.inner {
+            TimestampContextInner::Forward { key } => key,
+            TimestampContextInner::PDelayReq { id } => {
+                let result = self
+                    .peer_delay_state
+                    .handle_request_timestamp(id, timestamp);
+                return self.handle_peer_delay_result(result);
+            }
+            TimestampContextInner::PDelayResp {
+                id,
+                requestor_identity,
+            } => return self.handle_pdelay_response_timestamp(id, requestor_identity, timestamp),
+            TimestampContextInner::Sync { .. }
+            | TimestampContextInner::DelayReq { .. }
+            | TimestampContextInner::OneStep => {
+                log::error!("Transparent port received timestamp context of an ordinary port");
+                return TransparentPortActionIterator::empty();
+            }
+        };
+
+        let Some(entry) = self
+            .residence_times
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.key == key)
+        else {
+            log::warn!("Residence time entry was dropped before its send timestamp arrived");
+            return TransparentPortActionIterator::empty();
+        };
+
+        let (ingress_timestamp, ingress_link_delay, one_step_sync) = match entry.residence {
+            Residence::Pending {
+                ingress_timestamp,
+                ingress_link_delay,
+                one_step_sync,
+            } => (ingress_timestamp, ingress_link_delay, one_step_sync),
+            Residence::Measured(_) => {
+                log::error!("Received send timestamp twice for the same message");
+                return TransparentPortActionIterator::empty();
+            }
+        };
+
+        let correction = timestamp - ingress_timestamp + ingress_link_delay;
+        entry.residence = Residence::Measured(correction);
+
+        if let Some(index) = self
+            .waiting_messages
+            .iter()
+            .position(|entry| entry.key == key)
+        {
+            return match self.waiting_messages.remove(index).message {
+                WaitingMessage::FollowUp(message) => self.send_follow_up(&message, correction),
+                WaitingMessage::DelayResp(message) => {
+                    self.forward_with_correction(message.data(), correction)
+                }
+            };
+        }
+
+        let Some((sync_header, origin_timestamp)) = one_step_sync else {
+            return TransparentPortActionIterator::empty();
+        };
+
+        let follow_up = Message {
+            header: Header {
+                two_step_flag: false,
+                correction_field: TimeInterval::from(correction),
+                ..sync_header
+            },
+            body: MessageBody::FollowUp(FollowUpMessage {
+                precise_origin_timestamp: origin_timestamp,
+            }),
+            suffix: TlvSet::default(),
+        };
+
+        let packet_length = match follow_up.serialize(&mut self.packet_buffer) {
+            Ok(length) => length,
+            Err(error) => {
+                log::error!("Statime bug: Could not serialize follow up: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+        Self::push_evicting(
+            &mut self.sent_messages,
+            MessageKey::new(MessageType::FollowUp, &follow_up.header),
+        );
+
+        TransparentPortActionIterator::single(TransparentPortAction::SendGeneral {
+            data: &self.packet_buffer[..packet_length],
+            link_local: false,
+        })
+    }
+
+    /// Send a forwarded follow up with the residence time of its sync added
+    /// to the correction
+    fn send_follow_up(
+        &mut self,
+        message: &ForwardedMessage,
+        residence: Duration,
+    ) -> TransparentPortActionIterator<'_> {
+        let data = message.data();
+        let key = match Header::deserialize_header(data) {
+            Ok(header_data) => MessageKey::new(MessageType::FollowUp, &header_data.header),
+            Err(error) => {
+                log::error!("Could not parse forwarded packet: {:?}", error);
+                return TransparentPortActionIterator::empty();
+            }
+        };
+
+        let buffer = &mut self.packet_buffer[..data.len()];
+        buffer.copy_from_slice(data);
+        if let Err(error) = Header::add_to_serialized_correction_field(buffer, residence.into()) {
+            log::error!("Could not update correction field: {:?}", error);
+            return TransparentPortActionIterator::empty();
+        }
+        Self::push_evicting(&mut self.sent_messages, key);
+
+        TransparentPortActionIterator::single(TransparentPortAction::SendGeneral {
+            data: &self.packet_buffer[..data.len()],
+            link_local: false,
+        })
+    }
+
+    fn residence_time(&self, key: MessageKey) -> Option<&Residence> {
+        self.residence_times
+            .iter()
+            .rev()
+            .find(|entry| entry.key == key)
+            .map(|entry| &entry.residence)
+    }
+
+    fn push_evicting<T, const N: usize>(list: &mut ArrayVec<T, N>, value: T) {
+        if list.is_full() {
+            list.remove(0);
+        }
+        list.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fixed::types::I48F16;
+
+    use super::*;
+    use crate::{
+        datastructures::{
+            common::{ClockIdentity, Tlv, TlvSetBuilder, TlvType},
+            messages::{DelayReqMessage, DelayRespMessage, SyncMessage},
+        },
+        time::Interval,
+    };
+
+    fn config(delay_mechanism: DelayMechanism) -> TransparentClockConfig {
+        TransparentClockConfig {
+            clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+            primary_domain: 0,
+            sdo_id: Default::default(),
+            delay_mechanism,
+            time_inaccuracy: 0,
+        }
+    }
+
+    fn test_port(port_number: u16) -> TransparentPort {
+        let config = config(DelayMechanism::E2E {
+            interval: Interval::ONE_SECOND,
+        });
+        TransparentPort::new(
+            config,
+            PortIdentity {
+                clock_identity: config.clock_identity,
+                port_number,
+            },
+        )
+    }
+
+    fn p2p_port(port_number: u16) -> TransparentPort {
+        let config = config(DelayMechanism::P2P {
+            interval: Interval::ONE_SECOND,
+        });
+        TransparentPort::new(
+            config,
+            PortIdentity {
+                clock_identity: config.clock_identity,
+                port_number,
+            },
+        )
+    }
+
+    fn master_identity() -> PortIdentity {
+        PortIdentity {
+            clock_identity: ClockIdentity([8, 7, 6, 5, 4, 3, 2, 1]),
+            port_number: 1,
+        }
+    }
+
+    fn serialize(message: Message) -> ([u8; MAX_DATA_LEN], usize) {
+        let mut buffer = [0; MAX_DATA_LEN];
+        let length = message.serialize(&mut buffer).unwrap();
+        (buffer, length)
+    }
+
+    fn expect_forward(mut actions: TransparentPortActionIterator) -> ForwardedMessage {
+        let Some(TransparentPortAction::Forward { message }) = actions.next() else {
+            panic!("Expected forward action");
+        };
+        assert!(actions.next().is_none());
+        message
+    }
+
+    fn expect_send_event(
+        mut actions: TransparentPortActionIterator,
+    ) -> (TimestampContext, Message) {
+        let Some(TransparentPortAction::SendEvent {
+            context,
+            data,
+            link_local: false,
+        }) = actions.next()
+        else {
+            panic!("Expected send event action");
+        };
+        let message = Message::deserialize(data).unwrap();
+        let message = Message {
+            suffix: TlvSet::default(),
+            ..message
+        };
+        assert!(actions.next().is_none());
+        (context, message)
+    }
+
+    fn expect_send_general(mut actions: TransparentPortActionIterator) -> Message<'static> {
+        let Some(TransparentPortAction::SendGeneral {
+            data,
+            link_local: false,
+        }) = actions.next()
+        else {
+            panic!("Expected send general action");
+        };
+        let message = Message::deserialize(data).unwrap();
+        assert!(actions.next().is_none());
+        Message {
+            header: message.header,
+            body: message.body,
+            suffix: TlvSet::default(),
+        }
+    }
+
+    fn sync(two_step: bool) -> Message<'static> {
+        Message {
+            header: Header {
+                two_step_flag: two_step,
+                correction_field: TimeInterval(I48F16::from_num(10)),
+                source_port_identity: master_identity(),
+                sequence_id: 5,
+                ..Default::default()
+            },
+            body: MessageBody::Sync(SyncMessage {
+                origin_timestamp: Time::from_micros(100).into(),
+            }),
+            suffix: TlvSet::default(),
+        }
+    }
+
+    #[test]
+    fn test_two_step_sync_residence_time() {
+        let mut ingress = test_port(0);
+        let mut egress = test_port(1);
+
+        let (buffer, length) = serialize(sync(true));
+        let forwarded = expect_forward(
+            ingress.handle_event_receive(&buffer[..length], Time::from_micros(1000)),
+        );
+        assert_eq!(forwarded.ingress_port(), 0);
+        assert!(ingress
+            .handle_forwarded_message(&forwarded)
+            .next()
+            .is_none());
+
+        let (context, message) = expect_send_event(egress.handle_forwarded_message(&forwarded));
+        assert_eq!(message, sync(true));
+        assert!(egress
+            .handle_send_timestamp(context, Time::from_micros(1003))
+            .next()
+            .is_none());
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                correction_field: TimeInterval(I48F16::from_num(20)),
+                ..sync(true).header
+            },
+            body: MessageBody::FollowUp(FollowUpMessage {
+                precise_origin_timestamp: Time::from_micros(100).into(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        let forwarded = expect_forward(ingress.handle_general_receive(&buffer[..length]));
+
+        let follow_up = expect_send_general(egress.handle_forwarded_message(&forwarded));
+        assert_eq!(
+            follow_up.header.correction_field,
+            TimeInterval(I48F16::from_num(3020))
+        );
+    }
+
+    #[test]
+    fn test_one_step_sync_conversion() {
+        let mut ingress = test_port(0);
+        let mut egress = test_port(1);
+
+        let (buffer, length) = serialize(sync(false));
+        let forwarded = expect_forward(
+            ingress.handle_event_receive(&buffer[..length], Time::from_micros(1000)),
+        );
+
+        let (context, message) = expect_send_event(egress.handle_forwarded_message(&forwarded));
+        assert_eq!(message, sync(true));
+
+        let follow_up =
+            expect_send_general(egress.handle_send_timestamp(context, Time::from_micros(1002)));
+        assert!(!follow_up.header.two_step_flag);
+        assert_eq!(follow_up.header.sequence_id, 5);
+        assert_eq!(follow_up.header.source_port_identity, master_identity());
+        assert_eq!(
+            follow_up.header.correction_field,
+            TimeInterval(I48F16::from_num(2000))
+        );
+        assert_eq!(
+            follow_up.body,
+            MessageBody::FollowUp(FollowUpMessage {
+                precise_origin_timestamp: Time::from_micros(100).into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_delay_resp_residence_time() {
+        // The delay request travels from slave_side to master_side, the
+        // response in the opposite direction
+        let mut slave_side = test_port(0);
+        let mut master_side = test_port(1);
+
+        let slave_identity = PortIdentity {
+            clock_identity: ClockIdentity([9; 8]),
+            port_number: 1,
+        };
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                source_port_identity: slave_identity,
+                sequence_id: 42,
+                ..Default::default()
+            },
+            body: MessageBody::DelayReq(DelayReqMessage {
+                origin_timestamp: Default::default(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        let forwarded = expect_forward(
+            slave_side.handle_event_receive(&buffer[..length], Time::from_micros(500)),
+        );
+        let (context, _) = expect_send_event(master_side.handle_forwarded_message(&forwarded));
+
+        let response = Message {
+            header: Header {
+                correction_field: TimeInterval(I48F16::from_num(1)),
+                source_port_identity: master_identity(),
+                sequence_id: 42,
+                ..Default::default()
+            },
+            body: MessageBody::DelayResp(DelayRespMessage {
+                receive_timestamp: Time::from_micros(700).into(),
+                requesting_port_identity: slave_identity,
+            }),
+            suffix: TlvSet::default(),
+        };
+        let (buffer, length) = serialize(response.clone());
+
+        assert!(master_side
+            .handle_send_timestamp(context, Time::from_micros(501))
+            .next()
+            .is_none());
+
+        let forwarded = expect_forward(master_side.handle_general_receive(&buffer[..length]));
+        let corrected = expect_send_general(slave_side.handle_forwarded_message(&forwarded));
+        assert_eq!(
+            corrected.header.correction_field,
+            TimeInterval(I48F16::from_num(1001))
+        );
+        assert_eq!(corrected.body, response.body);
+
+        // Responses to requests that did not pass through are left alone
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                sequence_id: 43,
+                ..response.header
+            },
+            ..response
+        });
+        let forwarded = expect_forward(master_side.handle_general_receive(&buffer[..length]));
+        let uncorrected = expect_send_general(slave_side.handle_forwarded_message(&forwarded));
+        assert_eq!(
+            uncorrected.header.correction_field,
+            TimeInterval(I48F16::from_num(1))
+        );
+    }
+
+    #[test]
+    fn test_general_message_before_send_timestamp() {
+        let mut ingress = test_port(0);
+        let mut egress = test_port(1);
+
+        // The follow up of the sync is forwarded before the egress port knows
+        // the send timestamp of the sync
+        let (buffer, length) = serialize(sync(true));
+        let forwarded = expect_forward(
+            ingress.handle_event_receive(&buffer[..length], Time::from_micros(1000)),
+        );
+        let (context, _) = expect_send_event(egress.handle_forwarded_message(&forwarded));
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                correction_field: TimeInterval(I48F16::from_num(20)),
+                ..sync(true).header
+            },
+            body: MessageBody::FollowUp(FollowUpMessage {
+                precise_origin_timestamp: Time::from_micros(100).into(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        let forwarded = expect_forward(ingress.handle_general_receive(&buffer[..length]));
+        assert!(egress.handle_forwarded_message(&forwarded).next().is_none());
+
+        let follow_up =
+            expect_send_general(egress.handle_send_timestamp(context, Time::from_micros(1003)));
+        assert_eq!(
+            follow_up.header.correction_field,
+            TimeInterval(I48F16::from_num(3020))
+        );
+        assert_eq!(follow_up.header.sequence_id, 5);
+
+        // The delay response arrives before the send timestamp of the request
+        let slave_identity = PortIdentity {
+            clock_identity: ClockIdentity([9; 8]),
+            port_number: 1,
+        };
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                source_port_identity: slave_identity,
+                sequence_id: 42,
+                ..Default::default()
+            },
+            body: MessageBody::DelayReq(DelayReqMessage {
+                origin_timestamp: Default::default(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        let forwarded =
+            expect_forward(egress.handle_event_receive(&buffer[..length], Time::from_micros(500)));
+        let (context, _) = expect_send_event(ingress.handle_forwarded_message(&forwarded));
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                correction_field: TimeInterval(I48F16::from_num(1)),
+                source_port_identity: master_identity(),
+                sequence_id: 42,
+                ..Default::default()
+            },
+            body: MessageBody::DelayResp(DelayRespMessage {
+                receive_timestamp: Time::from_micros(700).into(),
+                requesting_port_identity: slave_identity,
+            }),
+            suffix: TlvSet::default(),
+        });
+        assert!(ingress
+            .handle_general_receive(&buffer[..length])
+            .next()
+            .is_none());
+
+        let forwarded =
+            expect_forward(ingress.handle_send_timestamp(context, Time::from_micros(502)));
+        let corrected = expect_send_general(egress.handle_forwarded_message(&forwarded));
+        assert_eq!(
+            corrected.header.correction_field,
+            TimeInterval(I48F16::from_num(2001))
+        );
+    }
+
+    #[test]
+    fn test_ignore_own_transmissions() {
+        let mut ingress = test_port(0);
+        let mut egress = test_port(1);
+
+        let (buffer, length) = serialize(sync(true));
+        let forwarded = expect_forward(
+            ingress.handle_event_receive(&buffer[..length], Time::from_micros(1000)),
+        );
+        let (context, _) = expect_send_event(egress.handle_forwarded_message(&forwarded));
+        assert!(egress
+            .handle_send_timestamp(context, Time::from_micros(1001))
+            .next()
+            .is_none());
+
+        // The sync we just sent loops back to the egress port
+        assert!(egress
+            .handle_event_receive(&buffer[..length], Time::from_micros(1002))
+            .next()
+            .is_none());
+
+        // Peer delay messages are never forwarded
+        let (buffer, length) = serialize(Message {
+            header: Header::default(),
+            body: MessageBody::PDelayReq(PDelayReqMessage {
+                origin_timestamp: Default::default(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        assert!(ingress
+            .handle_event_receive(&buffer[..length], Time::from_micros(1003))
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_p2p_link_delay_correction() {
+        let mut ingress = p2p_port(0);
+        let mut egress = p2p_port(1);
+
+        let mut actions = ingress.start();
+        assert!(matches!(
+            actions.next(),
+            Some(TransparentPortAction::ResetDelayRequestTimer { duration })
+                if duration == core::time::Duration::ZERO
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        // Syncs can't be corrected before the link delay is known
+        let (buffer, length) = serialize(sync(true));
+        assert!(ingress
+            .handle_event_receive(&buffer[..length], Time::from_micros(900))
+            .next()
+            .is_none());
+
+        let mut actions = ingress.handle_delay_request_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(TransparentPortAction::ResetDelayRequestTimer { duration })
+                if duration == core::time::Duration::from_secs(1)
+        ));
+        let Some(TransparentPortAction::SendEvent {
+            context,
+            data,
+            link_local: true,
+        }) = actions.next()
+        else {
+            panic!("Expected peer delay request");
+        };
+        let request = Message::deserialize(data).unwrap().header;
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        assert!(ingress
+            .handle_send_timestamp(context, Time::from_micros(1000))
+            .next()
+            .is_none());
+
+        let peer_identity = PortIdentity {
+            clock_identity: ClockIdentity([9; 8]),
+            port_number: 1,
+        };
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                two_step_flag: true,
+                source_port_identity: peer_identity,
+                sequence_id: request.sequence_id,
+                ..Default::default()
+            },
+            body: MessageBody::PDelayResp(PDelayRespMessage {
+                request_receive_timestamp: Time::from_micros(5000).into(),
+                requesting_port_identity: request.source_port_identity,
+            }),
+            suffix: TlvSet::default(),
+        });
+        assert!(ingress
+            .handle_event_receive(&buffer[..length], Time::from_micros(1120))
+            .next()
+            .is_none());
+        assert_eq!(ingress.mean_link_delay(), None);
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                source_port_identity: peer_identity,
+                sequence_id: request.sequence_id,
+                ..Default::default()
+            },
+            body: MessageBody::PDelayRespFollowUp(PDelayRespFollowUpMessage {
+                response_origin_timestamp: Time::from_micros(5020).into(),
+                requesting_port_identity: request.source_port_identity,
+            }),
+            suffix: TlvSet::default(),
+        });
+        assert!(ingress
+            .handle_general_receive(&buffer[..length])
+            .next()
+            .is_none());
+        assert_eq!(ingress.mean_link_delay(), Some(Duration::from_micros(50)));
+
+        let (buffer, length) = serialize(sync(true));
+        let forwarded = expect_forward(
+            ingress.handle_event_receive(&buffer[..length], Time::from_micros(2000)),
+        );
+        let (context, _) = expect_send_event(egress.handle_forwarded_message(&forwarded));
+        assert!(egress
+            .handle_send_timestamp(context, Time::from_micros(2003))
+            .next()
+            .is_none());
+
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                correction_field: TimeInterval(I48F16::from_num(20)),
+                ..sync(true).header
+            },
+            body: MessageBody::FollowUp(FollowUpMessage {
+                precise_origin_timestamp: Time::from_micros(100).into(),
+            }),
+            suffix: TlvSet::default(),
+        });
+        let forwarded = expect_forward(ingress.handle_general_receive(&buffer[..length]));
+        let follow_up = expect_send_general(egress.handle_forwarded_message(&forwarded));
+        assert_eq!(
+            follow_up.header.correction_field,
+            TimeInterval(I48F16::from_num(53020))
+        );
+    }
+
+    #[test]
+    fn test_p2p_pdelay_response() {
+        let mut port = p2p_port(0);
+
+        let peer_identity = PortIdentity {
+            clock_identity: ClockIdentity([9; 8]),
+            port_number: 1,
+        };
+        let (buffer, length) = serialize(Message {
+            header: Header {
+                source_port_identity: peer_identity,
+                sequence_id: 7,
+                ..Default::default()
+            },
+            body: MessageBody::PDelayReq(PDelayReqMessage {
+                origin_timestamp: Default::default(),
+            }),
+            suffix: TlvSet::default(),
+        });
+
+        let mut actions = port.handle_event_receive(&buffer[..length], Time::from_micros(1000));
+        let Some(TransparentPortAction::SendEvent {
+            context,
+            data,
+            link_local: true,
+        }) = actions.next()
+        else {
+            panic!("Expected peer delay response");
+        };
+        let response = Message::deserialize(data).unwrap();
+        assert!(actions.next().is_none());
+        assert!(response.header.two_step_flag);
+        assert_eq!(response.header.sequence_id, 7);
+        assert_eq!(
+            response.body,
+            MessageBody::PDelayResp(PDelayRespMessage {
+                request_receive_timestamp: Time::from_micros(1000).into(),
+                requesting_port_identity: peer_identity,
+            })
+        );
+        drop(actions);
+
+        let mut actions = port.handle_send_timestamp(context, Time::from_micros(1005));
+        let Some(TransparentPortAction::SendGeneral {
+            data,
+            link_local: true,
+        }) = actions.next()
+        else {
+            panic!("Expected peer delay response follow up");
+        };
+        let follow_up = Message::deserialize(data).unwrap();
+        assert!(actions.next().is_none());
+        assert_eq!(follow_up.header.sequence_id, 7);
+        assert_eq!(
+            follow_up.body,
+            MessageBody::PDelayRespFollowUp(PDelayRespFollowUpMessage {
+                response_origin_timestamp: Time::from_micros(1005).into(),
+                requesting_port_identity: peer_identity,
+            })
+        );
+    }
+
+    #[test]
+    fn test_announce_network_time_inaccuracy() {
+        let config = TransparentClockConfig {
+            time_inaccuracy: 100,
+            ..config(DelayMechanism::P2P {
+                interval: Interval::ONE_SECOND,
+            })
+        };
+        let mut ingress = TransparentPort::new(config, PortIdentity::default());
+        let mut egress = TransparentPort::new(
+            config,
+            PortIdentity {
+                port_number: 1,
+                ..Default::default()
+            },
+        );
+
+        let information = PowerProfileInformation {
+            grandmaster_id: 3,
+            grandmaster_time_inaccuracy: 50,
+            network_time_inaccuracy: 200,
+        };
+        let state = crate::port::tests::setup_test_state();
+        let mut announce = Message::announce(&state.borrow(), master_identity(), 3);
+        let mut value = [0; 18];
+        let mut tlv_buffer = [0; 32];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        tlv_builder
+            .add(Tlv {
+                tlv_type: TlvType::OrganizationExtension,
+                value: information.serialize_value(&mut value).unwrap().into(),
+            })
+            .unwrap();
+        announce.suffix = tlv_builder.build();
+
+        let (buffer, length) = serialize(announce);
+        let forwarded = expect_forward(ingress.handle_general_receive(&buffer[..length]));
+
+        let mut actions = egress.handle_forwarded_message(&forwarded);
+        let Some(TransparentPortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Expected send general action");
+        };
+        let message = Message::deserialize(data).unwrap();
+        let tlv = message.suffix.tlv().next().unwrap();
+        assert_eq!(
+            PowerProfileInformation::from_tlv(&tlv).unwrap(),
+            Some(PowerProfileInformation {
+                network_time_inaccuracy: 300,
+                ..information
+            })
+        );
+    }
+}
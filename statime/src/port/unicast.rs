@@ -0,0 +1,1372 @@
+//! Unicast message negotiation, see *IEEE1588-2019 section 16.1*
+//!
+//! A port that negotiates unicast transmission is both a grantor, sending
+//! unicast messages to the ports that requested them, and a grantee,
+//! requesting unicast messages from its master.
+
+use arrayvec::ArrayVec;
+
+use super::{
+    sequence_id::SequenceIdGenerator, state::PortState, Port, PortAction, PortActionIterator,
+    Running,
+};
+use crate::{
+    config::{ClockIdentity, DelayMechanism, MAX_UNICAST_MASTER_TABLE_SIZE},
+    datastructures::{
+        common::{PortAddress, PortIdentity, Tlv, TlvSetBuilder},
+        messages::{Message, MessageType, SignalingMessage, UnicastNegotiationTlv, MAX_DATA_LEN},
+    },
+    filters::Filter,
+};
+
+/// Maximum number of ports we send unicast messages to
+const MAX_UNICAST_CLIENTS: usize = 64;
+/// Maximum number of masters we keep track of for requesting unicast messages,
+/// room for the unicast master table and as many masters found otherwise
+const MAX_UNICAST_MASTERS: usize = 2 * MAX_UNICAST_MASTER_TABLE_SIZE;
+
+/// Time between two updates of the grant bookkeeping
+pub(super) const UNICAST_TIMER_INTERVAL: core::time::Duration = core::time::Duration::from_secs(1);
+const UNICAST_TIMER_SECONDS: u32 = 1;
+
+/// Seconds to wait before repeating an unanswered request
+const REQUEST_RETRY_SECONDS: u32 = 4;
+/// Seconds to wait before repeating a denied or cancelled request
+const DENIED_RETRY_SECONDS: u32 = 30;
+
+pub(super) const ALL_PORTS: PortIdentity = PortIdentity {
+    clock_identity: ClockIdentity([0xff; 8]),
+    port_number: 0xffff,
+};
+
+/// The message types for which unicast transmission can be negotiated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnicastMessage {
+    Announce,
+    Sync,
+    DelayResp,
+}
+
+impl UnicastMessage {
+    const ALL: [Self; 3] = [Self::Announce, Self::Sync, Self::DelayResp];
+
+    fn index(self) -> usize {
+        match self {
+            Self::Announce => 0,
+            Self::Sync => 1,
+            Self::DelayResp => 2,
+        }
+    }
+
+    fn from_message_type(message_type: MessageType) -> Option<Self> {
+        match message_type {
+            MessageType::Announce => Some(Self::Announce),
+            MessageType::Sync => Some(Self::Sync),
+            MessageType::DelayResp => Some(Self::DelayResp),
+            _ => None,
+        }
+    }
+
+    fn message_type(self) -> MessageType {
+        match self {
+            Self::Announce => MessageType::Announce,
+            Self::Sync => MessageType::Sync,
+            Self::DelayResp => MessageType::DelayResp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Grant {
+    /// Number of timer periods between two messages
+    period: u32,
+    /// Timer periods until the next message
+    countdown: u32,
+    /// Seconds until the grant expires
+    remaining: u32,
+    /// Whether a message still needs to be sent in the current timer period
+    due: bool,
+}
+
+#[derive(Debug)]
+struct UnicastClient {
+    address: PortAddress,
+    port_identity: PortIdentity,
+    grants: [Option<Grant>; 3],
+    announce_seq_ids: SequenceIdGenerator,
+    sync_seq_ids: SequenceIdGenerator,
+}
+
+/// Bookkeeping of the grants we gave to other ports
+#[derive(Debug, Default)]
+pub(super) struct UnicastGrantor {
+    clients: ArrayVec<UnicastClient, MAX_UNICAST_CLIENTS>,
+}
+
+impl UnicastGrantor {
+    /// Register a grant of `message` to the port at `address`, for `duration`
+    /// seconds, with `period` timer periods between two messages.
+    ///
+    /// Returns false when there is no room for another client.
+    fn grant(
+        &mut self,
+        address: PortAddress,
+        port_identity: PortIdentity,
+        message: UnicastMessage,
+        period: u32,
+        duration: u32,
+        max_clients: usize,
+    ) -> bool {
+        let index = match self.clients.iter().position(|c| c.address == address) {
+            Some(index) => index,
+            None => {
+                if self.clients.len() >= max_clients.min(MAX_UNICAST_CLIENTS) {
+                    return false;
+                }
+                self.clients.push(UnicastClient {
+                    address,
+                    port_identity,
+                    grants: [None; 3],
+                    announce_seq_ids: SequenceIdGenerator::new(),
+                    sync_seq_ids: SequenceIdGenerator::new(),
+                });
+                self.clients.len() - 1
+            }
+        };
+
+        let client = &mut self.clients[index];
+        client.port_identity = port_identity;
+        let grant = &mut client.grants[message.index()];
+        match grant {
+            // A renewal, keep sending at the same moments
+            Some(grant) if grant.period == period => grant.remaining = duration,
+            _ => {
+                *grant = Some(Grant {
+                    period,
+                    countdown: 1,
+                    remaining: duration,
+                    due: false,
+                })
+            }
+        }
+
+        true
+    }
+
+    fn cancel(&mut self, address: PortAddress, message: UnicastMessage) {
+        if let Some(client) = self.clients.iter_mut().find(|c| c.address == address) {
+            client.grants[message.index()] = None;
+        }
+        self.clients
+            .retain(|c| c.grants.iter().any(Option::is_some));
+    }
+
+    /// Let `seconds` pass, dropping grants that expire
+    fn step(&mut self, seconds: u32) {
+        for client in self.clients.iter_mut() {
+            for grant in client.grants.iter_mut() {
+                if let Some(inner) = grant {
+                    inner.remaining = inner.remaining.saturating_sub(seconds);
+                    if inner.remaining == 0 {
+                        log::debug!("Unicast grant to {:?} expired", client.address);
+                        *grant = None;
+                    }
+                }
+            }
+        }
+        self.clients
+            .retain(|c| c.grants.iter().any(Option::is_some));
+    }
+
+    /// Start a new timer period for `message`, marking the clients that
+    /// should receive a message in it
+    fn start_period(&mut self, message: UnicastMessage) {
+        for client in self.clients.iter_mut() {
+            if let Some(grant) = &mut client.grants[message.index()] {
+                grant.countdown = grant.countdown.saturating_sub(1);
+                if grant.countdown == 0 {
+                    grant.countdown = grant.period;
+                    grant.due = true;
+                }
+            }
+        }
+    }
+
+    fn has_due(&self, message: UnicastMessage) -> bool {
+        self.clients
+            .iter()
+            .any(|c| matches!(c.grants[message.index()], Some(Grant { due: true, .. })))
+    }
+
+    /// Take the next client that should receive `message` in the current
+    /// timer period, together with the sequence id to use.
+    fn next_due(&mut self, message: UnicastMessage) -> Option<(PortAddress, u16)> {
+        self.clients.iter_mut().find_map(|client| {
+            let grant = client.grants[message.index()].as_mut()?;
+            if !grant.due {
+                return None;
+            }
+            grant.due = false;
+
+            let sequence_id = match message {
+                UnicastMessage::Announce => client.announce_seq_ids.generate(),
+                _ => client.sync_seq_ids.generate(),
+            };
+            Some((client.address, sequence_id))
+        })
+    }
+
+    fn is_granted(&self, address: PortAddress, message: UnicastMessage) -> bool {
+        self.clients
+            .iter()
+            .any(|c| c.address == address && c.grants[message.index()].is_some())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestState {
+    Idle,
+    /// Waiting for an answer to a request, repeat it in `retry` seconds
+    Requested {
+        retry: u32,
+    },
+    /// Receiving messages for `remaining` seconds, ask for renewal in
+    /// `renewal` seconds
+    Granted {
+        remaining: u32,
+        renewal: u32,
+    },
+    /// The request was denied or the grant cancelled, try again in `retry`
+    /// seconds
+    Denied {
+        retry: u32,
+    },
+}
+
+#[derive(Debug)]
+struct UnicastMaster {
+    address: PortAddress,
+    port_identity: Option<PortIdentity>,
+    /// Whether this master is in the unicast master table
+    in_table: bool,
+    requests: [RequestState; 3],
+}
+
+impl UnicastMaster {
+    fn is_idle(&self) -> bool {
+        !self.in_table && self.requests.iter().all(|r| *r == RequestState::Idle)
+    }
+}
+
+/// Bookkeeping of the grants we requested from other ports
+#[derive(Debug, Default)]
+pub(super) struct UnicastGrantee {
+    masters: ArrayVec<UnicastMaster, MAX_UNICAST_MASTERS>,
+}
+
+impl UnicastGrantee {
+    fn new(table: &[PortAddress]) -> Self {
+        let mut masters = ArrayVec::new();
+        for &address in table {
+            if !masters.iter().any(|m: &UnicastMaster| m.address == address) {
+                masters.push(UnicastMaster {
+                    address,
+                    port_identity: None,
+                    in_table: true,
+                    requests: [RequestState::Idle; 3],
+                });
+            }
+        }
+
+        Self { masters }
+    }
+
+    /// Remember the address a master sends its messages from
+    fn learn(&mut self, address: PortAddress, port_identity: PortIdentity) {
+        if let Some(master) = self.masters.iter_mut().find(|m| m.address == address) {
+            master.port_identity = Some(port_identity);
+            return;
+        }
+
+        if self.masters.is_full() {
+            // Make room by forgetting a master we have no business with
+            let Some(index) = self.masters.iter().position(UnicastMaster::is_idle) else {
+                return;
+            };
+            self.masters.remove(index);
+        }
+
+        self.masters.push(UnicastMaster {
+            address,
+            port_identity: Some(port_identity),
+            in_table: false,
+            requests: [RequestState::Idle; 3],
+        });
+    }
+
+    fn step(&mut self, seconds: u32) {
+        for master in self.masters.iter_mut() {
+            for request in master.requests.iter_mut() {
+                *request = match *request {
+                    RequestState::Idle => RequestState::Idle,
+                    RequestState::Requested { retry } => RequestState::Requested {
+                        retry: retry.saturating_sub(seconds),
+                    },
+                    RequestState::Granted { remaining, renewal } => {
+                        if remaining <= seconds {
+                            log::debug!("Unicast grant from {:?} expired", master.address);
+                            RequestState::Idle
+                        } else {
+                            RequestState::Granted {
+                                remaining: remaining - seconds,
+                                renewal: renewal.saturating_sub(seconds),
+                            }
+                        }
+                    }
+                    RequestState::Denied { retry } => RequestState::Denied {
+                        retry: retry.saturating_sub(seconds),
+                    },
+                }
+            }
+        }
+    }
+
+    fn granted(&mut self, address: PortAddress, message: UnicastMessage, duration: u32) {
+        let Some(master) = self.masters.iter_mut().find(|m| m.address == address) else {
+            log::debug!("Ignoring unicast grant from unknown master {:?}", address);
+            return;
+        };
+
+        master.requests[message.index()] = if duration == 0 {
+            log::info!("Master {:?} denied unicast {:?}", address, message);
+            RequestState::Denied {
+                retry: DENIED_RETRY_SECONDS,
+            }
+        } else {
+            log::debug!(
+                "Master {:?} granted unicast {:?} for {}s",
+                address,
+                message,
+                duration
+            );
+            RequestState::Granted {
+                remaining: duration,
+                // Renew once three quarters of the grant have passed
+                renewal: duration - duration / 4,
+            }
+        };
+    }
+
+    fn cancelled(&mut self, address: PortAddress, message: UnicastMessage) {
+        if let Some(master) = self.masters.iter_mut().find(|m| m.address == address) {
+            if master.requests[message.index()] != RequestState::Idle {
+                log::info!("Master {:?} cancelled unicast {:?}", address, message);
+                master.requests[message.index()] = RequestState::Denied {
+                    retry: DENIED_RETRY_SECONDS,
+                };
+            }
+        }
+    }
+
+    /// The address of `master` if it granted us `message`
+    fn granted_address(
+        &self,
+        master: PortIdentity,
+        message: UnicastMessage,
+    ) -> Option<PortAddress> {
+        self.masters
+            .iter()
+            .find(|m| {
+                m.port_identity == Some(master)
+                    && matches!(m.requests[message.index()], RequestState::Granted { .. })
+            })
+            .map(|m| m.address)
+    }
+
+    /// Determine the requests and cancellations that need to be sent to the
+    /// master at `index`, given the messages we want from it
+    fn negotiation_tlvs(
+        &mut self,
+        index: usize,
+        wanted: impl Fn(&UnicastMaster, UnicastMessage) -> bool,
+        log_interval: impl Fn(UnicastMessage) -> i8,
+        duration: u32,
+    ) -> ArrayVec<UnicastNegotiationTlv, 3> {
+        let mut tlvs = ArrayVec::new();
+        let master = &mut self.masters[index];
+
+        for message in UnicastMessage::ALL {
+            let wanted = wanted(master, message);
+            let request = &mut master.requests[message.index()];
+
+            let send_request = match *request {
+                RequestState::Idle => wanted,
+                RequestState::Requested { retry } | RequestState::Denied { retry } => {
+                    if !wanted {
+                        *request = RequestState::Idle;
+                    }
+                    wanted && retry == 0
+                }
+                RequestState::Granted { remaining, renewal } => {
+                    if !wanted {
+                        *request = RequestState::Idle;
+                        tlvs.push(UnicastNegotiationTlv::Cancel {
+                            message_type: message.message_type(),
+                        });
+                        false
+                    } else if renewal == 0 {
+                        // Keep using the current grant until the renewal arrives
+                        *request = RequestState::Granted {
+                            remaining,
+                            renewal: REQUEST_RETRY_SECONDS,
+                        };
+                        tlvs.push(UnicastNegotiationTlv::Request {
+                            message_type: message.message_type(),
+                            log_inter_message_period: log_interval(message),
+                            duration,
+                        });
+                        false
+                    } else {
+                        false
+                    }
+                }
+            };
+
+            if send_request {
+                *request = RequestState::Requested {
+                    retry: REQUEST_RETRY_SECONDS,
+                };
+                tlvs.push(UnicastNegotiationTlv::Request {
+                    message_type: message.message_type(),
+                    log_inter_message_period: log_interval(message),
+                    duration,
+                });
+            }
+        }
+
+        tlvs
+    }
+}
+
+/// Unicast negotiation state of a port
+#[derive(Debug, Default)]
+pub(super) struct UnicastState {
+    pub(super) grantor: UnicastGrantor,
+    pub(super) grantee: UnicastGrantee,
+    /// The next master to handle in the current timer period
+    cursor: usize,
+}
+
+impl UnicastState {
+    pub(super) fn new(unicast_master_table: &[PortAddress]) -> Self {
+        Self {
+            grantor: UnicastGrantor::default(),
+            grantee: UnicastGrantee::new(unicast_master_table),
+            cursor: 0,
+        }
+    }
+}
+
+impl<'a, A, C, F: Filter, R> Port<Running<'a>, A, R, C, F> {
+    /// Handle the unicast timer going off
+    ///
+    /// This is only needed when
+    /// [`PortConfig::unicast_negotiation`](`crate::config::PortConfig::unicast_negotiation`)
+    /// is enabled.
+    pub fn handle_unicast_timer(&mut self) -> PortActionIterator<'_> {
+        let (Some(unicast), Some(config)) = (&mut self.unicast, self.config.unicast_negotiation)
+        else {
+            return actions![];
+        };
+
+        if unicast.cursor == 0 {
+            unicast.grantor.step(UNICAST_TIMER_SECONDS);
+            unicast.grantee.step(UNICAST_TIMER_SECONDS);
+        }
+
+        if matches!(self.port_state, PortState::Disabled | PortState::Faulty) {
+            unicast.cursor = 0;
+            return actions![PortAction::ResetUnicastTimer {
+                duration: UNICAST_TIMER_INTERVAL,
+            }];
+        }
+
+        let parent = match &self.port_state {
+            PortState::Slave(state) => Some(state.remote_master()),
+            _ => None,
+        };
+        let e2e = matches!(self.config.delay_mechanism, DelayMechanism::E2E { .. });
+        let wanted = |master: &UnicastMaster, message| match message {
+            UnicastMessage::Announce => master.in_table,
+            UnicastMessage::Sync => parent.is_some() && master.port_identity == parent,
+            UnicastMessage::DelayResp => e2e && parent.is_some() && master.port_identity == parent,
+        };
+        let port_config = &self.config;
+        let log_interval = |message| match message {
+            UnicastMessage::Announce => port_config.announce_interval.as_log_2(),
+            UnicastMessage::Sync => port_config.sync_interval.as_log_2(),
+            UnicastMessage::DelayResp => port_config.min_delay_req_interval().as_log_2(),
+        };
+
+        while unicast.cursor < unicast.grantee.masters.len() {
+            let index = unicast.cursor;
+            unicast.cursor += 1;
+
+            let tlvs = unicast.grantee.negotiation_tlvs(
+                index,
+                wanted,
+                log_interval,
+                config.grant_duration,
+            );
+            if tlvs.is_empty() {
+                continue;
+            }
+
+            let master = &unicast.grantee.masters[index];
+            let address = master.address;
+            let target = master.port_identity.unwrap_or(ALL_PORTS);
+
+            // Handle the next master immediately, to keep the messages of one
+            // timer period together
+            let reset = PortAction::ResetUnicastTimer {
+                duration: core::time::Duration::ZERO,
+            };
+            return match self.serialize_signaling(target, &tlvs) {
+                Some(length) => actions![
+                    reset,
+                    PortAction::SendGeneral {
+                        data: &self.packet_buffer[..length],
+                        link_local: false,
+                        destination: Some(address),
+                    }
+                ],
+                None => actions![reset],
+            };
+        }
+
+        unicast.cursor = 0;
+        actions![PortAction::ResetUnicastTimer {
+            duration: UNICAST_TIMER_INTERVAL,
+        }]
+    }
+
+    /// Remember where the sender of an announce message can be reached
+    pub(super) fn learn_unicast_master(&mut self, address: PortAddress, identity: PortIdentity) {
+        if let Some(unicast) = &mut self.unicast {
+            unicast.grantee.learn(address, identity);
+        }
+    }
+
+    /// Whether a signaling message for `target` is meant for this port
+    pub(super) fn is_signaling_target(&self, target: PortIdentity) -> bool {
+        (target.clock_identity == self.port_identity.clock_identity
+            || target.clock_identity == ALL_PORTS.clock_identity)
+            && (target.port_number == self.port_identity.port_number
+                || target.port_number == ALL_PORTS.port_number)
+    }
+
+    pub(super) fn handle_signaling<'b>(
+        &'b mut self,
+        message: &Message<'b>,
+        signaling: SignalingMessage,
+        source: Option<PortAddress>,
+    ) -> PortActionIterator<'b> {
+        if self.unicast.is_none() {
+            return actions![];
+        }
+
+        if !self.is_signaling_target(signaling.target_port_identity) {
+            return actions![];
+        }
+
+        let Some(source) = source else {
+            log::debug!("Ignoring signaling message from an unknown address");
+            return actions![];
+        };
+        let requestor = message.header.source_port_identity;
+
+        let mut responses = ArrayVec::<UnicastNegotiationTlv, 8>::new();
+        for tlv in message.suffix.tlv() {
+            let tlv = match UnicastNegotiationTlv::from_tlv(&tlv) {
+                Ok(Some(tlv)) => tlv,
+                Ok(None) => continue,
+                Err(error) => {
+                    log::warn!("Could not parse unicast negotiation TLV: {:?}", error);
+                    continue;
+                }
+            };
+
+            let response = match tlv {
+                UnicastNegotiationTlv::Request {
+                    message_type,
+                    log_inter_message_period,
+                    duration,
+                } => {
+                    let duration = self.unicast_grant_duration(
+                        source,
+                        requestor,
+                        message_type,
+                        log_inter_message_period,
+                        duration,
+                    );
+                    Some(UnicastNegotiationTlv::Grant {
+                        message_type,
+                        log_inter_message_period,
+                        duration,
+                        renewal_invited: duration != 0,
+                    })
+                }
+                UnicastNegotiationTlv::Grant {
+                    message_type,
+                    duration,
+                    ..
+                } => {
+                    if let (Some(unicast), Some(message)) = (
+                        &mut self.unicast,
+                        UnicastMessage::from_message_type(message_type),
+                    ) {
+                        unicast.grantee.granted(source, message, duration);
+                    }
+                    None
+                }
+                UnicastNegotiationTlv::Cancel { message_type } => {
+                    if let (Some(unicast), Some(message)) = (
+                        &mut self.unicast,
+                        UnicastMessage::from_message_type(message_type),
+                    ) {
+                        unicast.grantor.cancel(source, message);
+                        unicast.grantee.cancelled(source, message);
+                    }
+                    Some(UnicastNegotiationTlv::AcknowledgeCancel { message_type })
+                }
+                UnicastNegotiationTlv::AcknowledgeCancel { .. } => None,
+            };
+
+            if let Some(response) = response {
+                if responses.try_push(response).is_err() {
+                    log::warn!("Too many unicast negotiation TLVs in one message");
+                    break;
+                }
+            }
+        }
+
+        if responses.is_empty() {
+            return actions![];
+        }
+
+        match self.serialize_signaling(requestor, &responses) {
+            Some(length) => actions![PortAction::SendGeneral {
+                data: &self.packet_buffer[..length],
+                link_local: false,
+                destination: Some(source),
+            }],
+            None => actions![],
+        }
+    }
+
+    /// Decide on a request for unicast messages, returning the granted
+    /// duration, or 0 when the request is denied.
+    fn unicast_grant_duration(
+        &mut self,
+        address: PortAddress,
+        port_identity: PortIdentity,
+        message_type: MessageType,
+        log_interval: i8,
+        duration: u32,
+    ) -> u32 {
+        let (Some(unicast), Some(config)) = (&mut self.unicast, self.config.unicast_negotiation)
+        else {
+            return 0;
+        };
+
+        let Some(message) = UnicastMessage::from_message_type(message_type) else {
+            log::debug!("Denied unicast {:?} to {:?}", message_type, address);
+            return 0;
+        };
+
+        let (own_interval, available) = match message {
+            UnicastMessage::Announce => (self.config.announce_interval, true),
+            UnicastMessage::Sync => (
+                self.config.sync_interval,
+                matches!(self.port_state, PortState::Master),
+            ),
+            UnicastMessage::DelayResp => (
+                self.config.min_delay_req_interval(),
+                matches!(self.port_state, PortState::Master)
+                    && matches!(self.config.delay_mechanism, DelayMechanism::E2E { .. }),
+            ),
+        };
+
+        // Don't send more messages than we would when multicasting
+        if !available || log_interval < own_interval.as_log_2() || duration == 0 {
+            log::debug!(
+                "Denied unicast {:?} to {:?} at log interval {}",
+                message,
+                address,
+                log_interval
+            );
+            return 0;
+        }
+
+        let duration = duration.min(config.max_grant_duration);
+        // The requested interval comes from the network, so it may be far
+        // outside of the range of our own intervals
+        let period_log =
+            (i32::from(log_interval) - i32::from(own_interval.as_log_2())).clamp(0, 31) as u32;
+        if !unicast.grantor.grant(
+            address,
+            port_identity,
+            message,
+            1 << period_log,
+            duration,
+            config.max_clients,
+        ) {
+            log::warn!(
+                "Denied unicast {:?} to {:?}, too many clients",
+                message,
+                address
+            );
+            return 0;
+        }
+
+        log::debug!(
+            "Granted unicast {:?} to {:?} for {}s",
+            message,
+            address,
+            duration
+        );
+        duration
+    }
+
+    /// Pick the next client that should get a unicast `message` in the current
+    /// timer period.
+    ///
+    /// Returns the address and sequence id to use, and whether more clients
+    /// are waiting in this period.
+    pub(super) fn next_unicast_client(
+        &mut self,
+        message: UnicastMessage,
+    ) -> Option<(PortAddress, u16, bool)> {
+        let unicast = self.unicast.as_mut()?;
+        let (address, sequence_id) = unicast.grantor.next_due(message)?;
+        Some((address, sequence_id, unicast.grantor.has_due(message)))
+    }
+
+    /// Start a new timer period for unicast `message`s, returns whether any
+    /// client should receive one in it.
+    pub(super) fn start_unicast_period(&mut self, message: UnicastMessage) -> bool {
+        match &mut self.unicast {
+            Some(unicast) => {
+                unicast.grantor.start_period(message);
+                unicast.grantor.has_due(message)
+            }
+            None => false,
+        }
+    }
+
+    pub(super) fn unicast_period_in_progress(&self, message: UnicastMessage) -> bool {
+        self.unicast
+            .as_ref()
+            .map_or(false, |unicast| unicast.grantor.has_due(message))
+    }
+
+    /// The address to send a delay response to, if the requestor negotiated
+    /// unicast delay responses
+    pub(super) fn unicast_delay_resp_destination(
+        &self,
+        source: Option<PortAddress>,
+    ) -> Option<PortAddress> {
+        let unicast = self.unicast.as_ref()?;
+        source.filter(|&address| {
+            unicast
+                .grantor
+                .is_granted(address, UnicastMessage::DelayResp)
+        })
+    }
+
+    /// The address to send delay requests to, if our master granted us unicast
+    /// delay responses
+    pub(super) fn unicast_delay_req_destination(&self) -> Option<PortAddress> {
+        let unicast = self.unicast.as_ref()?;
+        match &self.port_state {
+            PortState::Slave(state) => unicast
+                .grantee
+                .granted_address(state.remote_master(), UnicastMessage::DelayResp),
+            _ => None,
+        }
+    }
+
+    fn serialize_signaling(
+        &mut self,
+        target: PortIdentity,
+        tlvs: &[UnicastNegotiationTlv],
+    ) -> Option<usize> {
+        let mut tlv_buffer = [0; MAX_DATA_LEN];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        for tlv in tlvs {
+            let mut value = [0; 8];
+            let result = tlv.serialize_value(&mut value).and_then(|value| {
+                tlv_builder.add(Tlv {
+                    tlv_type: tlv.tlv_type(),
+                    value: value.into(),
+                })
+            });
+            if let Err(error) = result {
+                log::error!("Statime bug: Could not build signaling TLVs: {:?}", error);
+                return None;
+            }
+        }
+
+        let message = Message::signaling(
+            &self.lifecycle.state.default_ds,
+            self.port_identity,
+            target,
+            self.signaling_seq_ids.generate(),
+            tlv_builder.build(),
+        );
+
+        match message.serialize(&mut self.packet_buffer) {
+            Ok(length) => self.finish_message(length),
+            Err(error) => {
+                log::error!("Statime bug: Could not serialize signaling: {:?}", error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atomic_refcell::AtomicRefCell;
+
+    use super::*;
+    use crate::{
+        config::UnicastNegotiationConfig,
+        datastructures::messages::MessageBody,
+        port::{
+            state::SlaveState,
+            tests::{setup_test_port, setup_test_state},
+            NoForwardedTLVs,
+        },
+        ptp_instance::PtpInstanceState,
+    };
+
+    const CLIENT: PortAddress = PortAddress::Ipv4([10, 0, 0, 2]);
+    const MASTER: PortAddress = PortAddress::Ipv4([10, 0, 0, 1]);
+
+    fn remote_identity() -> PortIdentity {
+        PortIdentity {
+            clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+            port_number: 1,
+        }
+    }
+
+    fn signaling_packet(
+        state: &AtomicRefCell<PtpInstanceState>,
+        tlvs: &[UnicastNegotiationTlv],
+    ) -> std::vec::Vec<u8> {
+        let mut tlv_buffer = [0; MAX_DATA_LEN];
+        let mut tlv_builder = TlvSetBuilder::new(&mut tlv_buffer);
+        for tlv in tlvs {
+            let mut value = [0; 8];
+            let value = tlv.serialize_value(&mut value).unwrap();
+            tlv_builder
+                .add(Tlv {
+                    tlv_type: tlv.tlv_type(),
+                    value: value.into(),
+                })
+                .unwrap();
+        }
+
+        let message = Message::signaling(
+            &state.borrow().default_ds,
+            remote_identity(),
+            ALL_PORTS,
+            7,
+            tlv_builder.build(),
+        );
+        let mut buffer = [0; MAX_DATA_LEN];
+        let length = message.serialize(&mut buffer).unwrap();
+        buffer[..length].to_vec()
+    }
+
+    fn negotiation_tlvs(data: &[u8]) -> std::vec::Vec<UnicastNegotiationTlv> {
+        let message = Message::deserialize(data).unwrap();
+        assert!(matches!(message.body, MessageBody::Signaling(_)));
+        message
+            .suffix
+            .tlv()
+            .map(|tlv| UnicastNegotiationTlv::from_tlv(&tlv).unwrap().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn grant_requests_within_limits() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig {
+            max_grant_duration: 60,
+            ..Default::default()
+        });
+        port.unicast = Some(UnicastState::default());
+        port.set_forced_port_state(PortState::Master);
+
+        // The test port syncs every second, so faster syncs are denied
+        let request = signaling_packet(
+            &state,
+            &[
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period: -1,
+                    duration: 300,
+                },
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::DelayResp,
+                    log_inter_message_period: 1,
+                    duration: 300,
+                },
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::PDelayResp,
+                    log_inter_message_period: 1,
+                    duration: 300,
+                },
+            ],
+        );
+
+        let port_identity = port.port_identity;
+        let mut actions = port.handle_general_receive_from(&request, CLIENT);
+        let Some(PortAction::SendGeneral {
+            data,
+            link_local: false,
+            destination: Some(CLIENT),
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert!(actions.next().is_none());
+
+        let message = Message::deserialize(data).unwrap();
+        assert_eq!(message.header.source_port_identity, port_identity);
+        let MessageBody::Signaling(signaling) = message.body else {
+            panic!("Unexpected message type");
+        };
+        assert_eq!(signaling.target_port_identity, remote_identity());
+
+        assert_eq!(
+            negotiation_tlvs(data),
+            [
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period: -1,
+                    duration: 0,
+                    renewal_invited: false,
+                },
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::DelayResp,
+                    log_inter_message_period: 1,
+                    duration: 60,
+                    renewal_invited: true,
+                },
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::PDelayResp,
+                    log_inter_message_period: 1,
+                    duration: 0,
+                    renewal_invited: false,
+                },
+            ]
+        );
+        drop(actions);
+
+        assert_eq!(
+            port.unicast_delay_resp_destination(Some(CLIENT)),
+            Some(CLIENT)
+        );
+        assert_eq!(port.unicast_delay_resp_destination(Some(MASTER)), None);
+    }
+
+    #[test]
+    fn grant_extreme_intervals() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(Default::default());
+        port.config.sync_interval = crate::time::Interval::from_log_2(-7);
+        port.unicast = Some(UnicastState::default());
+        port.set_forced_port_state(PortState::Master);
+
+        for (log_inter_message_period, granted) in [(127, 10), (-128, 0)] {
+            let request = signaling_packet(
+                &state,
+                &[UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period,
+                    duration: 10,
+                }],
+            );
+
+            let mut actions = port.handle_general_receive_from(&request, CLIENT);
+            let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+                panic!("Unexpected action");
+            };
+            let [UnicastNegotiationTlv::Grant { duration, .. }] = negotiation_tlvs(data)[..] else {
+                panic!("Expected a single grant");
+            };
+            assert_eq!(duration, granted);
+        }
+    }
+
+    #[test]
+    fn deny_clients_beyond_maximum() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig {
+            max_clients: 1,
+            ..Default::default()
+        });
+        port.unicast = Some(UnicastState::default());
+
+        let request = signaling_packet(
+            &state,
+            &[UnicastNegotiationTlv::Request {
+                message_type: MessageType::Announce,
+                log_inter_message_period: 1,
+                duration: 10,
+            }],
+        );
+
+        for (address, granted) in [(CLIENT, 10), (MASTER, 0), (CLIENT, 10)] {
+            let mut actions = port.handle_general_receive_from(&request, address);
+            let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+                panic!("Unexpected action");
+            };
+            let [UnicastNegotiationTlv::Grant { duration, .. }] = negotiation_tlvs(data)[..] else {
+                panic!("Expected a single grant");
+            };
+            assert_eq!(duration, granted);
+        }
+    }
+
+    #[test]
+    fn unicast_sync_to_granted_clients() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig::default());
+        port.unicast = Some(UnicastState::default());
+        port.set_forced_port_state(PortState::Master);
+
+        // Without clients no syncs are sent
+        let mut actions = port.handle_sync_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetSyncTimer { .. })
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        // One client at the port's own rate, one at half that rate
+        for (address, log_inter_message_period) in [(CLIENT, 0), (MASTER, 1)] {
+            let request = signaling_packet(
+                &state,
+                &[UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period,
+                    duration: 2,
+                }],
+            );
+            let _ = port.handle_general_receive_from(&request, address);
+        }
+
+        let mut sent_to = std::vec::Vec::new();
+        for _ in 0..4 {
+            let mut actions = port.handle_sync_timer();
+            let Some(PortAction::ResetSyncTimer { duration }) = actions.next() else {
+                panic!("Unexpected action");
+            };
+            match actions.next() {
+                Some(PortAction::SendEvent {
+                    context,
+                    data,
+                    destination: Some(destination),
+                    ..
+                }) => {
+                    let message = Message::deserialize(data).unwrap();
+                    assert!(message.header.unicast_flag);
+                    assert!(matches!(
+                        context.inner,
+                        crate::port::actions::TimestampContextInner::Sync {
+                            destination: Some(d),
+                            ..
+                        } if d == destination
+                    ));
+                    sent_to.push((destination, duration.is_zero()));
+                }
+                _ => panic!("Unexpected action"),
+            }
+        }
+
+        assert_eq!(
+            sent_to,
+            [
+                // Both clients are due, the timer fires again immediately
+                (CLIENT, true),
+                (MASTER, false),
+                // Only the faster client
+                (CLIENT, false),
+                // Both again
+                (CLIENT, true),
+            ]
+        );
+
+        // The grants expire after two seconds
+        let _ = port.handle_unicast_timer();
+        let _ = port.handle_unicast_timer();
+        let mut actions = port.handle_sync_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetSyncTimer { .. })
+        ));
+        assert!(actions.next().is_none());
+    }
+
+    #[test]
+    fn unicast_only_announce() {
+        let state = setup_test_state();
+        state.borrow_mut().default_ds.profile = crate::config::Profile::G8275_2;
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig::default());
+        port.unicast = Some(UnicastState::default());
+        port.set_forced_port_state(PortState::Master);
+
+        // Without clients no announces are sent, not even multicast
+        let mut actions = port.handle_announce_timer(&mut NoForwardedTLVs);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        let request = signaling_packet(
+            &state,
+            &[UnicastNegotiationTlv::Request {
+                message_type: MessageType::Announce,
+                log_inter_message_period: 1,
+                duration: 60,
+            }],
+        );
+        let _ = port.handle_general_receive_from(&request, CLIENT);
+
+        let mut actions = port.handle_announce_timer(&mut NoForwardedTLVs);
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetAnnounceTimer { .. })
+        ));
+        let Some(PortAction::SendGeneral {
+            data,
+            destination: Some(CLIENT),
+            ..
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        let message = Message::deserialize(data).unwrap();
+        assert!(matches!(message.body, MessageBody::Announce(_)));
+        assert!(message.header.unicast_flag);
+    }
+
+    #[test]
+    fn request_announce_from_master_table() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig::default());
+        port.unicast = Some(UnicastState::new(&[MASTER, CLIENT, MASTER]));
+
+        for address in [MASTER, CLIENT] {
+            let mut actions = port.handle_unicast_timer();
+            assert!(matches!(
+                actions.next(),
+                Some(PortAction::ResetUnicastTimer { duration }) if duration.is_zero()
+            ));
+            let Some(PortAction::SendGeneral {
+                data,
+                destination: Some(destination),
+                ..
+            }) = actions.next()
+            else {
+                panic!("Unexpected action");
+            };
+            assert_eq!(destination, address);
+
+            // The identity of the master is not known yet
+            let message = Message::deserialize(data).unwrap();
+            let MessageBody::Signaling(signaling) = message.body else {
+                panic!("Unexpected message type");
+            };
+            assert_eq!(signaling.target_port_identity, ALL_PORTS);
+            assert_eq!(
+                negotiation_tlvs(data),
+                [UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Announce,
+                    log_inter_message_period: 1,
+                    duration: 300,
+                }]
+            );
+        }
+
+        let mut actions = port.handle_unicast_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetUnicastTimer { duration }) if duration == UNICAST_TIMER_INTERVAL
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        // Once the master is known, requests are addressed to it
+        port.learn_unicast_master(MASTER, remote_identity());
+        let unicast = port.unicast.as_ref().unwrap();
+        assert_eq!(unicast.grantee.masters.len(), 2);
+        assert_eq!(
+            unicast.grantee.masters[0].port_identity,
+            Some(remote_identity())
+        );
+    }
+
+    #[test]
+    fn slave_requests_from_master() {
+        let state = setup_test_state();
+        let mut port = setup_test_port(&state);
+        port.config.unicast_negotiation = Some(UnicastNegotiationConfig {
+            grant_duration: 8,
+            ..Default::default()
+        });
+        port.unicast = Some(UnicastState::default());
+
+        port.learn_unicast_master(MASTER, remote_identity());
+
+        // Not a slave, nothing to request
+        let mut actions = port.handle_unicast_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetUnicastTimer { duration }) if duration == UNICAST_TIMER_INTERVAL
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+
+        port.set_forced_port_state(PortState::Slave(SlaveState::new(remote_identity())));
+
+        let mut actions = port.handle_unicast_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetUnicastTimer { duration }) if duration.is_zero()
+        ));
+        let Some(PortAction::SendGeneral {
+            data,
+            destination: Some(MASTER),
+            ..
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert_eq!(
+            negotiation_tlvs(data),
+            [
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period: 0,
+                    duration: 8,
+                },
+                UnicastNegotiationTlv::Request {
+                    message_type: MessageType::DelayResp,
+                    log_inter_message_period: 1,
+                    duration: 8,
+                },
+            ]
+        );
+        drop(actions);
+
+        // The rest of the period nothing needs to be sent
+        let mut actions = port.handle_unicast_timer();
+        assert!(matches!(
+            actions.next(),
+            Some(PortAction::ResetUnicastTimer { duration }) if duration == UNICAST_TIMER_INTERVAL
+        ));
+        assert!(actions.next().is_none());
+        drop(actions);
+        assert_eq!(port.unicast_delay_req_destination(), None);
+
+        let grant = signaling_packet(
+            &state,
+            &[
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::Sync,
+                    log_inter_message_period: 0,
+                    duration: 8,
+                    renewal_invited: true,
+                },
+                UnicastNegotiationTlv::Grant {
+                    message_type: MessageType::DelayResp,
+                    log_inter_message_period: 1,
+                    duration: 8,
+                    renewal_invited: true,
+                },
+            ],
+        );
+        let mut actions = port.handle_general_receive_from(&grant, MASTER);
+        assert!(actions.next().is_none());
+        drop(actions);
+        assert_eq!(port.unicast_delay_req_destination(), Some(MASTER));
+
+        // Renewal after three quarters of the grant
+        let mut renewals = 0;
+        for _ in 0..6 {
+            let mut actions = port.handle_unicast_timer();
+            let _ = actions.next();
+            if let Some(PortAction::SendGeneral { data, .. }) = actions.next() {
+                assert_eq!(negotiation_tlvs(data).len(), 2);
+                renewals += 1;
+                drop(actions);
+                // finish the timer period
+                let _ = port.handle_unicast_timer();
+            }
+        }
+        assert_eq!(renewals, 1);
+
+        // Cancellation by the master is acknowledged
+        let cancel = signaling_packet(
+            &state,
+            &[UnicastNegotiationTlv::Cancel {
+                message_type: MessageType::DelayResp,
+            }],
+        );
+        let mut actions = port.handle_general_receive_from(&cancel, MASTER);
+        let Some(PortAction::SendGeneral {
+            data,
+            destination: Some(MASTER),
+            ..
+        }) = actions.next()
+        else {
+            panic!("Unexpected action");
+        };
+        assert_eq!(
+            negotiation_tlvs(data),
+            [UnicastNegotiationTlv::AcknowledgeCancel {
+                message_type: MessageType::DelayResp,
+            }]
+        );
+        drop(actions);
+        assert_eq!(port.unicast_delay_req_destination(), None);
+
+        // Leaving the slave state cancels the remaining grant
+        port.set_forced_port_state(PortState::Listening);
+        let mut actions = port.handle_unicast_timer();
+        let _ = actions.next();
+        let Some(PortAction::SendGeneral { data, .. }) = actions.next() else {
+            panic!("Unexpected action");
+        };
+        assert_eq!(
+            negotiation_tlvs(data),
+            [UnicastNegotiationTlv::Cancel {
+                message_type: MessageType::Sync,
+            }]
+        );
+    }
+}
@@ -11,15 +11,21 @@ use crate::float_polyfill::FloatPolyfill;
 use crate::{
     bmc::{acceptable_master::AcceptableMasterList, bmca::Bmca},
     clock::Clock,
-    config::{InstanceConfig, PortConfig},
+    config::{
+        AlternateTimeOffset, ClockQuality, HoldoverConfig, InstanceConfig, KeyProvider, PortConfig,
+        PowerProfileConfig, SecurityConfig, SynchronizationMetadata,
+    },
     datastructures::{
-        common::PortIdentity,
+        common::{PortIdentity, PowerProfileInformation},
         datasets::{InternalCurrentDS, InternalDefaultDS, InternalParentDS, TimePropertiesDS},
     },
-    filters::Filter,
-    observability::{current::CurrentDS, default::DefaultDS, parent::ParentDS},
+    filters::{ClockUncertainty, Filter},
+    holdover::Holdover,
+    observability::{
+        current::CurrentDS, default::DefaultDS, holdover::HoldoverState, parent::ParentDS,
+    },
     port::{InBmca, Port},
-    time::Duration,
+    time::{Duration, Time},
 };
 
 /// A PTP node.
@@ -56,7 +62,7 @@ use crate::{
 /// # let rng: rand::rngs::mock::StepRng = unimplemented!();
 /// #
 /// use statime::PtpInstance;
-/// use statime::config::{AcceptAnyMaster, ClockIdentity, InstanceConfig, TimePropertiesDS, TimeSource};
+/// use statime::config::{AcceptAnyMaster, ClockIdentity, InstanceConfig, Profile, TimePropertiesDS, TimeSource};
 /// use statime::filters::BasicFilter;
 ///
 /// let instance_config = InstanceConfig {
@@ -66,6 +72,8 @@ use crate::{
 ///     domain_number: 0,
 ///     slave_only: false,
 ///     sdo_id: Default::default(),
+///     profile: Profile::Default,
+///     local_priority: 128,
 /// };
 /// let time_properties_ds = TimePropertiesDS::new_arbitrary_time(false, false, TimeSource::InternalOscillator);
 ///
@@ -95,6 +103,30 @@ pub(crate) struct PtpInstanceState {
     pub(crate) current_ds: InternalCurrentDS,
     pub(crate) parent_ds: InternalParentDS,
     pub(crate) time_properties_ds: TimePropertiesDS,
+    /// The time properties announced while we are the grandmaster
+    pub(crate) local_time_properties_ds: TimePropertiesDS,
+    /// The quality of our clock when it is not in holdover
+    pub(crate) local_clock_quality: ClockQuality,
+    pub(crate) holdover_config: Option<HoldoverConfig>,
+    /// The state of our clock since losing a grandmaster with a traceable time
+    pub(crate) holdover: Option<Holdover>,
+    /// The uncertainty of our clock while it is synchronized, or the last one
+    /// before losing the grandmaster
+    pub(crate) synchronized_uncertainty: Option<ClockUncertainty>,
+    /// The synchronization metadata of the grandmaster
+    pub(crate) synchronization_metadata: Option<SynchronizationMetadata>,
+    /// The synchronization metadata sent while we are the grandmaster
+    pub(crate) local_synchronization_metadata: Option<SynchronizationMetadata>,
+    /// The power profile information received from the parent, or our own
+    /// while we are the grandmaster
+    pub(crate) power_profile: Option<PowerProfileInformation>,
+    pub(crate) local_power_profile: Option<PowerProfileConfig>,
+    /// The alternate time offset of the grandmaster
+    pub(crate) alternate_time_offset: Option<AlternateTimeOffset>,
+    /// The alternate time offset sent while we are the grandmaster
+    pub(crate) local_alternate_time_offset: Option<AlternateTimeOffset>,
+    /// The keys used to authenticate messages
+    pub(crate) security_config: Option<SecurityConfig>,
 }
 
 impl PtpInstanceState {
@@ -105,14 +137,28 @@ impl PtpInstanceState {
     ) {
         debug_assert_eq!(self.default_ds.number_ports as usize, ports.len());
 
+        // Apply changes requested through management messages
+        for port in ports.iter_mut() {
+            port.take_default_ds_changes().apply(&mut self.default_ds);
+        }
+
         for port in ports.iter_mut() {
             port.calculate_best_local_announce_message()
         }
 
+        // Remember how well our clock is synchronized while we still are, to start
+        // the holdover from there when the grandmaster goes away
+        let was_synchronized = ports.iter().any(|port| port.is_steering());
+        if was_synchronized {
+            self.synchronized_uncertainty = ports.iter().find_map(|port| port.clock_uncertainty());
+        }
+        let previous_time_properties_ds = self.time_properties_ds;
+
         let ebest = Bmca::<()>::find_best_announce_message(
             ports
                 .iter()
                 .filter_map(|port| port.best_local_announce_message_for_bmca()),
+            self.default_ds.profile,
         );
 
         for port in ports.iter_mut() {
@@ -139,11 +185,100 @@ impl PtpInstanceState {
             }
         }
 
+        // Take the information in the announce messages from the grandmaster
+        if self.parent_ds.grandmaster_identity == self.default_ds.clock_identity {
+            self.update_holdover(was_synchronized, previous_time_properties_ds, bmca_interval);
+            self.synchronization_metadata = self.local_synchronization_metadata;
+            self.power_profile = self
+                .local_power_profile
+                .map(|config| PowerProfileInformation {
+                    grandmaster_id: config.grandmaster_id,
+                    grandmaster_time_inaccuracy: config.time_inaccuracy,
+                    network_time_inaccuracy: 0,
+                });
+            self.alternate_time_offset = self.local_alternate_time_offset;
+        } else {
+            if self.holdover.take().is_some() {
+                log::info!("Leaving holdover");
+            }
+            self.default_ds.clock_quality = self.local_clock_quality;
+            self.synchronized_uncertainty = ports.iter().find_map(|port| port.clock_uncertainty());
+
+            let parent = self.parent_ds.parent_port_identity;
+            self.synchronization_metadata = ports
+                .iter()
+                .find_map(|port| port.synchronization_metadata_of(parent));
+            self.power_profile = ports.iter().find_map(|port| port.power_profile_of(parent));
+            self.alternate_time_offset = ports
+                .iter()
+                .find_map(|port| port.alternate_time_offset_of(parent));
+        }
+
         // And update announce message ages
         for port in ports.iter_mut() {
             port.step_announce_age(bmca_interval);
         }
     }
+
+    /// The clock quality of the grandmaster in our announce messages
+    ///
+    /// While synchronized to a remote grandmaster this includes the
+    /// uncertainty of our own clock, so downstream clocks see the quality of
+    /// the time they get through us (*IEEE1588-2019 section 7.6.3*). Our
+    /// defaultDS keeps the configured clock quality instead: it is compared to
+    /// the grandmaster by our own BMCA, which should not depend on how well we
+    /// are synchronized to that grandmaster.
+    pub(crate) fn announced_clock_quality(&self) -> ClockQuality {
+        let clock_quality = self.parent_ds.grandmaster_clock_quality;
+        match self.synchronized_uncertainty {
+            Some(uncertainty)
+                if self.parent_ds.grandmaster_identity != self.default_ds.clock_identity =>
+            {
+                clock_quality.degraded_by(
+                    uncertainty.clock_accuracy(),
+                    ClockQuality::scaled_log_variance(uncertainty.offset_variance()),
+                )
+            }
+            _ => clock_quality,
+        }
+    }
+
+    /// Apply the clock quality and time properties of our clock while we are
+    /// the grandmaster, which are those of the holdover after losing a
+    /// grandmaster with a traceable time unless our own are at least as good
+    fn update_holdover(
+        &mut self,
+        was_synchronized: bool,
+        previous_time_properties_ds: TimePropertiesDS,
+        bmca_interval: Duration,
+    ) {
+        match (&mut self.holdover, self.holdover_config) {
+            (Some(holdover), Some(_)) => holdover.step(bmca_interval),
+            (None, Some(_)) if was_synchronized && previous_time_properties_ds.time_traceable => {
+                log::info!("Lost the grandmaster, entering holdover");
+                self.holdover = Some(Holdover::new(
+                    self.synchronized_uncertainty,
+                    previous_time_properties_ds,
+                ));
+            }
+            (_, None) => self.holdover = None,
+            (None, Some(_)) => {}
+        }
+
+        let mut clock_quality = self.local_clock_quality;
+        let mut time_properties_ds = self.local_time_properties_ds;
+        if let (Some(holdover), Some(config)) = (&self.holdover, &self.holdover_config) {
+            let holdover_quality = holdover.clock_quality(config);
+            if holdover_quality.clock_class < clock_quality.clock_class {
+                clock_quality = holdover_quality;
+                time_properties_ds = holdover.time_properties_ds(config);
+            }
+        }
+
+        self.default_ds.clock_quality = clock_quality;
+        self.parent_ds.grandmaster_clock_quality = clock_quality;
+        self.time_properties_ds = time_properties_ds;
+    }
 }
 
 impl<F> PtpInstance<F> {
@@ -158,6 +293,18 @@ impl<F> PtpInstance<F> {
                 current_ds: Default::default(),
                 parent_ds: InternalParentDS::new(default_ds),
                 time_properties_ds,
+                local_time_properties_ds: time_properties_ds,
+                local_clock_quality: default_ds.clock_quality,
+                holdover_config: None,
+                holdover: None,
+                synchronized_uncertainty: None,
+                synchronization_metadata: None,
+                local_synchronization_metadata: None,
+                power_profile: None,
+                local_power_profile: None,
+                alternate_time_offset: None,
+                local_alternate_time_offset: None,
+                security_config: None,
             }),
             log_bmca_interval: AtomicI8::new(i8::MAX),
             _filter: PhantomData,
@@ -183,6 +330,132 @@ impl<F> PtpInstance<F> {
     pub fn time_properties_ds(&self) -> TimePropertiesDS {
         self.state.borrow().time_properties_ds
     }
+
+    /// Set the time properties announced while this instance is the
+    /// grandmaster
+    ///
+    /// This allows a grandmaster to follow the state of its time source, for
+    /// instance by claiming a traceable time only while it is locked to that
+    /// source. The change takes effect at the next run of the BMCA. This can
+    /// only be called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state.
+    pub fn set_time_properties_ds(&self, time_properties_ds: TimePropertiesDS) {
+        self.state.borrow_mut().local_time_properties_ds = time_properties_ds;
+    }
+
+    /// Set the quality of the clock of this instance, which is compared to that
+    /// of other grandmasters by the BMCA
+    ///
+    /// The change takes effect at the next run of the BMCA. This can only be
+    /// called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state.
+    pub fn set_clock_quality(&self, clock_quality: ClockQuality) {
+        let mut state = self.state.borrow_mut();
+        state.local_clock_quality = clock_quality;
+        if state.holdover.is_none() {
+            state.default_ds.clock_quality = clock_quality;
+        }
+    }
+
+    /// Set the specification of the clock of this instance while it is in
+    /// holdover, or disable holdover with `None`
+    ///
+    /// After losing a grandmaster with a traceable time, the instance keeps
+    /// announcing that time with the clock quality of its holdover, for as long
+    /// as that is better than its own clock quality. The change takes effect
+    /// at the next run of the BMCA. This can only be called while none of the
+    /// ports is in the [`Running`](`crate::port::Running`) state.
+    pub fn set_holdover_config(&self, config: Option<HoldoverConfig>) {
+        self.state.borrow_mut().holdover_config = config;
+    }
+
+    /// Set the keys used to authenticate the messages of all ports, or disable
+    /// authentication with `None`, see [`SecurityConfig`]
+    ///
+    /// While authentication is enabled, ports drop all messages that are not
+    /// authenticated with one of the keys. The change takes effect once the
+    /// ports are [`Running`](`crate::port::Running`) again. This can only be
+    /// called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state.
+    pub fn set_security_config(&self, config: Option<SecurityConfig>) {
+        self.state.borrow_mut().security_config = config;
+    }
+
+    /// Enable authentication with the keys of `provider` that are valid at
+    /// `now`, see [`SecurityConfig::active`]
+    ///
+    /// To rotate keys, call this regularly, for instance before every
+    /// [`bmca`](`Self::bmca`), with `now` in the timescale of the validity
+    /// windows of the keys. This can only be called while none of the ports
+    /// is in the [`Running`](`crate::port::Running`) state.
+    pub fn update_keys(&self, provider: &mut (impl KeyProvider + ?Sized), now: Time) {
+        let config = provider.security_config().active(now);
+        self.state.borrow_mut().security_config = Some(config);
+    }
+
+    /// Return the state of the clock of this instance while it is in holdover
+    pub fn holdover(&self) -> Option<HoldoverState> {
+        let state = self.state.borrow();
+        Some(state.holdover?.state(state.holdover_config.as_ref()?))
+    }
+
+    /// Return the synchronization metadata of the grandmaster for
+    /// introspection, see [`Profile::St2059_2`](`crate::config::Profile::St2059_2`)
+    ///
+    /// This is the metadata set with
+    /// [`PtpInstance::set_synchronization_metadata`] while this instance is the
+    /// grandmaster, and otherwise the metadata received from the parent.
+    pub fn synchronization_metadata(&self) -> Option<SynchronizationMetadata> {
+        self.state.borrow().synchronization_metadata
+    }
+
+    /// Set the synchronization metadata sent in announce messages while this
+    /// instance is the grandmaster, see
+    /// [`Profile::St2059_2`](`crate::config::Profile::St2059_2`)
+    ///
+    /// The change takes effect at the next run of the BMCA. This can only be
+    /// called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state, such as before they are
+    /// started.
+    pub fn set_synchronization_metadata(&self, metadata: Option<SynchronizationMetadata>) {
+        self.state.borrow_mut().local_synchronization_metadata = metadata;
+    }
+
+    /// Return the power profile information of the grandmaster for
+    /// introspection, see [`Profile::C37_238`](`crate::config::Profile::C37_238`)
+    ///
+    /// The network time inaccuracy covers the path from the grandmaster to the
+    /// parent of this instance.
+    pub fn power_profile(&self) -> Option<PowerProfileInformation> {
+        self.state.borrow().power_profile
+    }
+
+    /// Set the grandmaster identifier and time inaccuracy of this instance,
+    /// see [`Profile::C37_238`](`crate::config::Profile::C37_238`)
+    ///
+    /// The change takes effect at the next run of the BMCA. This can only be
+    /// called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state, such as before they are
+    /// started.
+    pub fn set_power_profile(&self, config: Option<PowerProfileConfig>) {
+        self.state.borrow_mut().local_power_profile = config;
+    }
+
+    /// Return the alternate time offset of the grandmaster for introspection
+    pub fn alternate_time_offset(&self) -> Option<AlternateTimeOffset> {
+        self.state.borrow().alternate_time_offset
+    }
+
+    /// Set the alternate time offset sent in announce messages while this
+    /// instance is the grandmaster
+    ///
+    /// The change takes effect at the next run of the BMCA. This can only be
+    /// called while none of the ports is in the
+    /// [`Running`](`crate::port::Running`) state, such as before they are
+    /// started.
+    pub fn set_alternate_time_offset(&self, offset: Option<AlternateTimeOffset>) {
+        self.state.borrow_mut().local_alternate_time_offset = offset;
+    }
 }
 
 impl<F: Filter> PtpInstance<F> {
@@ -243,3 +516,87 @@ impl<F: Filter> PtpInstance<F> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{ClockIdentity, LeapIndicator, Profile, TimeSource},
+        filters::BasicFilter,
+    };
+
+    fn test_state() -> PtpInstanceState {
+        let instance = PtpInstance::<BasicFilter>::new(
+            InstanceConfig {
+                clock_identity: ClockIdentity([1, 2, 3, 4, 5, 6, 7, 8]),
+                priority_1: 128,
+                priority_2: 128,
+                domain_number: 0,
+                slave_only: false,
+                sdo_id: Default::default(),
+                profile: Profile::Default,
+                local_priority: 128,
+            },
+            TimePropertiesDS::new_arbitrary_time(false, false, TimeSource::InternalOscillator),
+        );
+        instance.set_holdover_config(Some(HoldoverConfig::default()));
+        instance.state.into_inner()
+    }
+
+    fn traceable() -> TimePropertiesDS {
+        TimePropertiesDS::new_ptp_time(
+            Some(37),
+            LeapIndicator::NoLeap,
+            true,
+            true,
+            TimeSource::Gnss,
+        )
+    }
+
+    #[test]
+    fn holdover_after_losing_grandmaster() {
+        let mut state = test_state();
+        state.synchronized_uncertainty = Some(ClockUncertainty {
+            offset: Duration::from_nanos(20),
+            frequency: 1e-9,
+        });
+
+        state.update_holdover(true, traceable(), Duration::from_secs(1));
+        assert!(state.holdover.is_some());
+        assert_eq!(state.default_ds.clock_quality.clock_class, 7);
+        assert_eq!(state.parent_ds.grandmaster_clock_quality.clock_class, 7);
+        assert_eq!(state.time_properties_ds, traceable());
+
+        state.update_holdover(false, state.time_properties_ds, Duration::from_secs(1000));
+        assert_eq!(state.default_ds.clock_quality.clock_class, 52);
+        assert!(!state.time_properties_ds.time_traceable);
+        assert_eq!(state.time_properties_ds.current_utc_offset, Some(37));
+    }
+
+    #[test]
+    fn no_holdover_without_traceable_grandmaster() {
+        let mut state = test_state();
+        let arbitrary = state.local_time_properties_ds;
+
+        state.update_holdover(true, arbitrary, Duration::from_secs(1));
+        assert!(state.holdover.is_none());
+        assert_eq!(state.default_ds.clock_quality, state.local_clock_quality);
+
+        state.holdover_config = None;
+        state.update_holdover(true, traceable(), Duration::from_secs(1));
+        assert!(state.holdover.is_none());
+        assert_eq!(state.default_ds.clock_quality, state.local_clock_quality);
+    }
+
+    #[test]
+    fn local_clock_quality_preferred_over_holdover() {
+        let mut state = test_state();
+        state.synchronized_uncertainty = Some(ClockUncertainty::default());
+        state.local_clock_quality.clock_class = 6;
+        state.local_time_properties_ds = traceable();
+
+        state.update_holdover(true, traceable(), Duration::from_secs(1));
+        assert!(state.holdover.is_some());
+        assert_eq!(state.default_ds.clock_quality.clock_class, 6);
+    }
+}
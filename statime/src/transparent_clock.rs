@@ -0,0 +1,75 @@
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use crate::{
+    config::TransparentClockConfig, datastructures::common::PortIdentity, port::TransparentPort,
+};
+
+/// A PTP end-to-end or peer-to-peer transparent clock.
+///
+/// A transparent clock does not synchronize to a master itself, instead it
+/// forwards PTP messages between its ports while adding the time these messages
+/// spent inside the device (the residence time) to their correction field. See
+/// *IEEE 1588-2019 section 10.2*.
+///
+/// # Example
+///
+/// ```no_run
+/// # mod system {
+/// #     pub fn get_mac() -> [u8; 6] { unimplemented!() }
+/// # }
+/// use statime::TransparentClock;
+/// use statime::config::{ClockIdentity, DelayMechanism, TransparentClockConfig};
+/// use statime::time::Interval;
+///
+/// let transparent_clock = TransparentClock::new(TransparentClockConfig {
+///     clock_identity: ClockIdentity::from_mac_address(system::get_mac()),
+///     primary_domain: 0,
+///     sdo_id: Default::default(),
+///     delay_mechanism: DelayMechanism::P2P { interval: Interval::ONE_SECOND },
+///     time_inaccuracy: 0,
+/// });
+///
+/// let port_a = transparent_clock.add_port();
+/// let port_b = transparent_clock.add_port();
+///
+/// // Send the ports of to their own threads/tasks, and hand the messages
+/// // forwarded by one port to the other.
+/// ```
+#[derive(Debug)]
+pub struct TransparentClock {
+    config: TransparentClockConfig,
+    number_ports: AtomicU16,
+}
+
+impl TransparentClock {
+    /// Construct a new [`TransparentClock`] with the given config
+    pub fn new(config: TransparentClockConfig) -> Self {
+        Self {
+            config,
+            number_ports: AtomicU16::new(0),
+        }
+    }
+
+    /// Add and initialize a port
+    pub fn add_port(&self) -> TransparentPort {
+        let port_number = self.number_ports.fetch_add(1, Ordering::Relaxed);
+
+        TransparentPort::new(
+            self.config,
+            PortIdentity {
+                clock_identity: self.config.clock_identity,
+                port_number,
+            },
+        )
+    }
+
+    /// The configuration of this [`TransparentClock`]
+    pub fn config(&self) -> &TransparentClockConfig {
+        &self.config
+    }
+
+    /// The number of ports added to this [`TransparentClock`]
+    pub fn number_ports(&self) -> u16 {
+        self.number_ports.load(Ordering::Relaxed)
+    }
+}